@@ -1,4 +1,4 @@
-use merco_agents::{Agent, AgentModelConfig, OutputFormat, AgentRole, AgentCapabilities, Task, Provider, LlmConfig};
+use merco_agents::{Agent, AgentModelConfig, OutputFormat, AgentRole, AgentCapabilities, Task, Provider, LlmConfig, Crew};
 use std::env;
 
 #[tokio::main]
@@ -39,15 +39,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 max_concurrent_tasks: 1,
                 supported_output_formats: vec![OutputFormat::Text, OutputFormat::Json],
             };
-            Agent::with_custom_role(
-                "Research Agent".to_string(),
-                "Specializes in gathering and analyzing information".to_string(),
-                role,
-                agent_llm_config.clone(),
-                vec![], // No tools for simplicity
-                capabilities,
-                Some(OutputFormat::Text),
-            )
+            Agent::builder("Research Agent", agent_llm_config.clone())
+                .description("Specializes in gathering and analyzing information")
+                .role(role)
+                .capabilities(capabilities)
+                .output_format(OutputFormat::Text)
+                .build()
         },
         // Analysis Agent
         {
@@ -59,15 +56,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 max_concurrent_tasks: 1,
                 supported_output_formats: vec![OutputFormat::Json, OutputFormat::Markdown],
             };
-            Agent::with_custom_role(
-                "Analysis Agent".to_string(),
-                "Specializes in data analysis and insights".to_string(),
-                role,
-                agent_llm_config.clone(),
-                vec![],
-                capabilities,
-                Some(OutputFormat::Json),
-            )
+            Agent::builder("Analysis Agent", agent_llm_config.clone())
+                .description("Specializes in data analysis and insights")
+                .role(role)
+                .capabilities(capabilities)
+                .output_format(OutputFormat::Json)
+                .build()
         },
         // Writing Agent
         {
@@ -79,15 +73,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 max_concurrent_tasks: 1,
                 supported_output_formats: vec![OutputFormat::Markdown, OutputFormat::Html],
             };
-            Agent::with_custom_role(
-                "Writing Agent".to_string(),
-                "Specializes in content creation and writing".to_string(),
-                role,
-                agent_llm_config.clone(),
-                vec![],
-                capabilities,
-                Some(OutputFormat::Markdown),
-            )
+            Agent::builder("Writing Agent", agent_llm_config.clone())
+                .description("Specializes in content creation and writing")
+                .role(role)
+                .capabilities(capabilities)
+                .output_format(OutputFormat::Markdown)
+                .build()
         },
     ];
     
@@ -161,40 +152,33 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
     
-    // Example 3: Simple Workflow
-    println!("\n🔄 Example 3: Simple Workflow");
+    // Example 3: Sequential Crew
+    println!("\n🔄 Example 3: Sequential Crew");
     println!("{}", "=".repeat(50));
     
-    // Each agent works on their own specialized task independently
+    // Each agent gets the previous agent's output as context for its own task
     let workflow_tasks = vec![
-        ("Research Agent", "Research the benefits of electric vehicles"),
-        ("Analysis Agent", "Analyze the data from the research phase"),
-        ("Writing Agent", "Create a summary report based on the analysis"),
+        "Research the benefits of electric vehicles",
+        "Analyze the data from the research phase",
+        "Create a summary report based on the analysis",
     ];
     
-    let mut workflow_results = Vec::new();
+    let mut crew = agents
+        .into_iter()
+        .zip(workflow_tasks)
+        .fold(Crew::new("EV Report"), |crew, (agent, task_description)| {
+            crew.with_step(agent, Task::new(task_description.to_string(), None))
+        });
     
-    for (agent_name, task_description) in workflow_tasks {
-        println!("\n🔍 {} working on: {}", agent_name, task_description);
-        
-        let task = Task::new(task_description.to_string(), None);
-        let agent_index = match agent_name {
-            "Research Agent" => 0,
-            "Analysis Agent" => 1,
-            "Writing Agent" => 2,
-            _ => 0,
-        };
-        
-        let response = agents[agent_index].call(task).await;
-        
-        if response.success {
-            println!("✅ {} completed successfully!", agent_name);
+    let crew_result = crew.kickoff_sequential().await;
+    
+    for step in &crew_result.steps {
+        if step.response.success {
+            println!("\n✅ {} completed successfully!", step.agent_name);
             println!("📊 Metrics: {}ms, {} tokens", 
-                response.execution_time_ms, response.total_tokens);
-            workflow_results.push(response.content);
+                step.response.execution_time_ms, step.response.total_tokens);
         } else {
-            println!("❌ {} failed: {}", agent_name, response.error.unwrap_or("Unknown error".to_string()));
-            workflow_results.push("Failed".to_string());
+            println!("\n❌ {} failed: {}", step.agent_name, step.response.error.clone().unwrap_or("Unknown error".to_string()));
         }
     }
     
@@ -202,7 +186,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("\n📊 Individual Agent Performance:");
     println!("{}", "=".repeat(40));
     
-    for (i, agent) in agents.iter().enumerate() {
+    for (i, agent) in crew.agents().enumerate() {
         let metrics = agent.get_performance_metrics();
         println!("Agent {} ({}):", i + 1, agent.get_name());
         println!("  - Total tasks: {}", metrics.total_tasks);
@@ -216,8 +200,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Show workflow results
     println!("\n📋 Workflow Results Summary:");
     println!("{}", "=".repeat(40));
-    for (i, result) in workflow_results.iter().enumerate() {
-        println!("Step {}: {}", i + 1, result);
+    for (i, step) in crew_result.steps.iter().enumerate() {
+        println!("Step {} ({}): {}", i + 1, step.agent_name, step.response.content);
+    }
+    if let Some(failed_step) = crew_result.failed_step {
+        println!("Crew stopped at step {}", failed_step + 1);
     }
     
     println!("\n🎉 Multi-agent example completed!");
@@ -50,15 +50,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             supported_output_formats: vec![format.clone()],
         };
         
-        let mut agent = Agent::new_with_output_format(
-            "Data Analyst".to_string(),
-            "Specializes in data analysis and insights".to_string(),
-            role,
-            agent_llm_config.clone(),
-            vec![], // No tools
-            capabilities,
-            format.clone(),
-        );
+        let mut agent = Agent::builder("Data Analyst", agent_llm_config.clone())
+            .description("Specializes in data analysis and insights")
+            .role(role)
+            .capabilities(capabilities)
+            .output_format(format.clone())
+            .build();
         
         // Create a task that matches the agent's format
         let task = match format {
@@ -133,15 +130,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         supported_output_formats: vec![OutputFormat::Json, OutputFormat::Markdown],
     };
     
-    let mut json_agent = Agent::new_with_output_format(
-        "JSON Specialist".to_string(),
-        "Specializes in JSON data formatting".to_string(),
-        role,
-        agent_llm_config.clone(),
-        vec![],
-        capabilities,
-        OutputFormat::Json,
-    );
+    let mut json_agent = Agent::builder("JSON Specialist", agent_llm_config.clone())
+        .description("Specializes in JSON data formatting")
+        .role(role)
+        .capabilities(capabilities)
+        .output_format(OutputFormat::Json)
+        .build();
     
     // Create task with Markdown format (different from agent)
     let markdown_task = Task::new(
@@ -0,0 +1,244 @@
+use crate::a2a::types::*;
+use crate::agent::http::shared_client;
+use crate::credentials::Credential;
+use crate::tools::registry::LocalTool;
+use async_stream::stream;
+use futures::{Stream, StreamExt};
+use serde_json::{json, Value};
+use std::pin::Pin;
+use uuid::Uuid;
+
+/// Talks to a remote agent over the A2A protocol
+#[derive(Debug, Clone)]
+pub struct A2aClient {
+    client: reqwest::Client,
+    card: AgentCard,
+    api_key: Option<Credential>,
+}
+
+impl A2aClient {
+    /// Use an already known agent card
+    pub fn new(card: AgentCard) -> Self {
+        Self {
+            client: shared_client(),
+            card,
+            api_key: None,
+        }
+    }
+
+    /// Fetch the agent card from `{base_url}/.well-known/agent.json`
+    pub async fn discover(base_url: &str) -> Result<Self, String> {
+        let url = format!("{}/.well-known/agent.json", base_url.trim_end_matches('/'));
+        let response = shared_client()
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to fetch agent card from {}: {}", url, e))?;
+        if !response.status().is_success() {
+            return Err(format!("HTTP {} fetching agent card from {}", response.status().as_u16(), url));
+        }
+        let card = response
+            .json::<AgentCard>()
+            .await
+            .map_err(|e| format!("Invalid agent card at {}: {}", url, e))?;
+        Ok(Self::new(card))
+    }
+
+    pub fn with_api_key(mut self, api_key: &str) -> Self {
+        self.api_key = Some(Credential::fixed(api_key));
+        self
+    }
+
+    /// Resolve the key from `credential` on every request
+    pub fn with_credential(mut self, credential: Credential) -> Self {
+        self.api_key = Some(credential);
+        self
+    }
+
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    pub fn card(&self) -> &AgentCard {
+        &self.card
+    }
+
+    async fn post(&self, method: &str, params: Value) -> Result<reqwest::Response, String> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": Uuid::new_v4().to_string(),
+            "method": method,
+            "params": params,
+        });
+        let mut request = self.client.post(&self.card.url).json(&body);
+        if let Some(api_key) = self.api_key.as_ref().and_then(Credential::resolve_or_log) {
+            request = request.bearer_auth(api_key);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| format!("Request to {} failed: {}", self.card.url, e))?;
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            let text = response.text().await.unwrap_or_default();
+            return Err(format!("HTTP {}: {}", status, text));
+        }
+        Ok(response)
+    }
+
+    async fn call(&self, method: &str, params: Value) -> Result<A2aTask, String> {
+        let body: Value = self
+            .post(method, params)
+            .await?
+            .json()
+            .await
+            .map_err(|e| format!("Invalid response: {}", e))?;
+        rpc_result(body)
+    }
+
+    /// Send a task and wait for it to finish
+    pub async fn send_task(&self, text: &str, session_id: Option<String>) -> Result<A2aTask, String> {
+        let params = TaskSendParams {
+            id: Uuid::new_v4().to_string(),
+            session_id,
+            message: Message::user_text(text),
+            metadata: None,
+        };
+        self.call("tasks/send", json!(params)).await
+    }
+
+    pub async fn get_task(&self, id: &str) -> Result<A2aTask, String> {
+        self.call("tasks/get", json!({ "id": id })).await
+    }
+
+    pub async fn cancel_task(&self, id: &str) -> Result<A2aTask, String> {
+        self.call("tasks/cancel", json!({ "id": id })).await
+    }
+
+    /// Send a task and stream its status and artifact updates
+    pub async fn send_subscribe(
+        &self,
+        text: &str,
+        session_id: Option<String>,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<TaskEvent, String>> + Send>>, String> {
+        if !self.card.capabilities.streaming {
+            return Err(format!("Agent '{}' does not support streaming", self.card.name));
+        }
+        let params = TaskSendParams {
+            id: Uuid::new_v4().to_string(),
+            session_id,
+            message: Message::user_text(text),
+            metadata: None,
+        };
+        let mut bytes = self.post("tasks/sendSubscribe", json!(params)).await?.bytes_stream();
+
+        Ok(Box::pin(stream! {
+            let mut buffer = String::new();
+            while let Some(next) = bytes.next().await {
+                let data = match next {
+                    Ok(data) => data,
+                    Err(e) => {
+                        yield Err(format!("Stream connection reset: {}", e));
+                        return;
+                    }
+                };
+                buffer.push_str(&String::from_utf8_lossy(&data));
+
+                while let Some(newline) = buffer.find('\n') {
+                    let line: String = buffer.drain(..=newline).collect();
+                    let payload = match line.trim().strip_prefix("data:") {
+                        Some(payload) => payload.trim(),
+                        None => continue,
+                    };
+                    let event = serde_json::from_str::<Value>(payload)
+                        .map_err(|e| format!("Invalid stream event: {}", e))
+                        .and_then(rpc_result::<TaskEvent>);
+                    let done = match &event {
+                        Ok(TaskEvent::Status(update)) => update.is_final,
+                        Ok(TaskEvent::Artifact(_)) => false,
+                        Err(_) => true,
+                    };
+                    yield event;
+                    if done {
+                        return;
+                    }
+                }
+            }
+        }))
+    }
+
+    /// Expose the remote agent as a local tool so agents can delegate to it
+    pub fn into_tool(self) -> RemoteAgentTool {
+        let name = self
+            .card
+            .name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+            .collect();
+        let description = self.card.description.clone().unwrap_or_else(|| format!("Delegate a task to {}", self.card.name));
+        RemoteAgentTool { name, description, client: self }
+    }
+}
+
+fn rpc_result<T: serde::de::DeserializeOwned>(body: Value) -> Result<T, String> {
+    if let Some(error) = body.get("error") {
+        let message = error.get("message").and_then(|m| m.as_str()).unwrap_or("unknown error");
+        return Err(format!("Remote agent error: {}", message));
+    }
+    let result = body.get("result").cloned().ok_or_else(|| "Response has no result".to_string())?;
+    serde_json::from_value(result).map_err(|e| format!("Invalid result: {}", e))
+}
+
+/// A remote A2A agent callable as a tool with a single `task` argument
+pub struct RemoteAgentTool {
+    name: String,
+    description: String,
+    client: A2aClient,
+}
+
+impl RemoteAgentTool {
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+}
+
+impl LocalTool for RemoteAgentTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "task": { "type": "string", "description": "What the remote agent should do" }
+            },
+            "required": ["task"]
+        })
+    }
+
+    fn call(&self, arguments: &str) -> Result<String, String> {
+        let arguments: Value = serde_json::from_str(arguments).map_err(|e| format!("Invalid arguments: {}", e))?;
+        let text = arguments
+            .get("task")
+            .and_then(|t| t.as_str())
+            .ok_or_else(|| "Missing 'task' argument".to_string())?;
+        crate::tools::network::check_url(&self.client.card().url)?;
+
+        // Tools are called synchronously from within the agent's runtime
+        let task = tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current().block_on(self.client.send_task(text, None))
+        })?;
+        match task.status.state {
+            TaskState::Completed => Ok(task.output_text()),
+            TaskState::InputRequired => Ok(format!("Remote agent needs more input: {}", task.output_text())),
+            state => Err(format!("Remote agent task ended as {:?}: {}", state, task.output_text())),
+        }
+    }
+}
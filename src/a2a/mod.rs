@@ -0,0 +1,9 @@
+pub mod types;
+pub mod client;
+pub mod server;
+#[cfg(feature = "server")]
+pub mod routes;
+
+pub use client::{A2aClient, RemoteAgentTool};
+pub use server::A2aServer;
+pub use types::{A2aTask, AgentCard, AgentSkill, Artifact, Message, Part, TaskEvent, TaskState, TaskStatus};
@@ -0,0 +1,38 @@
+use crate::a2a::server::A2aServer;
+use axum::body::Body;
+use axum::extract::State;
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use futures::StreamExt;
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Axum routes for an `A2aServer`: the agent card at `/.well-known/agent.json`
+/// and JSON-RPC at `path`, streaming `tasks/sendSubscribe` as server-sent events
+pub fn a2a_router(server: A2aServer, path: &str) -> Router {
+    Router::new()
+        .route("/.well-known/agent.json", get(agent_card))
+        .route(path, post(rpc))
+        .with_state(Arc::new(server))
+}
+
+async fn agent_card(State(server): State<Arc<A2aServer>>) -> Json<Value> {
+    Json(serde_json::to_value(server.card()).unwrap_or_default())
+}
+
+async fn rpc(State(server): State<Arc<A2aServer>>, Json(request): Json<Value>) -> Response {
+    if request.get("method").and_then(|m| m.as_str()) != Some("tasks/sendSubscribe") {
+        return Json(server.handle_request(&request).await).into_response();
+    }
+    let frames = server
+        .subscribe(&request)
+        .await
+        .map(Ok::<_, std::convert::Infallible>);
+    Response::builder()
+        .header(header::CONTENT_TYPE, "text/event-stream")
+        .header(header::CACHE_CONTROL, "no-cache")
+        .body(Body::from_stream(frames))
+        .unwrap_or_else(|_| Json(serde_json::json!({ "error": "Failed to build response" })).into_response())
+}
@@ -0,0 +1,253 @@
+use crate::a2a::types::*;
+use crate::agent::agent::{Agent, AgentResponse};
+use crate::agent::streaming::{StreamingChunk, StreamingHandler};
+use crate::task::cancellation::CancellationToken;
+use crate::task::task::Task;
+use async_stream::stream;
+use futures::{Stream, StreamExt};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+const INVALID_PARAMS: i64 = -32602;
+const METHOD_NOT_FOUND: i64 = -32601;
+const TASK_NOT_FOUND: i64 = -32001;
+const TASK_NOT_CANCELABLE: i64 = -32002;
+
+struct TaskEntry {
+    task: A2aTask,
+    cancel_token: CancellationToken,
+}
+
+/// Serves one agent over the A2A protocol. Transport-agnostic: hand it the
+/// JSON-RPC body of each request (`handle_request`), or the body of a
+/// `tasks/sendSubscribe` request to get its SSE frames (`subscribe`), and
+/// publish `card()` at `/.well-known/agent.json`.
+pub struct A2aServer {
+    agent: Agent,
+    card: AgentCard,
+    tasks: Arc<Mutex<HashMap<String, TaskEntry>>>,
+}
+
+impl A2aServer {
+    /// `url` is where this server receives JSON-RPC requests
+    pub fn new(agent: Agent, url: &str) -> Self {
+        let card = AgentCard {
+            name: agent.name.clone(),
+            description: Some(agent.description.clone()),
+            url: url.to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            capabilities: AgentCardCapabilities {
+                streaming: true,
+                push_notifications: false,
+                state_transition_history: false,
+            },
+            default_input_modes: vec!["text".to_string()],
+            default_output_modes: vec!["text".to_string()],
+            skills: vec![AgentSkill {
+                id: agent.id.clone(),
+                name: agent.name.clone(),
+                description: Some(agent.role.get_description()),
+                tags: Vec::new(),
+            }],
+        };
+        Self {
+            agent,
+            card,
+            tasks: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Replace the generated skills, e.g. to advertise more specific capabilities
+    pub fn with_skills(mut self, skills: Vec<AgentSkill>) -> Self {
+        self.card.skills = skills;
+        self
+    }
+
+    pub fn card(&self) -> &AgentCard {
+        &self.card
+    }
+
+    /// Handle a JSON-RPC request body and return the response body
+    pub async fn handle_request(&self, request: &Value) -> Value {
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+        let result = match request.get("method").and_then(|m| m.as_str()) {
+            Some("tasks/send") => self.send(params).await,
+            Some("tasks/get") => self.get(&params),
+            Some("tasks/cancel") => self.cancel(&params),
+            Some("tasks/sendSubscribe") => Err((METHOD_NOT_FOUND, "tasks/sendSubscribe must be served as a stream".to_string())),
+            Some(method) => Err((METHOD_NOT_FOUND, format!("Method not found: {}", method))),
+            None => Err((METHOD_NOT_FOUND, "Missing method".to_string())),
+        };
+        match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } }),
+        }
+    }
+
+    fn start(&self, params: Value) -> Result<(TaskSendParams, Task), (i64, String)> {
+        let params: TaskSendParams =
+            serde_json::from_value(params).map_err(|e| (INVALID_PARAMS, format!("Invalid params: {}", e)))?;
+        let mut task = Task::new(params.message.text(), None);
+        task.id = params.id.clone();
+
+        let entry = TaskEntry {
+            task: A2aTask {
+                id: params.id.clone(),
+                session_id: params.session_id.clone(),
+                status: TaskStatus::new(TaskState::Working, None),
+                artifacts: Vec::new(),
+                metadata: params.metadata.clone(),
+            },
+            cancel_token: task.cancel_handle(),
+        };
+        self.tasks.lock().unwrap().insert(params.id.clone(), entry);
+        Ok((params, task))
+    }
+
+    async fn send(&self, params: Value) -> Result<Value, (i64, String)> {
+        let (params, task) = self.start(params)?;
+        let response = self.agent.execute_task(task).await;
+        let finished = finish(&self.tasks, &params.id, &response);
+        Ok(json!(finished))
+    }
+
+    fn get(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let id = task_id(params)?;
+        self.tasks
+            .lock()
+            .unwrap()
+            .get(id)
+            .map(|entry| json!(entry.task))
+            .ok_or_else(|| (TASK_NOT_FOUND, format!("Task not found: {}", id)))
+    }
+
+    fn cancel(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let id = task_id(params)?;
+        let mut tasks = self.tasks.lock().unwrap();
+        let entry = tasks.get_mut(id).ok_or_else(|| (TASK_NOT_FOUND, format!("Task not found: {}", id)))?;
+        if entry.task.status.state.is_final() {
+            return Err((TASK_NOT_CANCELABLE, format!("Task {} already finished", id)));
+        }
+        entry.cancel_token.cancel();
+        entry.task.status = TaskStatus::new(TaskState::Canceled, None);
+        Ok(json!(entry.task))
+    }
+
+    /// Run a `tasks/sendSubscribe` request; yields SSE frames carrying JSON-RPC
+    /// responses whose results are status and artifact update events
+    pub async fn subscribe(&self, request: &Value) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+        let rpc_id = request.get("id").cloned().unwrap_or(Value::Null);
+        let frame = move |result: Value| format!("data: {}\n\n", json!({ "jsonrpc": "2.0", "id": rpc_id, "result": result }));
+
+        let (params, task) = match self.start(request.get("params").cloned().unwrap_or(Value::Null)) {
+            Ok(started) => started,
+            Err((code, message)) => {
+                let error = format!(
+                    "data: {}\n\n",
+                    json!({ "jsonrpc": "2.0", "id": request.get("id"), "error": { "code": code, "message": message } })
+                );
+                return Box::pin(futures::stream::once(async move { error }));
+            }
+        };
+
+        let mut agent = self.agent.clone();
+        let tasks = self.tasks.clone();
+        let id = params.id.clone();
+        let mut chunks = agent.call_stream_with_handler(task, SilentHandler).await;
+
+        Box::pin(stream! {
+            yield frame(json!(TaskStatusUpdateEvent {
+                id: id.clone(),
+                status: TaskStatus::new(TaskState::Working, None),
+                is_final: false,
+            }));
+
+            while let Some(item) = chunks.next().await {
+                match item {
+                    Ok(chunk) if chunk.is_final => {
+                        if let Some(response) = chunk.response.as_deref() {
+                            let finished = finish(&tasks, &id, response);
+                            yield frame(json!(TaskArtifactUpdateEvent {
+                                id: id.clone(),
+                                artifact: Artifact { name: None, parts: Vec::new(), index: 0, append: true, last_chunk: true },
+                            }));
+                            yield frame(json!(TaskStatusUpdateEvent { id: id.clone(), status: finished.status, is_final: true }));
+                        }
+                        return;
+                    }
+                    Ok(chunk) if chunk.content.is_empty() => {}
+                    Ok(chunk) => {
+                        yield frame(json!(TaskArtifactUpdateEvent {
+                            id: id.clone(),
+                            artifact: Artifact {
+                                name: None,
+                                parts: vec![Part::Text { text: chunk.content }],
+                                index: 0,
+                                append: true,
+                                last_chunk: false,
+                            },
+                        }));
+                    }
+                    Err(error) => {
+                        let status = TaskStatus::new(TaskState::Failed, Some(Message::agent_text(&error)));
+                        if let Some(entry) = tasks.lock().unwrap().get_mut(&id) {
+                            entry.task.status = status.clone();
+                        }
+                        yield frame(json!(TaskStatusUpdateEvent { id: id.clone(), status, is_final: true }));
+                        return;
+                    }
+                }
+            }
+        })
+    }
+}
+
+/// Record the agent's response on the task and return the finished task
+fn finish(tasks: &Mutex<HashMap<String, TaskEntry>>, id: &str, response: &AgentResponse) -> A2aTask {
+    let (state, message) = if response.cancelled {
+        (TaskState::Canceled, None)
+    } else if response.success {
+        (TaskState::Completed, None)
+    } else {
+        (TaskState::Failed, Some(Message::agent_text(response.error.as_deref().unwrap_or("Agent failed"))))
+    };
+
+    let mut tasks = tasks.lock().unwrap();
+    let Some(entry) = tasks.get_mut(id) else {
+        return A2aTask {
+            id: id.to_string(),
+            session_id: None,
+            status: TaskStatus::new(state, message),
+            artifacts: Vec::new(),
+            metadata: None,
+        };
+    };
+    if response.success {
+        entry.task.artifacts = vec![Artifact {
+            name: None,
+            parts: vec![Part::Text { text: response.content.clone() }],
+            index: 0,
+            append: false,
+            last_chunk: true,
+        }];
+    }
+    entry.task.status = TaskStatus::new(state, message);
+    entry.task.clone()
+}
+
+fn task_id(params: &Value) -> Result<&str, (i64, String)> {
+    params
+        .get("id")
+        .and_then(|id| id.as_str())
+        .ok_or_else(|| (INVALID_PARAMS, "Missing task id".to_string()))
+}
+
+/// Stream consumers get the chunks as SSE frames; nothing to do per chunk here
+struct SilentHandler;
+
+impl StreamingHandler for SilentHandler {
+    fn handle_chunk(&self, _chunk: StreamingChunk) {}
+}
@@ -0,0 +1,198 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Self-description an A2A agent publishes at `/.well-known/agent.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentCard {
+    pub name: String,
+    pub description: Option<String>,
+    /// Endpoint receiving JSON-RPC requests
+    pub url: String,
+    pub version: String,
+    pub capabilities: AgentCardCapabilities,
+    #[serde(default)]
+    pub default_input_modes: Vec<String>,
+    #[serde(default)]
+    pub default_output_modes: Vec<String>,
+    #[serde(default)]
+    pub skills: Vec<AgentSkill>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentCardCapabilities {
+    #[serde(default)]
+    pub streaming: bool,
+    #[serde(default)]
+    pub push_notifications: bool,
+    #[serde(default)]
+    pub state_transition_history: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentSkill {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TaskState {
+    Submitted,
+    Working,
+    InputRequired,
+    Completed,
+    Canceled,
+    Failed,
+    Unknown,
+}
+
+impl TaskState {
+    /// Whether the task can no longer change
+    pub fn is_final(&self) -> bool {
+        matches!(self, TaskState::Completed | TaskState::Canceled | TaskState::Failed)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Part {
+    Text { text: String },
+    Data { data: Value },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageRole {
+    User,
+    Agent,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Message {
+    pub role: MessageRole,
+    pub parts: Vec<Part>,
+}
+
+impl Message {
+    pub fn user_text(text: &str) -> Self {
+        Self {
+            role: MessageRole::User,
+            parts: vec![Part::Text { text: text.to_string() }],
+        }
+    }
+
+    pub fn agent_text(text: &str) -> Self {
+        Self {
+            role: MessageRole::Agent,
+            parts: vec![Part::Text { text: text.to_string() }],
+        }
+    }
+
+    /// Text parts joined; data parts as JSON
+    pub fn text(&self) -> String {
+        parts_text(&self.parts)
+    }
+}
+
+fn parts_text(parts: &[Part]) -> String {
+    parts
+        .iter()
+        .map(|part| match part {
+            Part::Text { text } => text.clone(),
+            Part::Data { data } => data.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskStatus {
+    pub state: TaskState,
+    pub message: Option<Message>,
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+impl TaskStatus {
+    pub fn new(state: TaskState, message: Option<Message>) -> Self {
+        Self {
+            state,
+            message,
+            timestamp: Some(Utc::now()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Artifact {
+    pub name: Option<String>,
+    pub parts: Vec<Part>,
+    #[serde(default)]
+    pub index: u32,
+    /// Streaming: append these parts to the artifact with the same index
+    #[serde(default)]
+    pub append: bool,
+    #[serde(default)]
+    pub last_chunk: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct A2aTask {
+    pub id: String,
+    pub session_id: Option<String>,
+    pub status: TaskStatus,
+    #[serde(default)]
+    pub artifacts: Vec<Artifact>,
+    #[serde(default)]
+    pub metadata: Option<Value>,
+}
+
+impl A2aTask {
+    /// Text of all artifacts, or of the status message when there are none
+    pub fn output_text(&self) -> String {
+        if self.artifacts.is_empty() {
+            return self.status.message.as_ref().map(Message::text).unwrap_or_default();
+        }
+        self.artifacts.iter().map(|a| parts_text(&a.parts)).collect::<Vec<_>>().join("\n")
+    }
+}
+
+/// Params of `tasks/send` and `tasks/sendSubscribe`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskSendParams {
+    pub id: String,
+    pub session_id: Option<String>,
+    pub message: Message,
+    #[serde(default)]
+    pub metadata: Option<Value>,
+}
+
+/// Events of a `tasks/sendSubscribe` stream
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TaskEvent {
+    Status(TaskStatusUpdateEvent),
+    Artifact(TaskArtifactUpdateEvent),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskStatusUpdateEvent {
+    pub id: String,
+    pub status: TaskStatus,
+    #[serde(rename = "final", default)]
+    pub is_final: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskArtifactUpdateEvent {
+    pub id: String,
+    pub artifact: Artifact,
+}
@@ -2,6 +2,10 @@ use crate::agent::role::{AgentRole, AgentCapabilities};
 use crate::agent::state::{AgentState, AgentContext};
 use crate::agent::output_handler::OutputHandler;
 use crate::agent::provider::LlmConfig;
+use crate::task::queue::TaskQueue;
+use crate::task::run_history::RunStore;
+use crate::agent::scoring::{EvaluationResult, OutputScorer};
+use crate::agent::streaming::StreamingOptions;
 use merco_llmproxy::{LlmProvider, Tool};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -24,7 +28,9 @@ pub struct Agent {
     pub llm_config: AgentModelConfig,
     
     // Tools
-    pub tools: Vec<Tool>,
+    pub tools: Arc<Vec<Tool>>,
+    // Serialized once per tool set change (see `add_tool`/`remove_tool`)
+    pub tool_schemas: Arc<crate::agent::tool_schema::ToolSchemas>,
     
     // State and Context
     pub state: AgentState,
@@ -35,8 +41,79 @@ pub struct Agent {
     
     // LLM Provider
     pub provider: Arc<dyn LlmProvider + Send + Sync>,
+
+    // Background task queue (shared between clones of this agent)
+    pub task_queue: Arc<TaskQueue>,
+
+    // In-flight tasks and recent results for `status()` (shared between clones of this agent)
+    pub activity: Arc<crate::agent::status::ActivityTracker>,
+
+    // Per-tool usage from calls and streams (shared between clones of this agent)
+    pub tool_usage: Arc<crate::agent::state::ToolUsageTracker>,
+
+    // Limits in-flight tasks to `capabilities.max_concurrent_tasks` (shared between clones of this agent)
+    pub task_slots: Arc<tokio::sync::Semaphore>,
+
+    // Optional persistence for completed runs
+    pub run_store: Option<Arc<dyn RunStore>>,
+
+    // Scorer used for tasks that request evaluation (lexical if unset)
+    pub output_scorer: Option<Arc<dyn OutputScorer>>,
+
+    // Retry/resume behaviour of streaming calls
+    pub streaming_options: StreamingOptions,
+
+    // Prices every run when set
+    pub cost_tracker: Option<Arc<crate::cost::CostTracker>>,
+
+    // Spending alerts and caps checked before each provider request
+    pub budgets: Option<Arc<crate::cost::BudgetManager>>,
+
+    // Audit trail written when the security context enables audit logging
+    #[cfg(feature = "audit")]
+    pub audit_log: Option<Arc<crate::audit::AuditLog>>,
+
+    // Records provider responses for replay (see `with_run_traces`)
+    pub trace_recorder: Option<Arc<crate::agent::replay::RecordingProvider>>,
+
+    // Where lifecycle events are published (the global bus unless replaced)
+    pub event_bus: Arc<crate::events::EventBus>,
+
+    // Scrubs secrets from persisted runs, tool call records and logged tool errors
+    pub redactor: Arc<crate::redaction::Redactor>,
+
+    // Screens tool results for prompt injection before they enter the prompt
+    pub injection_guard: Option<Arc<crate::injection::InjectionGuard>>,
+
+    // Signs the provenance recorded on tool calls
+    #[cfg(feature = "audit")]
+    pub provenance_signer: Option<Arc<dyn crate::provenance::ProvenanceSigner>>,
+
+    // Tenant the agent serves; None for agents shared between tenants
+    pub tenant_id: Option<crate::tenant::TenantId>,
+
+    // Caps how many tasks each end user may start
+    pub user_rate_limiter: Option<Arc<crate::agent::rate_limit::UserRateLimiter>>,
+
+    // Rules the final output is checked against
+    pub content_policy: Option<Arc<crate::agent::content_policy::ContentPolicy>>,
+
+    // Deployment environment settings applied at construction (`MERCO_ENV`)
+    pub profile: Option<crate::agent::profile::EnvironmentProfile>,
+
+    // Set while a scripted provider from `with_mock_provider` answers requests
+    pub mock_provider: bool,
+
+    // Overrides for the built-in system, correction and format prompts
+    pub prompt_registry: Arc<crate::agent::prompt_registry::PromptRegistry>,
 }
 
+// Agents are shared between request handlers and moved into spawned tasks; keep it that way
+const _: fn() = || {
+    fn assert<T: Send + Sync + 'static>() {}
+    assert::<Agent>();
+};
+
 /// LLM Configuration for agents
 #[derive(Debug, Clone)]
 pub struct AgentModelConfig {
@@ -44,11 +121,15 @@ pub struct AgentModelConfig {
     pub temperature: f32,
     pub max_tokens: u32,
     pub llm_config: LlmConfig,
+    /// Prompt plus response tokens the model accepts; prompts are trimmed to fit
+    /// before each request. Looked up from the model name, None if unknown.
+    pub context_window: Option<u32>,
 }
 
 impl AgentModelConfig {
     pub fn new(llm_config: LlmConfig, model_name: String, temperature: f32, max_tokens: u32) -> Self {
         Self {
+            context_window: crate::agent::context_window::known_context_window(&model_name),
             model_name,
             temperature,
             max_tokens,
@@ -56,12 +137,21 @@ impl AgentModelConfig {
         }
     }
 
+    /// Set the context window of a model the built-in table doesn't know
+    pub fn with_context_window(mut self, tokens: u32) -> Self {
+        self.context_window = Some(tokens);
+        self
+    }
+
     /// Convert to merco_llmproxy LlmConfig
     pub fn to_llmproxy_config(&self) -> merco_llmproxy::LlmConfig {
         self.llm_config.to_llmproxy_config()
     }
 }
 
+#[deprecated(since = "0.1.0", note = "renamed to `AgentModelConfig`")]
+pub type AgentLLMConfig = AgentModelConfig;
+
 /// Task execution result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskResult {
@@ -73,6 +163,23 @@ pub struct TaskResult {
     pub metadata: HashMap<String, serde_json::Value>,
 }
 
+impl From<&AgentResponse> for TaskResult {
+    fn from(response: &AgentResponse) -> Self {
+        Self {
+            success: response.success,
+            output: if response.success {
+                response.content.clone()
+            } else {
+                response.error.clone().unwrap_or_default()
+            },
+            execution_time_ms: response.execution_time_ms,
+            tokens_used: response.total_tokens,
+            tools_used: response.tools_used.clone(),
+            metadata: response.metadata.clone(),
+        }
+    }
+}
+
 /// Agent error types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum AgentError {
@@ -84,6 +191,12 @@ pub enum AgentError {
     TooManyConcurrentTasks,
     AgentNotFound,
     InvalidConfiguration,
+    TaskCancelled,
+    BudgetExceeded(String),
+    ShuttingDown,
+    ContextWindowExceeded(String),
+    TenantMismatch(String),
+    RateLimited(crate::agent::rate_limit::RateLimited),
 }
 
 impl std::fmt::Display for AgentError {
@@ -97,6 +210,12 @@ impl std::fmt::Display for AgentError {
             AgentError::TooManyConcurrentTasks => write!(f, "Too many concurrent tasks"),
             AgentError::AgentNotFound => write!(f, "Agent not found"),
             AgentError::InvalidConfiguration => write!(f, "Invalid configuration"),
+            AgentError::TaskCancelled => write!(f, "Task was cancelled"),
+            AgentError::BudgetExceeded(msg) => write!(f, "Budget exceeded: {}", msg),
+            AgentError::ShuttingDown => write!(f, "Agent is shutting down"),
+            AgentError::ContextWindowExceeded(msg) => write!(f, "Context window exceeded: {}", msg),
+            AgentError::TenantMismatch(msg) => write!(f, "Tenant mismatch: {}", msg),
+            AgentError::RateLimited(limited) => write!(f, "Rate limited: {}", limited),
         }
     }
 }
@@ -118,6 +237,17 @@ pub struct ToolCall {
     pub error: Option<String>,
     /// Output format of the tool result
     pub output_format: String,
+    /// Resource limit that stopped the call, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit_violation: Option<crate::tools::LimitViolation>,
+    /// Likely prompt injections found in the result; matched text was neutralized
+    /// before the result was added to the prompt
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub injection_findings: Vec<crate::injection::InjectionFinding>,
+    /// Where and when the result was produced; signed when the agent has a signer
+    #[cfg(feature = "audit")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<crate::provenance::ToolProvenance>,
 }
 
 impl ToolCall {
@@ -135,6 +265,10 @@ impl ToolCall {
             execution_time_ms,
             error: None,
             output_format,
+            limit_violation: None,
+            injection_findings: Vec::new(),
+            #[cfg(feature = "audit")]
+            provenance: None,
         }
     }
 
@@ -152,8 +286,48 @@ impl ToolCall {
             execution_time_ms,
             error: Some(error),
             output_format,
+            limit_violation: None,
+            injection_findings: Vec::new(),
+            #[cfg(feature = "audit")]
+            provenance: None,
+        }
+    }
+
+    /// Scrub secrets from the parameters, result and error
+    pub fn redact(&mut self, redactor: &crate::redaction::Redactor) {
+        redactor.redact_string(&mut self.parameters);
+        redactor.redact_string(&mut self.result);
+        if let Some(error) = &mut self.error {
+            redactor.redact_string(error);
+        }
+        for finding in &mut self.injection_findings {
+            redactor.redact_string(&mut finding.excerpt);
         }
     }
+
+    /// Record provenance for the call as it stands, signed if `signer` is given.
+    /// Attach it after redaction so the hash matches the stored record.
+    #[cfg(feature = "audit")]
+    pub fn attach_provenance(&mut self, signer: Option<&dyn crate::provenance::ProvenanceSigner>) {
+        let version = crate::tools::registry::local_tool(&self.tool_name)
+            .and_then(|tool| tool.version().map(str::to_string));
+        let mut provenance = crate::provenance::ToolProvenance::new(&self.tool_name, &self.parameters, &self.result, version);
+        if let Some(signer) = signer {
+            provenance.sign(signer);
+        }
+        self.provenance = Some(provenance);
+    }
+
+    /// Check that the call matches its provenance and, given a signer, that the
+    /// provenance was signed with its key
+    #[cfg(feature = "audit")]
+    pub fn verify_provenance(&self, signer: Option<&dyn crate::provenance::ProvenanceSigner>) -> Result<(), String> {
+        let provenance = self
+            .provenance
+            .as_ref()
+            .ok_or_else(|| format!("The '{}' call has no provenance", self.tool_name))?;
+        provenance.verify(&self.tool_name, &self.parameters, &self.result, signer)
+    }
 }
 
 // Agent Response structure with comprehensive metrics
@@ -185,8 +359,46 @@ pub struct AgentResponse {
     pub model_used: String,
     /// Temperature setting used
     pub temperature: f32,
+    /// Id of the task that produced this response
+    pub task_id: Option<String>,
+    /// Upstream trace id carried over from the task
+    pub trace_id: Option<String>,
+    /// End user carried over from the task
+    #[serde(default)]
+    pub user_id: Option<String>,
+    /// Tags carried over from the task
+    pub tags: Vec<String>,
+    /// Parsed data rows (header excluded) when the task requested CSV output
+    pub csv_rows: Option<Vec<Vec<String>>>,
+    /// Similarity of the output to the task's expected output, when requested
+    pub evaluation: Option<EvaluationResult>,
+    /// The model's unprocessed response, when processing changed it (e.g. JSON extraction)
+    #[serde(default)]
+    pub raw_content: Option<String>,
+    /// Post-processing stages applied to the output, in order
+    #[serde(default)]
+    pub output_stages: Vec<crate::agent::output_pipeline::OutputStage>,
+    /// Problems found in the last rejected output, when validation failed
+    #[serde(default)]
+    pub validation_report: Option<crate::task::validation_report::ValidationReport>,
+    /// Likely prompt injections found in tool results during the run
+    #[serde(default)]
+    pub injection_findings: Vec<crate::injection::InjectionFinding>,
+    /// Content policy rules the output broke when it was replaced by a refusal
+    #[serde(default)]
+    pub policy_violations: Vec<crate::agent::content_policy::PolicyViolation>,
+    /// Registered prompt versions in effect for the run, by prompt name; built-in
+    /// prompts are not listed
+    #[serde(default)]
+    pub prompt_versions: std::collections::BTreeMap<String, u32>,
+    /// Each LLM round of the run and what the model decided in it; not recorded
+    /// for streaming calls
+    #[serde(default)]
+    pub reasoning_trace: Option<crate::agent::reasoning_trace::ReasoningTrace>,
     /// Any error message if the task failed
     pub error: Option<String>,
+    /// Whether the task was cancelled before it completed
+    pub cancelled: bool,
     /// Additional metadata about the execution
     pub metadata: HashMap<String, serde_json::Value>,
     /// Timestamp when the response was generated
@@ -207,6 +419,7 @@ impl AgentResponse {
         output_format: String,
     ) -> Self {
         let tool_execution_time_ms = tool_calls.iter().map(|tc| tc.execution_time_ms).sum();
+        let injection_findings = tool_calls.iter().flat_map(|tc| tc.injection_findings.iter().cloned()).collect();
         Self {
             content,
             success: true,
@@ -221,7 +434,21 @@ impl AgentResponse {
             output_format,
             model_used,
             temperature,
+            task_id: None,
+            trace_id: None,
+            user_id: None,
+            tags: Vec::new(),
+            csv_rows: None,
+            evaluation: None,
+            raw_content: None,
+            output_stages: Vec::new(),
+            validation_report: None,
+            injection_findings,
+            policy_violations: Vec::new(),
+            prompt_versions: std::collections::BTreeMap::new(),
+            reasoning_trace: None,
             error: None,
+            cancelled: false,
             metadata: HashMap::new(),
             timestamp: chrono::Utc::now(),
         }
@@ -249,12 +476,56 @@ impl AgentResponse {
             output_format,
             model_used,
             temperature,
+            task_id: None,
+            trace_id: None,
+            user_id: None,
+            tags: Vec::new(),
+            csv_rows: None,
+            evaluation: None,
+            raw_content: None,
+            output_stages: Vec::new(),
+            validation_report: None,
+            injection_findings: Vec::new(),
+            policy_violations: Vec::new(),
+            prompt_versions: std::collections::BTreeMap::new(),
+            reasoning_trace: None,
             error: Some(error),
+            cancelled: false,
             metadata: HashMap::new(),
             timestamp: chrono::Utc::now(),
         }
     }
 
+    /// Create a response for a task that was cancelled
+    pub fn cancelled(
+        execution_time_ms: u64,
+        model_used: String,
+        temperature: f32,
+        output_format: String,
+    ) -> Self {
+        let mut response = Self::error(
+            AgentError::TaskCancelled.to_string(),
+            execution_time_ms,
+            model_used,
+            temperature,
+            output_format,
+        );
+        response.cancelled = true;
+        response
+    }
+
+    /// Copy the task's id, trace id, tags and metadata onto the response.
+    /// Execution metadata already present on the response takes precedence.
+    pub fn apply_task_context(&mut self, task: &crate::task::task::Task) {
+        self.task_id = Some(task.id.clone());
+        self.trace_id = task.trace_id.clone();
+        self.user_id = task.user_id.clone();
+        self.tags = task.tags.clone();
+        for (key, value) in &task.metadata {
+            self.metadata.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+    }
+
     /// Check if the response was successful
     pub fn is_success(&self) -> bool {
         self.success
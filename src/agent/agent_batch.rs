@@ -0,0 +1,136 @@
+use crate::agent::agent::{Agent, AgentResponse};
+use crate::agent::rate_limit::RateLimiter;
+use crate::task::task::Task;
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Options controlling batch execution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchOptions {
+    /// Maximum number of tasks in flight at once
+    pub max_concurrency: usize,
+    /// Shared request budget across all workers (None = unlimited)
+    pub requests_per_minute: Option<u32>,
+}
+
+impl BatchOptions {
+    pub fn new(max_concurrency: usize) -> Self {
+        Self {
+            max_concurrency: max_concurrency.max(1),
+            requests_per_minute: None,
+        }
+    }
+
+    pub fn with_rate_limit(mut self, requests_per_minute: u32) -> Self {
+        self.requests_per_minute = Some(requests_per_minute);
+        self
+    }
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        Self::new(4)
+    }
+}
+
+/// Aggregate metrics over a batch run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BatchMetrics {
+    pub total_tasks: usize,
+    pub successful_tasks: usize,
+    pub failed_tasks: usize,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub total_tokens: u64,
+    /// Wall-clock time for the whole batch
+    pub wall_clock_ms: u64,
+    /// Sum of per-task execution times
+    pub total_execution_time_ms: u64,
+    pub average_execution_time_ms: f64,
+    pub tool_usage: HashMap<String, u64>,
+}
+
+impl BatchMetrics {
+    pub fn from_responses(responses: &[AgentResponse], wall_clock_ms: u64) -> Self {
+        let mut metrics = Self {
+            total_tasks: responses.len(),
+            wall_clock_ms,
+            ..Self::default()
+        };
+
+        for response in responses {
+            if response.success {
+                metrics.successful_tasks += 1;
+            } else {
+                metrics.failed_tasks += 1;
+            }
+            metrics.input_tokens += response.input_tokens as u64;
+            metrics.output_tokens += response.output_tokens as u64;
+            metrics.total_tokens += response.total_tokens as u64;
+            metrics.total_execution_time_ms += response.execution_time_ms;
+            for tool in &response.tools_used {
+                *metrics.tool_usage.entry(tool.clone()).or_insert(0) += 1;
+            }
+        }
+
+        if metrics.total_tasks > 0 {
+            metrics.average_execution_time_ms =
+                metrics.total_execution_time_ms as f64 / metrics.total_tasks as f64;
+        }
+
+        metrics
+    }
+
+    pub fn success_rate(&self) -> f64 {
+        if self.total_tasks == 0 {
+            0.0
+        } else {
+            self.successful_tasks as f64 / self.total_tasks as f64
+        }
+    }
+}
+
+/// Responses of a batch run, in submission order, plus aggregate metrics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchResult {
+    pub responses: Vec<AgentResponse>,
+    pub metrics: BatchMetrics,
+}
+
+impl Agent {
+    /// Run many tasks with the default batch options; responses keep submission order
+    pub async fn call_batch(&mut self, tasks: Vec<Task>) -> Vec<AgentResponse> {
+        self.call_batch_with_options(tasks, BatchOptions::default()).await.responses
+    }
+
+    /// Run many tasks concurrently (bounded by `max_concurrency`) with an optional
+    /// shared rate limit, returning every response plus aggregate metrics
+    pub async fn call_batch_with_options(&mut self, tasks: Vec<Task>, options: BatchOptions) -> BatchResult {
+        let start_time = std::time::Instant::now();
+        let limiter = options.requests_per_minute.map(RateLimiter::per_minute);
+
+        let responses: Vec<AgentResponse> = {
+            let agent = &*self;
+            let limiter = limiter.as_ref();
+
+            stream::iter(tasks)
+                .map(|task| async move {
+                    if let Some(limiter) = limiter {
+                        limiter.acquire().await;
+                    }
+                    agent.execute_task(task).await
+                })
+                .buffered(options.max_concurrency.max(1))
+                .collect()
+                .await
+        };
+
+        for response in &responses {
+            self.update_performance_metrics_from_response(response);
+        }
+
+        let metrics = BatchMetrics::from_responses(&responses, start_time.elapsed().as_millis() as u64);
+        BatchResult { responses, metrics }
+    }
+}
@@ -2,11 +2,51 @@ use crate::agent::agent::{Agent, AgentModelConfig};
 use crate::agent::role::{AgentRole, AgentCapabilities, OutputFormat};
 use crate::agent::state::AgentState;
 use crate::agent::state::AgentContext;
+use crate::agent::state::ToolUsageTracker;
 use crate::agent::output_handler::OutputHandler;
-use merco_llmproxy::Tool;
+use crate::events::EventBus;
+use crate::task::queue::TaskQueue;
+use crate::agent::status::ActivityTracker;
+use crate::agent::tool_schema::ToolSchemas;
+use crate::redaction::Redactor;
+use crate::injection::InjectionGuard;
+use crate::agent::streaming::StreamingOptions;
+use crate::agent::profile::EnvironmentProfile;
+use crate::agent::prompt_registry::PromptRegistry;
+use crate::agent::provider::Provider;
+use crate::agent::huggingface::HuggingFaceProvider;
+use crate::agent::rotating_provider::RotatingProvider;
+use std::sync::Arc;
+use merco_llmproxy::{LlmProvider, Tool};
+
+/// Provider client for a model config. Hugging Face and TGI use the built-in
+/// client; everything else comes from merco-llmproxy, rebuilt when the key rotates.
+pub(crate) fn create_provider(llm_config: &AgentModelConfig) -> Arc<dyn LlmProvider + Send + Sync> {
+    let config = &llm_config.llm_config;
+    match &config.provider {
+        Provider::HuggingFace | Provider::Tgi(_) => {
+            let base_url = config.base_url.clone()
+                .or_else(|| config.provider.get_base_url())
+                .unwrap_or_default();
+            let provider = HuggingFaceProvider::new(&base_url, None);
+            Arc::new(match &config.api_key {
+                Some(credential) => provider.with_credential(credential.clone()),
+                None => provider,
+            })
+        }
+        _ => Arc::new(RotatingProvider::new(config.clone())),
+    }
+}
+
+/// Permits for the tasks an agent may run at once, shared by its clones.
+/// Tokio's semaphore hands out permits first-come first-served.
+pub(crate) fn task_slots(capabilities: &AgentCapabilities) -> Arc<tokio::sync::Semaphore> {
+    Arc::new(tokio::sync::Semaphore::new(capabilities.max_concurrent_tasks.max(1)))
+}
 
 impl Agent {
-    /// Create a new basic Agent
+    /// Create an agent. For anything beyond these parts (output format, a
+    /// default role or capabilities) use `Agent::builder`.
     pub fn new(
         name: String,
         description: String,
@@ -15,24 +55,15 @@ impl Agent {
         tools: Vec<Tool>,
         capabilities: AgentCapabilities,
     ) -> Self {
-        let provider = merco_llmproxy::get_provider(llm_config.to_llmproxy_config()).unwrap();
-        
-        Self {
-            id: uuid::Uuid::new_v4().to_string(),
-            name,
-            description,
-            role,
-            capabilities,
-            llm_config,
-            tools,
-            state: AgentState::new(),
-            context: AgentContext::new(),
-            output_handler: OutputHandler::new(OutputFormat::Text),
-            provider,
-        }
+        Self::from_parts(name, description, role, llm_config, tools, capabilities, OutputFormat::Text)
+    }
+
+    /// Start building an agent; unset parts get `AgentBuilder`'s defaults
+    pub fn builder(name: &str, llm_config: AgentModelConfig) -> AgentBuilder {
+        AgentBuilder::new(name, llm_config)
     }
 
-    /// Create a new Agent with custom output format
+    #[deprecated(since = "0.1.0", note = "use `Agent::builder(..).output_format(..)`")]
     pub fn new_with_output_format(
         name: String,
         description: String,
@@ -42,24 +73,10 @@ impl Agent {
         capabilities: AgentCapabilities,
         output_format: OutputFormat,
     ) -> Self {
-        let provider = merco_llmproxy::get_provider(llm_config.to_llmproxy_config()).unwrap();
-        
-        Self {
-            id: uuid::Uuid::new_v4().to_string(),
-            name,
-            description,
-            role,
-            capabilities,
-            llm_config,
-            tools,
-            state: AgentState::new(),
-            context: AgentContext::new(),
-            output_handler: OutputHandler::new(output_format),
-            provider,
-        }
+        Self::from_parts(name, description, role, llm_config, tools, capabilities, output_format)
     }
-    
-    /// Create a new enhanced Agent with full configuration
+
+    #[deprecated(since = "0.1.0", note = "use `Agent::builder(..)`")]
     pub fn new_enhanced(
         name: String,
         description: String,
@@ -69,24 +86,10 @@ impl Agent {
         capabilities: AgentCapabilities,
         output_format: Option<OutputFormat>,
     ) -> Self {
-        let provider = merco_llmproxy::get_provider(llm_config.to_llmproxy_config()).unwrap();
-        
-        Self {
-            id: uuid::Uuid::new_v4().to_string(),
-            name,
-            description,
-            role,
-            capabilities,
-            llm_config,
-            tools,
-            state: AgentState::new(),
-            context: AgentContext::new(),
-            output_handler: OutputHandler::new(output_format.unwrap_or(OutputFormat::Text)),
-            provider,
-        }
+        Self::from_parts(name, description, role, llm_config, tools, capabilities, output_format.unwrap_or(OutputFormat::Text))
     }
 
-    /// Create an Agent with a custom role
+    #[deprecated(since = "0.1.0", note = "use `Agent::builder(..).role(..)`")]
     pub fn with_custom_role(
         name: String,
         description: String,
@@ -96,22 +99,154 @@ impl Agent {
         capabilities: AgentCapabilities,
         output_format: Option<OutputFormat>,
     ) -> Self {
-        let provider = merco_llmproxy::get_provider(llm_config.to_llmproxy_config()).unwrap();
+        Self::from_parts(name, description, role, llm_config, tools, capabilities, output_format.unwrap_or(OutputFormat::Text))
+    }
+
+    /// The one place agents are put together
+    fn from_parts(
+        name: String,
+        description: String,
+        role: AgentRole,
+        llm_config: AgentModelConfig,
+        tools: Vec<Tool>,
+        capabilities: AgentCapabilities,
+        output_format: OutputFormat,
+    ) -> Self {
+        let provider = create_provider(&llm_config);
+        let task_slots = task_slots(&capabilities);
+        let tool_schemas = Arc::new(ToolSchemas::new(&tools));
         
-        Self {
+        let agent = Self {
             id: uuid::Uuid::new_v4().to_string(),
             name,
             description,
             role,
             capabilities,
             llm_config,
-            tools,
+            tools: Arc::new(tools),
+            tool_schemas,
             state: AgentState::new(),
             context: AgentContext::new(),
-            output_handler: OutputHandler::new(output_format.unwrap_or(OutputFormat::Text)),
+            output_handler: OutputHandler::new(output_format),
             provider,
+            task_queue: Arc::new(TaskQueue::new()),
+            activity: Arc::new(ActivityTracker::new()),
+            tool_usage: Arc::new(ToolUsageTracker::new()),
+            task_slots,
+            run_store: None,
+            output_scorer: None,
+            streaming_options: StreamingOptions::default(),
+            cost_tracker: None,
+            budgets: None,
+            #[cfg(feature = "audit")]
+            audit_log: None,
+            trace_recorder: None,
+            event_bus: EventBus::global(),
+            redactor: Redactor::global(),
+            injection_guard: Some(InjectionGuard::global()),
+            #[cfg(feature = "audit")]
+            provenance_signer: None,
+            tenant_id: None,
+            user_rate_limiter: None,
+            content_policy: None,
+            profile: None,
+            mock_provider: false,
+            prompt_registry: PromptRegistry::global(),
+        };
+        match EnvironmentProfile::from_env() {
+            Some(profile) => agent.with_profile(profile),
+            None => agent,
+        }
+    }
+}
+
+/// Builds an `Agent` from its required parts, the name and model, plus any
+/// of the optional ones. Everything else (run store, budgets, policies, ...) is
+/// set on the built agent with its `with_*` methods.
+///
+/// Defaults: empty description, a role named after the agent, no tools, one
+/// task at a time and text output.
+#[derive(Debug, Clone)]
+pub struct AgentBuilder {
+    name: String,
+    llm_config: AgentModelConfig,
+    description: String,
+    role: Option<AgentRole>,
+    tools: Vec<Tool>,
+    capabilities: Option<AgentCapabilities>,
+    output_format: OutputFormat,
+}
+
+impl AgentBuilder {
+    pub fn new(name: &str, llm_config: AgentModelConfig) -> Self {
+        Self {
+            name: name.to_string(),
+            llm_config,
+            description: String::new(),
+            role: None,
+            tools: Vec::new(),
+            capabilities: None,
+            output_format: OutputFormat::Text,
         }
     }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = description.to_string();
+        self
+    }
+
+    pub fn role(mut self, role: AgentRole) -> Self {
+        self.role = Some(role);
+        self
+    }
+
+    pub fn tools(mut self, tools: Vec<Tool>) -> Self {
+        self.tools = tools;
+        self
+    }
+
+    pub fn tool(mut self, tool: Tool) -> Self {
+        self.tools.push(tool);
+        self
+    }
+
+    pub fn capabilities(mut self, capabilities: AgentCapabilities) -> Self {
+        self.capabilities = Some(capabilities);
+        self
+    }
+
+    pub fn max_concurrent_tasks(mut self, max_concurrent_tasks: usize) -> Self {
+        let mut capabilities = self.capabilities.take().unwrap_or_else(|| default_capabilities(&self.output_format));
+        capabilities.max_concurrent_tasks = max_concurrent_tasks;
+        self.capabilities = Some(capabilities);
+        self
+    }
+
+    pub fn output_format(mut self, output_format: OutputFormat) -> Self {
+        self.output_format = output_format;
+        self
+    }
+
+    pub fn build(self) -> Agent {
+        let role = self.role.unwrap_or_else(|| AgentRole::new(self.name.clone(), self.description.clone()));
+        let capabilities = self.capabilities.unwrap_or_else(|| default_capabilities(&self.output_format));
+        Agent::from_parts(
+            self.name,
+            self.description,
+            role,
+            self.llm_config,
+            self.tools,
+            capabilities,
+            self.output_format,
+        )
+    }
+}
+
+fn default_capabilities(output_format: &OutputFormat) -> AgentCapabilities {
+    AgentCapabilities {
+        max_concurrent_tasks: 1,
+        supported_output_formats: vec![output_format.clone()],
+    }
 }
 
 // Helper trait to convert AgentModelConfig to merco_llmproxy LlmConfig
@@ -1,30 +1,227 @@
 use crate::task::task::Task;
+use crate::task::run_history::RunRecord;
+use crate::agent::replay::execute_tool;
+use crate::task::partial_json::{IncrementalJsonValidator, PartialJsonStatus};
+use crate::agent::scoring::{EvaluationResult, LexicalScorer, OutputScorer};
 use merco_llmproxy::{
     ChatMessage, CompletionKind, CompletionRequest,
-    execute_tool, traits::ChatMessageRole, StreamContentDelta,
+    traits::ChatMessageRole, StreamContentDelta,
 };
 use futures_util::StreamExt;
 use futures::stream::Stream;
+use std::collections::BTreeMap;
 use std::pin::Pin;
 use async_stream::stream;
 
-use crate::agent::agent::{Agent, AgentResponse};
-use crate::agent::streaming::{StreamingChunk, StreamingHandler, DefaultStreamingHandler};
+use crate::agent::agent::{Agent, AgentError, AgentModelConfig, AgentResponse};
+use crate::agent::streaming::{collect_response, is_transient_stream_error, StreamingChunk, StreamingHandler, StreamingResponse, DefaultStreamingHandler, StreamingMetricsRecorder, StreamingOptions, ChunkCoalescer, StreamPhase, AccumulatedText, TokenEstimator};
+use crate::agent::stream_buffer::{buffer_stream, BackgroundStreamingHandler, StreamBufferConfig};
+use crate::agent::stream_recording::StreamRecorder;
+use crate::agent::failover::track_endpoints;
+use crate::agent::output_handler::ProcessedOutput;
+use crate::agent::format_conversion::{detect_format, FormatConversion};
+use crate::events::{AgentEvent, EventKind};
+#[cfg(feature = "audit")]
+use crate::audit::{AuditAction, AuditOutcome};
+use crate::telemetry::traced;
+use crate::agent::context_window::{
+    fit_to_context_window, is_provider_context_overflow, overflow_error, shrink_after_overflow, MAX_CONTEXT_RECOVERIES,
+};
+use crate::injection::screen_tool_result;
+use crate::agent::reasoning_trace::{ReasoningTrace, RoundDecision, ValidationOutcome};
+use crate::task::validation_report::{ValidationIssueKind, ValidationReport};
 use serde_json;
 
+/// Tools for a provider request; none rather than an empty list for tool-less agents
+fn request_tools(tools: &[merco_llmproxy::Tool]) -> Option<Vec<merco_llmproxy::Tool>> {
+    (!tools.is_empty()).then(|| tools.to_vec())
+}
+
+/// Separate the limit violation from a failed tool call's message
+fn split_failure(
+    outcome: Result<String, crate::tools::ToolFailure>,
+) -> (Result<String, String>, Option<crate::tools::LimitViolation>) {
+    match outcome {
+        Ok(result) => (Ok(result), None),
+        Err(failure) => (Err(failure.message), failure.violation),
+    }
+}
+
+/// Estimated prompt tokens of `messages`
+pub(crate) fn estimate_input_tokens(messages: &[ChatMessage]) -> u32 {
+    let total_chars: usize = messages.iter()
+        .map(|msg| {
+            let content_len = msg.content.as_ref().unwrap_or(&String::new()).len();
+            // Add role and formatting overhead
+            content_len + 20
+        })
+        .sum();
+    // More accurate estimation: ~3.5 characters per token for English text
+    (total_chars as f64 / 3.5) as u32
+}
+
+/// Check the agent's budgets before a provider request, publishing any alerts.
+/// The estimate covers the prompt only since the response length isn't known yet.
+fn check_budgets(
+    budgets: Option<&crate::cost::BudgetManager>,
+    event_bus: &crate::events::EventBus,
+    agent_id: &str,
+    task: &Task,
+    model: &str,
+    input_tokens: u32,
+) -> Result<(), String> {
+    let Some(budgets) = budgets else {
+        return Ok(());
+    };
+    let estimate = budgets.tracker().estimate(model, input_tokens, 0);
+    let alerts = budgets
+        .check(agent_id, task.user_id.as_deref(), estimate)
+        .map_err(|e| AgentError::BudgetExceeded(e).to_string())?;
+    if event_bus.has_subscribers() {
+        for alert in alerts {
+            event_bus.publish(AgentEvent::new(agent_id, Some(task), EventKind::BudgetAlert {
+                scope: alert.budget.scope,
+                spent_usd: alert.spent_usd,
+                limit_usd: alert.budget.limit_usd,
+                fraction_used: alert.fraction_used,
+            }));
+        }
+    }
+    Ok(())
+}
+
+/// Append to the audit trail; a failing sink is reported but doesn't fail the run
+#[cfg(feature = "audit")]
+fn write_audit(
+    log: Option<&crate::audit::AuditLog>,
+    agent_id: &str,
+    task: &Task,
+    action: AuditAction,
+    target: &str,
+    params: &str,
+    outcome: AuditOutcome,
+) {
+    let Some(log) = log else {
+        return;
+    };
+    let actor = match &task.user_id {
+        Some(user_id) => format!("{}/{}", agent_id, user_id),
+        None => agent_id.to_string(),
+    };
+    if let Err(e) = log.record(&actor, Some(&task.id), action, target, params, outcome) {
+        eprintln!("Audit Log Error [{}]: {}", task.log_context(), e);
+    }
+}
+
+#[cfg(feature = "audit")]
+fn audit_outcome<T, E: ToString>(result: &Result<T, E>) -> AuditOutcome {
+    match result {
+        Ok(_) => AuditOutcome::Success,
+        Err(e) => AuditOutcome::Failure(e.to_string()),
+    }
+}
+
+/// Score a successful response against the task's expected output if the task asks for
+/// it, with the lexical scorer unless `scorer` is given
+async fn evaluate_with(scorer: Option<&dyn OutputScorer>, task: &Task, response: &mut AgentResponse) {
+    let (threshold, expected) = match (task.evaluation_threshold, &task.expected_output) {
+        (Some(threshold), Some(expected)) => (threshold, expected),
+        _ => return,
+    };
+
+    let default_scorer = LexicalScorer;
+    let scorer: &dyn OutputScorer = scorer.unwrap_or(&default_scorer);
+
+    match scorer.score(&response.content, expected).await {
+        Ok(score) => response.evaluation = Some(EvaluationResult::new(score, threshold, scorer.name())),
+        Err(e) => {
+            response.metadata.insert("evaluation_error".to_string(), serde_json::Value::String(e));
+        }
+    }
+}
+
+/// Why processing a task failed; validation failures carry the last report
+struct ProcessingError {
+    message: String,
+    validation: Option<ValidationReport>,
+}
+
 impl Agent {
     /// Execute a task and return comprehensive response with metrics
     pub async fn call(&mut self, task: Task) -> AgentResponse {
+        let response = self.execute_task(task).await;
+        
+        // Update agent performance metrics
+        self.update_performance_metrics_from_response(&response);
+        response
+    }
+
+    /// Run a task on the Tokio runtime as `call_concurrent` does and return its join
+    /// handle. The spawned task holds a clone of the `Arc`, so it may outlive the caller.
+    pub fn spawn(self: &std::sync::Arc<Self>, task: Task) -> tokio::task::JoinHandle<AgentResponse> {
+        let agent = std::sync::Arc::clone(self);
+        tokio::spawn(async move { agent.execute_task(task).await })
+    }
+
+    /// Run a task without touching agent state, so several can run against a shared reference
+    pub(crate) async fn execute_task(&self, mut task: Task) -> AgentResponse {
+        if let Err(error) = self.scope_to_tenant(&mut task) {
+            return self.refusal(&task, error);
+        }
+        if let Err(error) = self.admit_user(&task).await {
+            return self.refusal(&task, error);
+        }
+        // Wait for one of the agent's `max_concurrent_tasks` slots; the wait is
+        // reported as `queued_ms` and not counted in the execution time
+        let queued_at = std::time::Instant::now();
+        let _slot = self.task_slots.acquire().await;
+        let queued_ms = queued_at.elapsed().as_millis() as u64;
         let start_time = std::time::Instant::now();
+        if !self.activity.start(&task) {
+            return self.refusal(&task, AgentError::ShuttingDown);
+        }
+
+        // Race the work against the task's cancellation token; dropping the
+        // processing future aborts any in-flight provider request
+        let cancel_token = task.cancel_handle();
+        let output_format = task.output_format.clone();
+        task.callbacks.notify_start(&task);
+        self.emit(Some(&task), EventKind::TaskStarted {
+            description: task.description.clone(),
+            streaming: false,
+        });
+        let mut reasoning_trace = ReasoningTrace::new();
+        let (outcome, endpoints) = if cancel_token.is_cancelled() {
+            (None, Vec::new())
+        } else {
+            let work = track_endpoints(async {
+                tokio::select! {
+                    result = self.process_task_with_metrics(&task, &mut reasoning_trace) => Some(result),
+                    _ = cancel_token.cancelled() => None,
+                }
+            });
+            traced!(
+                work,
+                "merco.agent.task",
+                agent_id = %self.id,
+                task_id = %task.id,
+                trace_id = ?task.trace_id,
+                streaming = false
+            ).await
+        };
         
-        match self.process_task_with_metrics(task.clone()).await {
-            Ok((content, input_tokens, output_tokens, tools_used, tool_calls)) => {
+        let mut response = match outcome {
+            Some(Ok((processed, input_tokens, output_tokens, tools_used, tool_calls))) => {
                 let execution_time = start_time.elapsed();
+                let content = processed.content;
                 
                 // Determine output format
                 let output_format = format!("{:?}", task.output_format);
                 
-                let response = AgentResponse::success(
+                // Expose parsed rows for CSV tasks
+                let csv_rows = task.parse_csv_rows(&content);
+
+                let mut response = AgentResponse::success(
                     content,
                     execution_time.as_millis() as u64,
                     input_tokens,
@@ -35,36 +232,140 @@ impl Agent {
                     tool_calls,
                     output_format,
                 );
-                
-                // Update agent performance metrics
-                self.update_performance_metrics_from_response(&response);
+                response.csv_rows = csv_rows;
+                if processed.raw != response.content {
+                    response.raw_content = Some(processed.raw);
+                }
+                response.output_stages = processed.stages;
+                response.policy_violations = processed.policy_violations;
+                response.metadata.insert("produced_format".to_string(), serde_json::json!(processed.produced_format));
+                if let Some(conversion) = processed.converted {
+                    response.metadata.insert("format_conversion".to_string(), serde_json::json!(conversion));
+                }
+                self.evaluate_response(&task, &mut response).await;
                 response
             }
-            Err(error) => {
+            Some(Err(error)) => {
                 let execution_time = start_time.elapsed();
                 
                 // Determine output format for error case
                 let output_format = format!("{:?}", task.output_format);
                 
-                let response = AgentResponse::error(
-                    error,
+                let mut response = AgentResponse::error(
+                    error.message,
                     execution_time.as_millis() as u64,
                     self.llm_config.model_name.clone(),
                     self.llm_config.temperature,
                     output_format,
                 );
-                
-                // Update agent performance metrics
-                self.update_performance_metrics_from_response(&response);
+                response.validation_report = error.validation;
                 response
             }
+            None => AgentResponse::cancelled(
+                start_time.elapsed().as_millis() as u64,
+                self.llm_config.model_name.clone(),
+                self.llm_config.temperature,
+                format!("{:?}", task.output_format),
+            ),
+        };
+
+        // Carry the task's correlation data onto the response
+        response.apply_task_context(&task);
+        response.prompt_versions = self.prompt_versions(&task);
+        if !reasoning_trace.rounds.is_empty() {
+            response.reasoning_trace = Some(reasoning_trace);
+        }
+        response.metadata.insert("queued_ms".to_string(), serde_json::json!(queued_ms));
+        if !endpoints.is_empty() {
+            // Which failover endpoints served the provider calls of this run
+            response.metadata.insert("endpoints".to_string(), serde_json::json!(endpoints));
+        }
+        
+        self.record_run(&task, &response);
+        task.callbacks.notify_complete(&response);
+        self.activity.finish(
+            &task.id,
+            response.success,
+            response.execution_time_ms,
+            response.total_tokens,
+            response.error.as_deref(),
+        );
+        self.emit(Some(&task), EventKind::TaskCompleted {
+            success: response.success,
+            cancelled: response.cancelled,
+            execution_time_ms: response.execution_time_ms,
+            total_tokens: response.total_tokens,
+            error: response.error.clone(),
+        });
+        response
+    }
+
+    /// Give a task without a tenant the agent's and refuse one of another tenant
+    fn scope_to_tenant(&self, task: &mut Task) -> Result<(), AgentError> {
+        task.tenant_id = crate::tenant::resolve_tenant(self.tenant_id.as_ref(), task.tenant_id.as_ref())
+            .map_err(AgentError::TenantMismatch)?;
+        Ok(())
+    }
+
+    /// Wait for or refuse a task of a user over their rate limit
+    async fn admit_user(&self, task: &Task) -> Result<(), AgentError> {
+        match (&self.user_rate_limiter, &task.user_id) {
+            (Some(limiter), Some(user_id)) => limiter
+                .acquire(task.tenant_id.as_ref().map(|tenant| tenant.as_str()), user_id)
+                .await
+                .map_err(AgentError::RateLimited),
+            _ => Ok(()),
         }
     }
 
+    /// Response for a task refused before it started
+    fn refusal(&self, task: &Task, error: AgentError) -> AgentResponse {
+        let mut response = AgentResponse::error(
+            error.to_string(),
+            0,
+            self.llm_config.model_name.clone(),
+            self.llm_config.temperature,
+            format!("{:?}", task.output_format),
+        );
+        if let AgentError::RateLimited(limited) = &error {
+            response.metadata.insert("rate_limit".to_string(), serde_json::json!(limited));
+        }
+        response.apply_task_context(task);
+        response
+    }
+
+    /// Publish a lifecycle event on the agent's event bus
+    pub(crate) fn emit(&self, task: Option<&Task>, kind: EventKind) {
+        if self.profile.as_ref().is_some_and(|profile| profile.verbose_events) {
+            eprintln!("[{}] {:?}", self.name, kind);
+        }
+        if self.event_bus.has_subscribers() {
+            self.event_bus.publish(AgentEvent::new(&self.id, task, kind));
+        }
+    }
+
+    /// The audit log, when the security context has audit logging enabled
+    #[cfg(feature = "audit")]
+    fn active_audit_log(&self) -> Option<std::sync::Arc<crate::audit::AuditLog>> {
+        self.audit_log
+            .clone()
+            .filter(|_| self.context.environment.security_context.audit_logging)
+    }
+
+    /// Execute a task through a shared reference, so up to
+    /// `capabilities.max_concurrent_tasks` calls run at once on one agent (clones
+    /// share the limit). Further calls wait their turn in arrival order. Each
+    /// response carries its own timing, token and tool metrics plus `queued_ms`;
+    /// the agent's aggregate `PerformanceMetrics` are only updated by `call`.
+    pub async fn call_concurrent(&self, task: Task) -> AgentResponse {
+        self.execute_task(task).await
+    }
+
     /// Execute a task with user context
-    pub async fn call_with_user(&mut self, task: Task, _user_id: Option<String>) -> AgentResponse {
-        // For now, just call the regular call method
-        // User context can be added to the task description if needed
+    pub async fn call_with_user(&mut self, mut task: Task, user_id: Option<String>) -> AgentResponse {
+        if user_id.is_some() {
+            task.user_id = user_id;
+        }
         self.call(task).await
     }
 
@@ -98,24 +399,40 @@ impl Agent {
     }
 
     /// Core task processing logic with metrics tracking
-    async fn process_task_with_metrics(&self, task: Task) -> Result<(String, u32, u32, Vec<String>, Vec<crate::agent::agent::ToolCall>), String> {
+    async fn process_task_with_metrics(&self, task: &Task, trace: &mut ReasoningTrace) -> Result<(ProcessedOutput, u32, u32, Vec<String>, Vec<crate::agent::agent::ToolCall>), ProcessingError> {
         const MAX_RETRIES: usize = 3;
         let mut tools_used = Vec::new();
         let mut all_tool_calls = Vec::new();
+        // Built once so correction messages carry over to the next attempt
+        let mut messages = self.build_initial_messages(task);
+        let mut last_report = None;
         
         for attempt in 1..=MAX_RETRIES {
-            let mut messages = self.build_initial_messages(&task);
+            let checkpoint = messages.len();
             
-            let (raw_result, input_tokens, output_tokens, tool_calls) = match self.execute_with_llm_with_metrics(&mut messages).await {
+            let (raw_result, input_tokens, output_tokens) = match self.execute_with_llm_with_metrics(&mut messages, task, attempt, trace).await {
                 Ok((result, input_toks, output_toks, used_tools, tool_calls)) => {
                     tools_used.extend(used_tools);
                     all_tool_calls.extend(tool_calls);
-                    (result, input_toks, output_toks, all_tool_calls.clone())
+                    (result, input_toks, output_toks)
                 }
                 Err(e) => {
+                    // Retrying can't get past a spending cap or an oversized prompt
+                    if crate::cost::budget::is_budget_refusal(&e) || crate::agent::context_window::is_context_overflow(&e) {
+                        return Err(ProcessingError {
+                            message: e,
+                            validation: last_report,
+                        });
+                    }
                     if attempt == MAX_RETRIES {
-                        return Err(format!("LLM execution failed after {} attempts: {}", MAX_RETRIES, e));
+                        return Err(ProcessingError {
+                            message: format!("LLM execution failed after {} attempts: {}", MAX_RETRIES, e),
+                            validation: last_report,
+                        });
                     }
+                    task.callbacks.notify_retry(attempt, &e);
+                    // Drop the half-finished tool exchange of the failed request
+                    messages.truncate(checkpoint);
                     continue;
                 }
             };
@@ -135,55 +452,233 @@ impl Agent {
             };
 
             // Use the appropriate format for validation
-            match self.output_handler.process_output(&raw_result, Some(use_format)) {
-                Ok(processed_result) => return Ok((processed_result, input_tokens, output_tokens, tools_used, tool_calls)),
-                Err(validation_error) => {
+            // Format-level checks first, then the task's own schema (JSON fields, CSV columns, XML elements)
+            let processed = match self.output_handler.process(&raw_result, Some(use_format)) {
+                Err(error) if self.output_handler.conversion == FormatConversion::LlmAssisted => {
+                    self.convert_with_llm(&raw_result, use_format).await.map_err(|_| error)
+                }
+                processed => processed,
+            };
+            let validation = match processed {
+                Ok(processed) => {
+                    let report = task.validation_report(&processed.content);
+                    if report.is_valid() { Ok(processed) } else { Err(report) }
+                }
+                Err(e) => Err(ValidationReport::single(ValidationIssueKind::Format, e)),
+            }
+            .map(|mut processed| {
+                // Code tasks return just the code
+                if let Some(code) = task.extract_code(&processed.content) {
+                    processed.content = code;
+                }
+                processed
+            });
+
+            // Custom guards run only once the output has the right shape
+            let validation = match validation {
+                Ok(processed) => task.run_guards(&processed.content).await
+                    .map(|_| processed)
+                    .map_err(|e| ValidationReport::single(ValidationIssueKind::Guard, e)),
+                Err(report) => Err(report),
+            };
+
+            // Post-processing runs only on output that passed every check
+            let validation = validation.and_then(|processed| {
+                self.output_handler.finalize(processed)
+                    .map_err(|e| ValidationReport::single(ValidationIssueKind::Format, e))
+            });
+
+            // The content policy sees the output as it would be returned
+            let validation = validation.and_then(|processed| self.apply_content_policy(processed));
+
+            trace.record_validation(match &validation {
+                Ok(_) => ValidationOutcome::Passed,
+                Err(report) => ValidationOutcome::Rejected { report: report.clone() },
+            });
+            match validation {
+                Ok(processed_result) => return Ok((processed_result, input_tokens, output_tokens, tools_used, all_tool_calls)),
+                Err(report) => {
+                    let summary = report.summary();
                     if attempt == MAX_RETRIES {
-                        return Err(format!("Output validation failed after {} attempts: {}", MAX_RETRIES, validation_error));
+                        return Err(ProcessingError {
+                            message: format!("Output validation failed after {} attempts: {}", MAX_RETRIES, summary),
+                            validation: Some(report),
+                        });
                     }
+                    self.emit(Some(task), EventKind::ValidationFailed {
+                        attempt,
+                        report: report.clone(),
+                    });
+                    task.callbacks.notify_retry(attempt, &summary);
                     
+                    // Keep the rejected answer so the correction refers to it
+                    messages.push(ChatMessage::new(
+                        ChatMessageRole::Assistant,
+                        Some(raw_result),
+                        None,
+                        None,
+                    ));
                     messages.push(ChatMessage::new(
                         ChatMessageRole::User,
-                        Some(format!("Your previous response was invalid: {}. Please provide a corrected response in the required format.", validation_error)),
+                        Some(self.correction_prompt(&report)),
                         None,
                         None,
                     ));
+                    last_report = Some(report);
                 }
             }
         }
         
-        Err("Maximum retry attempts exceeded".to_string())
+        Err(ProcessingError {
+            message: "Maximum retry attempts exceeded".to_string(),
+            validation: last_report,
+        })
+    }
+
+    /// Refuse or reject output that breaks the agent's content policy
+    fn apply_content_policy(&self, mut processed: ProcessedOutput) -> Result<ProcessedOutput, ValidationReport> {
+        let Some(policy) = &self.content_policy else {
+            return Ok(processed);
+        };
+        let violations = policy.check(&processed.content);
+        if violations.is_empty() {
+            return Ok(processed);
+        }
+        if let Some(refusal) = policy.refusal(&violations) {
+            processed.content = refusal;
+            processed.policy_violations = violations;
+            return Ok(processed);
+        }
+        let mut report = ValidationReport::new();
+        for violation in violations {
+            let message = format!(
+                "The response breaks the content policy rule '{}' (\"{}\"); rewrite it without that content",
+                violation.rule, violation.matched
+            );
+            report.push(None, ValidationIssueKind::Policy { rule: violation.rule }, message);
+        }
+        Err(report)
+    }
+
+    /// Ask the model to rewrite a response in `format`
+    async fn convert_with_llm(&self, raw_output: &str, format: &crate::agent::role::OutputFormat) -> Result<ProcessedOutput, String> {
+        let prompt = format!(
+            "Rewrite the following content without changing its meaning. {}\n\nContent:\n{}",
+            self.get_format_instruction(format),
+            raw_output
+        );
+        let request = CompletionRequest::new(
+            vec![ChatMessage::new(ChatMessageRole::User, Some(prompt), None, None)],
+            self.llm_config.model_name.clone(),
+            Some(self.llm_config.temperature),
+            Some(self.llm_config.max_tokens),
+            None,
+        );
+        let converted = match self.provider.completion(request).await.map_err(|e| e.to_string())?.kind {
+            CompletionKind::Message { content } => content,
+            CompletionKind::ToolCall { .. } => return Err("Format conversion returned a tool call".to_string()),
+        };
+        self.output_handler.process_converted(&converted, raw_output, format, detect_format(raw_output), FormatConversion::LlmAssisted)
     }
 
     /// Core LLM execution logic with metrics tracking
-    async fn execute_with_llm_with_metrics(&self, messages: &mut Vec<ChatMessage>) -> Result<(String, u32, u32, Vec<String>, Vec<crate::agent::agent::ToolCall>), String> {
+    async fn execute_with_llm_with_metrics(
+        &self,
+        messages: &mut Vec<ChatMessage>,
+        task: &Task,
+        attempt: usize,
+        trace: &mut ReasoningTrace,
+    ) -> Result<(String, u32, u32, Vec<String>, Vec<crate::agent::agent::ToolCall>), String> {
         let mut tools_used = Vec::new();
         let mut tool_calls = Vec::new();
         let mut total_input_tokens = 0;
         let mut total_output_tokens = 0;
+        let mut round = 0;
+        let mut context_recoveries = 0;
         
         loop {
+            fit_to_context_window(messages, &self.llm_config, self.tool_schemas.estimated_tokens())?;
+            let prompt_tokens = self.count_input_tokens(messages);
+            check_budgets(
+                self.budgets.as_deref(),
+                &self.event_bus,
+                &self.id,
+                task,
+                &self.llm_config.model_name,
+                prompt_tokens,
+            )?;
             let request = CompletionRequest::new(
                 messages.clone(),
                 self.llm_config.model_name.clone(),
                 Some(self.llm_config.temperature),
                 Some(self.llm_config.max_tokens),
-                Some(self.tools.clone()),
+                request_tools(&self.tools),
+            );
+            round += 1;
+            self.emit(Some(task), EventKind::LlmRequest {
+                model: self.llm_config.model_name.clone(),
+                round,
+                message_count: messages.len(),
+            });
+
+            let round_start = std::time::Instant::now();
+            #[cfg(feature = "audit")]
+            let audit_log = self.active_audit_log();
+            // Only serialized for the audit trail
+            #[cfg(feature = "audit")]
+            let request_params = audit_log.as_ref()
+                .map(|_| serde_json::to_string(&request.messages).unwrap_or_default())
+                .unwrap_or_default();
+            let completion = traced!(
+                self.provider.completion(request),
+                "merco.provider.request",
+                agent_id = %self.id,
+                task_id = %task.id,
+                model = %self.llm_config.model_name,
+                round
+            ).await;
+            #[cfg(feature = "audit")]
+            write_audit(
+                audit_log.as_deref(),
+                &self.id,
+                task,
+                AuditAction::OutboundRequest,
+                &self.llm_config.model_name,
+                &request_params,
+                audit_outcome(&completion),
             );
 
-            match self.provider.completion(request).await {
+            match completion {
                 Ok(response) => {
-                    // Count tokens from messages and response
-                    let input_tokens = self.count_input_tokens(messages);
-                    total_input_tokens += input_tokens;
+                    total_input_tokens += prompt_tokens;
                     
                     match response.kind {
                         CompletionKind::Message { content } => {
                             let output_tokens = self.count_output_tokens(&content);
                             total_output_tokens += output_tokens;
+                            trace.push(
+                                attempt,
+                                round,
+                                messages,
+                                prompt_tokens,
+                                RoundDecision::Answer { output_tokens, validation: None },
+                                round_start.elapsed().as_millis() as u64,
+                                &self.redactor,
+                            );
                             return Ok((content, total_input_tokens, total_output_tokens, tools_used, tool_calls));
                         }
                         CompletionKind::ToolCall { tool_calls: llm_tool_calls } => {
+                            trace.push(
+                                attempt,
+                                round,
+                                messages,
+                                prompt_tokens,
+                                RoundDecision::ToolCalls {
+                                    tools: llm_tool_calls.iter().map(|call| call.function.name.clone()).collect(),
+                                },
+                                round_start.elapsed().as_millis() as u64,
+                                &self.redactor,
+                            );
                             messages.push(ChatMessage::new(
                                 ChatMessageRole::Assistant,
                                 None,
@@ -192,23 +687,47 @@ impl Agent {
                             ));
                             
                             for call in llm_tool_calls {
+                                // Skip remaining tool work once the task is cancelled
+                                if task.cancel_token.is_cancelled() {
+                                    return Err(crate::agent::agent::AgentError::TaskCancelled.to_string());
+                                }
+
                                 let tool_name = call.function.name.clone();
                                 let tool_args = call.function.arguments.clone();
                                 tools_used.push(tool_name.clone());
                                 
                                 // Track tool execution time
                                 let tool_start = std::time::Instant::now();
-                                let (tool_result_content, tool_error) = match execute_tool(&tool_name, &tool_args) {
+                                let (tool_outcome, limit_violation) = split_failure(traced!(
+                                    execute_tool(&tool_name, &tool_args, task.inputs.clone(), &self.context.environment),
+                                    "merco.tool.execute",
+                                    agent_id = %self.id,
+                                    task_id = %task.id,
+                                    tool = %tool_name
+                                ).await);
+                                #[cfg(feature = "audit")]
+                                write_audit(
+                                    audit_log.as_deref(),
+                                    &self.id,
+                                    task,
+                                    AuditAction::ToolExecution,
+                                    &tool_name,
+                                    &tool_args,
+                                    audit_outcome(&tool_outcome),
+                                );
+                                let (tool_result_content, tool_error) = match tool_outcome {
                                     Ok(result) => (result, None),
                                     Err(e) => {
-                                        eprintln!("Tool Execution Error: {}", e);
+                                        eprintln!("Tool Execution Error [{}]: {}", task.log_context(), self.redactor.redact(&e));
                                         (String::new(), Some(e))
                                     }
                                 };
                                 let tool_execution_time = tool_start.elapsed().as_millis() as u64;
+                                let (prompt_content, injection_findings) =
+                                    screen_tool_result(self.injection_guard.as_deref(), &tool_name, &tool_result_content).await;
                                 
                                 // Create detailed tool call information
-                                let tool_call = if let Some(error) = tool_error {
+                                let mut tool_call = if let Some(error) = tool_error {
                                     crate::agent::agent::ToolCall::with_error(
                                         tool_name.clone(),
                                         tool_args,
@@ -225,11 +744,22 @@ impl Agent {
                                         "text".to_string(), // Default format
                                     )
                                 };
+                                tool_call.limit_violation = limit_violation;
+                                tool_call.injection_findings = injection_findings;
+                                tool_call.redact(&self.redactor);
+                                #[cfg(feature = "audit")]
+                                tool_call.attach_provenance(self.provenance_signer.as_deref());
+                                task.callbacks.notify_tool_call(&tool_call);
+                                self.emit(Some(task), EventKind::ToolExecuted {
+                                    tool: tool_call.tool_name.clone(),
+                                    execution_time_ms: tool_call.execution_time_ms,
+                                    success: tool_call.error.is_none(),
+                                });
                                 tool_calls.push(tool_call);
                                 
                                 messages.push(ChatMessage::new(
                                     ChatMessageRole::Tool,
-                                    Some(tool_result_content),
+                                    Some(prompt_content),
                                     None,
                                     Some(call.id),
                                 ));
@@ -237,22 +767,43 @@ impl Agent {
                         }
                     }
                 },
-                Err(e) => return Err(e.to_string()),
+                Err(e) => {
+                    let message = e.to_string();
+                    let duration_ms = round_start.elapsed().as_millis() as u64;
+                    if !is_provider_context_overflow(&message) {
+                        let error = self.redactor.redact(&message).into_owned();
+                        trace.push(attempt, round, messages, prompt_tokens, RoundDecision::Failed { error }, duration_ms, &self.redactor);
+                        return Err(message);
+                    }
+                    // Recorded before shrinking, so the round shows what was sent
+                    trace.push(
+                        attempt,
+                        round,
+                        messages,
+                        prompt_tokens,
+                        RoundDecision::ContextOverflow { recovered: false },
+                        duration_ms,
+                        &self.redactor,
+                    );
+                    // Shorten the history and resend rather than failing the task
+                    let recovered = context_recoveries < MAX_CONTEXT_RECOVERIES && shrink_after_overflow(messages);
+                    trace.record_overflow_recovery(recovered);
+                    if !recovered {
+                        return Err(overflow_error(&self.llm_config, &message));
+                    }
+                    context_recoveries += 1;
+                    eprintln!(
+                        "Prompt too long for {} [{}]; retrying with shortened history ({}/{})",
+                        self.llm_config.model_name, task.log_context(), context_recoveries, MAX_CONTEXT_RECOVERIES
+                    );
+                }
             }
         }
     }
 
     /// Count input tokens from messages
     fn count_input_tokens(&self, messages: &[ChatMessage]) -> u32 {
-        let total_chars: usize = messages.iter()
-            .map(|msg| {
-                let content_len = msg.content.as_ref().unwrap_or(&String::new()).len();
-                // Add role and formatting overhead
-                content_len + 20
-            })
-            .sum();
-        // More accurate estimation: ~3.5 characters per token for English text
-        (total_chars as f64 / 3.5) as u32
+        estimate_input_tokens(messages) + self.tool_schemas.estimated_tokens()
     }
 
     /// Count output tokens from response content
@@ -261,257 +812,896 @@ impl Agent {
         (content.len() as f64 / 3.5) as u32
     }
 
+    /// Score a successful response against the task's expected output if the task asks for it
+    async fn evaluate_response(&self, task: &Task, response: &mut AgentResponse) {
+        evaluate_with(self.output_scorer.as_deref(), task, response).await
+    }
+
+    /// Persist the run to the configured run store, if any
+    fn record_run(&self, task: &Task, response: &AgentResponse) {
+        self.run_sink().record(task, response)
+    }
+
+    /// Where finished runs are persisted, detached from the agent for streams to own
+    fn run_sink(&self) -> RunSink {
+        RunSink {
+            agent_id: self.id.clone(),
+            agent_name: self.name.clone(),
+            cost_tracker: self.cost_tracker.clone(),
+            trace_recorder: self.trace_recorder.clone(),
+            run_store: self.run_store.clone(),
+            redactor: self.redactor.clone(),
+        }
+    }
+
     /// Update performance metrics from AgentResponse
-    fn update_performance_metrics_from_response(&mut self, response: &AgentResponse) {
+    pub(crate) fn update_performance_metrics_from_response(&mut self, response: &AgentResponse) {
+        for call in &response.tool_calls {
+            self.tool_usage.record(&call.tool_name, call.error.is_none(), call.execution_time_ms as f64);
+        }
+        self.record_task_metrics(response);
+    }
+
+    /// Task totals only; streams record their tool usage as the tools run
+    fn record_task_metrics(&mut self, response: &AgentResponse) {
         self.state.performance_metrics.record_task_completion(
             response.success,
             response.execution_time_ms as f64,
             response.total_tokens,
         );
+        self.state.performance_metrics.uptime_seconds = self.activity.uptime_seconds();
+        self.state.performance_metrics.tool_usage_stats = self.tool_usage.snapshot();
     }
 
     // ===== STREAMING METHODS =====
 
     /// Execute a task with streaming response - returns a stream of chunks
-    pub async fn call_stream(&mut self, task: Task) -> Pin<Box<dyn Stream<Item = Result<StreamingChunk, String>> + Send + '_>> {
+    pub async fn call_stream(&self, task: Task) -> Pin<Box<dyn Stream<Item = Result<StreamingChunk, String>> + Send + 'static>> {
         let handler = DefaultStreamingHandler;
         self.call_stream_with_handler(task, handler).await
     }
 
     /// Execute a task with streaming response and custom handler - FULL tool call support
+    ///
+    /// The stream can be stopped cooperatively with the task's cancellation token
+    /// (`task.cancel_handle()`), which tears down the provider stream and skips pending
+    /// tool executions. Dropping the returned stream also closes the provider connection.
     pub async fn call_stream_with_handler<H: StreamingHandler + Send + Sync + 'static>(
-        &mut self, 
+        &self,
         task: Task, 
         handler: H
     ) -> Pin<Box<dyn Stream<Item = Result<StreamingChunk, String>> + Send + 'static>> {
+        let streaming_options = self.streaming_options.clone();
+        self.call_stream_with_options(task, handler, streaming_options).await
+    }
+
+    /// Like `call_stream_with_handler`, with streaming options (retries, idle timeout,
+    /// chunk coalescing) overridden for this stream only
+    pub async fn call_stream_with_options<H: StreamingHandler + Send + Sync + 'static>(
+        &self,
+        mut task: Task,
+        handler: H,
+        streaming_options: StreamingOptions,
+    ) -> Pin<Box<dyn Stream<Item = Result<StreamingChunk, String>> + Send + 'static>> {
+        let admitted = match self.scope_to_tenant(&mut task) {
+            Ok(()) if self.activity.is_shutting_down() => Err(AgentError::ShuttingDown),
+            Ok(()) => self.admit_user(&task).await,
+            Err(error) => Err(error),
+        };
+        if let Err(error) = admitted {
+            let message = error.to_string();
+            handler.handle_error(message.clone());
+            return Box::pin(futures::stream::once(async move { Err(message) }));
+        }
+        // Held by the stream until it ends, so streams count against the concurrency limit
+        let slot = self.task_slots.clone().acquire_owned().await.ok();
+        if !self.activity.start(&task) {
+            let message = AgentError::ShuttingDown.to_string();
+            handler.handle_error(message.clone());
+            return Box::pin(futures::stream::once(async move { Err(message) }));
+        }
         let messages = self.build_initial_messages(&task);
+        let prompt_built = StreamPhase::PromptBuilt {
+            message_count: messages.len(),
+            estimated_tokens: self.count_input_tokens(&messages),
+        };
+        let log_context = task.log_context();
+        let callbacks = task.callbacks.clone();
+        let task_inputs = task.inputs.clone();
+        let cancel_token = task.cancel_handle();
+        let output_format = task.output_format.clone();
+        task.callbacks.notify_start(&task);
+        let task_snapshot = task.clone();
         let provider = self.provider.clone();
+        let budgets = self.budgets.clone();
+        #[cfg(feature = "audit")]
+        let audit_log = self.active_audit_log();
         let llm_config = self.llm_config.clone();
         let tools = self.tools.clone();
+        let redactor = self.redactor.clone();
+        let injection_guard = self.injection_guard.clone();
+        #[cfg(feature = "audit")]
+        let provenance_signer = self.provenance_signer.clone();
+        let content_policy = self.content_policy.clone();
+        let output_scorer = self.output_scorer.clone();
+        let prompt_versions = self.prompt_versions(&task);
+        let environment = self.context.environment.clone();
+        let tool_tokens = self.tool_schemas.estimated_tokens();
+        let idle_timeout = streaming_options.idle_timeout_ms.map(std::time::Duration::from_millis);
         
-        Box::pin(stream! {
+        let event_bus = self.event_bus.clone();
+        let tool_usage = self.tool_usage.clone();
+        let agent_id = self.id.clone();
+        self.emit(Some(&task), EventKind::TaskStarted {
+            description: task.description.clone(),
+            streaming: true,
+        });
+        // Reports the end of the run, however it ends
+        let mut finalizer = StreamFinalizer {
+            sink: self.run_sink(),
+            task: task_snapshot.clone(),
+            agent_id: agent_id.clone(),
+            model_name: self.llm_config.model_name.clone(),
+            temperature: self.llm_config.temperature,
+            activity: self.activity.clone(),
+            event_bus: event_bus.clone(),
+            started: std::time::Instant::now(),
+            finished: false,
+        };
+
+        let chunks = stream! {
+            let _slot = slot;
             let mut current_messages = messages;
-            let mut accumulated_content = String::new();
+            // Sized for a full-length answer (~4 bytes per token) so the buffer rarely regrows
+            let mut accumulated_content = AccumulatedText::with_capacity(llm_config.max_tokens as usize * 4);
             let mut total_tokens = 0;
+            let mut input_tokens = 0;
+            let mut output_tokens = 0;
             let mut tools_used = Vec::new();
             let mut all_tool_calls = Vec::new();
+            // Tool calls of every round, for the final AgentResponse
+            let mut run_tool_calls = Vec::new();
+            let stream_started = std::time::Instant::now();
+            let mut coalescer = streaming_options.coalescing.clone().map(ChunkCoalescer::new);
+            let mut round = 0;
+            
+            handler.handle_progress(prompt_built.clone());
+            yield Ok(StreamingChunk::progress(&prompt_built));
+            let mut metrics = StreamingMetricsRecorder::start();
+            // JSON tasks: hold text back until it is recognisably JSON and stop forwarding it once it can't be
+            let mut json_validator = IncrementalJsonValidator::for_format(&output_format);
+            let mut held_back = String::new();
+            let mut stream_retries = 0;
+            let mut context_recoveries = 0;
+            let mut resume_partial: Option<String> = None;
             
             loop {
+                if cancel_token.is_cancelled() {
+                    let message = AgentError::TaskCancelled.to_string();
+                    handler.handle_error(message.clone());
+                    handler.handle_final(Agent::streaming_failure(
+                        &message,
+                        &accumulated_content,
+                        stream_started.elapsed().as_millis() as u64,
+                        &tools_used,
+                        &run_tool_calls,
+                        &llm_config,
+                        &output_format,
+                    ));
+                    yield Err(message);
+                    return;
+                }
+
+                let mut request_messages = current_messages.clone();
+                if let Some(partial) = resume_partial.take() {
+                    // Resume an interrupted generation from what was already streamed
+                    request_messages.push(ChatMessage::new(ChatMessageRole::Assistant, Some(partial), None, None));
+                    request_messages.push(ChatMessage::user(
+                        "Your previous response was interrupted. Continue exactly where it stopped, without repeating any of it.".to_string(),
+                    ));
+                }
+
+                let fitted = fit_to_context_window(&mut request_messages, &llm_config, tool_tokens);
+                // Also counted for the round if the provider reports no usage
+                let estimated_prompt_tokens = estimate_input_tokens(&request_messages) + tool_tokens;
+                let admitted = fitted.and_then(|_| check_budgets(
+                    budgets.as_deref(),
+                    &event_bus,
+                    &agent_id,
+                    &task_snapshot,
+                    &llm_config.model_name,
+                    estimated_prompt_tokens,
+                ));
+                if let Err(message) = admitted {
+                    handler.handle_error(message.clone());
+                    handler.handle_final(Agent::streaming_failure(
+                        &message,
+                        &accumulated_content,
+                        stream_started.elapsed().as_millis() as u64,
+                        &tools_used,
+                        &run_tool_calls,
+                        &llm_config,
+                        &output_format,
+                    ));
+                    yield Err(message);
+                    return;
+                }
+
                 let request = CompletionRequest::new(
-                    current_messages.clone(),
+                    request_messages,
                     llm_config.model_name.clone(),
                     Some(llm_config.temperature),
                     Some(llm_config.max_tokens),
-                    Some(tools.clone()),
+                    request_tools(&tools),
                 );
 
-                match provider.completion_stream(request).await {
+                round += 1;
+                if event_bus.has_subscribers() {
+                    event_bus.publish(AgentEvent::new(&agent_id, Some(&task_snapshot), EventKind::LlmRequest {
+                        model: llm_config.model_name.clone(),
+                        round,
+                        message_count: request.messages.len(),
+                    }));
+                }
+                let request_sent = StreamPhase::RequestSent { round };
+                handler.handle_progress(request_sent.clone());
+                yield Ok(StreamingChunk::progress(&request_sent));
+
+                let mut retry_stream = false;
+                #[cfg(feature = "audit")]
+                let request_params = audit_log.as_ref()
+                    .map(|_| serde_json::to_string(&request.messages).unwrap_or_default())
+                    .unwrap_or_default();
+                let started = traced!(
+                    provider.completion_stream(request),
+                    "merco.provider.request",
+                    agent_id = %agent_id,
+                    task_id = %task_snapshot.id,
+                    model = %llm_config.model_name,
+                    round,
+                    streaming = true
+                ).await;
+                #[cfg(feature = "audit")]
+                write_audit(
+                    audit_log.as_deref(),
+                    &agent_id,
+                    &task_snapshot,
+                    AuditAction::OutboundRequest,
+                    &llm_config.model_name,
+                    &request_params,
+                    audit_outcome(&started),
+                );
+                match started {
                     Ok(mut stream) => {
-                        let mut has_tool_calls = false;
-                        let mut pending_tool_calls = Vec::new();
+                        // Tool calls of this round, keyed by the index their deltas stream under
+                        let mut tool_call_builders: BTreeMap<usize, StreamedToolCall> = BTreeMap::new();
+                        let mut finish_reason = None;
+                        let mut final_usage = None;
+                        // Completion tokens of this round, estimated per delta as they arrive
+                        let mut round_tokens = TokenEstimator::new();
+                        let mut idle_fallback = false;
                         
-                        while let Some(chunk_result) = stream.next().await {
+                        loop {
+                            // Wait for the next chunk unless the task is cancelled, the stream stalls
+                            // or coalesced text is due first
+                            let flush_deadline = coalescer.as_ref().and_then(|c| c.deadline());
+                            let next = tokio::select! {
+                                _ = cancel_token.cancelled() => StreamWait::Cancelled,
+                                _ = idle_timer(idle_timeout) => StreamWait::IdleTimeout,
+                                _ = flush_timer(flush_deadline) => StreamWait::FlushDue,
+                                next = stream.next() => match next {
+                                    Some(chunk_result) => StreamWait::Chunk(chunk_result),
+                                    None => StreamWait::Ended,
+                                },
+                            };
+                            let chunk_result = match next {
+                                StreamWait::Chunk(chunk_result) => chunk_result,
+                                StreamWait::Ended => break,
+                                StreamWait::FlushDue => {
+                                    if let Some(text) = coalescer.as_mut().and_then(|c| c.take()) {
+                                        let streaming_chunk = StreamingChunk::new(text, false, accumulated_content.clone())
+                                            .with_metrics(metrics.snapshot());
+                                        handler.handle_chunk(streaming_chunk.clone());
+                                        yield Ok(streaming_chunk);
+                                    }
+                                    continue;
+                                }
+                                StreamWait::Cancelled => {
+                                    // Dropping `stream` on return closes the provider connection
+                                    let message = AgentError::TaskCancelled.to_string();
+                                    handler.handle_error(message.clone());
+                                    handler.handle_final(Agent::streaming_failure(
+                                        &message,
+                                        &accumulated_content,
+                                        stream_started.elapsed().as_millis() as u64,
+                                        &tools_used,
+                                        &run_tool_calls,
+                                        &llm_config,
+                                        &output_format,
+                                    ));
+                                    yield Err(message);
+                                    return;
+                                }
+                                StreamWait::IdleTimeout => {
+                                    let message = format!(
+                                        "Stream idle for more than {} ms",
+                                        idle_timeout.unwrap_or_default().as_millis()
+                                    );
+                                    handler.handle_error(message.clone());
+                                    if !streaming_options.fallback_to_completion {
+                                        handler.handle_final(Agent::streaming_failure(
+                                            &message,
+                                            &accumulated_content,
+                                            stream_started.elapsed().as_millis() as u64,
+                                            &tools_used,
+                                            &run_tool_calls,
+                                            &llm_config,
+                                            &output_format,
+                                        ));
+                                        yield Err(message);
+                                        return;
+                                    }
+                                    idle_fallback = true;
+                                    break;
+                                }
+                            };
+
                             match chunk_result {
                                 Ok(chunk) => {
                                     match chunk.delta {
                                         StreamContentDelta::Text(text) => {
                                             accumulated_content.push_str(&text);
+                                            round_tokens.push(&text);
                                             
-                                            let streaming_chunk = StreamingChunk::new(
-                                                text,
-                                                false,
-                                                accumulated_content.clone(),
-                                            );
+                                            let chunk_metrics = metrics.record_chunk(&text);
+                                            let text = match json_validator.as_mut() {
+                                                None => Some(text),
+                                                Some(validator) => {
+                                                    let already_violated = validator.violation().is_some();
+                                                    match validator.feed(&text) {
+                                                        PartialJsonStatus::Pending => {
+                                                            held_back.push_str(&text);
+                                                            None
+                                                        }
+                                                        PartialJsonStatus::Partial | PartialJsonStatus::Complete(_) => {
+                                                            held_back.push_str(&text);
+                                                            Some(std::mem::take(&mut held_back))
+                                                        }
+                                                        // Warn once and keep streaming; the final
+                                                        // validation decides whether to retry
+                                                        PartialJsonStatus::Violated(reason) => {
+                                                            if !already_violated {
+                                                                handler.handle_validation_warning(reason);
+                                                            }
+                                                            held_back.push_str(&text);
+                                                            Some(std::mem::take(&mut held_back))
+                                                        }
+                                                    }
+                                                }
+                                            };
                                             
-                                            handler.handle_chunk(streaming_chunk.clone());
-                                            yield Ok(streaming_chunk);
+                                            let text = match coalescer.as_mut() {
+                                                Some(coalescer) => text.and_then(|text| coalescer.push(&text)),
+                                                None => text,
+                                            };
+                                            
+                                            if let Some(text) = text {
+                                                let streaming_chunk = StreamingChunk::new(
+                                                    text,
+                                                    false,
+                                                    accumulated_content.clone(),
+                                                ).with_metrics(chunk_metrics);
+                                                
+                                                handler.handle_chunk(streaming_chunk.clone());
+                                                yield Ok(streaming_chunk);
+                                            }
                                         }
                                         StreamContentDelta::ToolCallDelta(tool_call_deltas) => {
-                                            // Handle streaming tool calls - accumulate deltas
-                                            has_tool_calls = true;
-                                            
+                                            // Only the first delta of a call carries its id and name;
+                                            // later argument fragments are matched by index
                                             for delta in tool_call_deltas {
-                                                // Check if we have complete function info
+                                                let index = delta.index as usize;
+                                                let builder = tool_call_builders.entry(index).or_default();
+                                                if let Some(call_id) = &delta.id {
+                                                    builder.id = Some(call_id.clone());
+                                                }
                                                 if let Some(func) = &delta.function {
-                                                    if let (Some(name), Some(args)) = (&func.name, &func.arguments) {
-                                                        // Check if this is the first time we see this tool call
-                                                        let is_new_tool_call = !tools_used.contains(name);
-                                                        
-                                                        if is_new_tool_call {
-                                                            // Tool call is starting
-                                                            if let Some(call_id) = &delta.id {
-                                                                handler.handle_tool_call_start(name.clone(), call_id.clone());
-                                                            }
-                                                        }
-                                                        
-                                                        // Always stream the current arguments (even if partial)
-                                                        if let Some(call_id) = &delta.id {
-                                                            handler.handle_tool_call_streaming(
-                                                                name.clone(), 
-                                                                call_id.clone(), 
-                                                                args.clone()
-                                                            );
-                                                        }
-                                                        
-                                                        // Check if JSON is complete before executing
-                                                        if args.starts_with('{') && args.ends_with('}') {
-                                                            match serde_json::from_str::<serde_json::Value>(args) {
-                                                                Ok(_) => {
-                                                                    // JSON is valid and complete - ready to execute
-                                                                    if let Some(call_id) = &delta.id {
-                                                                        handler.handle_tool_call_ready(
-                                                                            name.clone(), 
-                                                                            call_id.clone(), 
-                                                                            args.clone()
-                                                                        );
-                                                                    }
-                                                                    
-                                                                    tools_used.push(name.clone());
-                                                                    
-                                                                    // Execute the tool
-                                                                    let tool_start = std::time::Instant::now();
-                                                                    let (tool_result_content, tool_error) = match execute_tool(name, args) {
-                                                                        Ok(result) => (result, None),
-                                                                        Err(e) => {
-                                                                            eprintln!("Tool Execution Error: {}", e);
-                                                                            (String::new(), Some(e))
-                                                                        }
-                                                                    };
-                                                                    let tool_execution_time = tool_start.elapsed().as_millis() as u64;
-                                                                    
-                                                                    // Notify that tool execution is complete
-                                                                    if let Some(call_id) = &delta.id {
-                                                                        handler.handle_tool_call_executed(
-                                                                            name.clone(),
-                                                                            call_id.clone(),
-                                                                            tool_result_content.clone(),
-                                                                            tool_execution_time
-                                                                        );
-                                                                    }
-                                                                    
-                                                                    // Create detailed tool call information
-                                                                    let tool_call = if let Some(error) = tool_error {
-                                                                        crate::agent::agent::ToolCall::with_error(
-                                                                            name.clone(),
-                                                                            args.clone(),
-                                                                            error,
-                                                                            tool_execution_time,
-                                                                            "text".to_string(),
-                                                                        )
-                                                                    } else {
-                                                                        crate::agent::agent::ToolCall::new(
-                                                                            name.clone(),
-                                                                            args.clone(),
-                                                                            tool_result_content.clone(),
-                                                                            tool_execution_time,
-                                                                            "text".to_string(),
-                                                                        )
-                                                                    };
-                                                                    all_tool_calls.push(tool_call);
-                                                                    
-                                                                    // Store for adding to conversation after stream completes
-                                                                    pending_tool_calls.push((delta.id.clone(), tool_result_content));
-                                                                }
-                                                                Err(_) => {
-                                                                    // JSON not complete yet - continue streaming
-                                                                    // No need to log anything, just continue
-                                                                }
-                                                            }
+                                                    if let Some(name) = &func.name {
+                                                        if builder.name.is_empty() {
+                                                            builder.name = name.clone();
+                                                            handler.handle_tool_call_start(builder.name.clone(), builder.call_id(index));
                                                         }
                                                     }
+                                                    if let Some(args) = &func.arguments {
+                                                        builder.arguments.push_str(args);
+                                                        round_tokens.push(args);
+                                                        handler.handle_tool_call_streaming(
+                                                            builder.name.clone(),
+                                                            builder.call_id(index),
+                                                            builder.arguments.clone(),
+                                                        );
+                                                    }
                                                 }
                                             }
                                         }
                                     }
                                     
                                     // Handle usage statistics if available
-                                    if let Some(usage) = chunk.usage {
-                                        total_tokens = usage.total_tokens;
+                                    if let Some(usage) = &chunk.usage {
+                                        metrics.record_usage(output_tokens + usage.completion_tokens);
+                                        final_usage = Some(crate::agent::streaming::StreamingUsage {
+                                            prompt_tokens: usage.prompt_tokens,
+                                            completion_tokens: usage.completion_tokens,
+                                            total_tokens: usage.total_tokens,
+                                        });
                                     }
                                     
-                                    // Handle finish reason
                                     if let Some(reason) = chunk.finish_reason {
-                                        if has_tool_calls && !pending_tool_calls.is_empty() {
-                                            // Add tool results to conversation and continue
-                                            let tool_calls_to_add = pending_tool_calls.clone();
-                                            pending_tool_calls.clear(); // Clear for next iteration
-                                            
-                                            for (tool_call_id, tool_result) in tool_calls_to_add {
-                                                current_messages.push(ChatMessage::new(
-                                                    ChatMessageRole::Tool,
-                                                    Some(tool_result),
-                                                    None,
-                                                    tool_call_id,
-                                                ));
-                                            }
-                                            
-                                            // Notify handler about all tool calls
-                                            handler.handle_tool_calls(all_tool_calls.clone());
-                                            
-                                            // Reset for next iteration
-                                            accumulated_content.clear();
-                                            has_tool_calls = false;
-                                            all_tool_calls.clear();
-                                            
-                                            // Continue the conversation with tool results
-                                            continue;
-                                        } else {
-                                            // No tool calls, finish normally
-                                            let final_chunk = StreamingChunk::final_chunk(
-                                                String::new(),
-                                                accumulated_content.clone(),
-                                                chunk.usage.map(|u| crate::agent::streaming::StreamingUsage {
-                                                    prompt_tokens: u.prompt_tokens,
-                                                    completion_tokens: u.completion_tokens,
-                                                    total_tokens: u.total_tokens,
-                                                }),
-                                                Some(reason),
-                                            );
-                                            
-                                            handler.handle_chunk(final_chunk.clone());
-                                            yield Ok(final_chunk);
-                                            return;
-                                        }
+                                        finish_reason = Some(reason);
+                                        break;
                                     }
                                 }
                                 Err(e) => {
-                                    yield Err(format!("Stream error: {}", e));
+                                    let message = format!("Stream error: {}", e);
+                                    // Tools only run once the round is complete, so a retry never repeats them
+                                    if stream_retries < streaming_options.max_stream_retries && is_transient_stream_error(&message) {
+                                        stream_retries += 1;
+                                        retry_stream = true;
+                                        callbacks.notify_retry(stream_retries, &message);
+                                        break;
+                                    }
+                                    handler.handle_error(message.clone());
+                                    handler.handle_final(Agent::streaming_failure(
+                                        &message,
+                                        &accumulated_content,
+                                        stream_started.elapsed().as_millis() as u64,
+                                        &tools_used,
+                                        &run_tool_calls,
+                                        &llm_config,
+                                        &output_format,
+                                    ));
+                                    yield Err(message);
                                     return;
                                 }
                             }
                         }
                         
-                        // If we exit the loop without a finish reason, return the accumulated content
-                        if !has_tool_calls {
-                            let final_chunk = StreamingChunk::final_chunk(
-                                String::new(),
-                                accumulated_content.clone(),
-                                None,
+                        if idle_fallback {
+                            // Give up on the stalled stream and redo the round without streaming
+                            drop(stream);
+                            let request = CompletionRequest::new(
+                                current_messages.clone(),
+                                llm_config.model_name.clone(),
+                                Some(llm_config.temperature),
+                                Some(llm_config.max_tokens),
+                                request_tools(&tools),
+                            );
+                            match provider.completion(request).await {
+                                Ok(response) => match response.kind {
+                                    CompletionKind::Message { content } => {
+                                        if !accumulated_content.is_empty() {
+                                            let restart_chunk = StreamingChunk::restart();
+                                            handler.handle_chunk(restart_chunk.clone());
+                                            yield Ok(restart_chunk);
+                                        }
+                                        tool_call_builders.clear();
+                                        accumulated_content = AccumulatedText::from(content.clone());
+                                        round_tokens.push(&content);
+                                        let mut fallback_chunk = StreamingChunk::new(content.clone(), false, accumulated_content.clone())
+                                            .with_metrics(metrics.record_chunk(&content));
+                                        fallback_chunk.metadata.insert("idle_fallback".to_string(), serde_json::json!(true));
+                                        handler.handle_chunk(fallback_chunk.clone());
+                                        yield Ok(fallback_chunk);
+                                        finish_reason = Some("stop".to_string());
+                                    }
+                                    CompletionKind::ToolCall { tool_calls: llm_tool_calls } => {
+                                        tool_call_builders = llm_tool_calls
+                                            .into_iter()
+                                            .enumerate()
+                                            .map(|(index, call)| (index, StreamedToolCall {
+                                                id: Some(call.id),
+                                                name: call.function.name,
+                                                arguments: call.function.arguments,
+                                            }))
+                                            .collect();
+                                    }
+                                },
+                                Err(e) => {
+                                    let message = format!("Fallback completion failed: {}", e);
+                                    handler.handle_error(message.clone());
+                                    handler.handle_final(Agent::streaming_failure(
+                                        &message,
+                                        &accumulated_content,
+                                        stream_started.elapsed().as_millis() as u64,
+                                        &tools_used,
+                                        &run_tool_calls,
+                                        &llm_config,
+                                        &output_format,
+                                    ));
+                                    yield Err(message);
+                                    return;
+                                }
+                            }
+                        }
+                        
+                        // Emit text still held by the coalescer before tools run or the stream ends
+                        if let Some(text) = coalescer.as_mut().and_then(|c| c.take()) {
+                            let streaming_chunk = StreamingChunk::new(text, false, accumulated_content.clone())
+                                .with_metrics(metrics.snapshot());
+                            handler.handle_chunk(streaming_chunk.clone());
+                            yield Ok(streaming_chunk);
+                        }
+                        
+                        // Usage is reported per round; sum it over the whole run, falling
+                        // back to the running estimate for providers that don't report it
+                        match final_usage.take() {
+                            Some(usage) => {
+                                input_tokens += usage.prompt_tokens;
+                                output_tokens += usage.completion_tokens;
+                                total_tokens += usage.total_tokens;
+                            }
+                            None => {
+                                input_tokens += estimated_prompt_tokens;
+                                output_tokens += round_tokens.tokens();
+                                total_tokens += estimated_prompt_tokens + round_tokens.tokens();
+                            }
+                        }
+                        
+                        if !retry_stream && !tool_call_builders.is_empty() {
+                            if cancel_token.is_cancelled() {
+                                // Skip the pending tools; the check at the top of the loop ends the stream
+                                continue;
+                            }
+
+                            let tools_executing = StreamPhase::ToolsExecuting { count: tool_call_builders.len() };
+                            handler.handle_progress(tools_executing.clone());
+                            yield Ok(StreamingChunk::progress(&tools_executing));
+
+                            // Record the assistant turn that requested the tools, then run them in index order
+                            let requested_calls: Vec<serde_json::Value> = tool_call_builders
+                                .iter()
+                                .map(|(index, call)| serde_json::json!({
+                                    "id": call.call_id(*index),
+                                    "type": "function",
+                                    "function": { "name": call.name, "arguments": call.arguments },
+                                }))
+                                .collect();
+                            current_messages.push(ChatMessage::new(
+                                ChatMessageRole::Assistant,
+                                (!accumulated_content.is_empty()).then(|| accumulated_content.to_string()),
+                                serde_json::from_value(serde_json::Value::Array(requested_calls)).ok(),
                                 None,
+                            ));
+
+                            for (index, call) in std::mem::take(&mut tool_call_builders) {
+                                let call_id = call.call_id(index);
+                                let arguments = if call.arguments.trim().is_empty() { "{}".to_string() } else { call.arguments };
+                                handler.handle_tool_call_ready(call.name.clone(), call_id.clone(), arguments.clone());
+                                tools_used.push(call.name.clone());
+                                
+                                // Execute the tool
+                                let tool_start = std::time::Instant::now();
+                                let (tool_outcome, limit_violation) = split_failure(traced!(
+                                    execute_tool(&call.name, &arguments, task_inputs.clone(), &environment),
+                                    "merco.tool.execute",
+                                    agent_id = %agent_id,
+                                    task_id = %task_snapshot.id,
+                                    tool = %call.name
+                                ).await);
+                                #[cfg(feature = "audit")]
+                                write_audit(
+                                    audit_log.as_deref(),
+                                    &agent_id,
+                                    &task_snapshot,
+                                    AuditAction::ToolExecution,
+                                    &call.name,
+                                    &arguments,
+                                    audit_outcome(&tool_outcome),
+                                );
+                                let (tool_result_content, tool_error) = match tool_outcome {
+                                    Ok(result) => (result, None),
+                                    Err(e) => {
+                                        eprintln!("Tool Execution Error [{}]: {}", log_context, redactor.redact(&e));
+                                        (String::new(), Some(e))
+                                    }
+                                };
+                                let tool_execution_time = tool_start.elapsed().as_millis() as u64;
+                                let (prompt_content, injection_findings) =
+                                    screen_tool_result(injection_guard.as_deref(), &call.name, &tool_result_content).await;
+                                
+                                // Notify that tool execution is complete
+                                handler.handle_tool_call_executed(
+                                    call.name.clone(),
+                                    call_id.clone(),
+                                    tool_result_content.clone(),
+                                    tool_execution_time,
+                                );
+                                
+                                // Create detailed tool call information
+                                let mut tool_call = if let Some(error) = tool_error {
+                                    crate::agent::agent::ToolCall::with_error(
+                                        call.name.clone(),
+                                        arguments,
+                                        error,
+                                        tool_execution_time,
+                                        "text".to_string(),
+                                    )
+                                } else {
+                                    crate::agent::agent::ToolCall::new(
+                                        call.name.clone(),
+                                        arguments,
+                                        tool_result_content.clone(),
+                                        tool_execution_time,
+                                        "text".to_string(),
+                                    )
+                                };
+                                tool_call.limit_violation = limit_violation;
+                                tool_call.injection_findings = injection_findings;
+                                tool_call.redact(&redactor);
+                                #[cfg(feature = "audit")]
+                                tool_call.attach_provenance(provenance_signer.as_deref());
+                                callbacks.notify_tool_call(&tool_call);
+                                // Recorded as each tool runs, so a stream dropped mid-run still counts it
+                                tool_usage.record(&tool_call.tool_name, tool_call.error.is_none(), tool_execution_time as f64);
+                                event_bus.publish(AgentEvent::new(&agent_id, Some(&task_snapshot), EventKind::ToolExecuted {
+                                    tool: tool_call.tool_name.clone(),
+                                    execution_time_ms: tool_execution_time,
+                                    success: tool_call.error.is_none(),
+                                }));
+                                run_tool_calls.push(tool_call.clone());
+                                all_tool_calls.push(tool_call);
+                                
+                                current_messages.push(ChatMessage::new(
+                                    ChatMessageRole::Tool,
+                                    Some(prompt_content),
+                                    None,
+                                    Some(call_id),
+                                ));
+                            }
+                            
+                            // Notify handler about all tool calls
+                            handler.handle_tool_calls(all_tool_calls.clone());
+                            
+                            // Reset for next iteration
+                            accumulated_content.clear();
+                            json_validator = IncrementalJsonValidator::for_format(&output_format);
+                            held_back.clear();
+                            all_tool_calls.clear();
+                            
+                            // Continue the conversation with tool results
+                            continue;
+                        }
+                        
+                        if !retry_stream {
+                            let run_usage = (total_tokens > 0).then(|| crate::agent::streaming::StreamingUsage {
+                                prompt_tokens: input_tokens,
+                                completion_tokens: output_tokens,
+                                total_tokens,
+                            });
+                            let run_metrics = metrics.snapshot();
+                            // Text a JSON task never got far enough to release goes out with the final chunk
+                            let mut final_chunk = StreamingChunk::final_chunk(
+                                std::mem::take(&mut held_back),
+                                accumulated_content.clone(),
+                                run_usage.clone(),
+                                finish_reason.clone(),
+                            ).with_metrics(run_metrics.clone());
+                            if let Some(validator) = &json_validator {
+                                final_chunk.metadata.insert("json_validation".to_string(), validator.report());
+                            }
+                            // Streamed text has already been delivered, so violations are only reported
+                            let policy_violations = content_policy
+                                .as_ref()
+                                .map(|policy| policy.check(&accumulated_content.to_string()))
+                                .unwrap_or_default();
+                            if !policy_violations.is_empty() {
+                                final_chunk.metadata.insert("policy_violations".to_string(), serde_json::json!(policy_violations));
+                            }
+                            let mut agent_response = Agent::streaming_response(
+                                &task_snapshot,
+                                accumulated_content.to_string(),
+                                stream_started.elapsed().as_millis() as u64,
+                                input_tokens,
+                                output_tokens,
+                                &llm_config,
+                                tools_used.clone(),
+                                run_tool_calls.clone(),
                             );
+                            agent_response.prompt_versions = prompt_versions.clone();
+                            evaluate_with(output_scorer.as_deref(), &task_snapshot, &mut agent_response).await;
+                            final_chunk.response = Some(Box::new(agent_response));
                             handler.handle_chunk(final_chunk.clone());
+                            
+                            let mut final_response = StreamingResponse::success(
+                                accumulated_content.to_string(),
+                                stream_started.elapsed().as_millis() as u64,
+                                total_tokens,
+                                tools_used.clone(),
+                                run_tool_calls.clone(),
+                                format!("{:?}", output_format),
+                                llm_config.model_name.clone(),
+                                llm_config.temperature,
+                            ).with_metrics(run_metrics);
+                            if let Some(usage) = &run_usage {
+                                final_response.metadata.insert("usage".to_string(), serde_json::json!(usage));
+                            }
+                            if let Some(reason) = &finish_reason {
+                                final_response.metadata.insert("finish_reason".to_string(), serde_json::json!(reason));
+                            }
+                            handler.handle_final(final_response);
+                            
                             yield Ok(final_chunk);
                             return;
                         }
                     }
                     Err(e) => {
-                        yield Err(format!("Failed to start streaming: {}", e));
-                        return;
+                        let mut message = format!("Failed to start streaming: {}", e);
+                        let overflow = is_provider_context_overflow(&message);
+                        if overflow && context_recoveries < MAX_CONTEXT_RECOVERIES && shrink_after_overflow(&mut current_messages) {
+                            // Resent with shortened history on the next pass of the loop
+                            context_recoveries += 1;
+                        } else if !overflow && stream_retries < streaming_options.max_stream_retries && is_transient_stream_error(&message) {
+                            stream_retries += 1;
+                            retry_stream = true;
+                            callbacks.notify_retry(stream_retries, &message);
+                        } else {
+                            if overflow {
+                                message = overflow_error(&llm_config, &message);
+                            }
+                            handler.handle_error(message.clone());
+                            handler.handle_final(Agent::streaming_failure(
+                                &message,
+                                &accumulated_content,
+                                stream_started.elapsed().as_millis() as u64,
+                                &tools_used,
+                                &run_tool_calls,
+                                &llm_config,
+                                &output_format,
+                            ));
+                            yield Err(message);
+                            return;
+                        }
+                    }
+                }
+
+                if retry_stream {
+                    let backoff = streaming_options.retry_backoff_ms.saturating_mul(1 << (stream_retries - 1).min(16));
+                    tokio::time::sleep(std::time::Duration::from_millis(backoff)).await;
+
+                    if streaming_options.resume_partial && !accumulated_content.is_empty() {
+                        resume_partial = Some(accumulated_content.to_string());
+                    } else if !accumulated_content.is_empty() {
+                        // Restarting from scratch: tell consumers to discard what they have shown
+                        accumulated_content.clear();
+                        json_validator = IncrementalJsonValidator::for_format(&output_format);
+                        held_back.clear();
+                        let restart_chunk = StreamingChunk::restart();
+                        handler.handle_chunk(restart_chunk.clone());
+                        yield Ok(restart_chunk);
                     }
                 }
             }
-        })
+        };
+
+        Box::pin(chunks.inspect(move |item| finalizer.observe(item)))
+    }
+
+    /// Execute a task with streaming and wait for the complete `AgentResponse`, the same
+    /// struct `call` returns. Agent metrics are updated as for `call`.
+    pub async fn call_stream_collect<H: StreamingHandler + Send + Sync + 'static>(
+        &mut self,
+        task: Task,
+        handler: H,
+    ) -> AgentResponse {
+        let start_time = std::time::Instant::now();
+        let output_format = format!("{:?}", task.output_format);
+        let stream = self.call_stream_with_handler(task.clone(), handler).await;
+        let response = match collect_response(stream).await {
+            Ok(response) => response,
+            Err(error) => {
+                let mut response = AgentResponse::error(
+                    error,
+                    start_time.elapsed().as_millis() as u64,
+                    self.llm_config.model_name.clone(),
+                    self.llm_config.temperature,
+                    output_format,
+                );
+                response.apply_task_context(&task);
+                response
+            }
+        };
+        self.record_task_metrics(&response);
+        response
+    }
+
+    /// `StreamingResponse` delivered to `handle_final` when a streaming run fails
+    fn streaming_failure(
+        error: &str,
+        partial_content: &AccumulatedText,
+        execution_time_ms: u64,
+        tools_used: &[String],
+        tool_calls: &[crate::agent::agent::ToolCall],
+        llm_config: &AgentModelConfig,
+        output_format: &crate::task::task::OutputFormat,
+    ) -> StreamingResponse {
+        let mut response = StreamingResponse::error(
+            error.to_string(),
+            execution_time_ms,
+            format!("{:?}", output_format),
+            llm_config.model_name.clone(),
+            llm_config.temperature,
+        );
+        response.content = partial_content.to_string();
+        response.tools_used = tools_used.to_vec();
+        response.tool_calls = tool_calls.to_vec();
+        response
+    }
+
+    /// Build the `AgentResponse` attached to the final chunk of a streaming run
+    #[allow(clippy::too_many_arguments)]
+    fn streaming_response(
+        task: &Task,
+        content: String,
+        execution_time_ms: u64,
+        input_tokens: u32,
+        output_tokens: u32,
+        llm_config: &AgentModelConfig,
+        tools_used: Vec<String>,
+        tool_calls: Vec<crate::agent::agent::ToolCall>,
+    ) -> AgentResponse {
+        let csv_rows = task.parse_csv_rows(&content);
+        let mut response = AgentResponse::success(
+            content,
+            execution_time_ms,
+            input_tokens,
+            output_tokens,
+            llm_config.model_name.clone(),
+            llm_config.temperature,
+            tools_used,
+            tool_calls,
+            format!("{:?}", task.output_format),
+        );
+        response.csv_rows = csv_rows;
+        response.apply_task_context(task);
+        response
+    }
+
+    /// Execute a task with streaming through a bounded buffer. Chunks are queued using
+    /// `config.policy` when the consumer falls behind, and the handler runs on its own task
+    /// so slow handlers don't hold up the provider stream.
+    pub async fn call_stream_buffered<H: StreamingHandler + Send + Sync + 'static>(
+        &self,
+        task: Task,
+        handler: H,
+        config: StreamBufferConfig,
+    ) -> Pin<Box<dyn Stream<Item = Result<StreamingChunk, String>> + Send + 'static>> {
+        let handler = BackgroundStreamingHandler::spawn(handler, config.clone());
+        let stream = self.call_stream_with_handler(task, handler).await;
+        buffer_stream(stream, config)
+    }
+
+    /// Execute a task with streaming and record every chunk and handler event to a JSONL
+    /// file, which can be played back later with [`crate::agent::StreamReplay`]
+    pub async fn call_stream_recorded<H: StreamingHandler + Send + Sync + 'static>(
+        &self,
+        task: Task,
+        handler: H,
+        path: impl AsRef<std::path::Path>,
+    ) -> anyhow::Result<Pin<Box<dyn Stream<Item = Result<StreamingChunk, String>> + Send + 'static>>> {
+        let recorder = StreamRecorder::create(path)?;
+        let stream = self.call_stream_with_handler(task, recorder.wrap_handler(handler)).await;
+        Ok(recorder.wrap_stream(stream))
     }
 
     /// Simple string input method with streaming - returns a stream of chunks
-    pub async fn call_str_stream(&mut self, input: &str) -> Pin<Box<dyn Stream<Item = Result<StreamingChunk, String>> + Send + '_>> {
+    pub async fn call_str_stream(&self, input: &str) -> Pin<Box<dyn Stream<Item = Result<StreamingChunk, String>> + Send + 'static>> {
         let task = Task::new(input.to_string(), None);
         self.call_stream(task).await
     }
 
     /// Simple string input method with streaming and custom handler - returns a stream of chunks
     pub async fn call_str_stream_with_handler<H: StreamingHandler + Send + Sync + 'static>(
-        &mut self, 
+        &self,
         input: &str, 
         handler: H
     ) -> Pin<Box<dyn Stream<Item = Result<StreamingChunk, String>> + Send + 'static>> {
@@ -519,4 +1709,150 @@ impl Agent {
         self.call_stream_with_handler(task, handler).await
     }
 
-}
\ No newline at end of file
+}
+
+/// Tool call assembled from streamed deltas
+#[derive(Debug, Default)]
+struct StreamedToolCall {
+    id: Option<String>,
+    name: String,
+    arguments: String,
+}
+
+impl StreamedToolCall {
+    /// Provider id, or a stable placeholder for providers that don't send one
+    fn call_id(&self, index: usize) -> String {
+        self.id.clone().unwrap_or_else(|| format!("call_{}", index))
+    }
+}
+
+/// Records finished runs: their cost, and the redacted run with its provider trace
+struct RunSink {
+    agent_id: String,
+    agent_name: String,
+    cost_tracker: Option<std::sync::Arc<crate::cost::CostTracker>>,
+    trace_recorder: Option<std::sync::Arc<crate::agent::replay::RecordingProvider>>,
+    run_store: Option<std::sync::Arc<dyn crate::task::run_history::RunStore>>,
+    redactor: std::sync::Arc<crate::redaction::Redactor>,
+}
+
+impl RunSink {
+    fn record(&self, task: &Task, response: &AgentResponse) {
+        if let Some(tracker) = &self.cost_tracker {
+            tracker.record_response(&self.agent_id, response);
+        }
+        // Taken even without a store so the recorder doesn't grow across runs
+        let trace = self.trace_recorder.as_ref().map(|recorder| recorder.take_trace());
+        if let Some(store) = &self.run_store {
+            let mut record = RunRecord::new(&self.agent_id, &self.agent_name, task, response);
+            record.trace = trace;
+            record.redact(&self.redactor);
+            if let Err(e) = store.save(record) {
+                eprintln!("Failed to persist run [{}]: {}", task.log_context(), e);
+            }
+        }
+    }
+}
+
+/// Ends a streaming run once: persists it, fires the task's `on_complete`, marks it
+/// finished in the activity tracker and publishes `TaskCompleted`. Owned by the returned stream, so a
+/// stream dropped before its final chunk or error still ends the run, as cancelled.
+struct StreamFinalizer {
+    sink: RunSink,
+    task: Task,
+    agent_id: String,
+    model_name: String,
+    temperature: f32,
+    activity: std::sync::Arc<crate::agent::status::ActivityTracker>,
+    event_bus: std::sync::Arc<crate::events::EventBus>,
+    started: std::time::Instant,
+    finished: bool,
+}
+
+impl StreamFinalizer {
+    fn observe(&mut self, item: &Result<StreamingChunk, String>) {
+        match item {
+            Ok(chunk) if chunk.is_final => {
+                let response = match chunk.response.as_deref() {
+                    Some(response) => response.clone(),
+                    None => self.failure("Final chunk did not carry a response"),
+                };
+                self.finish(&response);
+            }
+            Ok(_) => {}
+            Err(error) => {
+                let response = self.failure(error);
+                self.finish(&response);
+            }
+        }
+    }
+
+    /// Response of a run that ended with `error`
+    fn failure(&self, error: &str) -> AgentResponse {
+        let execution_time_ms = self.started.elapsed().as_millis() as u64;
+        let output_format = format!("{:?}", self.task.output_format);
+        let mut response = if error == AgentError::TaskCancelled.to_string() {
+            AgentResponse::cancelled(execution_time_ms, self.model_name.clone(), self.temperature, output_format)
+        } else {
+            AgentResponse::error(error.to_string(), execution_time_ms, self.model_name.clone(), self.temperature, output_format)
+        };
+        response.apply_task_context(&self.task);
+        response
+    }
+
+    fn finish(&mut self, response: &AgentResponse) {
+        if std::mem::replace(&mut self.finished, true) {
+            return;
+        }
+        self.sink.record(&self.task, response);
+        self.task.callbacks.notify_complete(response);
+        self.activity.finish(
+            &self.task.id,
+            response.success,
+            response.execution_time_ms,
+            response.total_tokens,
+            response.error.as_deref(),
+        );
+        self.event_bus.publish(AgentEvent::new(&self.agent_id, Some(&self.task), EventKind::TaskCompleted {
+            success: response.success,
+            cancelled: response.cancelled,
+            execution_time_ms: response.execution_time_ms,
+            total_tokens: response.total_tokens,
+            error: response.error.clone(),
+        }));
+    }
+}
+
+impl Drop for StreamFinalizer {
+    fn drop(&mut self) {
+        if !self.finished {
+            let response = self.failure(&AgentError::TaskCancelled.to_string());
+            self.finish(&response);
+        }
+    }
+}
+
+/// Outcome of waiting for the next provider chunk
+enum StreamWait<T> {
+    Chunk(T),
+    Ended,
+    Cancelled,
+    IdleTimeout,
+    FlushDue,
+}
+
+/// Resolves after `timeout`, or never when no timeout is configured
+async fn idle_timer(timeout: Option<std::time::Duration>) {
+    match timeout {
+        Some(timeout) => tokio::time::sleep(timeout).await,
+        None => futures::future::pending::<()>().await,
+    }
+}
+
+/// Resolves at `deadline`, or never when nothing is waiting to be flushed
+async fn flush_timer(deadline: Option<tokio::time::Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => futures::future::pending::<()>().await,
+    }
+}
@@ -2,7 +2,12 @@ use crate::agent::role::OutputFormat;
 use crate::agent::state::{AgentState, AgentStatus};
 
 use crate::agent::agent::Agent;
-use merco_llmproxy::Tool;
+use crate::task::run_history::{RunFilter, RunRecord, RunStore};
+use crate::agent::scoring::OutputScorer;
+use crate::agent::streaming::StreamingOptions;
+use crate::agent::tool_schema::ToolSchemas;
+use merco_llmproxy::{LlmProvider, Tool};
+use std::sync::Arc;
 
 impl Agent {
     // Getters - consolidated to avoid duplication
@@ -29,6 +34,18 @@ impl Agent {
         self.output_handler.post_processing = Some(processor);
     }
 
+    pub fn set_format_conversion(&mut self, conversion: crate::agent::format_conversion::FormatConversion) {
+        self.output_handler.conversion = conversion;
+    }
+
+    pub fn add_output_validator(&mut self, validator: std::sync::Arc<dyn crate::agent::output_handler::OutputValidator>) {
+        self.output_handler.add_validator(validator);
+    }
+
+    pub fn add_output_transform(&mut self, transform: std::sync::Arc<dyn crate::agent::output_pipeline::OutputTransform>) {
+        self.output_handler.add_transform(transform);
+    }
+
     // State management methods
     pub fn start_task(&mut self, task_description: String) {
         self.state.start_task(task_description);
@@ -76,6 +93,188 @@ impl Agent {
         self.state.performance_metrics.failed_tasks
     }
 
+    /// Usage of each tool over calls and streams, including streams still running
+    pub fn get_tool_usage_stats(&self) -> std::collections::HashMap<String, crate::agent::state::ToolUsageStats> {
+        self.tool_usage.snapshot()
+    }
+
+    pub fn get_tool_stats(&self, tool_name: &str) -> Option<crate::agent::state::ToolUsageStats> {
+        self.tool_usage.get(tool_name)
+    }
+
+    // Run history
+    pub fn with_run_store(mut self, store: std::sync::Arc<dyn RunStore>) -> Self {
+        self.run_store = Some(store);
+        self
+    }
+
+    pub fn set_run_store(&mut self, store: std::sync::Arc<dyn RunStore>) {
+        self.run_store = Some(store);
+    }
+
+    pub fn get_result(&self, task_id: &str) -> Option<RunRecord> {
+        self.run_store.as_ref().and_then(|store| store.get(task_id))
+    }
+
+    pub fn list_runs(&self, filter: &RunFilter) -> Vec<RunRecord> {
+        self.run_store
+            .as_ref()
+            .map(|store| store.list(filter))
+            .unwrap_or_default()
+    }
+
+    // Output evaluation
+    pub fn with_output_scorer(mut self, scorer: std::sync::Arc<dyn OutputScorer>) -> Self {
+        self.output_scorer = Some(scorer);
+        self
+    }
+
+    // Provider
+    /// Replace the provider built from the model config (scripted ones go through `with_mock_provider`)
+    pub fn with_provider(mut self, provider: std::sync::Arc<dyn LlmProvider + Send + Sync>) -> Self {
+        self.provider = provider;
+        self.mock_provider = false;
+        self
+    }
+
+    /// Answer requests from a scripted `MockProvider`. Refused under a profile that
+    /// doesn't allow mock providers, such as production's.
+    pub fn with_mock_provider(mut self, provider: std::sync::Arc<crate::agent::mock_provider::MockProvider>) -> Self {
+        if self.profile.as_ref().is_some_and(|profile| !profile.allow_mock_providers) {
+            eprintln!("Agent '{}': mock providers aren't allowed in this deployment environment", self.name);
+            return self;
+        }
+        self.provider = provider;
+        self.mock_provider = true;
+        self
+    }
+
+    // Streaming resilience
+    pub fn with_streaming_options(mut self, options: StreamingOptions) -> Self {
+        self.streaming_options = options;
+        self
+    }
+
+    /// Price every run of this agent into `tracker`
+    pub fn with_cost_tracker(mut self, tracker: std::sync::Arc<crate::cost::CostTracker>) -> Self {
+        self.cost_tracker = Some(tracker);
+        self
+    }
+
+    /// Enforce `budgets` before each provider request. Runs are priced into the
+    /// manager's tracker unless the agent already has one.
+    pub fn with_budgets(mut self, budgets: std::sync::Arc<crate::cost::BudgetManager>) -> Self {
+        if self.cost_tracker.is_none() {
+            self.cost_tracker = Some(budgets.tracker());
+        }
+        self.budgets = Some(budgets);
+        self
+    }
+
+    /// Write tool executions and provider requests to `log` while
+    /// `context.environment.security_context.audit_logging` is enabled
+    #[cfg(feature = "audit")]
+    pub fn with_audit_log(mut self, log: std::sync::Arc<crate::audit::AuditLog>) -> Self {
+        self.audit_log = Some(log);
+        self
+    }
+
+    /// Scrub secrets with `redactor` instead of the global one
+    pub fn with_redactor(mut self, redactor: std::sync::Arc<crate::redaction::Redactor>) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    /// Screen tool results with `guard` instead of the global one
+    pub fn with_injection_guard(mut self, guard: std::sync::Arc<crate::injection::InjectionGuard>) -> Self {
+        self.injection_guard = Some(guard);
+        self
+    }
+
+    /// Insert tool results into the prompt unscreened. Ignored under a profile with
+    /// strict guardrails.
+    pub fn without_injection_guard(mut self) -> Self {
+        if self.profile.as_ref().is_some_and(|profile| profile.strict_guardrails) {
+            eprintln!("Agent '{}': the injection guard can't be disabled under strict guardrails", self.name);
+            return self;
+        }
+        self.injection_guard = None;
+        self
+    }
+
+    /// Write the built-in prompts in `language` (e.g. `de`; see `PromptLocale`).
+    /// Languages without a bundled or registered locale fall back to English.
+    pub fn with_prompt_language(mut self, language: &str) -> Self {
+        self.context.preferences.language = language.to_string();
+        self
+    }
+
+    /// Take prompt overrides from `registry` instead of the global one
+    pub fn with_prompt_registry(mut self, registry: std::sync::Arc<crate::agent::prompt_registry::PromptRegistry>) -> Self {
+        self.prompt_registry = registry;
+        self
+    }
+
+    /// Apply the preset behaviour of `environment` (see `EnvironmentProfile`)
+    pub fn with_deployment_environment(self, environment: crate::agent::state::DeploymentEnvironment) -> Self {
+        self.with_profile(crate::agent::profile::EnvironmentProfile::for_environment(environment))
+    }
+
+    /// Apply an environment profile, e.g. a preset adjusted for one deployment.
+    /// Replaces the user rate limiter with one for the profile's `rate_limits`.
+    pub fn with_profile(mut self, profile: crate::agent::profile::EnvironmentProfile) -> Self {
+        profile.apply_to(&mut self.context.environment);
+        self.user_rate_limiter = Some(Arc::new(crate::agent::rate_limit::UserRateLimiter::from_rate_limits(
+            &profile.rate_limits,
+        )));
+        if self.mock_provider && !profile.allow_mock_providers {
+            eprintln!("Agent '{}': mock provider replaced by the configured one for this deployment environment", self.name);
+            self.provider = crate::agent::agent_constructors::create_provider(&self.llm_config);
+            self.mock_provider = false;
+        }
+        if profile.strict_guardrails {
+            self.output_handler.validation_enabled = true;
+            if self.injection_guard.is_none() {
+                self.injection_guard = Some(crate::injection::InjectionGuard::global());
+            }
+        }
+        self.profile = Some(profile);
+        self
+    }
+
+    /// Serve only `tenant`: its tasks and tasks without a tenant, which take it
+    pub fn with_tenant(mut self, tenant: crate::tenant::TenantId) -> Self {
+        self.tenant_id = Some(tenant);
+        self
+    }
+
+    /// Limit how many tasks each user may start; share one limiter between agents
+    /// to give users a budget across them
+    pub fn with_user_rate_limit(mut self, limiter: std::sync::Arc<crate::agent::rate_limit::UserRateLimiter>) -> Self {
+        self.user_rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Check every final output against `policy`; violations are corrected or
+    /// refused as the policy's action says
+    pub fn with_content_policy(mut self, policy: crate::agent::content_policy::ContentPolicy) -> Self {
+        self.content_policy = Some(std::sync::Arc::new(policy));
+        self
+    }
+
+    /// Sign the provenance recorded on every tool call with `signer`
+    #[cfg(feature = "audit")]
+    pub fn with_provenance_signer(mut self, signer: std::sync::Arc<dyn crate::provenance::ProvenanceSigner>) -> Self {
+        self.provenance_signer = Some(signer);
+        self
+    }
+
+    /// Publish this agent's lifecycle events on `bus` instead of the global one
+    pub fn with_event_bus(mut self, bus: std::sync::Arc<crate::events::EventBus>) -> Self {
+        self.event_bus = bus;
+        self
+    }
+
     // Context management
     pub fn add_context(&mut self, key: String, value: String) {
         self.context.store_shared_memory(key, serde_json::Value::String(value));
@@ -98,12 +297,16 @@ impl Agent {
     // Tool management
     pub fn add_tool(&mut self, tool: Tool) {
         if !self.tools.iter().any(|t| t.name == tool.name) {
-            self.tools.push(tool);
+            Arc::make_mut(&mut self.tools).push(tool);
+            self.tool_schemas = Arc::new(ToolSchemas::new(&self.tools));
         }
     }
 
     pub fn remove_tool(&mut self, tool_name: &str) {
-        self.tools.retain(|t| t.name != tool_name);
+        if self.has_tool(tool_name) {
+            Arc::make_mut(&mut self.tools).retain(|t| t.name != tool_name);
+            self.tool_schemas = Arc::new(ToolSchemas::new(&self.tools));
+        }
     }
 
     pub fn has_tool(&self, tool_name: &str) -> bool {
@@ -136,6 +339,11 @@ impl Agent {
         count <= self.capabilities.max_concurrent_tasks
     }
 
+    /// Tasks that could start right now without waiting for a slot
+    pub fn available_task_slots(&self) -> usize {
+        self.task_slots.available_permits()
+    }
+
     // Agent information
     pub fn get_agent_info(&self) -> String {
         format!(
@@ -154,7 +362,12 @@ impl Agent {
         self.role = new_role;
     }
 
+    /// Tasks already running keep their slots; the new `max_concurrent_tasks` limit
+    /// applies to calls started afterwards from this agent and clones made from it
     pub fn update_capabilities(&mut self, new_capabilities: crate::agent::role::AgentCapabilities) {
+        if new_capabilities.max_concurrent_tasks != self.capabilities.max_concurrent_tasks {
+            self.task_slots = crate::agent::agent_constructors::task_slots(&new_capabilities);
+        }
         self.capabilities = new_capabilities;
     }
 
@@ -168,6 +381,8 @@ impl Agent {
         cloned.id = new_id;
         cloned.state = AgentState::new();
         cloned.context = crate::agent::state::AgentContext::new();
+        cloned.task_queue = std::sync::Arc::new(crate::task::queue::TaskQueue::new());
+        cloned.task_slots = crate::agent::agent_constructors::task_slots(&cloned.capabilities);
         cloned
     }
 
@@ -0,0 +1,62 @@
+use crate::agent::agent::Agent;
+use crate::task::multi_step::{CheckpointStore, MultiStepResult, MultiStepTask, StepCheckpoint};
+
+impl Agent {
+    /// Run a multi-step task, skipping steps that already have checkpoints.
+    /// Each successful step is checkpointed before the next one starts; on
+    /// failure the run stops and calling this again resumes at the failed step.
+    pub async fn run_multi_step(&mut self, task: &MultiStepTask, store: &dyn CheckpointStore) -> MultiStepResult {
+        let mut checkpoints = match store.load(&task.id) {
+            Ok(checkpoints) => checkpoints,
+            Err(e) => {
+                eprintln!("Failed to load checkpoints for {}: {}", task.id, e);
+                Vec::new()
+            }
+        };
+
+        // Only a contiguous prefix of completed steps can be reused
+        let resumed_from = checkpoints
+            .iter()
+            .enumerate()
+            .take_while(|(i, c)| c.step_index == *i)
+            .count();
+        checkpoints.truncate(resumed_from);
+
+        let mut result = MultiStepResult {
+            task_id: task.id.clone(),
+            success: false,
+            step_responses: Vec::new(),
+            resumed_from,
+            failed_step: None,
+            final_output: None,
+        };
+
+        for index in resumed_from..task.steps.len() {
+            let step = task.prepare_step(index, &checkpoints);
+            let step_task_id = step.id.clone();
+            let response = self.call(step).await;
+            result.step_responses.push(response.clone());
+
+            if !response.success {
+                result.failed_step = Some(index);
+                return result;
+            }
+
+            let checkpoint = StepCheckpoint {
+                step_index: index,
+                task_id: step_task_id,
+                output: response.content.clone(),
+                response,
+                completed_at: chrono::Utc::now(),
+            };
+            if let Err(e) = store.save(&task.id, checkpoint.clone()) {
+                eprintln!("Failed to checkpoint step {} of {}: {}", index + 1, task.id, e);
+            }
+            checkpoints.push(checkpoint);
+        }
+
+        result.success = true;
+        result.final_output = checkpoints.last().map(|c| c.output.clone());
+        result
+    }
+}
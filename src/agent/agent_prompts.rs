@@ -1,5 +1,10 @@
 use crate::agent::role::OutputFormat;
 use crate::agent::agent::Agent;
+use crate::agent::prompt_locale::{prompt_locale, PromptLocale};
+use crate::agent::prompt_registry::{format_prompt_name, render_template, CORRECTION_PROMPT, SYSTEM_PROMPT};
+use crate::task::validation_report::ValidationReport;
+use std::collections::BTreeMap;
+use std::sync::Arc;
 
 impl Agent {
     /// Build initial messages for the agent
@@ -14,50 +19,86 @@ impl Agent {
     }
 
     /// Build system prompt for the agent
-    fn build_system_prompt(&self) -> String {
-        format!(
-            "You are {}, a specialized AI agent.\n\n\
-            ROLE AND CAPABILITIES:\n\
-            - Role: {}\n\
-            - Description: {}\n\
-            - Max Concurrent Tasks: {}\n\
-            - Supported Output Formats: {:?}\n\n\
-            You have access to the following tools: {}\n\n\
-            Always follow the output format specified in the task and provide accurate, helpful responses.",
-            self.name,
-            self.role.get_description(),
-            self.description,
-            self.capabilities.max_concurrent_tasks,
-            self.capabilities.supported_output_formats,
-            self.tools.len()
-        )
+    pub(crate) fn build_system_prompt(&self) -> String {
+        let vars = [
+            ("name", self.name.clone()),
+            ("role", self.role.get_description()),
+            ("description", self.description.clone()),
+            ("max_concurrent_tasks", self.capabilities.max_concurrent_tasks.to_string()),
+            ("output_formats", format!("{:?}", self.capabilities.supported_output_formats)),
+            ("tool_count", self.tools.len().to_string()),
+        ];
+        match self.prompt_registry.active(SYSTEM_PROMPT) {
+            Some(template) => template.render(&vars),
+            None => render_template(&self.prompt_locale().system, &vars),
+        }
+    }
+
+    /// Built-in prompt text in the agent's language (`context.preferences.language`)
+    pub fn prompt_locale(&self) -> Arc<PromptLocale> {
+        prompt_locale(&self.context.preferences.language)
     }
 
     fn get_output_format_instruction(&self) -> String {
         self.get_format_instruction(&self.output_handler.default_format)
     }
 
-    fn get_format_instruction(&self, format: &OutputFormat) -> String {
-        match format {
-            OutputFormat::Text => "Provide your response in plain text format. Be clear and concise.".to_string(),
-            OutputFormat::Json => "Provide your response in valid JSON format. Structure your response as a JSON object with appropriate keys and values. Do not wrap your response in markdown code blocks - provide raw JSON only.".to_string(),
-            OutputFormat::Markdown => "Provide your response in Markdown format. Use appropriate headers, lists, and formatting.".to_string(),
-            OutputFormat::Html => "Provide your response in HTML format. Use proper HTML tags and structure.".to_string(),
-            OutputFormat::MultiModal => "Provide your response in a multi-modal format that can include text, images, and other media.".to_string(),
+    pub(crate) fn get_format_instruction(&self, format: &OutputFormat) -> String {
+        match self.prompt_registry.active(&format_prompt_name(format)) {
+            Some(template) => template.template,
+            None => self.prompt_locale().format_instruction(format).to_string(),
         }
     }
 
+    /// Message asking the model to fix the problems in `report`
+    pub(crate) fn correction_prompt(&self, report: &ValidationReport) -> String {
+        match self.prompt_registry.active(CORRECTION_PROMPT) {
+            Some(template) => template.render(&[("issues", report.correction_items())]),
+            None => {
+                let locale = self.prompt_locale();
+                format!("{}\n{}{}", locale.correction_intro, report.correction_items(), locale.correction_outro)
+            }
+        }
+    }
+
+    /// Registered prompt versions a run of `task` uses, by prompt name
+    pub(crate) fn prompt_versions(&self, task: &crate::task::task::Task) -> BTreeMap<String, u32> {
+        let format = self.convert_task_format_to_role_format(&task.output_format);
+        [SYSTEM_PROMPT.to_string(), format_prompt_name(&format), CORRECTION_PROMPT.to_string()]
+            .into_iter()
+            .filter_map(|name| self.prompt_registry.active(&name).map(|template| (name, template.version)))
+            .collect()
+    }
+
     /// Build task-specific prompt
-    fn build_task_prompt(&self, task: &crate::task::task::Task) -> String {
-        let mut prompt = format!("Task: {}", task.description);
+    pub(crate) fn build_task_prompt(&self, task: &crate::task::task::Task) -> String {
+        let locale = self.prompt_locale();
+        let mut prompt = format!("{}: {}", locale.task_label, task.description);
+
+        if let Some(inputs) = task.render_inputs() {
+            prompt.push_str(&format!("\n\n{}", inputs));
+        }
         
         if let Some(expected_output) = &task.expected_output {
-            prompt.push_str(&format!("\nExpected Output: {}", expected_output));
+            prompt.push_str(&format!("\n{}: {}", locale.expected_output_label, expected_output));
         }
         
         // Always add output format instruction for the task
         let task_role_format = self.convert_task_format_to_role_format(&task.output_format);
-        prompt.push_str(&format!("\n\nIMPORTANT - Output Format: {}", self.get_format_instruction(&task_role_format)));
+        prompt.push_str(&format!("\n\n{}: {}", locale.output_format_label, self.get_format_instruction(&task_role_format)));
+
+        if let Some(language) = &task.language {
+            let instruction = render_template(
+                &locale.language_instruction,
+                &[("language", crate::task::language::language_name(language))],
+            );
+            prompt.push_str(&format!("\n\n{}", instruction));
+        }
+
+        // Structured formats carry a schema the model needs to see
+        if task.output_format != crate::task::task::OutputFormat::Text {
+            prompt.push_str(&format!("\n\n{}", task.get_format_prompt()));
+        }
         
         prompt
     }
@@ -67,6 +108,9 @@ impl Agent {
         match task_format {
             crate::task::task::OutputFormat::Text => OutputFormat::Text,
             crate::task::task::OutputFormat::Json { .. } => OutputFormat::Json,
+            crate::task::task::OutputFormat::Csv { delimiter, .. } => OutputFormat::Csv { delimiter: *delimiter },
+            crate::task::task::OutputFormat::Xml { .. } => OutputFormat::Xml,
+            crate::task::task::OutputFormat::Code { .. } => OutputFormat::Code,
         }
     }
 }
\ No newline at end of file
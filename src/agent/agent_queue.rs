@@ -0,0 +1,70 @@
+use crate::agent::agent::{Agent, AgentError, AgentResponse};
+use crate::task::cancellation::TaskHandle;
+use crate::task::queue::QueuedTaskStatus;
+use crate::task::task::Task;
+use crate::telemetry::traced;
+
+impl Agent {
+    /// Submit a task for background execution and return a handle that can
+    /// await or cancel it.
+    /// Tasks are executed one at a time in priority order by a worker loop
+    /// that is started on demand and exits once the queue is drained.
+    /// The worker runs on a clone of this agent, so its performance metrics
+    /// are not reflected in this instance.
+    /// Fails outside a Tokio runtime, since the worker couldn't be started, and
+    /// once the agent has begun shutting down.
+    pub fn enqueue(&self, task: Task) -> Result<TaskHandle, String> {
+        let runtime = tokio::runtime::Handle::try_current()
+            .map_err(|e| format!("Cannot enqueue task {} outside a Tokio runtime: {}", task.id, e))?;
+        if self.activity.is_shutting_down() {
+            return Err(format!("Cannot enqueue task {}: {}", task.id, AgentError::ShuttingDown));
+        }
+        let handle = TaskHandle::new(
+            task.id.clone(),
+            task.cancel_handle(),
+            self.task_queue.clone(),
+            self.llm_config.model_name.clone(),
+            self.llm_config.temperature,
+            format!("{:?}", task.output_format),
+        );
+
+        if self.task_queue.push(task) {
+            let mut worker = self.clone();
+            runtime.spawn(async move {
+                worker.run_queue_worker().await;
+            });
+        }
+
+        Ok(handle)
+    }
+
+    /// Drain the task queue, storing each response for later retrieval
+    async fn run_queue_worker(&mut self) {
+        while let Some(task) = self.task_queue.next_task() {
+            let task_id = task.id.clone();
+            let response = traced!(self.call(task), "merco.queue.task", task_id = %task_id).await;
+            self.task_queue.complete(&task_id, response);
+        }
+    }
+
+    /// Take the result of a queued task if it has finished
+    pub fn poll_result(&self, task_id: &str) -> Option<AgentResponse> {
+        self.task_queue.take_result(task_id)
+    }
+
+    /// Wait for a queued task to finish and return its result.
+    /// Returns None if the task id was never submitted or its result was already taken.
+    pub async fn await_result(&self, task_id: &str) -> Option<AgentResponse> {
+        self.task_queue.wait_for_result(task_id).await
+    }
+
+    /// Status of a queued task
+    pub fn get_task_status(&self, task_id: &str) -> QueuedTaskStatus {
+        self.task_queue.status(task_id)
+    }
+
+    /// Number of tasks waiting in the queue
+    pub fn queue_depth(&self) -> usize {
+        self.task_queue.len()
+    }
+}
@@ -0,0 +1,206 @@
+use crate::agent::mock_provider::{MockProvider, MockResponse};
+use crate::agent::replay::{capture_stream, recorded};
+use crate::stable_hash::fnv1a_hex;
+use anyhow::Result;
+use async_trait::async_trait;
+use merco_llmproxy::traits::{CompletionResponse, CompletionStream, ProviderError};
+use merco_llmproxy::{CompletionRequest, LlmProvider};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Environment variable selecting the cassette mode: `record`, `replay` or `auto`
+pub const CASSETTE_MODE_ENV: &str = "MERCO_CASSETTE_MODE";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Always call the real service and overwrite recorded interactions
+    Record,
+    /// Never call the real service; unrecorded requests fail (for CI)
+    Replay,
+    /// Replay what was recorded and record the rest
+    Auto,
+}
+
+impl CassetteMode {
+    /// Mode from `MERCO_CASSETTE_MODE`, `Auto` when unset or unknown
+    pub fn from_env() -> Self {
+        match std::env::var(CASSETTE_MODE_ENV).unwrap_or_default().to_lowercase().as_str() {
+            "record" => CassetteMode::Record,
+            "replay" => CassetteMode::Replay,
+            _ => CassetteMode::Auto,
+        }
+    }
+}
+
+/// One recorded request and its response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interaction {
+    /// Hash of the request, used for matching
+    pub key: String,
+    /// "completion" or "embedding"
+    pub kind: String,
+    /// The request as sent, for reading and diffing cassettes
+    pub request: Value,
+    pub response: Value,
+}
+
+/// File of recorded HTTP interactions shared by `CassetteProvider` and
+/// `EmbeddingScorer::with_cassette`. Identical requests replay their recordings in order.
+#[derive(Debug)]
+pub struct Cassette {
+    path: PathBuf,
+    mode: CassetteMode,
+    interactions: Mutex<Vec<Interaction>>,
+    /// Recordings of each key replayed so far
+    played: Mutex<HashMap<String, usize>>,
+}
+
+impl Cassette {
+    /// Open (or start) the cassette at `path`; in `Record` mode existing recordings are dropped
+    pub fn open<P: AsRef<Path>>(path: P, mode: CassetteMode) -> Result<Arc<Self>> {
+        let path = path.as_ref().to_path_buf();
+        let interactions = if mode != CassetteMode::Record && path.exists() {
+            serde_json::from_str(&std::fs::read_to_string(&path)?)?
+        } else {
+            Vec::new()
+        };
+        Ok(Arc::new(Self {
+            path,
+            mode,
+            interactions: Mutex::new(interactions),
+            played: Mutex::new(HashMap::new()),
+        }))
+    }
+
+    /// Open with the mode from `MERCO_CASSETTE_MODE`
+    pub fn from_env<P: AsRef<Path>>(path: P) -> Result<Arc<Self>> {
+        Self::open(path, CassetteMode::from_env())
+    }
+
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    pub fn len(&self) -> usize {
+        self.interactions.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn key(kind: &str, request: &Value) -> String {
+        fnv1a_hex(format!("{}:{}", kind, request).as_bytes())
+    }
+
+    /// Next recorded response for `key`; the last one repeats once all were played
+    pub fn replay(&self, key: &str) -> Option<Value> {
+        if self.mode == CassetteMode::Record {
+            return None;
+        }
+        let interactions = self.interactions.lock().unwrap();
+        let matching: Vec<&Interaction> = interactions.iter().filter(|i| i.key == key).collect();
+        let mut played = self.played.lock().unwrap();
+        let count = played.entry(key.to_string()).or_default();
+        let interaction = matching.get(*count).or(matching.last())?;
+        *count += 1;
+        Some(interaction.response.clone())
+    }
+
+    /// Store an interaction and rewrite the cassette file
+    pub fn record(&self, kind: &str, request: Value, response: Value) -> Result<()> {
+        let mut interactions = self.interactions.lock().unwrap();
+        interactions.push(Interaction {
+            key: Self::key(kind, &request),
+            kind: kind.to_string(),
+            request,
+            response,
+        });
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string_pretty(&*interactions)?)?;
+        Ok(())
+    }
+
+    pub(crate) fn missing(&self, kind: &str) -> String {
+        format!(
+            "No recorded {} in cassette {}; record it with {}=record",
+            kind,
+            self.path.display(),
+            CASSETTE_MODE_ENV
+        )
+    }
+}
+
+/// Provider wrapper that records real completions to a cassette on the first run
+/// and replays them afterwards, so tests and examples run without API keys
+pub struct CassetteProvider {
+    inner: Arc<dyn LlmProvider + Send + Sync>,
+    cassette: Arc<Cassette>,
+}
+
+impl CassetteProvider {
+    pub fn new(inner: Arc<dyn LlmProvider + Send + Sync>, cassette: Arc<Cassette>) -> Self {
+        Self { inner, cassette }
+    }
+
+    fn request_value(request: &CompletionRequest) -> Value {
+        serde_json::json!({
+            "model": request.model,
+            "messages": serde_json::to_value(&request.messages).unwrap_or_default(),
+        })
+    }
+
+    /// The recorded response for `request`, or an error in replay-only mode
+    fn lookup(&self, request_value: &Value) -> Result<Option<MockResponse>, ProviderError> {
+        match self.cassette.replay(&Cassette::key("completion", request_value)) {
+            Some(response) => serde_json::from_value(response)
+                .map(Some)
+                .map_err(|e| ProviderError::ApiError(format!("Invalid cassette entry: {}", e))),
+            None if self.cassette.mode() == CassetteMode::Replay => {
+                Err(ProviderError::ApiError(self.cassette.missing("completion")))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+fn record_completion(cassette: &Cassette, request: Value, response: MockResponse) {
+    // Failures are not recorded so a flaky first run doesn't get baked in
+    if matches!(response, MockResponse::Error(_)) {
+        return;
+    }
+    let response = serde_json::to_value(&response).unwrap_or_default();
+    if let Err(e) = cassette.record("completion", request, response) {
+        eprintln!("Failed to write cassette: {}", e);
+    }
+}
+
+#[async_trait]
+impl LlmProvider for CassetteProvider {
+    async fn completion(&self, request: CompletionRequest) -> Result<CompletionResponse, ProviderError> {
+        let request_value = Self::request_value(&request);
+        if let Some(response) = self.lookup(&request_value)? {
+            return MockProvider::new().respond_with(response).completion(request).await;
+        }
+        let response = self.inner.completion(request).await?;
+        record_completion(&self.cassette, request_value, recorded(&response.kind));
+        Ok(response)
+    }
+
+    async fn completion_stream(&self, request: CompletionRequest) -> Result<CompletionStream, ProviderError> {
+        let request_value = Self::request_value(&request);
+        if let Some(response) = self.lookup(&request_value)? {
+            return MockProvider::new().respond_with(response).completion_stream(request).await;
+        }
+        let stream = self.inner.completion_stream(request).await?;
+        let cassette = self.cassette.clone();
+        Ok(capture_stream(stream, move |response| {
+            record_completion(&cassette, request_value.clone(), response)
+        }))
+    }
+}
@@ -0,0 +1,161 @@
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+/// What a rule looks for in an agent's output
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PolicyRuleKind {
+    /// A topic the agent must not discuss, recognised by any of its keywords
+    BannedTopic { keywords: Vec<String> },
+    /// Words that must not appear
+    Profanity { words: Vec<String> },
+    /// Competitors that must not be mentioned by name
+    Competitor { names: Vec<String> },
+    /// A custom regular expression
+    Regex { pattern: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PolicyRule {
+    pub name: String,
+    #[serde(flatten)]
+    pub kind: PolicyRuleKind,
+}
+
+/// What happens to output that breaks a rule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum PolicyAction {
+    /// Send the violations back to the model through the correction loop
+    Correct,
+    /// Answer with `template` instead; `{rule}` is replaced by the rule's name
+    Refuse { template: String },
+}
+
+/// A rule an output broke, recorded on the response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PolicyViolation {
+    pub rule: String,
+    /// The text that matched
+    pub matched: String,
+}
+
+/// Rules an agent's output is checked against after generation (`Agent::with_content_policy`).
+/// Word lists match whole words, case-insensitively.
+#[derive(Debug, Clone)]
+pub struct ContentPolicy {
+    rules: Vec<(PolicyRule, Regex)>,
+    pub action: PolicyAction,
+}
+
+impl ContentPolicy {
+    /// An empty policy that corrects violations
+    pub fn new() -> Self {
+        Self { rules: Vec::new(), action: PolicyAction::Correct }
+    }
+
+    /// Build a policy from rules, e.g. loaded from configuration
+    pub fn from_rules(rules: Vec<PolicyRule>, action: PolicyAction) -> Result<Self, String> {
+        let mut policy = Self::new();
+        policy.action = action;
+        for rule in rules {
+            policy = policy.with_rule(rule)?;
+        }
+        Ok(policy)
+    }
+
+    pub fn with_rule(mut self, rule: PolicyRule) -> Result<Self, String> {
+        let regex = compile(&rule)?;
+        self.rules.push((rule, regex));
+        Ok(self)
+    }
+
+    pub fn ban_topic(self, name: &str, keywords: &[&str]) -> Result<Self, String> {
+        self.with_rule(PolicyRule {
+            name: name.to_string(),
+            kind: PolicyRuleKind::BannedTopic { keywords: to_strings(keywords) },
+        })
+    }
+
+    pub fn ban_words(self, name: &str, words: &[&str]) -> Result<Self, String> {
+        self.with_rule(PolicyRule {
+            name: name.to_string(),
+            kind: PolicyRuleKind::Profanity { words: to_strings(words) },
+        })
+    }
+
+    pub fn ban_competitors(self, names: &[&str]) -> Result<Self, String> {
+        self.with_rule(PolicyRule {
+            name: "competitor_mention".to_string(),
+            kind: PolicyRuleKind::Competitor { names: to_strings(names) },
+        })
+    }
+
+    pub fn ban_pattern(self, name: &str, pattern: &str) -> Result<Self, String> {
+        self.with_rule(PolicyRule {
+            name: name.to_string(),
+            kind: PolicyRuleKind::Regex { pattern: pattern.to_string() },
+        })
+    }
+
+    /// Answer with `template` instead of correcting; `{rule}` names the broken rule
+    pub fn refuse_with(mut self, template: &str) -> Self {
+        self.action = PolicyAction::Refuse { template: template.to_string() };
+        self
+    }
+
+    pub fn rules(&self) -> impl Iterator<Item = &PolicyRule> {
+        self.rules.iter().map(|(rule, _)| rule)
+    }
+
+    /// First match of every rule `output` breaks
+    pub fn check(&self, output: &str) -> Vec<PolicyViolation> {
+        self.rules
+            .iter()
+            .filter_map(|(rule, regex)| {
+                regex.find(output).map(|found| PolicyViolation {
+                    rule: rule.name.clone(),
+                    matched: found.as_str().to_string(),
+                })
+            })
+            .collect()
+    }
+
+    /// The refusal for `violations`, when the policy refuses rather than corrects
+    pub fn refusal(&self, violations: &[PolicyViolation]) -> Option<String> {
+        match (&self.action, violations.first()) {
+            (PolicyAction::Refuse { template }, Some(violation)) => Some(template.replace("{rule}", &violation.rule)),
+            _ => None,
+        }
+    }
+}
+
+impl Default for ContentPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn compile(rule: &PolicyRule) -> Result<Regex, String> {
+    let pattern = match &rule.kind {
+        PolicyRuleKind::BannedTopic { keywords: words }
+        | PolicyRuleKind::Profanity { words }
+        | PolicyRuleKind::Competitor { names: words } => {
+            if words.is_empty() {
+                return Err(format!("Policy rule '{}' has no words", rule.name));
+            }
+            let alternatives: Vec<String> = words.iter().map(|word| regex::escape(word.trim())).collect();
+            format!(r"\b(?:{})\b", alternatives.join("|"))
+        }
+        PolicyRuleKind::Regex { pattern } => pattern.clone(),
+    };
+    let word_list = !matches!(rule.kind, PolicyRuleKind::Regex { .. });
+    RegexBuilder::new(&pattern)
+        .case_insensitive(word_list)
+        .build()
+        .map_err(|e| format!("Invalid pattern for policy rule '{}': {}", rule.name, e))
+}
+
+fn to_strings(words: &[&str]) -> Vec<String> {
+    words.iter().map(|word| word.to_string()).collect()
+}
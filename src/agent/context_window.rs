@@ -0,0 +1,158 @@
+use crate::agent::agent::{AgentError, AgentModelConfig};
+use crate::agent::agent_execution::estimate_input_tokens;
+use merco_llmproxy::{ChatMessage, ChatMessageRole};
+
+/// Context windows in tokens at the time of writing; set others with
+/// `AgentModelConfig::with_context_window`
+const KNOWN_CONTEXT_WINDOWS: &[(&str, u32)] = &[
+    ("gpt-4o-mini", 128_000),
+    ("gpt-4o", 128_000),
+    ("gpt-4.1", 1_047_576),
+    ("gpt-4-turbo", 128_000),
+    ("gpt-4", 8_192),
+    ("gpt-3.5-turbo", 16_385),
+    ("o3-mini", 200_000),
+    ("o1-mini", 128_000),
+    ("o1", 200_000),
+    ("claude-3", 200_000),
+    ("gemini-1.5-flash", 1_048_576),
+    ("gemini-1.5-pro", 2_097_152),
+    ("gemini-2.0-flash", 1_048_576),
+    ("llama3", 8_192),
+    ("mistral", 32_768),
+];
+
+/// What replaces tool output dropped to fit the context window
+const OMITTED_TOOL_OUTPUT: &str = "[Earlier tool output omitted to fit the context window]";
+
+/// Times one request is shortened and resent after the provider rejects it as too long
+pub(crate) const MAX_CONTEXT_RECOVERIES: u32 = 3;
+
+/// Provider wording for a prompt longer than the model's context window
+const PROVIDER_OVERFLOW_MARKERS: &[&str] = &[
+    "context_length_exceeded",
+    "maximum context length",
+    "context length",
+    "context window",
+    "prompt is too long",
+    "input is too long",
+    "too many tokens",
+    "reduce the length",
+];
+
+/// Whether an error message is an `AgentError::ContextWindowExceeded` refusal
+pub(crate) fn is_context_overflow(message: &str) -> bool {
+    message.starts_with("Context window exceeded:")
+}
+
+/// Whether a provider error rejects the request for exceeding the context window
+pub(crate) fn is_provider_context_overflow(message: &str) -> bool {
+    let message = message.to_lowercase();
+    PROVIDER_OVERFLOW_MARKERS.iter().any(|marker| message.contains(marker))
+}
+
+/// The error reported once shortening the prompt didn't get it accepted
+pub(crate) fn overflow_error(config: &AgentModelConfig, provider_error: &str) -> String {
+    AgentError::ContextWindowExceeded(format!(
+        "{} rejected the prompt as too long even after shortening it: {}",
+        config.model_name, provider_error
+    ))
+    .to_string()
+}
+
+/// Shorten a prompt the provider rejected as too long, so it can be resent. First
+/// the older half of the remaining tool outputs is replaced by a placeholder; once
+/// none are left, the older half of the tool rounds and correction turns between
+/// the task and the latest round is dropped (calls stay paired with their results).
+/// The system prompt, the task and the latest round are kept. Returns false when
+/// nothing is left to shorten.
+pub(crate) fn shrink_after_overflow(messages: &mut Vec<ChatMessage>) -> bool {
+    // Rounds start at assistant messages; the first two messages are the system prompt and task
+    let round_starts: Vec<usize> = (2..messages.len())
+        .filter(|&index| matches!(messages[index].role, ChatMessageRole::Assistant))
+        .collect();
+    let latest_round = round_starts.last().copied().unwrap_or(messages.len());
+
+    let tool_outputs: Vec<usize> = (2..latest_round)
+        .filter(|&index| {
+            matches!(messages[index].role, ChatMessageRole::Tool)
+                && messages[index].content.as_deref().is_some_and(|c| c != OMITTED_TOOL_OUTPUT)
+        })
+        .collect();
+    if !tool_outputs.is_empty() {
+        for &index in &tool_outputs[..tool_outputs.len().div_ceil(2)] {
+            messages[index].content = Some(OMITTED_TOOL_OUTPUT.to_string());
+        }
+        return true;
+    }
+
+    let earlier_rounds = round_starts.len().saturating_sub(1);
+    if earlier_rounds == 0 {
+        return false;
+    }
+    // Cut at the start of a round so no tool result loses its call
+    let cut = round_starts[earlier_rounds.div_ceil(2)];
+    let dropped = cut - 2;
+    messages.drain(2..cut);
+    messages.insert(2, ChatMessage::user(format!(
+        "[{} earlier messages omitted to fit the context window]",
+        dropped
+    )));
+    true
+}
+
+/// Context window of a known model, matched like prices: exactly, then by the
+/// longest known prefix, ignoring a provider qualifier such as `openai/`
+pub fn known_context_window(model: &str) -> Option<u32> {
+    let model = model.to_lowercase();
+    let model = model.rsplit('/').next().unwrap_or(&model);
+    KNOWN_CONTEXT_WINDOWS
+        .iter()
+        .filter(|(known, _)| model.starts_with(known))
+        .max_by_key(|(known, _)| known.len())
+        .map(|(_, tokens)| *tokens)
+}
+
+/// Make `messages` fit the model's context window with room left for the tool
+/// definitions (`reserved_tokens`) and `max_tokens` of response, so an oversized prompt fails (or shrinks) before
+/// the round trip rather than being rejected by the provider.
+///
+/// The system prompt and the task are kept. Tool results are replaced by a
+/// placeholder oldest first, keeping every call paired with its result, until
+/// the prompt fits. Returns how many messages were shortened.
+pub(crate) fn fit_to_context_window(
+    messages: &mut [ChatMessage],
+    config: &AgentModelConfig,
+    reserved_tokens: u32,
+) -> Result<usize, String> {
+    let Some(window) = config.context_window else {
+        return Ok(0);
+    };
+    let limit = window.saturating_sub(config.max_tokens).saturating_sub(reserved_tokens);
+
+    let mut trimmed = 0;
+    let mut tokens = estimate_input_tokens(messages);
+    for index in 0..messages.len() {
+        if tokens <= limit {
+            return Ok(trimmed);
+        }
+        let message = &mut messages[index];
+        let is_tool_output = matches!(message.role, ChatMessageRole::Tool)
+            && message.content.as_deref().is_some_and(|c| c.len() > OMITTED_TOOL_OUTPUT.len());
+        if is_tool_output {
+            message.content = Some(OMITTED_TOOL_OUTPUT.to_string());
+            trimmed += 1;
+            tokens = estimate_input_tokens(messages);
+        }
+    }
+
+    if tokens <= limit {
+        Ok(trimmed)
+    } else {
+        Err(AgentError::ContextWindowExceeded(format!(
+            "prompt of ~{} tokens plus {} response tokens exceeds the {} token window of {}",
+            tokens + reserved_tokens, config.max_tokens, window, config.model_name
+        ))
+        .to_string())
+    }
+}
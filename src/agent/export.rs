@@ -0,0 +1,272 @@
+use crate::agent::agent::{Agent, AgentResponse, ToolCall};
+use crate::agent::state::{ConversationEntry, ConversationRole};
+use crate::redaction::Redactor;
+use crate::task::run_history::{RunFilter, RunRecord};
+use crate::task::task::Task;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+/// Token, time and tool totals of a run or a whole export
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExportMetrics {
+    pub execution_time_ms: u64,
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    pub total_tokens: u32,
+    pub tool_calls: usize,
+    pub tool_execution_time_ms: u64,
+}
+
+impl ExportMetrics {
+    fn add(&mut self, other: &ExportMetrics) {
+        self.execution_time_ms += other.execution_time_ms;
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.total_tokens += other.total_tokens;
+        self.tool_calls += other.tool_calls;
+        self.tool_execution_time_ms += other.tool_execution_time_ms;
+    }
+}
+
+/// One task run in an export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedRun {
+    pub task_id: String,
+    pub task: String,
+    pub output: String,
+    pub success: bool,
+    pub error: Option<String>,
+    pub model: String,
+    pub tool_calls: Vec<ToolCall>,
+    pub metrics: ExportMetrics,
+    pub completed_at: DateTime<Utc>,
+}
+
+impl ExportedRun {
+    pub fn new(task: &Task, response: &AgentResponse) -> Self {
+        Self {
+            task_id: task.id.clone(),
+            task: task.description.clone(),
+            output: response.content.clone(),
+            success: response.success,
+            error: response.error.clone(),
+            model: response.model_used.clone(),
+            tool_calls: response.tool_calls.clone(),
+            metrics: ExportMetrics {
+                execution_time_ms: response.execution_time_ms,
+                input_tokens: response.input_tokens,
+                output_tokens: response.output_tokens,
+                total_tokens: response.total_tokens,
+                tool_calls: response.tool_calls_count,
+                tool_execution_time_ms: response.tool_execution_time_ms,
+            },
+            completed_at: response.timestamp,
+        }
+    }
+
+    fn redact(&mut self, redactor: &Redactor) {
+        redactor.redact_string(&mut self.task);
+        redactor.redact_string(&mut self.output);
+        if let Some(error) = &mut self.error {
+            redactor.redact_string(error);
+        }
+        for call in &mut self.tool_calls {
+            call.redact(redactor);
+        }
+    }
+}
+
+impl From<&RunRecord> for ExportedRun {
+    fn from(record: &RunRecord) -> Self {
+        Self::new(&record.task, &record.response)
+    }
+}
+
+/// A session's conversation, runs (with their tool calls) and metrics, as a Markdown
+/// transcript or a JSON document to attach to bug reports and reviews. Text passes
+/// through the agent's redactor, so exports don't leak the secrets its runs saw.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionExport {
+    pub agent_id: String,
+    pub agent_name: String,
+    pub model: String,
+    pub session_id: Option<String>,
+    pub conversation: Vec<ConversationEntry>,
+    pub runs: Vec<ExportedRun>,
+    pub totals: ExportMetrics,
+    pub exported_at: DateTime<Utc>,
+    #[serde(skip)]
+    redactor: Option<std::sync::Arc<Redactor>>,
+}
+
+impl SessionExport {
+    /// The agent's conversation history and, if it has a run store, its recorded runs
+    pub fn from_agent(agent: &Agent) -> Self {
+        let mut export = Self {
+            agent_id: agent.id.clone(),
+            agent_name: agent.name.clone(),
+            model: agent.llm_config.model_name.clone(),
+            session_id: agent.context.session_id.clone(),
+            conversation: Vec::new(),
+            runs: Vec::new(),
+            totals: ExportMetrics::default(),
+            exported_at: Utc::now(),
+            redactor: Some(agent.redactor.clone()),
+        };
+        for entry in &agent.context.conversation_history {
+            export.push_entry(entry.clone());
+        }
+        if let Some(store) = &agent.run_store {
+            let mut records = store.list(&RunFilter::new().with_agent_id(&agent.id));
+            records.sort_by_key(|record| record.recorded_at);
+            for record in &records {
+                export.push_run(ExportedRun::from(record));
+            }
+        }
+        export
+    }
+
+    /// Add a run that wasn't recorded in a run store
+    pub fn with_run(mut self, task: &Task, response: &AgentResponse) -> Self {
+        self.push_run(ExportedRun::new(task, response));
+        self
+    }
+
+    pub fn with_records(mut self, records: &[RunRecord]) -> Self {
+        for record in records {
+            self.push_run(ExportedRun::from(record));
+        }
+        self
+    }
+
+    fn push_entry(&mut self, mut entry: ConversationEntry) {
+        if let Some(redactor) = &self.redactor {
+            redactor.redact_string(&mut entry.content);
+        }
+        self.conversation.push(entry);
+    }
+
+    fn push_run(&mut self, mut run: ExportedRun) {
+        if let Some(redactor) = &self.redactor {
+            run.redact(redactor);
+        }
+        self.totals.add(&run.metrics);
+        self.runs.push(run);
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# Session with {}", self.agent_name);
+        let _ = writeln!(out);
+        let _ = writeln!(out, "- Agent: `{}`", self.agent_id);
+        let _ = writeln!(out, "- Model: `{}`", self.model);
+        if let Some(session_id) = &self.session_id {
+            let _ = writeln!(out, "- Session: `{}`", session_id);
+        }
+        let _ = writeln!(out, "- Exported: {}", self.exported_at.to_rfc3339());
+
+        if !self.conversation.is_empty() {
+            let _ = writeln!(out, "\n## Conversation\n");
+            for entry in &self.conversation {
+                let speaker = match entry.role {
+                    ConversationRole::User => "User",
+                    ConversationRole::Agent => "Assistant",
+                    ConversationRole::System => "System",
+                    ConversationRole::Tool => "Tool",
+                };
+                let _ = writeln!(out, "**{}** ({}):\n", speaker, entry.timestamp.format("%H:%M:%S"));
+                let _ = writeln!(out, "{}\n", quote(&entry.content));
+            }
+        }
+
+        if !self.runs.is_empty() {
+            let _ = writeln!(out, "\n## Runs");
+            for (index, run) in self.runs.iter().enumerate() {
+                let status = if run.success { "succeeded" } else { "failed" };
+                let _ = writeln!(out, "\n### {}. {} ({})\n", index + 1, first_line(&run.task), status);
+                let _ = writeln!(
+                    out,
+                    "`{}` · {} · {} ms · {} tokens ({} in / {} out)\n",
+                    run.task_id,
+                    run.model,
+                    run.metrics.execution_time_ms,
+                    run.metrics.total_tokens,
+                    run.metrics.input_tokens,
+                    run.metrics.output_tokens
+                );
+                let _ = writeln!(out, "**Task**\n\n{}\n", quote(&run.task));
+                for call in &run.tool_calls {
+                    let _ = writeln!(out, "**Tool `{}`** ({} ms)\n", call.tool_name, call.execution_time_ms);
+                    let _ = writeln!(out, "{}", fenced("json", &call.parameters));
+                    match &call.error {
+                        Some(error) => {
+                            let _ = writeln!(out, "Error: {}\n", error);
+                        }
+                        None => {
+                            let _ = writeln!(out, "{}", fenced("", &call.result));
+                        }
+                    }
+                }
+                match &run.error {
+                    Some(error) => {
+                        let _ = writeln!(out, "**Error**\n\n{}\n", quote(error));
+                    }
+                    None => {
+                        let _ = writeln!(out, "**Output**\n\n{}\n", quote(&run.output));
+                    }
+                }
+            }
+        }
+
+        let _ = writeln!(out, "\n## Totals\n");
+        let _ = writeln!(out, "| Runs | Tool calls | Tool time (ms) | Time (ms) | Input tokens | Output tokens | Total tokens |");
+        let _ = writeln!(out, "|---|---|---|---|---|---|---|");
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} | {} | {} | {} | {} |",
+            self.runs.len(),
+            self.totals.tool_calls,
+            self.totals.tool_execution_time_ms,
+            self.totals.execution_time_ms,
+            self.totals.input_tokens,
+            self.totals.output_tokens,
+            self.totals.total_tokens
+        );
+        out
+    }
+}
+
+impl Agent {
+    /// Export this agent's session (see `SessionExport`)
+    pub fn export_session(&self) -> SessionExport {
+        SessionExport::from_agent(self)
+    }
+}
+
+fn quote(text: &str) -> String {
+    text.lines().map(|line| format!("> {}", line)).collect::<Vec<_>>().join("\n")
+}
+
+fn first_line(text: &str) -> String {
+    let line = text.lines().next().unwrap_or_default();
+    match line.char_indices().nth(80) {
+        Some((index, _)) => format!("{}…", &line[..index]),
+        None => line.to_string(),
+    }
+}
+
+/// Fence `text`, with a fence longer than any backtick run inside it
+fn fenced(language: &str, text: &str) -> String {
+    let longest = text
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!("{}{}\n{}\n{}\n", fence, language, text, fence)
+}
@@ -0,0 +1,228 @@
+use crate::agent::streaming::is_transient_stream_error;
+use async_trait::async_trait;
+use merco_llmproxy::traits::{CompletionResponse, CompletionStream, ProviderError};
+use merco_llmproxy::{CompletionRequest, LlmProvider};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+tokio::task_local! {
+    static SERVED_BY: Arc<Mutex<Vec<String>>>;
+}
+
+/// Run `future` and collect the names of the failover endpoints that served its provider calls
+pub async fn track_endpoints<F: Future>(future: F) -> (F::Output, Vec<String>) {
+    let served = Arc::new(Mutex::new(Vec::new()));
+    let output = SERVED_BY.scope(served.clone(), future).await;
+    let served = served.lock().unwrap().clone();
+    (output, served)
+}
+
+/// When an endpoint is taken out of rotation and when it gets another chance
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failures that open the circuit
+    pub failure_threshold: u32,
+    /// How long an open circuit skips the endpoint before a trial request is let through
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 3,
+            cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Healthy, receives requests
+    Closed,
+    /// Failing, skipped until the cooldown elapses
+    Open,
+    /// Cooldown elapsed, the next request is a trial
+    HalfOpen,
+}
+
+/// Health snapshot of one endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndpointHealth {
+    pub name: String,
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    pub total_requests: u64,
+    pub total_failures: u64,
+    pub last_error: Option<String>,
+}
+
+struct Endpoint {
+    name: String,
+    provider: Arc<dyn LlmProvider + Send + Sync>,
+    health: Mutex<EndpointHealth>,
+    opened_at: Mutex<Option<Instant>>,
+}
+
+impl Endpoint {
+    /// Whether the endpoint should receive a request now; moves an open circuit to half-open after the cooldown
+    fn available(&self, config: &CircuitBreakerConfig) -> bool {
+        let mut health = self.health.lock().unwrap();
+        match health.state {
+            CircuitState::Closed | CircuitState::HalfOpen => true,
+            CircuitState::Open => {
+                let cooled_down = self
+                    .opened_at
+                    .lock()
+                    .unwrap()
+                    .is_none_or(|opened| opened.elapsed() >= config.cooldown);
+                if cooled_down {
+                    health.state = CircuitState::HalfOpen;
+                }
+                cooled_down
+            }
+        }
+    }
+
+    fn record_success(&self) {
+        let mut health = self.health.lock().unwrap();
+        health.total_requests += 1;
+        health.consecutive_failures = 0;
+        health.state = CircuitState::Closed;
+    }
+
+    fn record_failure(&self, error: &str, config: &CircuitBreakerConfig) {
+        let mut health = self.health.lock().unwrap();
+        health.total_requests += 1;
+        health.total_failures += 1;
+        health.consecutive_failures += 1;
+        health.last_error = Some(error.to_string());
+        // A failed trial reopens immediately
+        if health.state == CircuitState::HalfOpen || health.consecutive_failures >= config.failure_threshold {
+            health.state = CircuitState::Open;
+            *self.opened_at.lock().unwrap() = Some(Instant::now());
+        }
+    }
+}
+
+/// Provider that spreads calls over several endpoints in priority order. Server
+/// errors and timeouts fail over to the next endpoint; endpoints that keep failing
+/// are skipped until their cooldown elapses. Client errors (bad request, auth)
+/// are returned as they are, since another endpoint would reject them too.
+pub struct FailoverProvider {
+    endpoints: Vec<Endpoint>,
+    config: CircuitBreakerConfig,
+    last_served_by: Mutex<Option<String>>,
+}
+
+impl FailoverProvider {
+    pub fn new() -> Self {
+        Self::with_config(CircuitBreakerConfig::default())
+    }
+
+    pub fn with_config(config: CircuitBreakerConfig) -> Self {
+        Self {
+            endpoints: Vec::new(),
+            config,
+            last_served_by: Mutex::new(None),
+        }
+    }
+
+    /// Add an endpoint; endpoints are tried in the order they were added
+    pub fn add_endpoint(mut self, name: &str, provider: Arc<dyn LlmProvider + Send + Sync>) -> Self {
+        self.endpoints.push(Endpoint {
+            name: name.to_string(),
+            provider,
+            health: Mutex::new(EndpointHealth {
+                name: name.to_string(),
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                total_requests: 0,
+                total_failures: 0,
+                last_error: None,
+            }),
+            opened_at: Mutex::new(None),
+        });
+        self
+    }
+
+    /// Health of every endpoint, in priority order
+    pub fn health(&self) -> Vec<EndpointHealth> {
+        self.endpoints.iter().map(|e| e.health.lock().unwrap().clone()).collect()
+    }
+
+    /// Endpoint that served the most recent successful call
+    pub fn last_served_by(&self) -> Option<String> {
+        self.last_served_by.lock().unwrap().clone()
+    }
+
+    /// Endpoints to try, in order. When every circuit is open the whole list is
+    /// tried anyway rather than failing without a request.
+    fn candidates(&self) -> Vec<&Endpoint> {
+        let available: Vec<&Endpoint> = self.endpoints.iter().filter(|e| e.available(&self.config)).collect();
+        if available.is_empty() {
+            self.endpoints.iter().collect()
+        } else {
+            available
+        }
+    }
+
+    fn served(&self, endpoint: &Endpoint) {
+        endpoint.record_success();
+        *self.last_served_by.lock().unwrap() = Some(endpoint.name.clone());
+        let _ = SERVED_BY.try_with(|served| served.lock().unwrap().push(endpoint.name.clone()));
+    }
+
+    async fn call<T, F, Fut>(&self, request: CompletionRequest, send: F) -> Result<T, ProviderError>
+    where
+        F: Fn(Arc<dyn LlmProvider + Send + Sync>, CompletionRequest) -> Fut,
+        Fut: Future<Output = Result<T, ProviderError>>,
+    {
+        let candidates = self.candidates();
+        let mut last_error = None;
+        for endpoint in candidates {
+            match send(endpoint.provider.clone(), request.clone()).await {
+                Ok(result) => {
+                    self.served(endpoint);
+                    return Ok(result);
+                }
+                Err(e) => {
+                    let message = e.to_string();
+                    if !is_failover_error(&message) {
+                        return Err(e);
+                    }
+                    eprintln!("Endpoint '{}' failed, trying next: {}", endpoint.name, crate::redaction::redact(&message));
+                    endpoint.record_failure(&message, &self.config);
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| ProviderError::ConfigError("FailoverProvider has no endpoints".to_string())))
+    }
+}
+
+impl Default for FailoverProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Server-side failures and timeouts are worth another endpoint; rate limits are not,
+/// as they usually apply per account
+pub fn is_failover_error(error: &str) -> bool {
+    is_transient_stream_error(error) && !error.contains("429")
+}
+
+#[async_trait]
+impl LlmProvider for FailoverProvider {
+    async fn completion(&self, request: CompletionRequest) -> Result<CompletionResponse, ProviderError> {
+        self.call(request, |provider, request| async move { provider.completion(request).await }).await
+    }
+
+    /// Fails over only while opening the stream; errors after the first chunk are the caller's to retry
+    async fn completion_stream(&self, request: CompletionRequest) -> Result<CompletionStream, ProviderError> {
+        self.call(request, |provider, request| async move { provider.completion_stream(request).await }).await
+    }
+}
@@ -0,0 +1,266 @@
+use crate::agent::output_handler::extract_json;
+use crate::agent::role::OutputFormat;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::sync::OnceLock;
+
+/// How the output handler reconciles a response produced in the wrong format
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FormatConversion {
+    /// Validate against the requested format only
+    #[default]
+    Disabled,
+    /// Convert with built-in rules (JSON, CSV, Markdown, HTML, text)
+    RuleBased,
+    /// Rules first, then ask the model to rewrite the response in the requested format
+    LlmAssisted,
+}
+
+/// Best guess at the format a response is written in
+pub fn detect_format(output: &str) -> OutputFormat {
+    let trimmed = crate::task::task::strip_code_fence(output);
+    if (trimmed.starts_with('{') || trimmed.starts_with('[')) && extract_json(trimmed) == Some(trimmed) {
+        return OutputFormat::Json;
+    }
+    if trimmed.starts_with('<') {
+        if trimmed.get(..15).is_some_and(|start| start.to_lowercase().starts_with("<!doctype html")) || html_tag().is_match(trimmed) {
+            return OutputFormat::Html;
+        }
+        if crate::task::xml_format::parse_xml(trimmed).is_ok() {
+            return OutputFormat::Xml;
+        }
+    }
+    if looks_like_csv(trimmed) {
+        return OutputFormat::Csv { delimiter: ',' };
+    }
+    if markdown_marker().is_match(trimmed) {
+        return OutputFormat::Markdown;
+    }
+    OutputFormat::Text
+}
+
+fn html_tag() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?i)^<(html|body|div|p|h[1-6]|ul|ol|table|span|section|article)[\s>]").unwrap())
+}
+
+fn markdown_marker() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?m)^(#{1,6} |[-*+] |\d+\. |> |\|.*\|$)|\*\*[^*]+\*\*|\[[^\]]+\]\([^)]+\)").unwrap())
+}
+
+/// At least two lines, all with the same number of comma-separated fields (two or more)
+fn looks_like_csv(text: &str) -> bool {
+    let Ok(rows) = crate::task::csv_format::parse_csv(text, ',') else {
+        return false;
+    };
+    rows.len() >= 2 && rows[0].len() >= 2 && rows.iter().all(|row| row.len() == rows[0].len())
+}
+
+/// Convert `output` from one format to another with built-in rules
+pub fn convert(output: &str, from: &OutputFormat, to: &OutputFormat) -> Result<String, String> {
+    if from == to {
+        return Ok(output.to_string());
+    }
+    let body = crate::task::task::strip_code_fence(output);
+    match (from, to) {
+        (OutputFormat::Json, OutputFormat::Markdown) => Ok(json_to_markdown(&parse_json(body)?, 0)),
+        (OutputFormat::Json, OutputFormat::Text) => Ok(json_to_text(&parse_json(body)?, "")),
+        (OutputFormat::Json, OutputFormat::Csv { delimiter }) => json_to_csv(&parse_json(body)?, *delimiter),
+        (OutputFormat::Csv { delimiter: from }, OutputFormat::Csv { delimiter: to }) => {
+            let (header, rows) = parse_table(body, *from)?;
+            Ok(csv_lines(&header, &rows, *to))
+        }
+        (OutputFormat::Csv { delimiter }, OutputFormat::Json) => {
+            let (header, rows) = parse_table(body, *delimiter)?;
+            let objects: Vec<Value> = rows
+                .iter()
+                .map(|row| {
+                    let object: Map<String, Value> = header
+                        .iter()
+                        .cloned()
+                        .zip(row.iter().map(|field| Value::String(field.clone())))
+                        .collect();
+                    Value::Object(object)
+                })
+                .collect();
+            serde_json::to_string_pretty(&objects).map_err(|e| e.to_string())
+        }
+        (OutputFormat::Csv { delimiter }, OutputFormat::Markdown) => {
+            let (header, rows) = parse_table(body, *delimiter)?;
+            Ok(markdown_table(&header, &rows))
+        }
+        (OutputFormat::Markdown, OutputFormat::Text) => Ok(markdown_to_text(body)),
+        (OutputFormat::Html, OutputFormat::Text) => Ok(html_to_text(body)),
+        (OutputFormat::Html, OutputFormat::Markdown) => Ok(html_to_text(body)),
+        (OutputFormat::Text, OutputFormat::Markdown) => Ok(body.to_string()),
+        _ => Err(format!("No rule to convert {:?} to {:?}", from, to)),
+    }
+}
+
+fn parse_json(text: &str) -> Result<Value, String> {
+    serde_json::from_str(text).map_err(|e| format!("Invalid JSON: {}", e))
+}
+
+fn parse_table(text: &str, delimiter: char) -> Result<(Vec<String>, Vec<Vec<String>>), String> {
+    let mut rows = crate::task::csv_format::parse_csv(text, delimiter).map_err(|e| e.to_string())?;
+    if rows.is_empty() {
+        return Err("CSV has no header row".to_string());
+    }
+    let header = rows.remove(0);
+    Ok((header, rows))
+}
+
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn json_to_markdown(value: &Value, depth: usize) -> String {
+    let indent = "  ".repeat(depth);
+    match value {
+        Value::Object(object) => object
+            .iter()
+            .map(|(key, value)| match value {
+                Value::Object(_) | Value::Array(_) => format!("{}- **{}**:\n{}", indent, key, json_to_markdown(value, depth + 1)),
+                _ => format!("{}- **{}**: {}", indent, key, scalar(value)),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Value::Array(items) if depth == 0 && !items.is_empty() && items.iter().all(Value::is_object) => {
+            let (header, rows) = objects_to_rows(items);
+            markdown_table(&header, &rows)
+        }
+        Value::Array(items) => items
+            .iter()
+            .map(|item| match item {
+                Value::Object(_) | Value::Array(_) => format!("{}-\n{}", indent, json_to_markdown(item, depth + 1)),
+                _ => format!("{}- {}", indent, scalar(item)),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        other => format!("{}{}", indent, scalar(other)),
+    }
+}
+
+fn json_to_text(value: &Value, prefix: &str) -> String {
+    match value {
+        Value::Object(object) => object
+            .iter()
+            .map(|(key, value)| {
+                let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                match value {
+                    Value::Object(_) | Value::Array(_) => json_to_text(value, &path),
+                    _ => format!("{}: {}", path, scalar(value)),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| json_to_text(item, &format!("{}[{}]", prefix, i)))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        other if prefix.is_empty() => scalar(other),
+        other => format!("{}: {}", prefix, scalar(other)),
+    }
+}
+
+/// Header is the union of keys in first-seen order
+fn objects_to_rows(items: &[Value]) -> (Vec<String>, Vec<Vec<String>>) {
+    let mut header: Vec<String> = Vec::new();
+    for item in items {
+        if let Value::Object(object) = item {
+            for key in object.keys() {
+                if !header.contains(key) {
+                    header.push(key.clone());
+                }
+            }
+        }
+    }
+    let rows = items
+        .iter()
+        .map(|item| header.iter().map(|key| item.get(key).map(scalar).unwrap_or_default()).collect())
+        .collect();
+    (header, rows)
+}
+
+fn json_to_csv(value: &Value, delimiter: char) -> Result<String, String> {
+    // Accept a bare array of objects or an object wrapping exactly one
+    let items = match value {
+        Value::Array(items) => items,
+        Value::Object(object) => match object.values().filter(|v| v.is_array()).collect::<Vec<_>>().as_slice() {
+            [Value::Array(items)] => items,
+            _ => return Err("JSON must be an array of objects to convert to CSV".to_string()),
+        },
+        _ => return Err("JSON must be an array of objects to convert to CSV".to_string()),
+    };
+    if !items.iter().all(Value::is_object) {
+        return Err("JSON must be an array of objects to convert to CSV".to_string());
+    }
+    let (header, rows) = objects_to_rows(items);
+    Ok(csv_lines(&header, &rows, delimiter))
+}
+
+fn csv_lines(header: &[String], rows: &[Vec<String>], delimiter: char) -> String {
+    let line = |fields: &[String]| {
+        fields.iter().map(|f| csv_field(f, delimiter)).collect::<Vec<_>>().join(&delimiter.to_string())
+    };
+    let mut lines = vec![line(header)];
+    lines.extend(rows.iter().map(|row| line(row)));
+    lines.join("\n")
+}
+
+fn csv_field(field: &str, delimiter: char) -> String {
+    if field.contains([delimiter, '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn markdown_table(header: &[String], rows: &[Vec<String>]) -> String {
+    let line = |fields: &[String]| format!("| {} |", fields.iter().map(|f| f.replace('|', "\\|")).collect::<Vec<_>>().join(" | "));
+    let mut lines = vec![line(header), format!("|{}", " --- |".repeat(header.len()))];
+    lines.extend(rows.iter().map(|row| line(row)));
+    lines.join("\n")
+}
+
+fn markdown_to_text(markdown: &str) -> String {
+    static LINK: OnceLock<Regex> = OnceLock::new();
+    static EMPHASIS: OnceLock<Regex> = OnceLock::new();
+    static PREFIX: OnceLock<Regex> = OnceLock::new();
+    let link = LINK.get_or_init(|| Regex::new(r"\[([^\]]+)\]\(([^)]+)\)").unwrap());
+    let emphasis = EMPHASIS.get_or_init(|| Regex::new(r"(\*\*|__|\*|_|`)").unwrap());
+    let prefix = PREFIX.get_or_init(|| Regex::new(r"(?m)^(#{1,6} |> )").unwrap());
+
+    let text = link.replace_all(markdown, "$1 ($2)");
+    let text = prefix.replace_all(&text, "");
+    emphasis.replace_all(&text, "").into_owned()
+}
+
+fn html_to_text(html: &str) -> String {
+    static BREAK: OnceLock<Regex> = OnceLock::new();
+    static TAG: OnceLock<Regex> = OnceLock::new();
+    let line_break = BREAK.get_or_init(|| Regex::new(r"(?i)<br\s*/?>|</(p|div|h[1-6]|li|tr)>").unwrap());
+    let tag = TAG.get_or_init(|| Regex::new(r"<[^>]+>").unwrap());
+
+    let text = line_break.replace_all(html, "\n");
+    let text = tag.replace_all(&text, "");
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
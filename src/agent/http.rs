@@ -0,0 +1,84 @@
+use crate::agent::state::{NetworkContext, ProxySettings};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// Settings of the HTTP client shared by all outgoing requests of the crate
+#[derive(Debug, Clone)]
+pub struct HttpClientConfig {
+    /// Whole-request timeout
+    pub timeout: Duration,
+    pub connect_timeout: Duration,
+    /// How long idle pooled connections are kept open
+    pub pool_idle_timeout: Duration,
+    pub pool_max_idle_per_host: usize,
+    /// Let HTTP/2 connections size their flow-control window to the link
+    pub http2_adaptive_window: bool,
+    pub proxy: Option<ProxySettings>,
+    /// Redirects followed per request; 0 returns redirect responses as they are
+    pub max_redirects: usize,
+}
+
+impl Default for HttpClientConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(120),
+            connect_timeout: Duration::from_secs(10),
+            pool_idle_timeout: Duration::from_secs(90),
+            pool_max_idle_per_host: 16,
+            http2_adaptive_window: true,
+            proxy: None,
+            max_redirects: 10,
+        }
+    }
+}
+
+impl HttpClientConfig {
+    /// Default settings with the proxy declared in the agent's network context
+    pub fn from_network_context(network: &NetworkContext) -> Self {
+        Self {
+            proxy: network.proxy_settings.clone(),
+            ..Self::default()
+        }
+    }
+
+    pub fn build(&self) -> Result<reqwest::Client, String> {
+        let mut builder = reqwest::Client::builder()
+            .timeout(self.timeout)
+            .connect_timeout(self.connect_timeout)
+            .pool_idle_timeout(self.pool_idle_timeout)
+            .pool_max_idle_per_host(self.pool_max_idle_per_host)
+            .http2_adaptive_window(self.http2_adaptive_window)
+            .redirect(match self.max_redirects {
+                0 => reqwest::redirect::Policy::none(),
+                max => reqwest::redirect::Policy::limited(max),
+            });
+
+        if let Some(settings) = &self.proxy {
+            let mut proxy = reqwest::Proxy::all(format!("http://{}:{}", settings.host, settings.port))
+                .map_err(|e| format!("Invalid proxy settings: {}", e))?;
+            if let Some(auth) = &settings.authentication {
+                proxy = proxy.basic_auth(&auth.username, &auth.password);
+            }
+            builder = builder.proxy(proxy);
+        }
+
+        builder.build().map_err(|e| format!("Failed to build HTTP client: {}", e))
+    }
+}
+
+static SHARED_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+
+/// Install the process-wide client. Returns false if a client was already in use,
+/// so call this at startup before any request goes out.
+pub fn init_shared_client(config: &HttpClientConfig) -> Result<bool, String> {
+    let client = config.build()?;
+    Ok(SHARED_CLIENT.set(client).is_ok())
+}
+
+/// The process-wide client; built with default settings on first use.
+/// Clones share one connection pool.
+pub fn shared_client() -> reqwest::Client {
+    SHARED_CLIENT
+        .get_or_init(|| HttpClientConfig::default().build().unwrap_or_default())
+        .clone()
+}
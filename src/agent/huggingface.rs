@@ -0,0 +1,287 @@
+use crate::agent::http::shared_client;
+use crate::agent::tool_schema::ToolSchemas;
+use crate::credentials::Credential;
+use async_stream::stream;
+use async_trait::async_trait;
+use futures::StreamExt;
+use merco_llmproxy::traits::{
+    CompletionResponse, CompletionStream, PartialFunctionCall, ProviderError, StreamChunk,
+    TokenUsage, ToolCall, ToolCallStreamDelta,
+};
+use merco_llmproxy::{CompletionKind, CompletionRequest, LlmProvider, StreamContentDelta, Tool};
+use serde::Serialize;
+use serde_json::value::RawValue;
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+/// Serverless Hugging Face inference router (OpenAI-compatible)
+pub const HUGGINGFACE_ROUTER_URL: &str = "https://router.huggingface.co/v1";
+
+/// Provider for Hugging Face Inference Endpoints, the serverless router and
+/// self-hosted Text Generation Inference, all through the OpenAI-style messages API.
+///
+/// TGI returns tool call arguments as JSON objects rather than strings and may
+/// omit call ids; both are normalised so tool calls work like other providers'.
+#[derive(Debug, Clone)]
+pub struct HuggingFaceProvider {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: Option<Credential>,
+    /// Serialized schemas of the last tool set sent, reused while it doesn't change
+    tool_schemas: Arc<Mutex<Option<Arc<ToolSchemas>>>>,
+}
+
+/// Request body with the tool schemas spliced in already serialized
+#[derive(Serialize)]
+struct RequestBody<'a> {
+    #[serde(flatten)]
+    fields: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<&'a RawValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<&'static str>,
+}
+
+impl HuggingFaceProvider {
+    /// `base_url` is the API root ending in `/v1`
+    pub fn new(base_url: &str, api_key: Option<String>) -> Self {
+        Self {
+            client: shared_client(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: api_key.map(Credential::fixed),
+            tool_schemas: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Hugging Face's serverless router; the model is picked by `model_name`
+    pub fn serverless(api_key: String) -> Self {
+        Self::new(HUGGINGFACE_ROUTER_URL, Some(api_key))
+    }
+
+    /// A TGI server or Inference Endpoint at `url` (without the `/v1` suffix)
+    pub fn tgi(url: &str, api_key: Option<String>) -> Self {
+        let url = url.trim_end_matches('/');
+        let base_url = if url.ends_with("/v1") { url.to_string() } else { format!("{}/v1", url) };
+        Self::new(&base_url, api_key)
+    }
+
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Resolve the token from `credential` on every request
+    pub fn with_credential(mut self, credential: Credential) -> Self {
+        self.api_key = Some(credential);
+        self
+    }
+
+    fn schemas_for(&self, tools: &[Tool]) -> Arc<ToolSchemas> {
+        let mut cached = self.tool_schemas.lock().unwrap();
+        if let Some(schemas) = cached.as_ref().filter(|schemas| schemas.matches(tools)) {
+            return schemas.clone();
+        }
+        let schemas = Arc::new(ToolSchemas::new(tools));
+        *cached = Some(schemas.clone());
+        schemas
+    }
+
+    fn body<'a>(&self, request: &CompletionRequest, stream: bool, schemas: Option<&'a ToolSchemas>) -> RequestBody<'a> {
+        let mut body = json!({
+            "model": request.model,
+            "messages": request.messages,
+            "stream": stream,
+        });
+        if let Some(temperature) = request.temperature {
+            body["temperature"] = json!(temperature);
+        }
+        if let Some(max_tokens) = request.max_tokens {
+            body["max_tokens"] = json!(max_tokens);
+        }
+        if stream {
+            body["stream_options"] = json!({ "include_usage": true });
+        }
+        RequestBody {
+            fields: body,
+            tools: schemas.map(ToolSchemas::json),
+            tool_choice: schemas.map(|_| "auto"),
+        }
+    }
+
+    /// Tool schemas for the request, none when it has no tools
+    fn request_schemas(&self, request: &CompletionRequest) -> Option<Arc<ToolSchemas>> {
+        request
+            .tools
+            .as_deref()
+            .filter(|tools| !tools.is_empty())
+            .map(|tools| self.schemas_for(tools))
+    }
+
+    async fn send(&self, body: &RequestBody<'_>) -> Result<reqwest::Response, ProviderError> {
+        let mut request = self.client.post(format!("{}/chat/completions", self.base_url)).json(body);
+        if let Some(api_key) = self.api_key.as_ref().and_then(Credential::resolve_or_log) {
+            request = request.bearer_auth(api_key);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| ProviderError::ApiError(format!("Request to {} failed: {}", self.base_url, e)))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            // Keep the status code in the message so failover can recognise 5xx responses
+            return Err(ProviderError::ApiError(format!("HTTP {}: {}", status.as_u16(), text)));
+        }
+        Ok(response)
+    }
+}
+
+/// Tool call arguments as a JSON string, whichever form the server used
+fn arguments_string(arguments: Option<&Value>) -> String {
+    match arguments {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Null) | None => String::new(),
+        Some(other) => other.to_string(),
+    }
+}
+
+fn parse_usage(body: &Value) -> Option<TokenUsage> {
+    let usage = body.get("usage").filter(|u| !u.is_null())?;
+    let field = |key: &str| usage.get(key).and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+    Some(TokenUsage {
+        prompt_tokens: field("prompt_tokens"),
+        completion_tokens: field("completion_tokens"),
+        total_tokens: field("total_tokens"),
+    })
+}
+
+fn parse_tool_calls(calls: &[Value]) -> Vec<ToolCall> {
+    let calls: Vec<Value> = calls
+        .iter()
+        .enumerate()
+        .map(|(index, call)| {
+            let function = call.get("function").cloned().unwrap_or(Value::Null);
+            json!({
+                "id": call.get("id").and_then(|id| id.as_str()).map(str::to_string).unwrap_or_else(|| format!("call_{}", index)),
+                "type": "function",
+                "function": {
+                    "name": function.get("name").and_then(|n| n.as_str()).unwrap_or_default(),
+                    "arguments": arguments_string(function.get("arguments")),
+                },
+            })
+        })
+        .collect();
+    serde_json::from_value(Value::Array(calls)).unwrap_or_default()
+}
+
+/// Translate one streamed `chat.completion.chunk` into the provider-neutral chunk
+fn parse_stream_chunk(body: &Value) -> StreamChunk {
+    let choice = body.get("choices").and_then(|c| c.get(0));
+    let delta = choice.and_then(|c| c.get("delta"));
+    let tool_deltas: Vec<ToolCallStreamDelta> = delta
+        .and_then(|d| d.get("tool_calls"))
+        .and_then(|calls| calls.as_array())
+        .map(|calls| {
+            calls
+                .iter()
+                .enumerate()
+                .map(|(position, call)| {
+                    let function = call.get("function");
+                    ToolCallStreamDelta {
+                        index: call.get("index").and_then(|i| i.as_u64()).unwrap_or(position as u64) as u32,
+                        id: call.get("id").and_then(|id| id.as_str()).map(str::to_string),
+                        function: function.map(|f| PartialFunctionCall {
+                            name: f.get("name").and_then(|n| n.as_str()).map(str::to_string),
+                            arguments: f.get("arguments").map(|a| arguments_string(Some(a))),
+                        }),
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let delta = if tool_deltas.is_empty() {
+        let text = delta.and_then(|d| d.get("content")).and_then(|c| c.as_str()).unwrap_or_default();
+        StreamContentDelta::Text(text.to_string())
+    } else {
+        StreamContentDelta::ToolCallDelta(tool_deltas)
+    };
+
+    StreamChunk {
+        delta,
+        finish_reason: choice
+            .and_then(|c| c.get("finish_reason"))
+            .and_then(|r| r.as_str())
+            .map(str::to_string),
+        usage: parse_usage(body),
+    }
+}
+
+#[async_trait]
+impl LlmProvider for HuggingFaceProvider {
+    async fn completion(&self, request: CompletionRequest) -> Result<CompletionResponse, ProviderError> {
+        let schemas = self.request_schemas(&request);
+        let response = self.send(&self.body(&request, false, schemas.as_deref())).await?;
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| ProviderError::ApiError(format!("Invalid response: {}", e)))?;
+        let message = body
+            .get("choices")
+            .and_then(|c| c.get(0))
+            .and_then(|c| c.get("message"))
+            .ok_or_else(|| ProviderError::ApiError("Response has no choices".to_string()))?;
+
+        let kind = match message.get("tool_calls").and_then(|t| t.as_array()).filter(|calls| !calls.is_empty()) {
+            Some(calls) => CompletionKind::ToolCall { tool_calls: parse_tool_calls(calls) },
+            None => CompletionKind::Message {
+                content: message.get("content").and_then(|c| c.as_str()).unwrap_or_default().to_string(),
+            },
+        };
+        Ok(CompletionResponse { kind, usage: parse_usage(&body) })
+    }
+
+    async fn completion_stream(&self, request: CompletionRequest) -> Result<CompletionStream, ProviderError> {
+        let schemas = self.request_schemas(&request);
+        let response = self.send(&self.body(&request, true, schemas.as_deref())).await?;
+        let mut bytes = response.bytes_stream();
+
+        Ok(Box::pin(stream! {
+            let mut buffer = String::new();
+            while let Some(next) = bytes.next().await {
+                let data = match next {
+                    Ok(data) => data,
+                    Err(e) => {
+                        yield Err(ProviderError::ApiError(format!("Stream connection reset: {}", e)));
+                        return;
+                    }
+                };
+                buffer.push_str(&String::from_utf8_lossy(&data));
+
+                // Server-sent events: one `data:` line per chunk, terminated by `[DONE]`
+                while let Some(newline) = buffer.find('\n') {
+                    let line: String = buffer.drain(..=newline).collect();
+                    let payload = match line.trim().strip_prefix("data:") {
+                        Some(payload) => payload.trim(),
+                        None => continue,
+                    };
+                    if payload == "[DONE]" {
+                        return;
+                    }
+                    match serde_json::from_str::<Value>(payload) {
+                        Ok(body) if body.get("error").is_some() => {
+                            yield Err(ProviderError::ApiError(format!("Stream error: {}", body["error"])));
+                            return;
+                        }
+                        Ok(body) => yield Ok(parse_stream_chunk(&body)),
+                        Err(e) => {
+                            yield Err(ProviderError::ApiError(format!("Invalid stream chunk: {}", e)));
+                            return;
+                        }
+                    }
+                }
+            }
+        }))
+    }
+}
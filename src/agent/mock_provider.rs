@@ -0,0 +1,291 @@
+use async_trait::async_trait;
+use futures::stream::{self, StreamExt};
+use merco_llmproxy::traits::{
+    CompletionResponse, CompletionStream, PartialFunctionCall, ProviderError, StreamChunk,
+    TokenUsage, ToolCall, ToolCallStreamDelta,
+};
+use merco_llmproxy::{ChatMessage, CompletionKind, CompletionRequest, LlmProvider, StreamContentDelta};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// One scripted provider reply
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum MockResponse {
+    /// A plain text answer
+    Message(String),
+    /// Tool calls as `(name, arguments)` pairs
+    ToolCalls(Vec<(String, String)>),
+    /// The request fails before anything is returned
+    Error(String),
+}
+
+#[derive(Debug, Clone)]
+struct MockStep {
+    response: MockResponse,
+    /// Streaming only: fail with this message after emitting this many chunks
+    stream_error: Option<(usize, String)>,
+}
+
+/// Provider that replays scripted responses, for testing agents, crews and
+/// streaming handlers without network access or API keys.
+///
+/// Each request consumes the next scripted response; once the script is used up
+/// the default response is repeated (or requests fail if there is none).
+#[derive(Debug)]
+pub struct MockProvider {
+    script: Mutex<VecDeque<MockStep>>,
+    default_response: Option<MockResponse>,
+    latency: Duration,
+    chunk_size: usize,
+    chunk_delay: Duration,
+    requests: Mutex<Vec<Vec<ChatMessage>>>,
+}
+
+impl Default for MockProvider {
+    fn default() -> Self {
+        Self {
+            script: Mutex::new(VecDeque::new()),
+            default_response: None,
+            latency: Duration::ZERO,
+            chunk_size: 8,
+            chunk_delay: Duration::ZERO,
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl MockProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a text answer
+    pub fn respond(self, content: &str) -> Self {
+        self.push(MockResponse::Message(content.to_string()), None)
+    }
+
+    /// Queue a single tool call
+    pub fn respond_with_tool_call(self, name: &str, arguments: &str) -> Self {
+        self.respond_with_tool_calls(vec![(name, arguments)])
+    }
+
+    /// Queue several tool calls requested in one turn
+    pub fn respond_with_tool_calls(self, calls: Vec<(&str, &str)>) -> Self {
+        let calls = calls
+            .into_iter()
+            .map(|(name, arguments)| (name.to_string(), arguments.to_string()))
+            .collect();
+        self.push(MockResponse::ToolCalls(calls), None)
+    }
+
+    /// Queue a request that fails outright
+    pub fn fail(self, message: &str) -> Self {
+        self.push(MockResponse::Error(message.to_string()), None)
+    }
+
+    /// Queue a text answer whose stream breaks after `chunks` chunks.
+    /// Non-streaming requests receive the full answer.
+    pub fn respond_then_fail(self, content: &str, chunks: usize, message: &str) -> Self {
+        self.push(
+            MockResponse::Message(content.to_string()),
+            Some((chunks, message.to_string())),
+        )
+    }
+
+    /// Queue any scripted response, e.g. from a recorded run
+    pub fn respond_with(self, response: MockResponse) -> Self {
+        self.push(response, None)
+    }
+
+    /// Response used once the script is exhausted
+    pub fn with_default_response(mut self, content: &str) -> Self {
+        self.default_response = Some(MockResponse::Message(content.to_string()));
+        self
+    }
+
+    /// Delay before every response (or before the first chunk of a stream)
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Characters per streamed text chunk
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Delay between streamed chunks
+    pub fn with_chunk_delay(mut self, delay: Duration) -> Self {
+        self.chunk_delay = delay;
+        self
+    }
+
+    /// Messages of every request received so far, in order
+    pub fn requests(&self) -> Vec<Vec<ChatMessage>> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    pub fn request_count(&self) -> usize {
+        self.requests.lock().unwrap().len()
+    }
+
+    /// Number of scripted responses not yet consumed
+    pub fn remaining(&self) -> usize {
+        self.script.lock().unwrap().len()
+    }
+
+    fn push(self, response: MockResponse, stream_error: Option<(usize, String)>) -> Self {
+        self.script.lock().unwrap().push_back(MockStep { response, stream_error });
+        self
+    }
+
+    /// Record the request and take the next scripted step
+    async fn next_step(&self, request: &CompletionRequest) -> Result<MockStep, ProviderError> {
+        self.requests.lock().unwrap().push(request.messages.clone());
+        if !self.latency.is_zero() {
+            tokio::time::sleep(self.latency).await;
+        }
+
+        let step = self.script.lock().unwrap().pop_front().or_else(|| {
+            self.default_response.clone().map(|response| MockStep { response, stream_error: None })
+        });
+        match step {
+            Some(MockStep { response: MockResponse::Error(message), .. }) => Err(ProviderError::ApiError(message)),
+            Some(step) => Ok(step),
+            None => Err(ProviderError::ApiError("MockProvider script exhausted".to_string())),
+        }
+    }
+
+    fn split_text(&self, content: &str) -> Vec<String> {
+        let chars: Vec<char> = content.chars().collect();
+        chars.chunks(self.chunk_size).map(|chunk| chunk.iter().collect()).collect()
+    }
+}
+
+/// Usage with ~4 characters per token, so token accounting has something to sum
+fn estimated_usage(request: &CompletionRequest, output: &str) -> TokenUsage {
+    let prompt_chars: usize = request
+        .messages
+        .iter()
+        .filter_map(|message| message.content.as_deref())
+        .map(str::len)
+        .sum();
+    let prompt_tokens = prompt_chars.div_ceil(4) as u32;
+    let completion_tokens = output.len().div_ceil(4) as u32;
+    TokenUsage {
+        prompt_tokens,
+        completion_tokens,
+        total_tokens: prompt_tokens + completion_tokens,
+    }
+}
+
+fn tool_calls(calls: &[(String, String)]) -> Vec<ToolCall> {
+    let calls: Vec<_> = calls
+        .iter()
+        .enumerate()
+        .map(|(index, (name, arguments))| {
+            json!({
+                "id": format!("mock_call_{}", index),
+                "type": "function",
+                "function": { "name": name, "arguments": arguments },
+            })
+        })
+        .collect();
+    serde_json::from_value(serde_json::Value::Array(calls)).unwrap_or_default()
+}
+
+#[async_trait]
+impl LlmProvider for MockProvider {
+    async fn completion(&self, request: CompletionRequest) -> Result<CompletionResponse, ProviderError> {
+        let step = self.next_step(&request).await?;
+        let (kind, output) = match step.response {
+            MockResponse::Message(content) => {
+                let output = content.clone();
+                (CompletionKind::Message { content }, output)
+            }
+            MockResponse::ToolCalls(calls) => {
+                let output = calls.iter().map(|(_, arguments)| arguments.as_str()).collect::<String>();
+                (CompletionKind::ToolCall { tool_calls: tool_calls(&calls) }, output)
+            }
+            MockResponse::Error(_) => unreachable!("errors are returned by next_step"),
+        };
+        Ok(CompletionResponse {
+            kind,
+            usage: Some(estimated_usage(&request, &output)),
+        })
+    }
+
+    async fn completion_stream(&self, request: CompletionRequest) -> Result<CompletionStream, ProviderError> {
+        let step = self.next_step(&request).await?;
+        let mut items: Vec<Result<StreamChunk, ProviderError>> = Vec::new();
+        let output = match &step.response {
+            MockResponse::Message(content) => {
+                for text in self.split_text(content) {
+                    items.push(Ok(StreamChunk {
+                        delta: StreamContentDelta::Text(text),
+                        finish_reason: None,
+                        usage: None,
+                    }));
+                }
+                content.clone()
+            }
+            MockResponse::ToolCalls(calls) => {
+                // Name and id first, then the arguments in fragments, like real providers
+                for (index, (name, arguments)) in calls.iter().enumerate() {
+                    items.push(Ok(StreamChunk {
+                        delta: StreamContentDelta::ToolCallDelta(vec![ToolCallStreamDelta {
+                            index: index as u32,
+                            id: Some(format!("mock_call_{}", index)),
+                            function: Some(PartialFunctionCall { name: Some(name.clone()), arguments: None }),
+                        }]),
+                        finish_reason: None,
+                        usage: None,
+                    }));
+                    for fragment in self.split_text(arguments) {
+                        items.push(Ok(StreamChunk {
+                            delta: StreamContentDelta::ToolCallDelta(vec![ToolCallStreamDelta {
+                                index: index as u32,
+                                id: None,
+                                function: Some(PartialFunctionCall { name: None, arguments: Some(fragment) }),
+                            }]),
+                            finish_reason: None,
+                            usage: None,
+                        }));
+                    }
+                }
+                calls.iter().map(|(_, arguments)| arguments.as_str()).collect()
+            }
+            MockResponse::Error(_) => unreachable!("errors are returned by next_step"),
+        };
+
+        match step.stream_error {
+            Some((after, message)) => {
+                items.truncate(after);
+                items.push(Err(ProviderError::ApiError(message)));
+            }
+            None => {
+                let finish_reason = match step.response {
+                    MockResponse::ToolCalls(_) => "tool_calls",
+                    _ => "stop",
+                };
+                items.push(Ok(StreamChunk {
+                    delta: StreamContentDelta::Text(String::new()),
+                    finish_reason: Some(finish_reason.to_string()),
+                    usage: Some(estimated_usage(&request, &output)),
+                }));
+            }
+        }
+
+        let delay = self.chunk_delay;
+        Ok(Box::pin(stream::iter(items).then(move |item| async move {
+            if !delay.is_zero() {
+                tokio::time::sleep(delay).await;
+            }
+            item
+        })))
+    }
+}
@@ -2,16 +2,49 @@ pub mod agent;
 pub mod role;
 pub mod state;
 pub mod output_handler;
+pub mod output_pipeline;
+pub mod content_policy;
+pub mod format_conversion;
 pub mod agent_constructors;
 pub mod agent_execution;
 pub mod agent_management;
 pub mod agent_prompts;
+pub mod agent_queue;
+pub mod agent_batch;
+pub mod agent_multi_step;
+pub mod context_window;
+pub mod tool_schema;
+pub mod rate_limit;
+pub mod profile;
+pub mod prompt_registry;
+pub mod prompt_locale;
+pub mod registry;
+pub mod export;
+pub mod tool_analytics;
+pub mod reasoning_trace;
+pub mod scoring;
 pub mod provider;
+pub mod mock_provider;
+pub mod replay;
+pub mod cassette;
+pub mod failover;
+pub mod rotating_provider;
+pub mod http;
+pub mod huggingface;
 pub mod streaming;
+pub mod sse;
+pub mod stream_buffer;
+pub mod stream_recording;
+pub mod stream_transform;
+pub mod stream_multiplex;
+pub mod terminal;
+pub mod repl;
+pub mod status;
 
 // Re-export main types for easier access
 pub use agent::Agent;
 pub use agent::AgentModelConfig;
+pub use agent_constructors::AgentBuilder;
 pub use agent::AgentResponse;
 pub use agent::TaskResult;
 pub use agent::AgentError;
@@ -19,5 +52,36 @@ pub use agent::ToolCall;
 pub use role::*;
 pub use state::*;
 pub use output_handler::*;
+pub use format_conversion::{FormatConversion, detect_format};
+pub use output_pipeline::{OutputTransform, OutputStage, Trim, Redact, TemplateWrap, AppendCitations, FnTransform};
+pub use content_policy::{ContentPolicy, PolicyRule, PolicyRuleKind, PolicyAction, PolicyViolation};
 pub use provider::*;
+pub use mock_provider::{MockProvider, MockResponse};
+pub use replay::{RecordingProvider, ReplayResult, RunTrace};
+pub use cassette::{Cassette, CassetteMode, CassetteProvider};
+pub use huggingface::HuggingFaceProvider;
+pub use http::{HttpClientConfig, init_shared_client, shared_client};
+pub use failover::{FailoverProvider, CircuitBreakerConfig, CircuitState, EndpointHealth};
+pub use rotating_provider::RotatingProvider;
 pub use streaming::*;
+pub use context_window::known_context_window;
+pub use tool_schema::ToolSchemas;
+pub use agent_batch::{BatchOptions, BatchMetrics, BatchResult};
+pub use prompt_registry::{PromptRegistry, PromptTemplate, SYSTEM_PROMPT, CORRECTION_PROMPT, format_prompt_name, render_template};
+pub use reasoning_trace::{ReasoningTrace, TraceRound, RoundDecision, ValidationOutcome};
+pub use tool_analytics::{ToolAnalyticsReport, ToolUsageReport, PruneReason, PruningThresholds};
+pub use export::{SessionExport, ExportedRun, ExportMetrics};
+pub use registry::{AgentRegistry, ReloadReport, WatchHandle};
+pub use prompt_locale::{PromptLocale, prompt_locale, register_prompt_locale, unregister_prompt_locale};
+pub use profile::{EnvironmentProfile, DEPLOYMENT_ENV_VAR};
+pub use rate_limit::{RateLimiter, UserRateLimiter, RateLimitStore, InMemoryRateLimitStore, RateWindow, RateLimited, OverLimit};
+pub use sse::{SseEvent, SseOptions, sse_stream};
+pub use stream_buffer::{BackpressurePolicy, StreamBufferConfig, BackgroundStreamingHandler};
+pub use stream_recording::{StreamRecorder, StreamReplay, ReplayTiming};
+pub use stream_transform::{ChunkStream, ChunkStreamExt};
+pub use stream_multiplex::{StreamMultiplexer, TaggedChunk};
+pub use status::{ActiveTask, AgentStatusSnapshot, HeartbeatHandle, RecentError, RollingStats, ShutdownReport};
+pub use terminal::{TerminalRenderer, TerminalRendererOptions};
+pub use repl::{Repl, ReplAction};
+pub use scoring::{OutputScorer, EvaluationResult, LexicalScorer};
+pub use scoring::EmbeddingScorer;
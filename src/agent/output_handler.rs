@@ -1,12 +1,42 @@
+use crate::agent::format_conversion::{convert, detect_format, FormatConversion};
+use crate::agent::output_pipeline::{run_pipeline, OutputStage, OutputTransform};
 use crate::agent::role::OutputFormat;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Custom output check run after the built-in format validation.
+/// A failing validator feeds its message into the correction-retry loop.
+pub trait OutputValidator: Send + Sync {
+    /// Name shown in validation errors
+    fn name(&self) -> &str;
+
+    fn validate(&self, output: &str, format: &OutputFormat) -> Result<(), String>;
+}
 
 /// Output Handler for configurable output processing and validation
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct OutputHandler {
     pub default_format: OutputFormat,
     pub validation_enabled: bool,
     pub post_processing: Option<fn(&str) -> String>,
+    pub validators: Vec<Arc<dyn OutputValidator>>,
+    /// Transforms run in order once the output passed all validation
+    pub pipeline: Vec<Arc<dyn OutputTransform>>,
+    /// Whether responses in the wrong format are converted instead of rejected
+    pub conversion: FormatConversion,
+}
+
+impl std::fmt::Debug for OutputHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OutputHandler")
+            .field("default_format", &self.default_format)
+            .field("validation_enabled", &self.validation_enabled)
+            .field("post_processing", &self.post_processing.is_some())
+            .field("validators", &self.validators.iter().map(|v| v.name()).collect::<Vec<_>>())
+            .field("pipeline", &self.pipeline.iter().map(|t| t.name()).collect::<Vec<_>>())
+            .field("conversion", &self.conversion)
+            .finish()
+    }
 }
 
 impl OutputHandler {
@@ -16,6 +46,9 @@ impl OutputHandler {
             default_format,
             validation_enabled: true,
             post_processing: None,
+            validators: Vec::new(),
+            pipeline: Vec::new(),
+            conversion: FormatConversion::Disabled,
         }
     }
 
@@ -31,23 +64,130 @@ impl OutputHandler {
         self
     }
 
+    /// Convert responses written in another format (typically the agent's default)
+    /// into the requested one instead of rejecting them
+    pub fn with_format_conversion(mut self, conversion: FormatConversion) -> Self {
+        self.conversion = conversion;
+        self
+    }
+
+    /// Add a custom validator; validators run in the order they were added
+    pub fn with_validator(mut self, validator: Arc<dyn OutputValidator>) -> Self {
+        self.validators.push(validator);
+        self
+    }
+
+    pub fn add_validator(&mut self, validator: Arc<dyn OutputValidator>) {
+        self.validators.push(validator);
+    }
+
+    /// Append a post-processing stage
+    pub fn with_transform(mut self, transform: Arc<dyn OutputTransform>) -> Self {
+        self.pipeline.push(transform);
+        self
+    }
+
+    pub fn add_transform(&mut self, transform: Arc<dyn OutputTransform>) {
+        self.pipeline.push(transform);
+    }
+
+    /// Run the post-processing pipeline on validated output, recording each stage
+    pub fn finalize(&self, mut output: ProcessedOutput) -> Result<ProcessedOutput, String> {
+        if self.pipeline.is_empty() {
+            return Ok(output);
+        }
+        let (content, stages) = run_pipeline(&self.pipeline, output.content)?;
+        output.content = content;
+        output.stages = stages;
+        Ok(output)
+    }
+
     /// Process and validate output based on configured format
     pub fn process_output(&self, raw_output: &str, expected_format: Option<&OutputFormat>) -> Result<String, String> {
+        self.process(raw_output, expected_format).map(|output| output.content)
+    }
+
+    /// Like `process_output`, but keeps the raw response alongside the processed content
+    pub fn process(&self, raw_output: &str, expected_format: Option<&OutputFormat>) -> Result<ProcessedOutput, String> {
         let format = expected_format.unwrap_or(&self.default_format);
-        
+        let produced = detect_format(raw_output);
+        if self.conversion == FormatConversion::Disabled || &produced == format {
+            return self.process_as(raw_output, raw_output, format, produced);
+        }
+
+        // The model answered in the agent's default format rather than the task's
+        if format != &self.default_format && produced == self.default_format {
+            if let Ok(converted) = convert(raw_output, &produced, format) {
+                return self.process_converted(&converted, raw_output, format, produced, FormatConversion::RuleBased);
+            }
+        }
+
+        match self.process_as(raw_output, raw_output, format, produced.clone()) {
+            Ok(output) => Ok(output),
+            Err(error) => match convert(raw_output, &produced, format) {
+                Ok(converted) => self
+                    .process_converted(&converted, raw_output, format, produced, FormatConversion::RuleBased)
+                    .map_err(|_| error),
+                Err(_) => Err(error),
+            },
+        }
+    }
+
+    /// Process a conversion of `raw_output` into `format`, recording how it was made
+    pub(crate) fn process_converted(
+        &self,
+        converted: &str,
+        raw_output: &str,
+        format: &OutputFormat,
+        produced: OutputFormat,
+        method: FormatConversion,
+    ) -> Result<ProcessedOutput, String> {
+        let mut output = self.process_as(converted, raw_output, format, produced)?;
+        output.converted = Some(method);
+        Ok(output)
+    }
+
+    fn process_as(
+        &self,
+        response: &str,
+        raw_output: &str,
+        format: &OutputFormat,
+        produced_format: OutputFormat,
+    ) -> Result<ProcessedOutput, String> {
+        // Models often wrap JSON in prose or code fences; keep just the JSON
+        let extracted = match format {
+            OutputFormat::Json => extract_json(response),
+            _ => None,
+        };
+        let json_extracted = extracted.is_some_and(|json| json != response);
+        let output = extracted.unwrap_or(response);
+
         // Apply post-processing if configured
         let processed_output = if let Some(processor) = self.post_processing {
-            processor(raw_output)
+            processor(output)
         } else {
-            raw_output.to_string()
+            output.to_string()
         };
 
         // Validate based on format if validation is enabled
         if self.validation_enabled {
             self.validate_output(&processed_output, format)?;
+            for validator in &self.validators {
+                validator
+                    .validate(&processed_output, format)
+                    .map_err(|e| format!("{}: {}", validator.name(), e))?;
+            }
         }
 
-        Ok(processed_output)
+        Ok(ProcessedOutput {
+            content: processed_output,
+            raw: raw_output.to_string(),
+            json_extracted,
+            produced_format,
+            converted: None,
+            stages: Vec::new(),
+            policy_violations: Vec::new(),
+        })
     }
 
     /// Validate output based on the specified format
@@ -108,6 +248,28 @@ impl OutputHandler {
                 }
                 Ok(())
             }
+            OutputFormat::Csv { delimiter } => {
+                // Basic CSV validation - column schema is checked by the task
+                let rows = crate::task::csv_format::parse_csv(output, *delimiter)
+                    .map_err(|e| format!("Invalid CSV format: {}", e))?;
+                if rows.is_empty() {
+                    return Err("CSV output cannot be empty".to_string());
+                }
+                Ok(())
+            }
+            OutputFormat::Code => {
+                // Language and syntax are checked by the task, which knows the language
+                if output.trim().is_empty() {
+                    return Err("Code output cannot be empty".to_string());
+                }
+                Ok(())
+            }
+            OutputFormat::Xml => {
+                // Well-formedness check - element schema is checked by the task
+                crate::task::xml_format::parse_xml(output)
+                    .map(|_| ())
+                    .map_err(|e| format!("Invalid XML format: {}", e))
+            }
         }
     }
 
@@ -133,6 +295,78 @@ impl Default for OutputHandler {
     }
 }
 
+/// Output after extraction, post-processing and validation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessedOutput {
+    pub content: String,
+    /// The model's response before any extraction or post-processing
+    pub raw: String,
+    /// Whether the JSON was cut out of surrounding prose or code fences
+    pub json_extracted: bool,
+    /// Format the model actually wrote its response in
+    pub produced_format: OutputFormat,
+    /// How the response was converted into the requested format, if it was
+    #[serde(default)]
+    pub converted: Option<FormatConversion>,
+    /// Post-processing stages applied by `OutputHandler::finalize`
+    #[serde(default)]
+    pub stages: Vec<OutputStage>,
+    /// Content policy rules the output broke; set when the policy refused it
+    #[serde(default)]
+    pub policy_violations: Vec<crate::agent::content_policy::PolicyViolation>,
+}
+
+/// Locate the first balanced JSON object or array in `text` that parses.
+/// Braces inside strings are ignored; candidates that don't parse are skipped.
+pub fn extract_json(text: &str) -> Option<&str> {
+    let bytes = text.as_bytes();
+    let mut start = 0;
+    while let Some(offset) = text[start..].find(['{', '[']) {
+        let open = start + offset;
+        if let Some(end) = balanced_end(bytes, open) {
+            let candidate = &text[open..=end];
+            if serde_json::from_str::<serde_json::Value>(candidate).is_ok() {
+                return Some(candidate);
+            }
+        }
+        start = open + 1;
+    }
+    None
+}
+
+/// Index of the bracket closing the one at `open`
+fn balanced_end(bytes: &[u8], open: usize) -> Option<usize> {
+    let mut stack = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    for (index, &byte) in bytes.iter().enumerate().skip(open) {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match byte {
+            b'"' => in_string = true,
+            b'{' => stack.push(b'}'),
+            b'[' => stack.push(b']'),
+            b'}' | b']' => {
+                if stack.pop() != Some(byte) {
+                    return None;
+                }
+                if stack.is_empty() {
+                    return Some(index);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
 /// Output validation result with detailed information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidationResult {
@@ -0,0 +1,182 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// One stage of the post-processing pipeline run after validation
+pub trait OutputTransform: Send + Sync {
+    /// Name recorded on the response for each run of the stage
+    fn name(&self) -> &str;
+
+    fn apply(&self, output: &str) -> Result<String, String>;
+}
+
+/// What one pipeline stage did to the output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputStage {
+    pub name: String,
+    pub changed: bool,
+    pub length_before: usize,
+    pub length_after: usize,
+}
+
+/// Run `transforms` in order, recording each stage
+pub fn run_pipeline(
+    transforms: &[Arc<dyn OutputTransform>],
+    output: String,
+) -> Result<(String, Vec<OutputStage>), String> {
+    let mut stages = Vec::with_capacity(transforms.len());
+    let mut current = output;
+    for transform in transforms {
+        let next = transform
+            .apply(&current)
+            .map_err(|e| format!("Output transform '{}' failed: {}", transform.name(), e))?;
+        stages.push(OutputStage {
+            name: transform.name().to_string(),
+            changed: next != current,
+            length_before: current.len(),
+            length_after: next.len(),
+        });
+        current = next;
+    }
+    Ok((current, stages))
+}
+
+/// Strip leading and trailing whitespace
+pub struct Trim;
+
+impl OutputTransform for Trim {
+    fn name(&self) -> &str {
+        "trim"
+    }
+
+    fn apply(&self, output: &str) -> Result<String, String> {
+        Ok(output.trim().to_string())
+    }
+}
+
+/// Replace every match of the patterns with a fixed replacement
+pub struct Redact {
+    patterns: Vec<Regex>,
+    replacement: String,
+}
+
+impl Redact {
+    pub fn new(patterns: &[&str], replacement: &str) -> Result<Self, String> {
+        let patterns = patterns
+            .iter()
+            .map(|pattern| Regex::new(pattern).map_err(|e| format!("Invalid redaction pattern '{}': {}", pattern, e)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            patterns,
+            replacement: replacement.to_string(),
+        })
+    }
+}
+
+impl OutputTransform for Redact {
+    fn name(&self) -> &str {
+        "redact"
+    }
+
+    fn apply(&self, output: &str) -> Result<String, String> {
+        let mut redacted = output.to_string();
+        for pattern in &self.patterns {
+            redacted = pattern.replace_all(&redacted, self.replacement.as_str()).into_owned();
+        }
+        Ok(redacted)
+    }
+}
+
+/// Wrap the output in a template; `{output}` marks where it goes
+pub struct TemplateWrap {
+    template: String,
+}
+
+impl TemplateWrap {
+    pub fn new(template: &str) -> Result<Self, String> {
+        if !template.contains("{output}") {
+            return Err("Template must contain {output}".to_string());
+        }
+        Ok(Self { template: template.to_string() })
+    }
+}
+
+impl OutputTransform for TemplateWrap {
+    fn name(&self) -> &str {
+        "template_wrap"
+    }
+
+    fn apply(&self, output: &str) -> Result<String, String> {
+        Ok(self.template.replace("{output}", output))
+    }
+}
+
+/// Append a numbered list of sources
+pub struct AppendCitations {
+    heading: String,
+    citations: Vec<String>,
+}
+
+impl AppendCitations {
+    pub fn new(citations: Vec<String>) -> Self {
+        Self {
+            heading: "Sources:".to_string(),
+            citations,
+        }
+    }
+
+    pub fn with_heading(mut self, heading: &str) -> Self {
+        self.heading = heading.to_string();
+        self
+    }
+}
+
+impl OutputTransform for AppendCitations {
+    fn name(&self) -> &str {
+        "append_citations"
+    }
+
+    fn apply(&self, output: &str) -> Result<String, String> {
+        if self.citations.is_empty() {
+            return Ok(output.to_string());
+        }
+        let list: Vec<String> = self
+            .citations
+            .iter()
+            .enumerate()
+            .map(|(i, citation)| format!("[{}] {}", i + 1, citation))
+            .collect();
+        Ok(format!("{}\n\n{}\n{}", output.trim_end(), self.heading, list.join("\n")))
+    }
+}
+
+/// A named closure as a pipeline stage
+pub struct FnTransform<F> {
+    name: String,
+    transform: F,
+}
+
+impl<F> FnTransform<F>
+where
+    F: Fn(&str) -> Result<String, String> + Send + Sync,
+{
+    pub fn new(name: &str, transform: F) -> Self {
+        Self {
+            name: name.to_string(),
+            transform,
+        }
+    }
+}
+
+impl<F> OutputTransform for FnTransform<F>
+where
+    F: Fn(&str) -> Result<String, String> + Send + Sync,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn apply(&self, output: &str) -> Result<String, String> {
+        (self.transform)(output)
+    }
+}
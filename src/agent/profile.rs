@@ -0,0 +1,138 @@
+use crate::agent::state::{
+    AccessLevel, DeploymentEnvironment, EnvironmentContext, Permission, RateLimits,
+};
+use serde::{Deserialize, Serialize};
+
+/// Variable naming the deployment environment agents are built for
+/// (`development`, `staging`, `production` or `testing`)
+pub const DEPLOYMENT_ENV_VAR: &str = "MERCO_ENV";
+
+impl DeploymentEnvironment {
+    /// The environment named by `MERCO_ENV`; None if unset or unknown
+    pub fn from_env() -> Option<Self> {
+        let value = std::env::var(DEPLOYMENT_ENV_VAR).ok()?;
+        value
+            .parse()
+            .map_err(|e: String| eprintln!("{}; no environment profile applied", e))
+            .ok()
+    }
+}
+
+impl std::str::FromStr for DeploymentEnvironment {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, String> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "development" | "dev" => Ok(Self::Development),
+            "staging" | "stage" => Ok(Self::Staging),
+            "production" | "prod" => Ok(Self::Production),
+            "testing" | "test" => Ok(Self::Testing),
+            other => Err(format!("Unknown deployment environment '{}'", other)),
+        }
+    }
+}
+
+/// Behaviour bundle an agent gets for its deployment environment, applied when it
+/// is constructed if `MERCO_ENV` is set, or by `Agent::with_deployment_environment`.
+/// Without either, agents keep `EnvironmentContext::default()`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvironmentProfile {
+    pub environment: DeploymentEnvironment,
+    /// Print every lifecycle event to stderr
+    pub verbose_events: bool,
+    pub audit_logging: bool,
+    /// Validate every output and never run without the prompt injection guard
+    pub strict_guardrails: bool,
+    pub access_level: AccessLevel,
+    pub permissions: Vec<Permission>,
+    /// Tasks each user may start, enforced by the agent's `UserRateLimiter`
+    pub rate_limits: RateLimits,
+    /// Timeout for each tool call
+    pub max_response_time_ms: u64,
+    /// Whether scripted providers (`Agent::with_mock_provider`) may answer requests;
+    /// without it agents only talk to real providers
+    #[serde(default)]
+    pub allow_mock_providers: bool,
+}
+
+impl EnvironmentProfile {
+    pub fn for_environment(environment: DeploymentEnvironment) -> Self {
+        match environment {
+            DeploymentEnvironment::Development => Self {
+                environment,
+                verbose_events: true,
+                audit_logging: true,
+                strict_guardrails: false,
+                access_level: AccessLevel::Internal,
+                permissions: vec![Permission::Read, Permission::Write],
+                rate_limits: RateLimits {
+                    requests_per_minute: 600,
+                    requests_per_hour: 10_000,
+                    requests_per_day: 100_000,
+                },
+                max_response_time_ms: 120_000,
+                allow_mock_providers: true,
+            },
+            DeploymentEnvironment::Testing => Self {
+                environment,
+                verbose_events: false,
+                audit_logging: false,
+                strict_guardrails: false,
+                access_level: AccessLevel::Internal,
+                permissions: vec![Permission::Read, Permission::Write],
+                rate_limits: RateLimits {
+                    requests_per_minute: 600,
+                    requests_per_hour: 10_000,
+                    requests_per_day: 100_000,
+                },
+                max_response_time_ms: 30_000,
+                allow_mock_providers: true,
+            },
+            DeploymentEnvironment::Staging => Self {
+                environment,
+                verbose_events: false,
+                audit_logging: true,
+                strict_guardrails: true,
+                access_level: AccessLevel::Internal,
+                permissions: vec![Permission::Read, Permission::Write],
+                rate_limits: RateLimits {
+                    requests_per_minute: 60,
+                    requests_per_hour: 1000,
+                    requests_per_day: 10000,
+                },
+                max_response_time_ms: 30_000,
+                allow_mock_providers: false,
+            },
+            DeploymentEnvironment::Production => Self {
+                environment,
+                verbose_events: false,
+                audit_logging: true,
+                strict_guardrails: true,
+                access_level: AccessLevel::Restricted,
+                permissions: vec![Permission::Read],
+                rate_limits: RateLimits {
+                    requests_per_minute: 60,
+                    requests_per_hour: 1000,
+                    requests_per_day: 10000,
+                },
+                max_response_time_ms: 30_000,
+                allow_mock_providers: false,
+            },
+        }
+    }
+
+    /// The profile for `MERCO_ENV`, if set
+    pub fn from_env() -> Option<Self> {
+        DeploymentEnvironment::from_env().map(Self::for_environment)
+    }
+
+    /// Write the profile's settings into an environment context
+    pub fn apply_to(&self, environment: &mut EnvironmentContext) {
+        environment.deployment_environment = self.environment.clone();
+        environment.resource_limits.max_response_time_ms = self.max_response_time_ms;
+        environment.security_context.access_level = self.access_level.clone();
+        environment.security_context.permissions = self.permissions.clone();
+        environment.security_context.audit_logging = self.audit_logging;
+        environment.network_context.rate_limits = self.rate_limits.clone();
+    }
+}
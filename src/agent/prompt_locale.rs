@@ -0,0 +1,206 @@
+use crate::agent::role::OutputFormat;
+use crate::task::language::base_language;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// The text of the built-in prompts in one language. Agents pick theirs from
+/// `context.preferences.language` (`Agent::with_prompt_language`); prompts
+/// registered in the agent's `PromptRegistry` still take precedence.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromptLocale {
+    /// Placeholders as for the `system` prompt of the registry
+    pub system: String,
+    pub task_label: String,
+    pub expected_output_label: String,
+    pub output_format_label: String,
+    /// `{language}` is the English name of the requested response language
+    pub language_instruction: String,
+    pub format_text: String,
+    pub format_json: String,
+    pub format_markdown: String,
+    pub format_html: String,
+    pub format_multi_modal: String,
+    pub format_csv: String,
+    pub format_xml: String,
+    pub format_code: String,
+    /// Precedes the list of problems in a correction message
+    pub correction_intro: String,
+    /// Follows the list of problems in a correction message
+    pub correction_outro: String,
+}
+
+impl PromptLocale {
+    pub fn format_instruction(&self, format: &OutputFormat) -> &str {
+        match format {
+            OutputFormat::Text => &self.format_text,
+            OutputFormat::Json => &self.format_json,
+            OutputFormat::Markdown => &self.format_markdown,
+            OutputFormat::Html => &self.format_html,
+            OutputFormat::MultiModal => &self.format_multi_modal,
+            OutputFormat::Csv { .. } => &self.format_csv,
+            OutputFormat::Xml => &self.format_xml,
+            OutputFormat::Code => &self.format_code,
+        }
+    }
+
+    /// The bundled locale for a language code (`en`, `de`, `fr`, `es`), if any
+    pub fn bundled(language: &str) -> Option<Arc<PromptLocale>> {
+        bundled().get(base_language(language).as_str()).cloned()
+    }
+
+    fn english() -> Self {
+        Self {
+            system: "You are {name}, a specialized AI agent.\n\n\
+                ROLE AND CAPABILITIES:\n\
+                - Role: {role}\n\
+                - Description: {description}\n\
+                - Max Concurrent Tasks: {max_concurrent_tasks}\n\
+                - Supported Output Formats: {output_formats}\n\n\
+                You have access to the following tools: {tool_count}\n\n\
+                Always follow the output format specified in the task and provide accurate, helpful responses.".to_string(),
+            task_label: "Task".to_string(),
+            expected_output_label: "Expected Output".to_string(),
+            output_format_label: "IMPORTANT - Output Format".to_string(),
+            language_instruction: "IMPORTANT - Language: Write your entire response in {language}.".to_string(),
+            format_text: "Provide your response in plain text format. Be clear and concise.".to_string(),
+            format_json: "Provide your response in valid JSON format. Structure your response as a JSON object with appropriate keys and values. Do not wrap your response in markdown code blocks - provide raw JSON only.".to_string(),
+            format_markdown: "Provide your response in Markdown format. Use appropriate headers, lists, and formatting.".to_string(),
+            format_html: "Provide your response in HTML format. Use proper HTML tags and structure.".to_string(),
+            format_multi_modal: "Provide your response in a multi-modal format that can include text, images, and other media.".to_string(),
+            format_csv: "Provide your response as CSV data with a header row. Do not wrap your response in markdown code blocks - provide raw CSV only.".to_string(),
+            format_xml: "Provide your response as a well-formed XML document with a single root element. Do not wrap your response in markdown code blocks - provide raw XML only.".to_string(),
+            format_code: "Provide your response as code in a fenced markdown code block tagged with its language.".to_string(),
+            correction_intro: "Your previous response was invalid:".to_string(),
+            correction_outro: "Please provide a corrected response in the required format, fixing every item above.".to_string(),
+        }
+    }
+
+    fn german() -> Self {
+        Self {
+            system: "Du bist {name}, ein spezialisierter KI-Agent.\n\n\
+                ROLLE UND FÄHIGKEITEN:\n\
+                - Rolle: {role}\n\
+                - Beschreibung: {description}\n\
+                - Maximale gleichzeitige Aufgaben: {max_concurrent_tasks}\n\
+                - Unterstützte Ausgabeformate: {output_formats}\n\n\
+                Du hast Zugriff auf folgende Anzahl an Werkzeugen: {tool_count}\n\n\
+                Halte dich immer an das in der Aufgabe angegebene Ausgabeformat und antworte genau und hilfreich.".to_string(),
+            task_label: "Aufgabe".to_string(),
+            expected_output_label: "Erwartete Ausgabe".to_string(),
+            output_format_label: "WICHTIG - Ausgabeformat".to_string(),
+            language_instruction: "WICHTIG - Sprache: Schreibe deine gesamte Antwort auf {language}.".to_string(),
+            format_text: "Antworte als reiner Text. Sei klar und prägnant.".to_string(),
+            format_json: "Antworte mit gültigem JSON. Gliedere die Antwort als JSON-Objekt mit passenden Schlüsseln und Werten. Setze die Antwort nicht in Markdown-Codeblöcke – gib nur reines JSON aus.".to_string(),
+            format_markdown: "Antworte in Markdown. Verwende passende Überschriften, Listen und Formatierungen.".to_string(),
+            format_html: "Antworte in HTML. Verwende korrekte HTML-Tags und -Struktur.".to_string(),
+            format_multi_modal: "Antworte in einem multimodalen Format, das Text, Bilder und andere Medien enthalten kann.".to_string(),
+            format_csv: "Antworte mit CSV-Daten samt Kopfzeile. Setze die Antwort nicht in Markdown-Codeblöcke – gib nur reines CSV aus.".to_string(),
+            format_xml: "Antworte mit einem wohlgeformten XML-Dokument mit genau einem Wurzelelement. Setze die Antwort nicht in Markdown-Codeblöcke – gib nur reines XML aus.".to_string(),
+            format_code: "Antworte mit Code in einem Markdown-Codeblock, der mit seiner Sprache gekennzeichnet ist.".to_string(),
+            correction_intro: "Deine vorherige Antwort war ungültig:".to_string(),
+            correction_outro: "Bitte gib eine korrigierte Antwort im geforderten Format, die jeden der obigen Punkte behebt.".to_string(),
+        }
+    }
+
+    fn french() -> Self {
+        Self {
+            system: "Tu es {name}, un agent d'IA spécialisé.\n\n\
+                RÔLE ET CAPACITÉS :\n\
+                - Rôle : {role}\n\
+                - Description : {description}\n\
+                - Tâches simultanées maximales : {max_concurrent_tasks}\n\
+                - Formats de sortie pris en charge : {output_formats}\n\n\
+                Nombre d'outils à ta disposition : {tool_count}\n\n\
+                Respecte toujours le format de sortie indiqué dans la tâche et fournis des réponses exactes et utiles.".to_string(),
+            task_label: "Tâche".to_string(),
+            expected_output_label: "Résultat attendu".to_string(),
+            output_format_label: "IMPORTANT - Format de sortie".to_string(),
+            language_instruction: "IMPORTANT - Langue : rédige toute ta réponse en {language}.".to_string(),
+            format_text: "Réponds en texte brut. Sois clair et concis.".to_string(),
+            format_json: "Réponds en JSON valide. Structure ta réponse comme un objet JSON avec des clés et des valeurs appropriées. N'entoure pas ta réponse de blocs de code Markdown : fournis uniquement du JSON brut.".to_string(),
+            format_markdown: "Réponds en Markdown. Utilise des titres, des listes et une mise en forme appropriés.".to_string(),
+            format_html: "Réponds en HTML. Utilise des balises et une structure HTML correctes.".to_string(),
+            format_multi_modal: "Réponds dans un format multimodal pouvant inclure du texte, des images et d'autres médias.".to_string(),
+            format_csv: "Réponds avec des données CSV comprenant une ligne d'en-tête. N'entoure pas ta réponse de blocs de code Markdown : fournis uniquement du CSV brut.".to_string(),
+            format_xml: "Réponds avec un document XML bien formé comportant un seul élément racine. N'entoure pas ta réponse de blocs de code Markdown : fournis uniquement du XML brut.".to_string(),
+            format_code: "Réponds avec du code dans un bloc de code Markdown annoté de son langage.".to_string(),
+            correction_intro: "Ta réponse précédente n'était pas valide :".to_string(),
+            correction_outro: "Fournis une réponse corrigée dans le format demandé, en corrigeant chacun des points ci-dessus.".to_string(),
+        }
+    }
+
+    fn spanish() -> Self {
+        Self {
+            system: "Eres {name}, un agente de IA especializado.\n\n\
+                ROL Y CAPACIDADES:\n\
+                - Rol: {role}\n\
+                - Descripción: {description}\n\
+                - Tareas simultáneas máximas: {max_concurrent_tasks}\n\
+                - Formatos de salida admitidos: {output_formats}\n\n\
+                Número de herramientas disponibles: {tool_count}\n\n\
+                Sigue siempre el formato de salida indicado en la tarea y ofrece respuestas precisas y útiles.".to_string(),
+            task_label: "Tarea".to_string(),
+            expected_output_label: "Resultado esperado".to_string(),
+            output_format_label: "IMPORTANTE - Formato de salida".to_string(),
+            language_instruction: "IMPORTANTE - Idioma: escribe toda tu respuesta en {language}.".to_string(),
+            format_text: "Responde en texto plano. Sé claro y conciso.".to_string(),
+            format_json: "Responde en JSON válido. Estructura tu respuesta como un objeto JSON con claves y valores adecuados. No envuelvas tu respuesta en bloques de código Markdown: entrega solo JSON sin formato.".to_string(),
+            format_markdown: "Responde en Markdown. Usa encabezados, listas y formato adecuados.".to_string(),
+            format_html: "Responde en HTML. Usa etiquetas y estructura HTML correctas.".to_string(),
+            format_multi_modal: "Responde en un formato multimodal que pueda incluir texto, imágenes y otros medios.".to_string(),
+            format_csv: "Responde con datos CSV con una fila de encabezado. No envuelvas tu respuesta en bloques de código Markdown: entrega solo CSV sin formato.".to_string(),
+            format_xml: "Responde con un documento XML bien formado con un único elemento raíz. No envuelvas tu respuesta en bloques de código Markdown: entrega solo XML sin formato.".to_string(),
+            format_code: "Responde con código en un bloque de código Markdown etiquetado con su lenguaje.".to_string(),
+            correction_intro: "Tu respuesta anterior no era válida:".to_string(),
+            correction_outro: "Proporciona una respuesta corregida en el formato requerido, resolviendo cada punto anterior.".to_string(),
+        }
+    }
+}
+
+impl Default for PromptLocale {
+    fn default() -> Self {
+        Self::english()
+    }
+}
+
+fn bundled() -> &'static HashMap<&'static str, Arc<PromptLocale>> {
+    static BUNDLED: OnceLock<HashMap<&'static str, Arc<PromptLocale>>> = OnceLock::new();
+    BUNDLED.get_or_init(|| {
+        HashMap::from([
+            ("en", Arc::new(PromptLocale::english())),
+            ("de", Arc::new(PromptLocale::german())),
+            ("fr", Arc::new(PromptLocale::french())),
+            ("es", Arc::new(PromptLocale::spanish())),
+        ])
+    })
+}
+
+fn overrides() -> &'static RwLock<HashMap<String, Arc<PromptLocale>>> {
+    static OVERRIDES: OnceLock<RwLock<HashMap<String, Arc<PromptLocale>>>> = OnceLock::new();
+    OVERRIDES.get_or_init(Default::default)
+}
+
+/// Add a locale, or replace a bundled one, for `language` (a code such as `it`
+/// or a locale tag such as `pt-BR`)
+pub fn register_prompt_locale(language: &str, locale: PromptLocale) {
+    overrides().write().unwrap().insert(language.trim().to_lowercase(), Arc::new(locale));
+}
+
+pub fn unregister_prompt_locale(language: &str) -> bool {
+    overrides().write().unwrap().remove(&language.trim().to_lowercase()).is_some()
+}
+
+/// Locale for `language`: a registered one for the exact tag, then for its base
+/// language, then the bundled one, falling back to English
+pub fn prompt_locale(language: &str) -> Arc<PromptLocale> {
+    let tag = language.trim().to_lowercase();
+    let base = base_language(&tag);
+    let registered = {
+        let overrides = overrides().read().unwrap();
+        overrides.get(&tag).or_else(|| overrides.get(&base)).cloned()
+    };
+    registered
+        .or_else(|| PromptLocale::bundled(&base))
+        .unwrap_or_else(|| bundled()["en"].clone())
+}
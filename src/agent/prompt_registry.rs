@@ -0,0 +1,171 @@
+use crate::agent::role::OutputFormat;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// Name of the agent system prompt. Placeholders: `{name}`, `{role}`,
+/// `{description}`, `{max_concurrent_tasks}`, `{output_formats}`, `{tool_count}`.
+pub const SYSTEM_PROMPT: &str = "system";
+/// Name of the message sent back after invalid output. Placeholder: `{issues}`,
+/// one `- ` line per problem.
+pub const CORRECTION_PROMPT: &str = "correction";
+
+/// Name of the instruction for `format`, e.g. `format.json`
+pub fn format_prompt_name(format: &OutputFormat) -> String {
+    format!("format.{:?}", format).to_ascii_lowercase()
+}
+
+/// One registered version of a named prompt template
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromptTemplate {
+    pub name: String,
+    pub version: u32,
+    pub template: String,
+}
+
+impl PromptTemplate {
+    /// `name@version`, as recorded on responses
+    pub fn reference(&self) -> String {
+        format!("{}@{}", self.name, self.version)
+    }
+
+    /// Replace `{key}` placeholders; unknown placeholders are left as written
+    pub fn render(&self, vars: &[(&str, String)]) -> String {
+        render_template(&self.template, vars)
+    }
+}
+
+/// Replace `{key}` placeholders in `template`; unknown placeholders are left as written
+pub fn render_template(template: &str, vars: &[(&str, String)]) -> String {
+    vars.iter().fold(template.to_string(), |text, (key, value)| {
+        text.replace(&format!("{{{}}}", key), value)
+    })
+}
+
+#[derive(Debug, Default)]
+struct PromptVersions {
+    versions: BTreeMap<u32, PromptTemplate>,
+    /// Active version; the latest when unset
+    active: Option<u32>,
+    /// Versions activated before the current one, for `rollback`
+    history: Vec<u32>,
+}
+
+/// Named, versioned prompt templates overriding the built-in system, correction and
+/// format prompts. A new version goes live with `activate` and is undone with
+/// `rollback`; responses record the versions they were produced with
+/// (`AgentResponse::prompt_versions`). Prompts without registered versions use
+/// the built-in text.
+#[derive(Debug, Default)]
+pub struct PromptRegistry {
+    prompts: RwLock<HashMap<String, PromptVersions>>,
+}
+
+impl PromptRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry used by agents without one of their own
+    pub fn global() -> Arc<PromptRegistry> {
+        static GLOBAL: OnceLock<Arc<PromptRegistry>> = OnceLock::new();
+        GLOBAL.get_or_init(|| Arc::new(PromptRegistry::new())).clone()
+    }
+
+    /// Register a template version. Published versions are immutable: registering an
+    /// existing version with different text is an error, re-registering it unchanged is not.
+    /// Until a version is activated explicitly the latest one is active.
+    pub fn register(&self, name: &str, version: u32, template: &str) -> Result<PromptTemplate> {
+        if name.is_empty() || name.contains('@') {
+            return Err(anyhow!("Invalid prompt name: '{}'", name));
+        }
+        let entry = PromptTemplate {
+            name: name.to_string(),
+            version,
+            template: template.to_string(),
+        };
+
+        let mut prompts = self.prompts.write().unwrap();
+        let prompt = prompts.entry(name.to_string()).or_default();
+        if let Some(existing) = prompt.versions.get(&version) {
+            if existing != &entry {
+                return Err(anyhow!("Prompt {} is already registered with different text", entry.reference()));
+            }
+        }
+        prompt.versions.insert(version, entry.clone());
+        Ok(entry)
+    }
+
+    /// Make `version` the one agents use
+    pub fn activate(&self, name: &str, version: u32) -> Result<()> {
+        let mut prompts = self.prompts.write().unwrap();
+        let prompt = prompts.get_mut(name).ok_or_else(|| anyhow!("Unknown prompt: '{}'", name))?;
+        if !prompt.versions.contains_key(&version) {
+            return Err(anyhow!("Unknown prompt version: '{}@{}'", name, version));
+        }
+        let current = Self::active_version(prompt);
+        if current != Some(version) {
+            prompt.history.extend(current);
+            prompt.active = Some(version);
+        }
+        Ok(())
+    }
+
+    /// Return to the version active before the last `activate`; returns it
+    pub fn rollback(&self, name: &str) -> Result<u32> {
+        let mut prompts = self.prompts.write().unwrap();
+        let prompt = prompts.get_mut(name).ok_or_else(|| anyhow!("Unknown prompt: '{}'", name))?;
+        let previous = prompt
+            .history
+            .pop()
+            .ok_or_else(|| anyhow!("Prompt '{}' has no earlier version to roll back to", name))?;
+        prompt.active = Some(previous);
+        Ok(previous)
+    }
+
+    /// The active version of `name`, if any is registered
+    pub fn active(&self, name: &str) -> Option<PromptTemplate> {
+        let prompts = self.prompts.read().unwrap();
+        let prompt = prompts.get(name)?;
+        Self::active_version(prompt).and_then(|version| prompt.versions.get(&version).cloned())
+    }
+
+    /// Resolve `name` (active version) or `name@version`
+    pub fn resolve(&self, reference: &str) -> Result<PromptTemplate> {
+        match reference.split_once('@') {
+            Some((name, version)) => {
+                let version = version
+                    .parse::<u32>()
+                    .map_err(|_| anyhow!("Invalid prompt version in '{}'", reference))?;
+                self.prompts
+                    .read()
+                    .unwrap()
+                    .get(name)
+                    .and_then(|prompt| prompt.versions.get(&version).cloned())
+                    .ok_or_else(|| anyhow!("Unknown prompt version: '{}'", reference))
+            }
+            None => self.active(reference).ok_or_else(|| anyhow!("Unknown prompt: '{}'", reference)),
+        }
+    }
+
+    /// Registered versions of `name`, oldest first
+    pub fn versions(&self, name: &str) -> Vec<u32> {
+        self.prompts
+            .read()
+            .unwrap()
+            .get(name)
+            .map(|prompt| prompt.versions.keys().copied().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.prompts.read().unwrap().keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    fn active_version(prompt: &PromptVersions) -> Option<u32> {
+        prompt.active.or_else(|| prompt.versions.keys().next_back().copied())
+    }
+}
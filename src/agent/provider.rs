@@ -1,3 +1,4 @@
+use crate::credentials::Credential;
 use serde::{Deserialize, Serialize};
 
 /// LLM Provider types supported by merco-agents
@@ -11,6 +12,10 @@ pub enum Provider {
     Google,
     /// Ollama local models
     Ollama,
+    /// Hugging Face serverless inference router
+    HuggingFace,
+    /// Self-hosted Text Generation Inference server or Inference Endpoint at this URL
+    Tgi(String),
     /// Custom provider with custom base URL
     Custom(String),
 }
@@ -23,6 +28,8 @@ impl Provider {
             Provider::Anthropic => merco_llmproxy::config::Provider::Anthropic,
             Provider::Google => merco_llmproxy::config::Provider::OpenAI, // Map Google to OpenAI for now
             Provider::Ollama => merco_llmproxy::config::Provider::Ollama,
+            // Served by HuggingFaceProvider; the messages API is OpenAI-compatible
+            Provider::HuggingFace | Provider::Tgi(_) => merco_llmproxy::config::Provider::OpenAI,
             Provider::Custom(_) => merco_llmproxy::config::Provider::Custom,
         }
     }
@@ -34,6 +41,8 @@ impl Provider {
             Provider::Anthropic => Some("https://api.anthropic.com".to_string()),
             Provider::Google => Some("https://generativelanguage.googleapis.com/v1beta".to_string()),
             Provider::Ollama => Some("http://localhost:11434".to_string()),
+            Provider::HuggingFace => Some(crate::agent::huggingface::HUGGINGFACE_ROUTER_URL.to_string()),
+            Provider::Tgi(url) => Some(format!("{}/v1", url.trim_end_matches('/').trim_end_matches("/v1"))),
             Provider::Custom(url) => Some(url.clone()),
         }
     }
@@ -44,8 +53,10 @@ impl Provider {
 pub struct LlmConfig {
     /// The provider to use
     pub provider: Provider,
-    /// API key for the provider
-    pub api_key: Option<String>,
+    /// API key for the provider, resolved on every request so rotated keys apply.
+    /// Never serialized; config files may give a key, `env:NAME` or `file:PATH`.
+    #[serde(default, skip_serializing)]
+    pub api_key: Option<Credential>,
     /// Custom base URL (overrides default for provider)
     pub base_url: Option<String>,
     /// Additional headers for the request
@@ -57,7 +68,7 @@ impl LlmConfig {
     pub fn new(provider: Provider, api_key: Option<String>) -> Self {
         Self {
             provider,
-            api_key,
+            api_key: api_key.map(Credential::fixed),
             base_url: None,
             headers: None,
         }
@@ -67,17 +78,29 @@ impl LlmConfig {
     pub fn new_with_base_url(provider: Provider, api_key: Option<String>, base_url: String) -> Self {
         Self {
             provider,
-            api_key,
+            api_key: api_key.map(Credential::fixed),
             base_url: Some(base_url),
             headers: None,
         }
     }
 
+    /// Read the API key from `credential` (environment, file, keyring or a
+    /// provider of your own) instead of a fixed string
+    pub fn with_credential(mut self, credential: Credential) -> Self {
+        self.api_key = Some(credential);
+        self
+    }
+
+    /// The current API key; None if there is none or its source can't be read
+    pub fn resolve_api_key(&self) -> Option<String> {
+        self.api_key.as_ref().and_then(Credential::resolve_or_log)
+    }
+
     /// Convert to merco_llmproxy LlmConfig
     pub fn to_llmproxy_config(&self) -> merco_llmproxy::LlmConfig {
         merco_llmproxy::LlmConfig {
             provider: self.provider.to_llmproxy_provider(),
-            api_key: self.api_key.clone(),
+            api_key: self.resolve_api_key(),
             base_url: self.base_url.clone().or_else(|| self.provider.get_base_url()),
         }
     }
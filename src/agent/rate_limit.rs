@@ -0,0 +1,221 @@
+use crate::agent::state::RateLimits;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Spaces requests evenly so that no more than the configured number start per minute.
+/// Shared by reference between concurrent workers.
+#[derive(Debug)]
+pub struct RateLimiter {
+    interval: Duration,
+    next_slot: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    /// Create a limiter allowing `requests_per_minute` requests (0 is treated as 1)
+    pub fn per_minute(requests_per_minute: u32) -> Self {
+        let requests = requests_per_minute.max(1) as u64;
+        Self::with_interval(Duration::from_millis(60_000 / requests))
+    }
+
+    /// Create a limiter with a fixed minimum gap between requests
+    pub fn with_interval(interval: Duration) -> Self {
+        Self {
+            interval,
+            next_slot: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Wait until the caller may start its next request
+    pub async fn acquire(&self) {
+        let wait_until = {
+            let mut next_slot = self.next_slot.lock().await;
+            let now = Instant::now();
+            let slot = if *next_slot > now { *next_slot } else { now };
+            *next_slot = slot + self.interval;
+            slot
+        };
+
+        tokio::time::sleep_until(tokio::time::Instant::from_std(wait_until)).await;
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+}
+
+/// A request limit over a sliding window
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RateWindow {
+    pub limit: u32,
+    pub window: Duration,
+}
+
+impl RateWindow {
+    pub fn new(limit: u32, window: Duration) -> Self {
+        Self { limit, window }
+    }
+
+    /// Per-minute, per-hour and per-day windows of a security context's `RateLimits`;
+    /// zero limits are left out
+    pub fn from_rate_limits(limits: &RateLimits) -> Vec<Self> {
+        [
+            (limits.requests_per_minute, 60),
+            (limits.requests_per_hour, 3_600),
+            (limits.requests_per_day, 86_400),
+        ]
+        .into_iter()
+        .filter(|(limit, _)| *limit > 0)
+        .map(|(limit, secs)| Self::new(limit, Duration::from_secs(secs)))
+        .collect()
+    }
+}
+
+/// Where request counts are kept. Implement it over Redis or a database to share
+/// limits between replicas.
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    /// Record a request for `key` if every window has room and return None;
+    /// otherwise record nothing and return the first window that is full and
+    /// how long until it has room again
+    async fn try_acquire(&self, key: &str, windows: &[RateWindow]) -> Result<Option<(RateWindow, Duration)>, String>;
+}
+
+/// Sliding-window request log kept in process memory
+#[derive(Debug, Default)]
+pub struct InMemoryRateLimitStore {
+    requests: Mutex<HashMap<String, VecDeque<Instant>>>,
+}
+
+impl InMemoryRateLimitStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for InMemoryRateLimitStore {
+    async fn try_acquire(&self, key: &str, windows: &[RateWindow]) -> Result<Option<(RateWindow, Duration)>, String> {
+        let now = Instant::now();
+        let mut requests = self.requests.lock().await;
+        let log = requests.entry(key.to_string()).or_default();
+        if let Some(longest) = windows.iter().map(|w| w.window).max() {
+            while log.front().is_some_and(|at| now.duration_since(*at) >= longest) {
+                log.pop_front();
+            }
+        }
+        for window in windows {
+            let in_window: Vec<&Instant> = log.iter().filter(|at| now.duration_since(**at) < window.window).collect();
+            if in_window.len() >= window.limit as usize {
+                // Room opens when the oldest request counted against the limit ages out
+                let retry_after = in_window
+                    .get(in_window.len() - window.limit as usize)
+                    .map_or(window.window, |oldest| window.window.saturating_sub(now.duration_since(**oldest)));
+                return Ok(Some((*window, retry_after)));
+            }
+        }
+        log.push_back(now);
+        Ok(None)
+    }
+}
+
+/// What happens to a request over its user's limit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OverLimit {
+    /// Fail it straight away with `AgentError::RateLimited`
+    Reject,
+    /// Wait for room for up to this long, then fail it
+    Queue { max_wait: Duration },
+}
+
+/// A request refused by a `UserRateLimiter`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RateLimited {
+    pub user_id: String,
+    pub limit: u32,
+    pub window_secs: u64,
+    pub retry_after_ms: u64,
+}
+
+impl std::fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "user '{}' exceeded {} requests per {}s, retry in {} ms",
+            self.user_id, self.limit, self.window_secs, self.retry_after_ms
+        )
+    }
+}
+
+/// Limits how many tasks each end user (`Task::user_id`, set by `call_with_user`)
+/// may start. Tasks without a user id are not limited. Users are counted per
+/// tenant, so equal user ids of different tenants don't share a budget.
+pub struct UserRateLimiter {
+    store: Arc<dyn RateLimitStore>,
+    windows: Vec<RateWindow>,
+    over_limit: OverLimit,
+}
+
+impl UserRateLimiter {
+    /// In-memory limiter rejecting requests over `windows`
+    pub fn new(windows: Vec<RateWindow>) -> Self {
+        Self {
+            store: Arc::new(InMemoryRateLimitStore::new()),
+            windows,
+            over_limit: OverLimit::Reject,
+        }
+    }
+
+    /// Limiter for the per-minute/hour/day limits of a security context
+    pub fn from_rate_limits(limits: &RateLimits) -> Self {
+        Self::new(RateWindow::from_rate_limits(limits))
+    }
+
+    /// Keep counts in `store`, e.g. one shared between replicas
+    pub fn with_store(mut self, store: Arc<dyn RateLimitStore>) -> Self {
+        self.store = store;
+        self
+    }
+
+    pub fn with_over_limit(mut self, over_limit: OverLimit) -> Self {
+        self.over_limit = over_limit;
+        self
+    }
+
+    /// Admit a request of `user_id`, waiting for room when queueing is enabled.
+    /// A failing store admits the request rather than blocking every user.
+    pub async fn acquire(&self, tenant: Option<&str>, user_id: &str) -> Result<(), RateLimited> {
+        let key = match tenant {
+            Some(tenant) => format!("{}/{}", tenant, user_id),
+            None => user_id.to_string(),
+        };
+        let deadline = match self.over_limit {
+            OverLimit::Reject => None,
+            OverLimit::Queue { max_wait } => Some(Instant::now() + max_wait),
+        };
+        loop {
+            let (window, retry_after) = match self.store.try_acquire(&key, &self.windows).await {
+                Ok(None) => return Ok(()),
+                Ok(Some(full)) => full,
+                Err(e) => {
+                    eprintln!("Rate limit store failed for user '{}': {}", user_id, e);
+                    return Ok(());
+                }
+            };
+            match deadline {
+                Some(deadline) if Instant::now() + retry_after <= deadline => tokio::time::sleep(retry_after).await,
+                _ => {
+                    return Err(RateLimited {
+                        user_id: user_id.to_string(),
+                        limit: window.limit,
+                        window_secs: window.window.as_secs(),
+                        retry_after_ms: retry_after.as_millis() as u64,
+                    })
+                }
+            }
+        }
+    }
+}
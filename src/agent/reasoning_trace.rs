@@ -0,0 +1,163 @@
+use crate::redaction::Redactor;
+use crate::task::validation_report::ValidationReport;
+use merco_llmproxy::{traits::ChatMessageRole, ChatMessage};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Characters of the latest prompt message kept in a round's summary
+const PROMPT_SUMMARY_CHARS: usize = 200;
+
+/// What the model did in a round
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "decision", rename_all = "snake_case")]
+pub enum RoundDecision {
+    /// Answered; `validation` is set once the answer has been checked
+    Answer {
+        output_tokens: u32,
+        validation: Option<ValidationOutcome>,
+    },
+    /// Asked for these tools, in call order
+    ToolCalls { tools: Vec<String> },
+    /// The prompt was too long for the model; `recovered` if a shortened history was resent
+    ContextOverflow { recovered: bool },
+    /// The request failed
+    Failed { error: String },
+}
+
+/// Whether an answer passed the task's checks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum ValidationOutcome {
+    Passed,
+    /// Rejected; a correction was sent (or the task failed on its last attempt)
+    Rejected { report: ValidationReport },
+}
+
+/// One provider request of a run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraceRound {
+    /// Attempt of the task the round belongs to; later attempts are retries
+    pub attempt: usize,
+    /// Request within the attempt, from 1
+    pub round: usize,
+    pub message_count: usize,
+    pub prompt_tokens: u32,
+    /// The latest prompt message, shortened and redacted
+    pub prompt_summary: String,
+    pub decision: RoundDecision,
+    pub duration_ms: u64,
+}
+
+/// Why an agent did what it did: each LLM round of a run with what was asked, what
+/// the model decided and how its answers were judged (`AgentResponse::reasoning_trace`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReasoningTrace {
+    pub rounds: Vec<TraceRound>,
+}
+
+impl ReasoningTrace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Attempts made; more than one means the task was retried
+    pub fn attempts(&self) -> usize {
+        self.rounds.iter().map(|round| round.attempt).max().unwrap_or(0)
+    }
+
+    /// Every tool the model asked for, in order
+    pub fn tools_called(&self) -> Vec<&str> {
+        self.rounds
+            .iter()
+            .flat_map(|round| match &round.decision {
+                RoundDecision::ToolCalls { tools } => tools.iter().map(String::as_str).collect(),
+                _ => Vec::new(),
+            })
+            .collect()
+    }
+
+    pub(crate) fn push(
+        &mut self,
+        attempt: usize,
+        round: usize,
+        messages: &[ChatMessage],
+        prompt_tokens: u32,
+        decision: RoundDecision,
+        duration_ms: u64,
+        redactor: &Redactor,
+    ) {
+        self.rounds.push(TraceRound {
+            attempt,
+            round,
+            message_count: messages.len(),
+            prompt_tokens,
+            prompt_summary: summarize_prompt(messages, redactor),
+            decision,
+            duration_ms,
+        });
+    }
+
+    /// Record whether the history was shortened after the latest round overflowed
+    pub(crate) fn record_overflow_recovery(&mut self, recovered: bool) {
+        if let Some(TraceRound {
+            decision: RoundDecision::ContextOverflow { recovered: outcome },
+            ..
+        }) = self.rounds.last_mut()
+        {
+            *outcome = recovered;
+        }
+    }
+
+    /// Record the validation result of the latest answer
+    pub(crate) fn record_validation(&mut self, outcome: ValidationOutcome) {
+        if let Some(TraceRound {
+            decision: RoundDecision::Answer { validation, .. },
+            ..
+        }) = self.rounds.last_mut()
+        {
+            *validation = Some(outcome);
+        }
+    }
+}
+
+impl fmt::Display for ReasoningTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for round in &self.rounds {
+            write!(
+                f,
+                "attempt {} round {} ({} messages, ~{} tokens, {} ms): ",
+                round.attempt, round.round, round.message_count, round.prompt_tokens, round.duration_ms
+            )?;
+            match &round.decision {
+                RoundDecision::Answer { validation: Some(ValidationOutcome::Rejected { report }), .. } => {
+                    writeln!(f, "answered, rejected: {}", report.summary())?
+                }
+                RoundDecision::Answer { validation: Some(ValidationOutcome::Passed), .. } => writeln!(f, "answered, accepted")?,
+                RoundDecision::Answer { validation: None, .. } => writeln!(f, "answered")?,
+                RoundDecision::ToolCalls { tools } => writeln!(f, "called {}", tools.join(", "))?,
+                RoundDecision::ContextOverflow { recovered: true } => writeln!(f, "prompt too long, resent with shortened history")?,
+                RoundDecision::ContextOverflow { recovered: false } => writeln!(f, "prompt too long")?,
+                RoundDecision::Failed { error } => writeln!(f, "failed: {}", error)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn summarize_prompt(messages: &[ChatMessage], redactor: &Redactor) -> String {
+    let Some(last) = messages.last() else {
+        return String::new();
+    };
+    let speaker = match last.role {
+        ChatMessageRole::System => "system",
+        ChatMessageRole::User => "user",
+        ChatMessageRole::Assistant => "assistant",
+        ChatMessageRole::Tool => "tool",
+    };
+    let content = redactor.redact(last.content.as_deref().unwrap_or_default()).into_owned();
+    let content = match content.char_indices().nth(PROMPT_SUMMARY_CHARS) {
+        Some((index, _)) => format!("{}…", &content[..index]),
+        None => content,
+    };
+    format!("{}: {}", speaker, content)
+}
@@ -0,0 +1,185 @@
+use crate::agent::agent::Agent;
+use crate::config::AgentConfigFile;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+const DEFINITION_EXTENSIONS: &[&str] = &["toml", "yaml", "yml", "json"];
+
+struct LoadedDefinition {
+    modified: SystemTime,
+    name: String,
+}
+
+/// What a `reload` changed
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReloadReport {
+    pub added: Vec<String>,
+    pub updated: Vec<String>,
+    pub removed: Vec<String>,
+    /// Files that failed to load, with the error; agents they defined keep their
+    /// last good definition
+    pub errors: Vec<(PathBuf, String)>,
+}
+
+impl ReloadReport {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.updated.is_empty() && self.removed.is_empty() && self.errors.is_empty()
+    }
+}
+
+/// Agents defined declaratively, one `AgentConfigFile` (TOML, YAML or JSON) per file
+/// in a directory, looked up by their `name`. `reload` or `watch` pick up added,
+/// changed and deleted files; callers holding an agent keep using the version they
+/// got until they look it up again.
+pub struct AgentRegistry {
+    dir: PathBuf,
+    agents: RwLock<HashMap<String, Arc<Agent>>>,
+    files: RwLock<HashMap<PathBuf, LoadedDefinition>>,
+}
+
+impl AgentRegistry {
+    /// Load every definition in `dir`. Fails if any file is invalid or two files
+    /// define the same agent name.
+    pub fn load_dir(dir: impl Into<PathBuf>) -> Result<Self> {
+        let registry = Self {
+            dir: dir.into(),
+            agents: RwLock::new(HashMap::new()),
+            files: RwLock::new(HashMap::new()),
+        };
+        let report = registry.reload()?;
+        if let Some((path, error)) = report.errors.first() {
+            return Err(anyhow!("Failed to load agent definition {}: {}", path.display(), error));
+        }
+        Ok(registry)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<Agent>> {
+        self.agents.read().unwrap().get(name).cloned()
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.agents.read().unwrap().keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    pub fn len(&self) -> usize {
+        self.agents.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Rescan the directory: load new and modified files, drop agents whose file
+    /// was deleted. Files that fail to load are reported and otherwise ignored.
+    pub fn reload(&self) -> Result<ReloadReport> {
+        let mut report = ReloadReport::default();
+        let current = definition_files(&self.dir)?;
+        let mut files = self.files.write().unwrap();
+        let mut agents = self.agents.write().unwrap();
+
+        let deleted: Vec<PathBuf> = files.keys().filter(|path| !current.contains_key(*path)).cloned().collect();
+        for path in deleted {
+            if let Some(definition) = files.remove(&path) {
+                agents.remove(&definition.name);
+                report.removed.push(definition.name);
+            }
+        }
+
+        for (path, modified) in current {
+            let previous = files.get(&path);
+            if previous.is_some_and(|definition| definition.modified == modified) {
+                continue;
+            }
+            let loaded = AgentConfigFile::load(&path).and_then(|config| {
+                let owner = files.iter().find(|(other, definition)| **other != path && definition.name == config.name);
+                if let Some((other, _)) = owner {
+                    return Err(anyhow!("agent '{}' is already defined in {}", config.name, other.display()));
+                }
+                Ok((config.name.clone(), config.build()?))
+            });
+            let (name, agent) = match loaded {
+                Ok(loaded) => loaded,
+                Err(e) => {
+                    eprintln!("Failed to load agent definition {}: {:#}", path.display(), e);
+                    report.errors.push((path, format!("{:#}", e)));
+                    continue;
+                }
+            };
+            match previous.map(|definition| definition.name.clone()) {
+                Some(old_name) if old_name != name => {
+                    agents.remove(&old_name);
+                    report.removed.push(old_name);
+                    report.added.push(name.clone());
+                }
+                Some(_) => report.updated.push(name.clone()),
+                None => report.added.push(name.clone()),
+            }
+            agents.insert(name.clone(), Arc::new(agent));
+            files.insert(path, LoadedDefinition { modified, name });
+        }
+        Ok(report)
+    }
+
+    /// Reload every `interval` until the returned handle is dropped
+    pub fn watch(self: &Arc<Self>, interval: Duration) -> WatchHandle {
+        let registry = Arc::clone(self);
+        let handle = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            // The first tick completes immediately; the directory was just loaded
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                if let Err(e) = registry.reload() {
+                    eprintln!("Failed to reload agent definitions from {}: {:#}", registry.dir.display(), e);
+                }
+            }
+        });
+        WatchHandle { handle }
+    }
+}
+
+/// Stops hot-reloading when dropped
+pub struct WatchHandle {
+    handle: tokio::task::JoinHandle<()>,
+}
+
+impl WatchHandle {
+    pub fn stop(self) {}
+}
+
+impl Drop for WatchHandle {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// Definition files in `dir` with their modification times
+fn definition_files(dir: &Path) -> Result<HashMap<PathBuf, SystemTime>> {
+    let entries = std::fs::read_dir(dir).with_context(|| format!("Failed to read agent directory {}", dir.display()))?;
+    let mut files = HashMap::new();
+    for entry in entries {
+        let path = entry?.path();
+        let is_definition = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| DEFINITION_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()));
+        if !is_definition || !path.is_file() {
+            continue;
+        }
+        let modified = std::fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .with_context(|| format!("Failed to stat {}", path.display()))?;
+        files.insert(path, modified);
+    }
+    Ok(files)
+}
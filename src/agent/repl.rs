@@ -0,0 +1,174 @@
+use crate::agent::agent::{Agent, AgentResponse};
+use crate::agent::state::ConversationRole;
+use crate::agent::terminal::TerminalRenderer;
+use crate::task::task::Task;
+use serde_json::json;
+use std::io::Write;
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+const HELP: &str = "\
+/tools          list the agent's tools
+/memory         show the session transcript and stored context
+/reset          forget the conversation so far
+/model [name]   show the model, or switch to another one
+/help           show this help
+/exit           leave";
+
+/// What the caller of `Repl::handle_line` should do next
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplAction {
+    /// Print this and read the next line
+    Print(String),
+    /// Send this message to the agent
+    Send(String),
+    /// Nothing to do (blank line)
+    Continue,
+    Exit,
+}
+
+/// Interactive chat with an agent for probing it by hand: each message is sent with
+/// the conversation so far, responses stream to the terminal, and slash commands
+/// inspect or change the session (`/help` lists them). Start it with
+/// `Repl::new(agent).run().await`.
+pub struct Repl {
+    agent: Agent,
+    /// Earlier turns included with each message; older ones are dropped from the prompt
+    pub max_history_turns: usize,
+}
+
+impl Repl {
+    pub fn new(agent: Agent) -> Self {
+        Self { agent, max_history_turns: 20 }
+    }
+
+    pub fn agent(&self) -> &Agent {
+        &self.agent
+    }
+
+    pub fn into_agent(self) -> Agent {
+        self.agent
+    }
+
+    /// Read lines from stdin until `/exit` or end of input
+    pub async fn run(&mut self) -> std::io::Result<()> {
+        println!("Chatting with {} ({}). /help lists commands.", self.agent.name, self.agent.llm_config.model_name);
+        let mut lines = BufReader::new(tokio::io::stdin()).lines();
+        loop {
+            print!("> ");
+            std::io::stdout().flush()?;
+            let Some(line) = lines.next_line().await? else {
+                return Ok(());
+            };
+            match self.handle_line(&line) {
+                ReplAction::Print(text) => println!("{}", text),
+                ReplAction::Send(message) => {
+                    let response = self.send(&message).await;
+                    if let Some(error) = &response.error {
+                        println!("error: {}", error);
+                    }
+                }
+                ReplAction::Continue => {}
+                ReplAction::Exit => return Ok(()),
+            }
+        }
+    }
+
+    /// Run a slash command, or turn a line into a message for the agent
+    pub fn handle_line(&mut self, line: &str) -> ReplAction {
+        let line = line.trim();
+        if line.is_empty() {
+            return ReplAction::Continue;
+        }
+        let Some(command) = line.strip_prefix('/') else {
+            return ReplAction::Send(line.to_string());
+        };
+        let (command, argument) = match command.split_once(char::is_whitespace) {
+            Some((command, argument)) => (command, argument.trim()),
+            None => (command, ""),
+        };
+        match command {
+            "tools" => ReplAction::Print(self.describe_tools()),
+            "memory" => ReplAction::Print(self.describe_memory()),
+            "reset" => {
+                self.agent.context.conversation_history.clear();
+                ReplAction::Print("Conversation cleared.".to_string())
+            }
+            "model" if argument.is_empty() => ReplAction::Print(format!(
+                "{} ({:?}, temperature {})",
+                self.agent.llm_config.model_name, self.agent.llm_config.llm_config.provider, self.agent.llm_config.temperature
+            )),
+            "model" => {
+                self.agent.llm_config.model_name = argument.to_string();
+                self.agent.llm_config.context_window = crate::agent::context_window::known_context_window(argument);
+                ReplAction::Print(format!("Now using {}.", argument))
+            }
+            "help" => ReplAction::Print(HELP.to_string()),
+            "exit" | "quit" => ReplAction::Exit,
+            other => ReplAction::Print(format!("Unknown command /{}. /help lists commands.", other)),
+        }
+    }
+
+    /// Send `message` with the conversation so far, streaming the answer to stdout
+    pub async fn send(&mut self, message: &str) -> AgentResponse {
+        let mut task = Task::new(message.to_string(), None);
+        let transcript = self.transcript();
+        if !transcript.is_empty() {
+            task = task
+                .with_inputs(json!({ "conversation": transcript }))
+                .with_input_template("Conversation so far:\n{{conversation}}");
+        }
+        let response = self.agent.call_stream_collect(task, TerminalRenderer::new()).await;
+        self.agent.context.add_conversation_entry(ConversationRole::User, message.to_string());
+        if response.success {
+            self.agent.context.add_conversation_entry(ConversationRole::Agent, response.content.clone());
+        }
+        response
+    }
+
+    fn transcript(&self) -> String {
+        let history = &self.agent.context.conversation_history;
+        let skip = history.len().saturating_sub(self.max_history_turns * 2);
+        history
+            .iter()
+            .skip(skip)
+            .map(|entry| {
+                let speaker = match entry.role {
+                    ConversationRole::User => "User",
+                    ConversationRole::Agent => "Assistant",
+                    ConversationRole::System => "System",
+                    ConversationRole::Tool => "Tool",
+                };
+                format!("{}: {}", speaker, entry.content)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn describe_tools(&self) -> String {
+        if self.agent.tools.is_empty() {
+            return "No tools.".to_string();
+        }
+        self.agent
+            .tools
+            .iter()
+            .map(|tool| format!("{:<16} {}", tool.name, tool.description))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Agents keep no long-term memory; what the session remembers is the
+    /// transcript plus the agent's stored context
+    fn describe_memory(&self) -> String {
+        let context = self.agent.get_all_context();
+        let mut lines = vec![format!("{} turns in this session", self.agent.context.conversation_history.len())];
+        if context.is_empty() {
+            lines.push("No stored context.".to_string());
+        }
+        let mut keys: Vec<&String> = context.keys().collect();
+        keys.sort();
+        for key in keys {
+            lines.push(format!("{} = {}", key, context[key]));
+        }
+        lines.join("\n")
+    }
+}
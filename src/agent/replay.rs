@@ -0,0 +1,225 @@
+use crate::agent::agent::{Agent, AgentResponse, ToolCall};
+use crate::agent::mock_provider::{MockProvider, MockResponse};
+use crate::task::run_history::RunRecord;
+use crate::agent::state::EnvironmentContext;
+use crate::tools::permissions::check_tool_permissions;
+use crate::tools::sandbox::{supervise, ToolFailure};
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use merco_llmproxy::traits::{CompletionResponse, CompletionStream, ProviderError};
+use merco_llmproxy::{CompletionKind, CompletionRequest, LlmProvider, StreamContentDelta};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+/// Provider responses of one run in request order, stored on its `RunRecord`
+/// so the run can be replayed without calling the model
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunTrace {
+    pub provider_responses: Vec<MockResponse>,
+}
+
+impl RunTrace {
+    /// Scrub secrets from the recorded answers, tool arguments and errors. A replay
+    /// then sees the redacted text, as the stored run does.
+    pub fn redact(&mut self, redactor: &crate::redaction::Redactor) {
+        for response in &mut self.provider_responses {
+            match response {
+                MockResponse::Message(text) | MockResponse::Error(text) => redactor.redact_string(text),
+                MockResponse::ToolCalls(calls) => calls
+                    .iter_mut()
+                    .for_each(|(_, arguments)| redactor.redact_string(arguments)),
+            }
+        }
+    }
+}
+
+/// Records every response of the wrapped provider, streamed or not.
+/// Installed by `Agent::with_run_traces`.
+pub struct RecordingProvider {
+    inner: Arc<dyn LlmProvider + Send + Sync>,
+    responses: Arc<Mutex<Vec<MockResponse>>>,
+}
+
+impl RecordingProvider {
+    pub fn new(inner: Arc<dyn LlmProvider + Send + Sync>) -> Self {
+        Self {
+            inner,
+            responses: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Responses recorded since the last call
+    pub fn take_trace(&self) -> RunTrace {
+        RunTrace {
+            provider_responses: std::mem::take(&mut *self.responses.lock().unwrap()),
+        }
+    }
+}
+
+pub(crate) fn recorded(kind: &CompletionKind) -> MockResponse {
+    match kind {
+        CompletionKind::Message { content } => MockResponse::Message(content.clone()),
+        CompletionKind::ToolCall { tool_calls } => MockResponse::ToolCalls(
+            tool_calls
+                .iter()
+                .map(|call| (call.function.name.clone(), call.function.arguments.clone()))
+                .collect(),
+        ),
+    }
+}
+
+#[async_trait]
+impl LlmProvider for RecordingProvider {
+    async fn completion(&self, request: CompletionRequest) -> Result<CompletionResponse, ProviderError> {
+        let result = self.inner.completion(request).await;
+        let response = match &result {
+            Ok(response) => recorded(&response.kind),
+            Err(e) => MockResponse::Error(e.to_string()),
+        };
+        self.responses.lock().unwrap().push(response);
+        result
+    }
+
+    async fn completion_stream(&self, request: CompletionRequest) -> Result<CompletionStream, ProviderError> {
+        let stream = match self.inner.completion_stream(request).await {
+            Ok(stream) => stream,
+            Err(e) => {
+                self.responses.lock().unwrap().push(MockResponse::Error(e.to_string()));
+                return Err(e);
+            }
+        };
+
+        let responses = self.responses.clone();
+        Ok(capture_stream(stream, move |response| responses.lock().unwrap().push(response)))
+    }
+}
+
+/// Pass a provider stream through unchanged, reassembling the streamed answer and
+/// handing it to `on_response` once the stream finishes (or fails)
+pub(crate) fn capture_stream<F>(stream: CompletionStream, mut on_response: F) -> CompletionStream
+where
+    F: FnMut(MockResponse) + Send + 'static,
+{
+    let mut text = String::new();
+    let mut calls: BTreeMap<u32, (String, String)> = BTreeMap::new();
+    Box::pin(stream.inspect(move |item| match item {
+        Ok(chunk) => {
+            match &chunk.delta {
+                StreamContentDelta::Text(delta) => text.push_str(delta),
+                StreamContentDelta::ToolCallDelta(deltas) => {
+                    for delta in deltas {
+                        let call = calls.entry(delta.index).or_default();
+                        if let Some(function) = &delta.function {
+                            if let Some(name) = &function.name {
+                                call.0.push_str(name);
+                            }
+                            if let Some(arguments) = &function.arguments {
+                                call.1.push_str(arguments);
+                            }
+                        }
+                    }
+                }
+            }
+            if chunk.finish_reason.is_some() {
+                let response = if calls.is_empty() {
+                    MockResponse::Message(std::mem::take(&mut text))
+                } else {
+                    MockResponse::ToolCalls(std::mem::take(&mut calls).into_values().collect())
+                };
+                on_response(response);
+            }
+        }
+        Err(e) => on_response(MockResponse::Error(e.to_string())),
+    }))
+}
+
+tokio::task_local! {
+    static RECORDED_TOOLS: Mutex<VecDeque<ToolCall>>;
+}
+
+/// Run a tool if the agent's permissions allow it, under its resource limits,
+/// or return its recorded result while a run is being replayed
+pub(crate) async fn execute_tool(
+    name: &str,
+    arguments: &str,
+    inputs: Option<serde_json::Value>,
+    environment: &EnvironmentContext,
+) -> Result<String, ToolFailure> {
+    check_tool_permissions(name, &environment.security_context.permissions)?;
+    match replayed_tool(name) {
+        Some(result) => result.map_err(ToolFailure::from),
+        None => supervise(name, arguments, inputs, environment).await,
+    }
+}
+
+/// The recorded result of the next tool call, when replaying
+fn replayed_tool(name: &str) -> Option<Result<String, String>> {
+    RECORDED_TOOLS.try_with(|recorded| {
+        match recorded.lock().unwrap().pop_front() {
+            Some(call) if call.tool_name == name => match call.error {
+                Some(error) => Err(error),
+                None => Ok(call.result),
+            },
+            Some(call) => Err(format!("Replay diverged: expected tool '{}', got '{}'", call.tool_name, name)),
+            None => Err(format!("Replay diverged: no recorded result for tool '{}'", name)),
+        }
+    })
+    .ok()
+}
+
+/// A replayed run next to the original
+#[derive(Debug, Clone)]
+pub struct ReplayResult {
+    pub original: AgentResponse,
+    pub replayed: AgentResponse,
+}
+
+impl ReplayResult {
+    /// Same outcome and content as the recorded run
+    pub fn matches(&self) -> bool {
+        self.original.success == self.replayed.success && self.original.content == self.replayed.content
+    }
+}
+
+impl Agent {
+    /// Record provider responses onto every persisted `RunRecord` so runs can be
+    /// replayed. Call after `with_provider`, which would replace the recorder.
+    pub fn with_run_traces(mut self) -> Self {
+        let recorder = Arc::new(RecordingProvider::new(self.provider.clone()));
+        self.provider = recorder.clone();
+        self.trace_recorder = Some(recorder);
+        self
+    }
+
+    /// Re-run a recorded task with this agent's current prompts, validation and
+    /// output handling, answering provider requests and tool calls from the record.
+    /// Nothing is persisted, priced or sent to the model.
+    pub async fn replay(&self, record: &RunRecord) -> Result<ReplayResult, String> {
+        let trace = record
+            .trace
+            .as_ref()
+            .ok_or_else(|| format!("Run {} has no recorded trace", record.task_id))?;
+
+        let provider = trace
+            .provider_responses
+            .iter()
+            .cloned()
+            .fold(MockProvider::new(), MockProvider::respond_with);
+        let mut agent = self.clone().with_provider(Arc::new(provider));
+        agent.trace_recorder = None;
+        agent.run_store = None;
+        agent.cost_tracker = None;
+        agent.budgets = None;
+        agent.tool_usage = Default::default();
+        #[cfg(feature = "audit")]
+        agent.audit_log = None;
+
+        let tools = Mutex::new(record.response.tool_calls.iter().cloned().collect());
+        let replayed = RECORDED_TOOLS.scope(tools, agent.call(record.task.clone())).await;
+        Ok(ReplayResult {
+            original: record.response.clone(),
+            replayed,
+        })
+    }
+}
@@ -69,6 +69,9 @@ pub enum OutputFormat {
     Markdown,
     Html,
     MultiModal,
+    Csv { delimiter: char },
+    Xml,
+    Code,
 }
 
 
@@ -0,0 +1,47 @@
+use crate::agent::provider::LlmConfig;
+use async_trait::async_trait;
+use merco_llmproxy::traits::{CompletionResponse, CompletionStream, ProviderError};
+use merco_llmproxy::{CompletionRequest, LlmProvider};
+use std::sync::{Arc, Mutex};
+
+type SharedProvider = Arc<dyn LlmProvider + Send + Sync>;
+
+/// A merco-llmproxy provider rebuilt whenever its config's credential resolves
+/// to a new key, so a rotated key takes effect on the next request
+pub struct RotatingProvider {
+    config: LlmConfig,
+    current: Mutex<Option<(Option<String>, SharedProvider)>>,
+}
+
+impl RotatingProvider {
+    pub fn new(config: LlmConfig) -> Self {
+        Self { config, current: Mutex::new(None) }
+    }
+
+    /// The provider for the current key, built on first use and after rotation
+    fn provider(&self) -> Result<SharedProvider, ProviderError> {
+        let config = self.config.to_llmproxy_config();
+        let key = config.api_key.clone();
+        let mut current = self.current.lock().unwrap();
+        if let Some((built_with, provider)) = current.as_ref() {
+            if *built_with == key {
+                return Ok(provider.clone());
+            }
+        }
+        let provider = merco_llmproxy::get_provider(config)
+            .map_err(|e| ProviderError::ApiError(format!("Failed to create provider: {}", e)))?;
+        *current = Some((key, provider.clone()));
+        Ok(provider)
+    }
+}
+
+#[async_trait]
+impl LlmProvider for RotatingProvider {
+    async fn completion(&self, request: CompletionRequest) -> Result<CompletionResponse, ProviderError> {
+        self.provider()?.completion(request).await
+    }
+
+    async fn completion_stream(&self, request: CompletionRequest) -> Result<CompletionStream, ProviderError> {
+        self.provider()?.completion_stream(request).await
+    }
+}
@@ -0,0 +1,207 @@
+use crate::agent::cassette::{Cassette, CassetteMode};
+use crate::agent::http::shared_client;
+use crate::telemetry::traced;
+use crate::agent::provider::LlmConfig;
+use crate::credentials::Credential;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Result of comparing an output against the task's expected output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvaluationResult {
+    /// Similarity score in [0, 1]
+    pub score: f64,
+    /// Minimum score required to pass
+    pub threshold: f64,
+    pub passed: bool,
+    /// Name of the scorer that produced the score
+    pub scorer: String,
+}
+
+impl EvaluationResult {
+    pub fn new(score: f64, threshold: f64, scorer: &str) -> Self {
+        Self {
+            score,
+            threshold,
+            passed: score >= threshold,
+            scorer: scorer.to_string(),
+        }
+    }
+}
+
+/// Scores how closely an output matches an expected output
+#[async_trait]
+pub trait OutputScorer: Send + Sync {
+    /// Short identifier recorded on the evaluation result
+    fn name(&self) -> &str;
+
+    /// Similarity in [0, 1]
+    async fn score(&self, output: &str, expected: &str) -> Result<f64, String>;
+}
+
+/// Cosine similarity between two vectors, 0.0 when either is all zeros
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f64 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+
+    let mut dot = 0.0f64;
+    let mut norm_a = 0.0f64;
+    let mut norm_b = 0.0f64;
+    for (x, y) in a.iter().zip(b.iter()) {
+        dot += (*x as f64) * (*y as f64);
+        norm_a += (*x as f64) * (*x as f64);
+        norm_b += (*y as f64) * (*y as f64);
+    }
+
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        (dot / (norm_a.sqrt() * norm_b.sqrt())).clamp(0.0, 1.0)
+    }
+}
+
+/// Offline scorer using bag-of-words cosine similarity. Cheap and deterministic,
+/// but only captures lexical overlap.
+#[derive(Debug, Clone, Default)]
+pub struct LexicalScorer;
+
+impl LexicalScorer {
+    fn term_frequencies(text: &str) -> HashMap<String, f32> {
+        let mut frequencies = HashMap::new();
+        for word in text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+        {
+            *frequencies.entry(word.to_lowercase()).or_insert(0.0) += 1.0;
+        }
+        frequencies
+    }
+}
+
+#[async_trait]
+impl OutputScorer for LexicalScorer {
+    fn name(&self) -> &str {
+        "lexical"
+    }
+
+    async fn score(&self, output: &str, expected: &str) -> Result<f64, String> {
+        let output_terms = Self::term_frequencies(output);
+        let expected_terms = Self::term_frequencies(expected);
+
+        let vocabulary: Vec<&String> = output_terms.keys().chain(expected_terms.keys()).collect();
+        let a: Vec<f32> = vocabulary.iter().map(|w| *output_terms.get(*w).unwrap_or(&0.0)).collect();
+        let b: Vec<f32> = vocabulary.iter().map(|w| *expected_terms.get(*w).unwrap_or(&0.0)).collect();
+
+        Ok(cosine_similarity(&a, &b))
+    }
+}
+
+/// Scorer that embeds both texts via an OpenAI-compatible `/embeddings`
+/// endpoint and compares them with cosine similarity
+#[derive(Debug, Clone)]
+pub struct EmbeddingScorer {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: Option<Credential>,
+    model: String,
+    cassette: Option<Arc<Cassette>>,
+}
+
+impl EmbeddingScorer {
+    pub fn new(base_url: String, api_key: Option<String>, model: String) -> Self {
+        Self {
+            client: shared_client(),
+            base_url,
+            api_key: api_key.map(Credential::fixed),
+            model,
+            cassette: None,
+        }
+    }
+
+    /// Send requests through a specific client instead of the shared one
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Record embedding requests to `cassette` and replay them on later runs
+    pub fn with_cassette(mut self, cassette: Arc<Cassette>) -> Self {
+        self.cassette = Some(cassette);
+        self
+    }
+
+    /// Reuse the endpoint and credentials of an agent's LLM configuration
+    pub fn from_llm_config(config: &LlmConfig, model: String) -> Self {
+        let base_url = config.base_url.clone()
+            .or_else(|| config.provider.get_base_url())
+            .unwrap_or_default();
+        let mut scorer = Self::new(base_url, None, model);
+        scorer.api_key = config.api_key.clone();
+        scorer
+    }
+
+    async fn embed(&self, inputs: &[&str]) -> Result<Vec<Vec<f32>>, String> {
+        let Some(cassette) = &self.cassette else {
+            return traced!(self.request_embeddings(inputs), "merco.embedding.request", model = %self.model, inputs = inputs.len()).await;
+        };
+        let request = serde_json::json!({ "url": self.base_url, "model": self.model, "input": inputs });
+        if let Some(response) = cassette.replay(&Cassette::key("embedding", &request)) {
+            return serde_json::from_value(response).map_err(|e| format!("Invalid cassette entry: {}", e));
+        }
+        if cassette.mode() == CassetteMode::Replay {
+            return Err(cassette.missing("embedding"));
+        }
+        let embeddings = traced!(self.request_embeddings(inputs), "merco.embedding.request", model = %self.model, inputs = inputs.len()).await?;
+        if let Err(e) = cassette.record("embedding", request, serde_json::json!(embeddings)) {
+            eprintln!("Failed to write cassette: {}", e);
+        }
+        Ok(embeddings)
+    }
+
+    async fn request_embeddings(&self, inputs: &[&str]) -> Result<Vec<Vec<f32>>, String> {
+        let url = format!("{}/embeddings", self.base_url.trim_end_matches('/'));
+        let mut request = self.client
+            .post(&url)
+            .json(&serde_json::json!({ "model": self.model, "input": inputs }));
+        if let Some(api_key) = self.api_key.as_ref().and_then(Credential::resolve_or_log) {
+            request = request.bearer_auth(api_key);
+        }
+
+        let response = request.send().await.map_err(|e| format!("Embedding request failed: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("Embedding request failed with status {}", response.status()));
+        }
+
+        let body: serde_json::Value = response.json().await
+            .map_err(|e| format!("Invalid embedding response: {}", e))?;
+        let data = body.get("data").and_then(|d| d.as_array())
+            .ok_or_else(|| "Embedding response has no data array".to_string())?;
+
+        data.iter()
+            .map(|item| {
+                item.get("embedding")
+                    .and_then(|e| e.as_array())
+                    .map(|values| values.iter().filter_map(|v| v.as_f64()).map(|v| v as f32).collect())
+                    .ok_or_else(|| "Embedding response item has no embedding".to_string())
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl OutputScorer for EmbeddingScorer {
+    fn name(&self) -> &str {
+        "embedding"
+    }
+
+    async fn score(&self, output: &str, expected: &str) -> Result<f64, String> {
+        let embeddings = self.embed(&[output, expected]).await?;
+        if embeddings.len() != 2 {
+            return Err(format!("Expected 2 embeddings, got {}", embeddings.len()));
+        }
+        Ok(cosine_similarity(&embeddings[0], &embeddings[1]))
+    }
+}
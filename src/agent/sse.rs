@@ -0,0 +1,143 @@
+use crate::agent::streaming::StreamingChunk;
+use async_stream::stream;
+use futures::{Stream, StreamExt};
+use serde_json::json;
+use std::pin::Pin;
+use std::time::Duration;
+
+/// A single Server-Sent Events frame
+#[derive(Debug, Clone, PartialEq)]
+pub struct SseEvent {
+    pub id: Option<String>,
+    pub event: String,
+    pub data: String,
+}
+
+impl SseEvent {
+    pub fn new(event: &str, data: String) -> Self {
+        Self {
+            id: None,
+            event: event.to_string(),
+            data,
+        }
+    }
+
+    pub fn with_id(mut self, id: impl ToString) -> Self {
+        self.id = Some(id.to_string());
+        self
+    }
+
+    /// Serialize to wire format. Multi-line data is split over several `data:` lines.
+    pub fn to_frame(&self) -> String {
+        let mut frame = String::new();
+        if let Some(id) = &self.id {
+            frame.push_str(&format!("id: {}\n", id));
+        }
+        frame.push_str(&format!("event: {}\n", self.event));
+        for line in self.data.split('\n') {
+            frame.push_str(&format!("data: {}\n", line));
+        }
+        frame.push('\n');
+        frame
+    }
+}
+
+/// Options for converting a chunk stream into SSE frames
+#[derive(Debug, Clone)]
+pub struct SseOptions {
+    /// Send a `: keep-alive` comment after this much idle time (None disables keep-alives)
+    pub keep_alive: Option<Duration>,
+    /// Include the accumulated content in every `chunk` event, not only in `final`
+    pub include_accumulated: bool,
+    /// Emit a closing `done` event after the final chunk or an error
+    pub send_done: bool,
+}
+
+impl Default for SseOptions {
+    fn default() -> Self {
+        Self {
+            keep_alive: Some(Duration::from_secs(15)),
+            include_accumulated: false,
+            send_done: true,
+        }
+    }
+}
+
+pub const SSE_KEEP_ALIVE_FRAME: &str = ": keep-alive\n\n";
+
+/// Map a streamed item to its SSE event.
+/// Event names: `chunk` for text, `progress` for phase announcements, `tool_calls` for chunks carrying tool calls,
+/// `final` for the last chunk and `error` for stream errors.
+pub fn chunk_to_sse_event(item: &Result<StreamingChunk, String>, include_accumulated: bool) -> SseEvent {
+    match item {
+        Ok(chunk) if chunk.is_final => SseEvent::new(
+            "final",
+            json!({
+                "content": chunk.accumulated_content,
+                "usage": chunk.usage,
+                "finish_reason": chunk.finish_reason,
+                "metrics": chunk.metrics,
+                "metadata": chunk.metadata,
+            })
+            .to_string(),
+        ),
+        Ok(chunk) if chunk.is_progress() => SseEvent::new(
+            "progress",
+            chunk.metadata.get("progress").map(|phase| phase.to_string()).unwrap_or_default(),
+        ),
+        Ok(chunk) if chunk.has_tool_calls => SseEvent::new(
+            "tool_calls",
+            json!({ "tool_calls": chunk.tool_calls }).to_string(),
+        ),
+        Ok(chunk) => {
+            let mut payload = json!({ "content": chunk.content });
+            if include_accumulated {
+                payload["accumulated_content"] = json!(chunk.accumulated_content);
+            }
+            SseEvent::new("chunk", payload.to_string())
+        }
+        Err(error) => SseEvent::new("error", json!({ "error": error }).to_string()),
+    }
+}
+
+/// Convert an agent chunk stream (e.g. from `call_stream_with_handler`) into SSE frames
+/// ready to be written to an HTTP response body.
+pub fn sse_stream<S>(chunks: S, options: SseOptions) -> Pin<Box<dyn Stream<Item = String> + Send>>
+where
+    S: Stream<Item = Result<StreamingChunk, String>> + Send + 'static,
+{
+    Box::pin(stream! {
+        let mut chunks = Box::pin(chunks);
+        let mut event_id: u64 = 0;
+
+        loop {
+            let next = match options.keep_alive {
+                Some(interval) => match tokio::time::timeout(interval, chunks.next()).await {
+                    Ok(next) => next,
+                    Err(_) => {
+                        yield SSE_KEEP_ALIVE_FRAME.to_string();
+                        continue;
+                    }
+                },
+                None => chunks.next().await,
+            };
+
+            let item = match next {
+                Some(item) => item,
+                None => break,
+            };
+
+            event_id += 1;
+            let is_terminal = matches!(&item, Ok(chunk) if chunk.is_final) || item.is_err();
+            yield chunk_to_sse_event(&item, options.include_accumulated).with_id(event_id).to_frame();
+
+            if is_terminal {
+                break;
+            }
+        }
+
+        if options.send_done {
+            yield SseEvent::new("done", "{}".to_string()).to_frame();
+        }
+    })
+}
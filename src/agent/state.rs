@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Mutex;
 use chrono::{DateTime, Utc};
 
 /// Current state of an agent
@@ -23,6 +24,8 @@ pub enum AgentStatus {
     Error,
     Offline,
     Maintenance,
+    /// A task has been processing longer than the heartbeat's stall threshold
+    Stalled,
 }
 
 /// Context information for an agent
@@ -185,12 +188,14 @@ pub struct PerformanceMetrics {
     pub failed_tasks: u64,
     pub average_response_time_ms: f64,
     pub average_tokens_used: f64,
+    /// Copy of the agent's tool usage as of its last completed call
+    /// (`Agent::get_tool_usage_stats` is always current)
     pub tool_usage_stats: HashMap<String, ToolUsageStats>,
     pub uptime_seconds: u64,
     pub last_reset: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ToolUsageStats {
     pub usage_count: u64,
     pub success_count: u64,
@@ -199,6 +204,49 @@ pub struct ToolUsageStats {
     pub last_used: Option<DateTime<Utc>>,
 }
 
+impl ToolUsageStats {
+    fn record(&mut self, success: bool, execution_time_ms: f64) {
+        self.usage_count += 1;
+        if success {
+            self.success_count += 1;
+        } else {
+            self.failure_count += 1;
+        }
+        self.average_execution_time_ms =
+            (self.average_execution_time_ms * (self.usage_count - 1) as f64 + execution_time_ms) / self.usage_count as f64;
+        self.last_used = Some(Utc::now());
+    }
+}
+
+/// Per-tool usage shared by an agent's clones, its calls and its streams
+#[derive(Debug, Default)]
+pub struct ToolUsageTracker {
+    stats: Mutex<HashMap<String, ToolUsageStats>>,
+}
+
+impl ToolUsageTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, tool_name: &str, success: bool, execution_time_ms: f64) {
+        self.stats
+            .lock()
+            .unwrap()
+            .entry(tool_name.to_string())
+            .or_default()
+            .record(success, execution_time_ms);
+    }
+
+    pub fn get(&self, tool_name: &str) -> Option<ToolUsageStats> {
+        self.stats.lock().unwrap().get(tool_name).cloned()
+    }
+
+    pub fn snapshot(&self) -> HashMap<String, ToolUsageStats> {
+        self.stats.lock().unwrap().clone()
+    }
+}
+
 impl AgentState {
     pub fn new() -> Self {
         Self {
@@ -305,23 +353,7 @@ impl PerformanceMetrics {
     }
 
     pub fn record_tool_usage(&mut self, tool_name: String, success: bool, execution_time_ms: f64) {
-        let stats = self.tool_usage_stats.entry(tool_name).or_insert(ToolUsageStats {
-            usage_count: 0,
-            success_count: 0,
-            failure_count: 0,
-            average_execution_time_ms: 0.0,
-            last_used: None,
-        });
-
-        stats.usage_count += 1;
-        if success {
-            stats.success_count += 1;
-        } else {
-            stats.failure_count += 1;
-        }
-        stats.average_execution_time_ms = 
-            (stats.average_execution_time_ms * (stats.usage_count - 1) as f64 + execution_time_ms) / stats.usage_count as f64;
-        stats.last_used = Some(Utc::now());
+        self.tool_usage_stats.entry(tool_name).or_default().record(success, execution_time_ms);
     }
 
     pub fn get_success_rate(&self) -> f64 {
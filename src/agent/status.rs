@@ -0,0 +1,335 @@
+use crate::agent::agent::Agent;
+use crate::agent::state::AgentStatus;
+use crate::task::task::Task;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use crate::events::{AgentEvent, EventKind};
+use std::collections::{HashSet, VecDeque};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// Runs kept for the rolling statistics
+const ROLLING_WINDOW: usize = 100;
+/// Errors kept for the snapshot
+const RECENT_ERRORS: usize = 20;
+
+/// A task currently being executed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveTask {
+    pub task_id: String,
+    pub description: String,
+    pub started_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentError {
+    pub task_id: String,
+    pub error: String,
+    pub at: DateTime<Utc>,
+}
+
+/// Latency and token statistics over the last runs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RollingStats {
+    pub runs: usize,
+    pub success_rate: f64,
+    pub average_latency_ms: f64,
+    pub p95_latency_ms: u64,
+    pub average_tokens: f64,
+}
+
+/// Serializable point-in-time view of an agent for dashboards and health endpoints
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentStatusSnapshot {
+    pub agent_id: String,
+    pub name: String,
+    pub status: AgentStatus,
+    pub current_tasks: Vec<ActiveTask>,
+    pub queue_depth: usize,
+    pub active_sessions: Vec<String>,
+    pub recent_errors: Vec<RecentError>,
+    pub rolling: RollingStats,
+    pub last_activity: Option<DateTime<Utc>>,
+    pub uptime_seconds: u64,
+    /// Seconds since the last task started or finished
+    pub idle_seconds: u64,
+    pub taken_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+struct FinishedRun {
+    success: bool,
+    latency_ms: u64,
+    tokens: u32,
+}
+
+#[derive(Debug, Default)]
+struct Activity {
+    active: Vec<ActiveTask>,
+    /// Active tasks the heartbeat reported as stalled
+    stalled: HashSet<String>,
+    runs: VecDeque<FinishedRun>,
+    errors: VecDeque<RecentError>,
+    last_activity: Option<DateTime<Utc>>,
+}
+
+/// In-flight tasks and recent results, shared between clones of an agent so
+/// `Agent::status` sees runs started from any of them
+#[derive(Debug)]
+pub struct ActivityTracker {
+    started_at: DateTime<Utc>,
+    inner: Mutex<Activity>,
+    /// Set by `Agent::shutdown`; new tasks are refused
+    shutting_down: AtomicBool,
+    /// Woken whenever a task finishes
+    finished: Notify,
+}
+
+impl Default for ActivityTracker {
+    fn default() -> Self {
+        Self {
+            started_at: Utc::now(),
+            inner: Mutex::new(Activity::default()),
+            shutting_down: AtomicBool::new(false),
+            finished: Notify::new(),
+        }
+    }
+}
+
+impl ActivityTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn uptime_seconds(&self) -> u64 {
+        (Utc::now() - self.started_at).num_seconds().max(0) as u64
+    }
+
+    /// Seconds since the last task started or finished (uptime if there was none)
+    pub fn idle_seconds(&self) -> u64 {
+        let last = self.inner.lock().unwrap().last_activity.unwrap_or(self.started_at);
+        (Utc::now() - last).num_seconds().max(0) as u64
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn begin_shutdown(&self) {
+        // Under the activity lock, so a task registers either before this (and is
+        // drained) or after it (and is refused)
+        let _activity = self.inner.lock().unwrap();
+        self.shutting_down.store(true, Ordering::SeqCst);
+    }
+
+    /// Wait until no task is active, returning the ids still running at the deadline
+    pub(crate) async fn drain(&self, timeout: Duration) -> Vec<String> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            // Registered before checking so a task finishing in between isn't missed
+            let finished = self.finished.notified();
+            let active: Vec<String> = self.inner.lock().unwrap().active.iter().map(|t| t.task_id.clone()).collect();
+            if active.is_empty() {
+                return active;
+            }
+            if tokio::time::timeout_at(deadline, finished).await.is_err() {
+                return active;
+            }
+        }
+    }
+
+    /// Active tasks running longer than `threshold` that weren't reported yet
+    fn newly_stalled(&self, threshold: Duration) -> Vec<(String, u64)> {
+        let now = Utc::now();
+        let mut activity = self.inner.lock().unwrap();
+        let stalled: Vec<(String, u64)> = activity
+            .active
+            .iter()
+            .map(|task| (task.task_id.clone(), (now - task.started_at).num_milliseconds().max(0) as u64))
+            .filter(|(task_id, running_ms)| {
+                *running_ms >= threshold.as_millis() as u64 && !activity.stalled.contains(task_id)
+            })
+            .collect();
+        activity.stalled.extend(stalled.iter().map(|(task_id, _)| task_id.clone()));
+        stalled
+    }
+
+    /// Register a task as active; false, and nothing registered, once shutdown has begun
+    pub(crate) fn start(&self, task: &Task) -> bool {
+        let mut activity = self.inner.lock().unwrap();
+        if self.is_shutting_down() {
+            return false;
+        }
+        activity.active.push(ActiveTask {
+            task_id: task.id.clone(),
+            description: task.description.clone(),
+            started_at: Utc::now(),
+        });
+        activity.last_activity = Some(Utc::now());
+        true
+    }
+
+    pub(crate) fn finish(&self, task_id: &str, success: bool, latency_ms: u64, tokens: u32, error: Option<&str>) {
+        let mut activity = self.inner.lock().unwrap();
+        if let Some(position) = activity.active.iter().position(|t| t.task_id == task_id) {
+            activity.active.remove(position);
+        }
+        activity.stalled.remove(task_id);
+        activity.runs.push_back(FinishedRun { success, latency_ms, tokens });
+        if activity.runs.len() > ROLLING_WINDOW {
+            activity.runs.pop_front();
+        }
+        if let Some(error) = error {
+            activity.errors.push_back(RecentError {
+                task_id: task_id.to_string(),
+                error: error.to_string(),
+                at: Utc::now(),
+            });
+            if activity.errors.len() > RECENT_ERRORS {
+                activity.errors.pop_front();
+            }
+        }
+        activity.last_activity = Some(Utc::now());
+        drop(activity);
+        self.finished.notify_waiters();
+    }
+
+    fn rolling(runs: &VecDeque<FinishedRun>) -> RollingStats {
+        if runs.is_empty() {
+            return RollingStats::default();
+        }
+        let count = runs.len() as f64;
+        let mut latencies: Vec<u64> = runs.iter().map(|r| r.latency_ms).collect();
+        latencies.sort_unstable();
+        let p95_index = ((latencies.len() as f64 * 0.95).ceil() as usize).saturating_sub(1);
+        RollingStats {
+            runs: runs.len(),
+            success_rate: runs.iter().filter(|r| r.success).count() as f64 / count,
+            average_latency_ms: latencies.iter().sum::<u64>() as f64 / count,
+            p95_latency_ms: latencies[p95_index.min(latencies.len() - 1)],
+            average_tokens: runs.iter().map(|r| r.tokens as f64).sum::<f64>() / count,
+        }
+    }
+}
+
+impl Agent {
+    /// Cheap snapshot of what the agent is doing and how it has performed recently
+    pub fn status(&self) -> AgentStatusSnapshot {
+        let uptime_seconds = self.activity.uptime_seconds();
+        let idle_seconds = self.activity.idle_seconds();
+        let activity = self.activity.inner.lock().unwrap();
+        let status = if !activity.stalled.is_empty() {
+            AgentStatus::Stalled
+        } else if !activity.active.is_empty() {
+            AgentStatus::Processing
+        } else {
+            self.state.status.clone()
+        };
+        AgentStatusSnapshot {
+            agent_id: self.id.clone(),
+            name: self.name.clone(),
+            status,
+            current_tasks: activity.active.clone(),
+            queue_depth: self.task_queue.len(),
+            active_sessions: self.state.active_sessions.clone(),
+            recent_errors: activity.errors.iter().cloned().collect(),
+            rolling: ActivityTracker::rolling(&activity.runs),
+            last_activity: activity.last_activity,
+            uptime_seconds,
+            idle_seconds,
+            taken_at: Utc::now(),
+        }
+    }
+
+    /// Seconds since the agent was created
+    pub fn uptime_seconds(&self) -> u64 {
+        self.activity.uptime_seconds()
+    }
+
+    /// Publish a `Heartbeat` event every `interval` and a `TaskStalled` event (once per
+    /// task) for tasks processing longer than `stall_threshold`. Stalled tasks make
+    /// `status()` report `AgentStatus::Stalled` until they finish.
+    /// The heartbeat stops when the returned handle is dropped. Fails for a zero `interval`.
+    pub fn start_heartbeat(&self, interval: Duration, stall_threshold: Duration) -> Result<HeartbeatHandle, String> {
+        if interval.is_zero() {
+            return Err("Heartbeat interval must be greater than zero".to_string());
+        }
+        let activity = self.activity.clone();
+        let event_bus = self.event_bus.clone();
+        let agent_id = self.id.clone();
+        let handle = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                for (task_id, running_ms) in activity.newly_stalled(stall_threshold) {
+                    let mut event = AgentEvent::new(&agent_id, None, EventKind::TaskStalled { running_ms });
+                    event.task_id = Some(task_id);
+                    event_bus.publish(event);
+                }
+                let active_tasks = activity.inner.lock().unwrap().active.len();
+                event_bus.publish(AgentEvent::new(&agent_id, None, EventKind::Heartbeat {
+                    uptime_seconds: activity.uptime_seconds(),
+                    idle_seconds: activity.idle_seconds(),
+                    active_tasks,
+                }));
+            }
+        });
+        Ok(HeartbeatHandle { handle })
+    }
+}
+
+/// How a shutdown went
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShutdownReport {
+    /// Every in-flight task finished before the timeout
+    pub drained: bool,
+    /// Tasks still running when the timeout expired
+    pub unfinished_tasks: Vec<String>,
+    /// Errors flushing the run history
+    pub flush_errors: Vec<String>,
+}
+
+impl Agent {
+    /// Stop accepting tasks on this agent and all its clones, wait up to `timeout` for
+    /// in-flight runs (LLM calls and tool executions) to finish, flush the run history
+    /// and go `Offline`. Tasks submitted afterwards fail with `AgentError::ShuttingDown`.
+    pub async fn shutdown(&mut self, timeout: Duration) -> ShutdownReport {
+        self.activity.begin_shutdown();
+        let unfinished_tasks = self.activity.drain(timeout).await;
+
+        let mut flush_errors = Vec::new();
+        if let Some(store) = &self.run_store {
+            if let Err(e) = store.flush() {
+                flush_errors.push(e.to_string());
+            }
+        }
+        self.state.update_status(AgentStatus::Offline);
+        ShutdownReport {
+            drained: unfinished_tasks.is_empty(),
+            unfinished_tasks,
+            flush_errors,
+        }
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.activity.is_shutting_down()
+    }
+}
+
+/// Running heartbeat of an agent; stops when dropped
+#[derive(Debug)]
+pub struct HeartbeatHandle {
+    handle: tokio::task::JoinHandle<()>,
+}
+
+impl HeartbeatHandle {
+    pub fn stop(self) {}
+}
+
+impl Drop for HeartbeatHandle {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
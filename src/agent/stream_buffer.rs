@@ -0,0 +1,397 @@
+use crate::agent::agent::ToolCall;
+use crate::agent::streaming::{StreamPhase, StreamingChunk, StreamingHandler, StreamingResponse};
+use async_stream::stream;
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+
+/// What to do when a stream buffer is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackpressurePolicy {
+    /// Wait for the consumer to make room (slows down the provider stream)
+    Block,
+    /// Discard the oldest buffered event; final chunks and errors are never discarded
+    DropOldest,
+    /// Merge the new event into the newest buffered one where possible (text deltas
+    /// are concatenated), otherwise fall back to dropping the oldest
+    Coalesce,
+}
+
+/// Configuration for buffered streaming
+#[derive(Debug, Clone)]
+pub struct StreamBufferConfig {
+    pub capacity: usize,
+    pub policy: BackpressurePolicy,
+}
+
+impl Default for StreamBufferConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 64,
+            policy: BackpressurePolicy::Block,
+        }
+    }
+}
+
+impl StreamBufferConfig {
+    pub fn new(capacity: usize, policy: BackpressurePolicy) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            policy,
+        }
+    }
+}
+
+/// Events that can pass through an [`EventBuffer`]
+pub trait BufferedEvent: Send + 'static {
+    /// Try to merge `next` into `self`; give it back if the two can't be merged
+    fn coalesce(&mut self, next: Self) -> Result<(), Self>
+    where
+        Self: Sized,
+    {
+        Err(next)
+    }
+
+    /// Whether this event may be discarded under `DropOldest`
+    fn droppable(&self) -> bool {
+        true
+    }
+}
+
+impl BufferedEvent for Result<StreamingChunk, String> {
+    fn coalesce(&mut self, next: Self) -> Result<(), Self> {
+        match (self, next) {
+            (Ok(current), Ok(next)) if is_text_delta(current) && is_text_delta(&next) => {
+                current.content.push_str(&next.content);
+                current.accumulated_content = next.accumulated_content;
+                current.timestamp = next.timestamp;
+                Ok(())
+            }
+            (_, next) => Err(next),
+        }
+    }
+
+    fn droppable(&self) -> bool {
+        matches!(self, Ok(chunk) if !chunk.is_final)
+    }
+}
+
+fn is_text_delta(chunk: &StreamingChunk) -> bool {
+    !chunk.is_final && !chunk.has_tool_calls && !chunk.is_progress()
+}
+
+struct BufferState<T> {
+    items: VecDeque<T>,
+    closed: bool,
+    dropped: u64,
+}
+
+/// Bounded single-producer/single-consumer queue applying a [`BackpressurePolicy`]
+pub struct EventBuffer<T: BufferedEvent> {
+    state: Mutex<BufferState<T>>,
+    config: StreamBufferConfig,
+    item_ready: Notify,
+    space_ready: Notify,
+}
+
+impl<T: BufferedEvent> EventBuffer<T> {
+    pub fn new(config: StreamBufferConfig) -> Self {
+        let config = StreamBufferConfig::new(config.capacity, config.policy);
+        Self {
+            state: Mutex::new(BufferState {
+                items: VecDeque::with_capacity(config.capacity),
+                closed: false,
+                dropped: 0,
+            }),
+            config,
+            item_ready: Notify::new(),
+            space_ready: Notify::new(),
+        }
+    }
+
+    /// Add an event, waiting for room under `Block`. Returns false once the buffer is closed.
+    pub async fn push(&self, item: T) -> bool {
+        let mut item = item;
+        loop {
+            match self.try_insert(item, false) {
+                Ok(inserted) => return inserted,
+                Err(rejected) => item = rejected,
+            }
+            self.space_ready.notified().await;
+        }
+    }
+
+    /// Add an event without waiting. Under `Block` the capacity is exceeded rather than
+    /// blocking the caller, since synchronous callers (e.g. handler callbacks) can't wait.
+    pub fn push_now(&self, item: T) -> bool {
+        self.try_insert(item, true).unwrap_or(false)
+    }
+
+    fn try_insert(&self, item: T, force: bool) -> Result<bool, T> {
+        let mut state = self.state.lock().unwrap();
+        if state.closed {
+            return Ok(false);
+        }
+
+        if state.items.len() >= self.config.capacity {
+            match self.config.policy {
+                BackpressurePolicy::Block if !force => return Err(item),
+                BackpressurePolicy::Block => {}
+                BackpressurePolicy::DropOldest => Self::evict_oldest(&mut state),
+                BackpressurePolicy::Coalesce => {
+                    let merged = match state.items.back_mut() {
+                        Some(last) => last.coalesce(item),
+                        None => Err(item),
+                    };
+                    let item = match merged {
+                        Ok(()) => {
+                            drop(state);
+                            self.item_ready.notify_one();
+                            return Ok(true);
+                        }
+                        Err(item) => item,
+                    };
+                    Self::evict_oldest(&mut state);
+                    state.items.push_back(item);
+                    drop(state);
+                    self.item_ready.notify_one();
+                    return Ok(true);
+                }
+            }
+        }
+
+        state.items.push_back(item);
+        drop(state);
+        self.item_ready.notify_one();
+        Ok(true)
+    }
+
+    fn evict_oldest(state: &mut BufferState<T>) {
+        if let Some(index) = state.items.iter().position(|item| item.droppable()) {
+            state.items.remove(index);
+            state.dropped += 1;
+        }
+    }
+
+    /// Take the next event, waiting if the buffer is empty. Returns None once the
+    /// buffer is closed and drained.
+    pub async fn pop(&self) -> Option<T> {
+        loop {
+            {
+                let mut state = self.state.lock().unwrap();
+                if let Some(item) = state.items.pop_front() {
+                    drop(state);
+                    self.space_ready.notify_one();
+                    return Some(item);
+                }
+                if state.closed {
+                    return None;
+                }
+            }
+            self.item_ready.notified().await;
+        }
+    }
+
+    /// Stop accepting events; already buffered events can still be popped
+    pub fn close(&self) {
+        self.state.lock().unwrap().closed = true;
+        self.item_ready.notify_one();
+        self.space_ready.notify_one();
+    }
+
+    /// Number of events discarded so far
+    pub fn dropped(&self) -> u64 {
+        self.state.lock().unwrap().dropped
+    }
+}
+
+struct BufferGuard<T: BufferedEvent> {
+    buffer: Arc<EventBuffer<T>>,
+    producer: JoinHandle<()>,
+}
+
+impl<T: BufferedEvent> Drop for BufferGuard<T> {
+    fn drop(&mut self) {
+        // Consumer went away: stop pulling from (and drop) the source stream
+        self.buffer.close();
+        self.producer.abort();
+    }
+}
+
+/// Decouple a chunk stream from its consumer with a bounded buffer. The source is
+/// driven on its own task; dropping the returned stream aborts it.
+pub fn buffer_stream<S>(
+    source: S,
+    config: StreamBufferConfig,
+) -> Pin<Box<dyn Stream<Item = Result<StreamingChunk, String>> + Send>>
+where
+    S: Stream<Item = Result<StreamingChunk, String>> + Send + 'static,
+{
+    let buffer = Arc::new(EventBuffer::new(config));
+    let producer_buffer = buffer.clone();
+    let producer = tokio::spawn(async move {
+        let mut source = Box::pin(source);
+        while let Some(item) = source.next().await {
+            if !producer_buffer.push(item).await {
+                break;
+            }
+        }
+        producer_buffer.close();
+    });
+
+    let guard = BufferGuard { buffer, producer };
+    Box::pin(stream! {
+        let guard = guard;
+        while let Some(item) = guard.buffer.pop().await {
+            yield item;
+        }
+    })
+}
+
+/// Handler callbacks captured as data so they can be replayed on another task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum HandlerEvent {
+    Chunk(StreamingChunk),
+    ToolCalls(Vec<ToolCall>),
+    ToolCallStart { tool_name: String, call_id: String },
+    ToolCallStreaming { tool_name: String, call_id: String, partial_args: String },
+    ToolCallReady { tool_name: String, call_id: String, complete_args: String },
+    ToolCallExecuted { tool_name: String, call_id: String, result: String, execution_time_ms: u64 },
+    Progress(StreamPhase),
+    ValidationWarning(String),
+    Final(StreamingResponse),
+    Error(String),
+}
+
+impl BufferedEvent for HandlerEvent {
+    fn coalesce(&mut self, next: Self) -> Result<(), Self> {
+        match (self, next) {
+            (HandlerEvent::Chunk(current), HandlerEvent::Chunk(next))
+                if is_text_delta(current) && is_text_delta(&next) =>
+            {
+                current.content.push_str(&next.content);
+                current.accumulated_content = next.accumulated_content;
+                current.timestamp = next.timestamp;
+                Ok(())
+            }
+            // Only the latest partial arguments of a call matter
+            (
+                HandlerEvent::ToolCallStreaming { call_id, partial_args, .. },
+                HandlerEvent::ToolCallStreaming { call_id: next_id, partial_args: next_args, .. },
+            ) if *call_id == next_id => {
+                *partial_args = next_args;
+                Ok(())
+            }
+            (_, next) => Err(next),
+        }
+    }
+
+    fn droppable(&self) -> bool {
+        matches!(self, HandlerEvent::Chunk(chunk) if !chunk.is_final)
+            || matches!(self, HandlerEvent::ToolCallStreaming { .. })
+    }
+}
+
+/// Runs a handler on a background task so slow handlers don't delay the stream.
+/// Events are queued in a bounded [`EventBuffer`]; the worker drains it after the
+/// stream finishes and this handler is dropped. Handler callbacks can't wait for
+/// room, so a `Block` policy is applied as `Coalesce` to keep the queue bounded.
+pub struct BackgroundStreamingHandler {
+    buffer: Arc<EventBuffer<HandlerEvent>>,
+}
+
+impl BackgroundStreamingHandler {
+    pub fn spawn<H: StreamingHandler + Send + Sync + 'static>(handler: H, config: StreamBufferConfig) -> Self {
+        let policy = match config.policy {
+            BackpressurePolicy::Block => BackpressurePolicy::Coalesce,
+            policy => policy,
+        };
+        let buffer = Arc::new(EventBuffer::new(StreamBufferConfig::new(config.capacity, policy)));
+        let worker_buffer = buffer.clone();
+        tokio::spawn(async move {
+            while let Some(event) = worker_buffer.pop().await {
+                dispatch(&handler, event);
+            }
+        });
+        Self { buffer }
+    }
+
+    /// Number of handler events discarded because the handler fell behind
+    pub fn dropped_events(&self) -> u64 {
+        self.buffer.dropped()
+    }
+}
+
+impl Drop for BackgroundStreamingHandler {
+    fn drop(&mut self) {
+        self.buffer.close();
+    }
+}
+
+pub(crate) fn dispatch<H: StreamingHandler>(handler: &H, event: HandlerEvent) {
+    match event {
+        HandlerEvent::Chunk(chunk) => handler.handle_chunk(chunk),
+        HandlerEvent::ToolCalls(tool_calls) => handler.handle_tool_calls(tool_calls),
+        HandlerEvent::ToolCallStart { tool_name, call_id } => handler.handle_tool_call_start(tool_name, call_id),
+        HandlerEvent::ToolCallStreaming { tool_name, call_id, partial_args } => {
+            handler.handle_tool_call_streaming(tool_name, call_id, partial_args)
+        }
+        HandlerEvent::ToolCallReady { tool_name, call_id, complete_args } => {
+            handler.handle_tool_call_ready(tool_name, call_id, complete_args)
+        }
+        HandlerEvent::ToolCallExecuted { tool_name, call_id, result, execution_time_ms } => {
+            handler.handle_tool_call_executed(tool_name, call_id, result, execution_time_ms)
+        }
+        HandlerEvent::Progress(phase) => handler.handle_progress(phase),
+        HandlerEvent::ValidationWarning(message) => handler.handle_validation_warning(message),
+        HandlerEvent::Final(response) => handler.handle_final(response),
+        HandlerEvent::Error(error) => handler.handle_error(error),
+    }
+}
+
+impl StreamingHandler for BackgroundStreamingHandler {
+    fn handle_chunk(&self, chunk: StreamingChunk) {
+        self.buffer.push_now(HandlerEvent::Chunk(chunk));
+    }
+
+    fn handle_tool_calls(&self, tool_calls: Vec<ToolCall>) {
+        self.buffer.push_now(HandlerEvent::ToolCalls(tool_calls));
+    }
+
+    fn handle_tool_call_start(&self, tool_name: String, call_id: String) {
+        self.buffer.push_now(HandlerEvent::ToolCallStart { tool_name, call_id });
+    }
+
+    fn handle_tool_call_streaming(&self, tool_name: String, call_id: String, partial_args: String) {
+        self.buffer.push_now(HandlerEvent::ToolCallStreaming { tool_name, call_id, partial_args });
+    }
+
+    fn handle_tool_call_ready(&self, tool_name: String, call_id: String, complete_args: String) {
+        self.buffer.push_now(HandlerEvent::ToolCallReady { tool_name, call_id, complete_args });
+    }
+
+    fn handle_tool_call_executed(&self, tool_name: String, call_id: String, result: String, execution_time_ms: u64) {
+        self.buffer.push_now(HandlerEvent::ToolCallExecuted { tool_name, call_id, result, execution_time_ms });
+    }
+
+    fn handle_progress(&self, phase: StreamPhase) {
+        self.buffer.push_now(HandlerEvent::Progress(phase));
+    }
+
+    fn handle_validation_warning(&self, message: String) {
+        self.buffer.push_now(HandlerEvent::ValidationWarning(message));
+    }
+
+    fn handle_final(&self, response: StreamingResponse) {
+        self.buffer.push_now(HandlerEvent::Final(response));
+    }
+
+    fn handle_error(&self, error: String) {
+        self.buffer.push_now(HandlerEvent::Error(error));
+    }
+}
@@ -0,0 +1,77 @@
+use crate::agent::agent::Agent;
+use crate::agent::streaming::{StreamingChunk, StreamingHandler};
+use crate::task::task::Task;
+use futures::stream::{self, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::pin::Pin;
+
+/// A chunk (or error) from one of several multiplexed agent streams
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaggedChunk {
+    pub agent_id: String,
+    pub agent_name: String,
+    pub task_id: String,
+    pub item: Result<StreamingChunk, String>,
+}
+
+impl TaggedChunk {
+    /// Whether this is the last event of its agent's stream
+    pub fn is_terminal(&self) -> bool {
+        match &self.item {
+            Ok(chunk) => chunk.is_final,
+            Err(_) => true,
+        }
+    }
+}
+
+type TaggedStream = Pin<Box<dyn Stream<Item = TaggedChunk> + Send>>;
+
+/// Merges the streams of several concurrently running agents into one stream.
+/// Events of different agents interleave as they arrive; each agent's own events keep their order.
+#[derive(Default)]
+pub struct StreamMultiplexer {
+    streams: Vec<TaggedStream>,
+}
+
+impl StreamMultiplexer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an existing chunk stream under the given ids
+    pub fn add_stream<S>(&mut self, agent_id: &str, agent_name: &str, task_id: &str, chunks: S)
+    where
+        S: Stream<Item = Result<StreamingChunk, String>> + Send + 'static,
+    {
+        let agent_id = agent_id.to_string();
+        let agent_name = agent_name.to_string();
+        let task_id = task_id.to_string();
+        self.streams.push(Box::pin(chunks.map(move |item| TaggedChunk {
+            agent_id: agent_id.clone(),
+            agent_name: agent_name.clone(),
+            task_id: task_id.clone(),
+            item,
+        })));
+    }
+
+    /// Start a streaming run of `task` on `agent` and add it. Runs make progress
+    /// concurrently once the merged stream is polled.
+    pub async fn add_run<H: StreamingHandler + Send + Sync + 'static>(&mut self, agent: &mut Agent, task: Task, handler: H) {
+        let task_id = task.id.clone();
+        let chunks = agent.call_stream_with_handler(task, handler).await;
+        self.add_stream(&agent.id, &agent.name, &task_id, chunks);
+    }
+
+    pub fn len(&self) -> usize {
+        self.streams.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.streams.is_empty()
+    }
+
+    /// The merged stream; it ends once every agent stream has ended
+    pub fn into_stream(self) -> TaggedStream {
+        Box::pin(stream::select_all(self.streams))
+    }
+}
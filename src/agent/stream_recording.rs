@@ -0,0 +1,221 @@
+use crate::agent::agent::ToolCall;
+use crate::agent::stream_buffer::{dispatch, HandlerEvent};
+use crate::agent::streaming::{StreamPhase, StreamingChunk, StreamingHandler, StreamingResponse};
+use anyhow::Result;
+use async_stream::stream;
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// One captured event of a streaming run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    /// Milliseconds since recording started
+    pub offset_ms: u64,
+    pub kind: RecordedKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum RecordedKind {
+    /// A handler callback
+    Handler(HandlerEvent),
+    /// An item yielded by the chunk stream
+    Chunk(StreamingChunk),
+    /// An error yielded by the chunk stream
+    Error(String),
+    /// The chunk stream ended
+    End,
+}
+
+/// Writes every chunk and handler event of a streaming run to a JSONL file as it happens
+pub struct StreamRecorder {
+    started: Instant,
+    writer: Mutex<BufWriter<File>>,
+}
+
+impl StreamRecorder {
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Arc<Self>> {
+        Ok(Arc::new(Self {
+            started: Instant::now(),
+            writer: Mutex::new(BufWriter::new(File::create(path)?)),
+        }))
+    }
+
+    fn record(&self, kind: RecordedKind) {
+        let event = RecordedEvent {
+            offset_ms: self.started.elapsed().as_millis() as u64,
+            kind,
+        };
+        let mut writer = self.writer.lock().unwrap();
+        let written = serde_json::to_string(&event)
+            .map_err(anyhow::Error::from)
+            .and_then(|line| Ok(writeln!(writer, "{}", line)?))
+            .and_then(|_| Ok(writer.flush()?));
+        if let Err(e) = written {
+            eprintln!("Failed to record stream event: {}", e);
+        }
+    }
+
+    /// Wrap a handler so every callback is recorded before being forwarded
+    pub fn wrap_handler<H: StreamingHandler>(self: &Arc<Self>, handler: H) -> RecordingHandler<H> {
+        RecordingHandler {
+            inner: handler,
+            recorder: self.clone(),
+        }
+    }
+
+    /// Wrap a chunk stream so every item is recorded as it is yielded
+    pub fn wrap_stream<S>(
+        self: &Arc<Self>,
+        chunks: S,
+    ) -> Pin<Box<dyn Stream<Item = Result<StreamingChunk, String>> + Send>>
+    where
+        S: Stream<Item = Result<StreamingChunk, String>> + Send + 'static,
+    {
+        let recorder = self.clone();
+        Box::pin(stream! {
+            let mut chunks = Box::pin(chunks);
+            while let Some(item) = chunks.next().await {
+                match &item {
+                    Ok(chunk) => recorder.record(RecordedKind::Chunk(chunk.clone())),
+                    Err(error) => recorder.record(RecordedKind::Error(error.clone())),
+                }
+                yield item;
+            }
+            recorder.record(RecordedKind::End);
+        })
+    }
+}
+
+/// Handler wrapper created by [`StreamRecorder::wrap_handler`]
+pub struct RecordingHandler<H> {
+    inner: H,
+    recorder: Arc<StreamRecorder>,
+}
+
+impl<H: StreamingHandler> RecordingHandler<H> {
+    fn forward(&self, event: HandlerEvent) {
+        self.recorder.record(RecordedKind::Handler(event.clone()));
+        dispatch(&self.inner, event);
+    }
+}
+
+impl<H: StreamingHandler> StreamingHandler for RecordingHandler<H> {
+    fn handle_chunk(&self, chunk: StreamingChunk) {
+        self.forward(HandlerEvent::Chunk(chunk));
+    }
+
+    fn handle_tool_calls(&self, tool_calls: Vec<ToolCall>) {
+        self.forward(HandlerEvent::ToolCalls(tool_calls));
+    }
+
+    fn handle_tool_call_start(&self, tool_name: String, call_id: String) {
+        self.forward(HandlerEvent::ToolCallStart { tool_name, call_id });
+    }
+
+    fn handle_tool_call_streaming(&self, tool_name: String, call_id: String, partial_args: String) {
+        self.forward(HandlerEvent::ToolCallStreaming { tool_name, call_id, partial_args });
+    }
+
+    fn handle_tool_call_ready(&self, tool_name: String, call_id: String, complete_args: String) {
+        self.forward(HandlerEvent::ToolCallReady { tool_name, call_id, complete_args });
+    }
+
+    fn handle_tool_call_executed(&self, tool_name: String, call_id: String, result: String, execution_time_ms: u64) {
+        self.forward(HandlerEvent::ToolCallExecuted { tool_name, call_id, result, execution_time_ms });
+    }
+
+    fn handle_progress(&self, phase: StreamPhase) {
+        self.forward(HandlerEvent::Progress(phase));
+    }
+
+    fn handle_validation_warning(&self, message: String) {
+        self.forward(HandlerEvent::ValidationWarning(message));
+    }
+
+    fn handle_final(&self, response: StreamingResponse) {
+        self.forward(HandlerEvent::Final(response));
+    }
+
+    fn handle_error(&self, error: String) {
+        self.forward(HandlerEvent::Error(error));
+    }
+}
+
+/// How fast a recording is played back
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplayTiming {
+    /// Emit everything without delays
+    Immediate,
+    /// Reproduce the original gaps between events
+    Original,
+    /// Original gaps multiplied by this factor (0.5 = twice as fast)
+    Scaled(f64),
+}
+
+/// A recorded streaming run that can be fed back to handlers and consumers offline
+#[derive(Debug, Clone)]
+pub struct StreamReplay {
+    events: Vec<RecordedEvent>,
+}
+
+impl StreamReplay {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut events = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            events.push(serde_json::from_str(&line)?);
+        }
+        Ok(Self { events })
+    }
+
+    pub fn from_events(events: Vec<RecordedEvent>) -> Self {
+        Self { events }
+    }
+
+    pub fn events(&self) -> &[RecordedEvent] {
+        &self.events
+    }
+
+    /// Replay the run: handler callbacks fire and chunks are yielded in the recorded order
+    pub fn replay<H: StreamingHandler + Send + Sync + 'static>(
+        &self,
+        handler: H,
+        timing: ReplayTiming,
+    ) -> Pin<Box<dyn Stream<Item = Result<StreamingChunk, String>> + Send>> {
+        let events = self.events.clone();
+        Box::pin(stream! {
+            let started = Instant::now();
+            for event in events {
+                let scale = match timing {
+                    ReplayTiming::Immediate => 0.0,
+                    ReplayTiming::Original => 1.0,
+                    ReplayTiming::Scaled(factor) => factor.max(0.0),
+                };
+                if scale > 0.0 {
+                    let due = Duration::from_secs_f64(event.offset_ms as f64 * scale / 1000.0);
+                    if let Some(wait) = due.checked_sub(started.elapsed()) {
+                        tokio::time::sleep(wait).await;
+                    }
+                }
+
+                match event.kind {
+                    RecordedKind::Handler(handler_event) => dispatch(&handler, handler_event),
+                    RecordedKind::Chunk(chunk) => yield Ok(chunk),
+                    RecordedKind::Error(error) => yield Err(error),
+                    RecordedKind::End => break,
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,190 @@
+use crate::agent::streaming::{AccumulatedText, StreamingChunk};
+use async_stream::stream;
+use futures::{Stream, StreamExt};
+use regex::Regex;
+use std::pin::Pin;
+use std::time::Duration;
+
+pub type ChunkStream = Pin<Box<dyn Stream<Item = Result<StreamingChunk, String>> + Send>>;
+
+/// Combinators over an agent chunk stream. Final chunks and errors always pass through;
+/// transformers that rewrite text keep `accumulated_content` consistent with what they emit.
+pub trait ChunkStreamExt: Stream<Item = Result<StreamingChunk, String>> + Send + Sized + 'static {
+    /// Transform every non-final chunk
+    fn map_chunks<F>(self, mut f: F) -> ChunkStream
+    where
+        F: FnMut(StreamingChunk) -> StreamingChunk + Send + 'static,
+    {
+        Box::pin(self.map(move |item| match item {
+            Ok(chunk) if !chunk.is_final => Ok(f(chunk)),
+            other => other,
+        }))
+    }
+
+    /// Drop non-final chunks that don't match the predicate
+    fn filter_chunks<F>(self, mut predicate: F) -> ChunkStream
+    where
+        F: FnMut(&StreamingChunk) -> bool + Send + 'static,
+    {
+        Box::pin(self.filter(move |item| {
+            let keep = match item {
+                Ok(chunk) if !chunk.is_final => predicate(chunk),
+                _ => true,
+            };
+            futures::future::ready(keep)
+        }))
+    }
+
+    /// Emit text only in complete sentences (e.g. for text-to-speech); the remainder is flushed with the final chunk
+    fn by_sentence(self) -> ChunkStream {
+        rechunk(self, sentence_boundary, |text| text.to_string())
+    }
+
+    /// Replace every match of `pattern` with `replacement`. Text is held back until a
+    /// whitespace boundary so matches spanning chunk boundaries are still caught.
+    fn redact(self, pattern: Regex, replacement: &str) -> ChunkStream {
+        let replacement = replacement.to_string();
+        rechunk(self, whitespace_boundary, move |text| {
+            pattern.replace_all(text, replacement.as_str()).into_owned()
+        })
+    }
+
+    /// Emit at most one chunk per `interval`, merging the text of chunks that arrive in between
+    fn throttle(self, interval: Duration) -> ChunkStream {
+        Box::pin(stream! {
+            let mut source = Box::pin(self);
+            let mut pending: Option<StreamingChunk> = None;
+            let mut last_emit = tokio::time::Instant::now() - interval;
+
+            loop {
+                let next = match &pending {
+                    Some(_) => {
+                        let deadline = last_emit + interval;
+                        tokio::select! {
+                            next = source.next() => Some(next),
+                            _ = tokio::time::sleep_until(deadline) => None,
+                        }
+                    }
+                    None => Some(source.next().await),
+                };
+
+                match next {
+                    // Interval elapsed with text waiting
+                    None => {
+                        if let Some(chunk) = pending.take() {
+                            last_emit = tokio::time::Instant::now();
+                            yield Ok(chunk);
+                        }
+                    }
+                    Some(Some(Ok(chunk))) if !chunk.is_final && !chunk.has_tool_calls && !chunk.is_progress() => {
+                        match pending.as_mut() {
+                            Some(merged) => {
+                                merged.content.push_str(&chunk.content);
+                                merged.accumulated_content = chunk.accumulated_content;
+                                merged.metrics = chunk.metrics;
+                                merged.timestamp = chunk.timestamp;
+                            }
+                            None => pending = Some(chunk),
+                        }
+                        if last_emit.elapsed() >= interval {
+                            if let Some(chunk) = pending.take() {
+                                last_emit = tokio::time::Instant::now();
+                                yield Ok(chunk);
+                            }
+                        }
+                    }
+                    Some(Some(item)) => {
+                        if let Some(chunk) = pending.take() {
+                            yield Ok(chunk);
+                        }
+                        last_emit = tokio::time::Instant::now();
+                        yield item;
+                    }
+                    Some(None) => {
+                        if let Some(chunk) = pending.take() {
+                            yield Ok(chunk);
+                        }
+                        break;
+                    }
+                }
+            }
+        })
+    }
+}
+
+impl<S> ChunkStreamExt for S where S: Stream<Item = Result<StreamingChunk, String>> + Send + Sized + 'static {}
+
+/// Buffer text and emit only the prefix `ready` says is complete, rewritten by `transform`
+fn rechunk<S, R, T>(source: S, ready: R, mut transform: T) -> ChunkStream
+where
+    S: Stream<Item = Result<StreamingChunk, String>> + Send + 'static,
+    R: Fn(&str) -> usize + Send + 'static,
+    T: FnMut(&str) -> String + Send + 'static,
+{
+    Box::pin(stream! {
+        let mut source = Box::pin(source);
+        let mut buffer = String::new();
+        let mut emitted = AccumulatedText::new();
+
+        while let Some(item) = source.next().await {
+            match item {
+                Ok(mut chunk) if !chunk.is_final && !chunk.has_tool_calls && !chunk.is_progress() => {
+                    buffer.push_str(&chunk.content);
+                    let cut = ready(&buffer);
+                    if cut == 0 {
+                        continue;
+                    }
+                    let rest = buffer.split_off(cut);
+                    let text = transform(&buffer);
+                    buffer = rest;
+
+                    emitted.push_str(&text);
+                    chunk.content = text;
+                    chunk.accumulated_content = emitted.clone();
+                    yield Ok(chunk);
+                }
+                Ok(mut chunk) if chunk.is_final => {
+                    buffer.push_str(&chunk.content);
+                    let text = transform(&buffer);
+                    buffer.clear();
+
+                    emitted.push_str(&text);
+                    chunk.content = text;
+                    chunk.accumulated_content = emitted.clone();
+                    yield Ok(chunk);
+                }
+                other => yield other,
+            }
+        }
+    })
+}
+
+/// Length of the buffer up to and including the last sentence end (`.`, `!`, `?` followed by whitespace, or a newline)
+fn sentence_boundary(text: &str) -> usize {
+    let mut boundary = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\n' => boundary = i + 1,
+            '.' | '!' | '?' => {
+                if let Some(&(next_i, next)) = chars.peek() {
+                    if next.is_whitespace() {
+                        boundary = next_i + next.len_utf8();
+                        chars.next();
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    boundary
+}
+
+/// Length of the buffer up to and including the last whitespace character
+fn whitespace_boundary(text: &str) -> usize {
+    text.char_indices()
+        .filter(|(_, c)| c.is_whitespace())
+        .last()
+        .map(|(i, c)| i + c.len_utf8())
+        .unwrap_or(0)
+}
@@ -1,7 +1,9 @@
-use crate::agent::agent::ToolCall;
+use crate::agent::agent::{AgentResponse, ToolCall};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
 use std::io::Write;
+use std::sync::{Arc, RwLock};
 use chrono;
 
 /// Streaming response chunk containing incremental content
@@ -11,8 +13,8 @@ pub struct StreamingChunk {
     pub content: String,
     /// Whether this is the final chunk
     pub is_final: bool,
-    /// Current accumulated content so far
-    pub accumulated_content: String,
+    /// Content accumulated so far; a cheap snapshot that is only copied out when read
+    pub accumulated_content: AccumulatedText,
     /// Tool call information if this chunk contains tool calls
     pub tool_calls: Option<Vec<crate::agent::agent::ToolCall>>,
     /// Whether this chunk contains tool calls
@@ -23,10 +25,260 @@ pub struct StreamingChunk {
     pub finish_reason: Option<String>,
     /// Timestamp of this chunk
     pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// Timing and token metrics up to and including this chunk
+    #[serde(default)]
+    pub metrics: Option<StreamingMetrics>,
+    /// Complete response of the run, set on the final chunk
+    #[serde(default)]
+    pub response: Option<Box<AgentResponse>>,
     /// Additional metadata
     pub metadata: HashMap<String, serde_json::Value>,
 }
 
+/// Text accumulated over a stream. Snapshots share one append-only buffer and only
+/// remember how much of it existed when they were taken, so handing the accumulated
+/// content to every chunk costs O(1) instead of copying everything streamed so far.
+#[derive(Debug, Clone, Default)]
+pub struct AccumulatedText {
+    buffer: Arc<RwLock<String>>,
+    len: usize,
+}
+
+impl AccumulatedText {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reserve room for `capacity` bytes up front so long streams don't keep regrowing the buffer
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            buffer: Arc::new(RwLock::new(String::with_capacity(capacity))),
+            len: 0,
+        }
+    }
+
+    /// Append text. A snapshot that is appended to after the buffer moved on gets a private copy first.
+    pub fn push_str(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        let mut buffer = self.buffer.write().unwrap();
+        if buffer.len() != self.len {
+            let forked = buffer[..self.len].to_string();
+            drop(buffer);
+            self.buffer = Arc::new(RwLock::new(forked));
+            buffer = self.buffer.write().unwrap();
+        }
+        buffer.push_str(text);
+        self.len = buffer.len();
+    }
+
+    /// Start over with an empty buffer; existing snapshots keep their text. The
+    /// allocation is reused when no snapshot shares it, otherwise a new buffer of
+    /// the same capacity is started.
+    pub fn clear(&mut self) {
+        if let Some(buffer) = Arc::get_mut(&mut self.buffer) {
+            buffer.get_mut().unwrap().clear();
+            self.len = 0;
+            return;
+        }
+        let capacity = self.buffer.read().unwrap().capacity();
+        *self = Self::with_capacity(capacity);
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Run `f` on the text without copying it
+    pub fn with_str<R>(&self, f: impl FnOnce(&str) -> R) -> R {
+        f(&self.buffer.read().unwrap()[..self.len])
+    }
+
+    /// Shared immutable copy of the text
+    pub fn to_arc(&self) -> Arc<str> {
+        self.with_str(Arc::from)
+    }
+
+    /// The text as a `String`, taking over the buffer instead of copying it when
+    /// no other snapshot shares it
+    pub fn into_string(self) -> String {
+        match Arc::try_unwrap(self.buffer) {
+            Ok(buffer) => {
+                let mut text = buffer.into_inner().unwrap();
+                text.truncate(self.len);
+                text
+            }
+            Err(buffer) => buffer.read().unwrap()[..self.len].to_string(),
+        }
+    }
+}
+
+impl fmt::Display for AccumulatedText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.with_str(|text| f.write_str(text))
+    }
+}
+
+impl PartialEq for AccumulatedText {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.with_str(|a| other.with_str(|b| a == b))
+    }
+}
+
+impl PartialEq<str> for AccumulatedText {
+    fn eq(&self, other: &str) -> bool {
+        self.with_str(|text| text == other)
+    }
+}
+
+impl From<String> for AccumulatedText {
+    fn from(text: String) -> Self {
+        Self {
+            len: text.len(),
+            buffer: Arc::new(RwLock::new(text)),
+        }
+    }
+}
+
+impl From<&str> for AccumulatedText {
+    fn from(text: &str) -> Self {
+        Self::from(text.to_string())
+    }
+}
+
+impl From<AccumulatedText> for String {
+    fn from(text: AccumulatedText) -> Self {
+        text.into_string()
+    }
+}
+
+impl Serialize for AccumulatedText {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.with_str(|text| serializer.serialize_str(text))
+    }
+}
+
+impl<'de> Deserialize<'de> for AccumulatedText {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::from)
+    }
+}
+
+/// Timing data collected while streaming
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StreamingMetrics {
+    /// Time from sending the request to the first content chunk
+    pub time_to_first_token_ms: Option<u64>,
+    /// Gap between the previous chunk and the latest one
+    pub last_inter_chunk_latency_ms: Option<u64>,
+    /// Average gap between consecutive chunks
+    pub avg_inter_chunk_latency_ms: Option<f64>,
+    /// Largest gap between consecutive chunks
+    pub max_inter_chunk_latency_ms: Option<u64>,
+    /// Number of content chunks received
+    pub chunk_count: u64,
+    /// Completion tokens so far (provider count when reported, otherwise estimated from text)
+    pub tokens_so_far: u32,
+    /// Tokens per second since the first token
+    pub tokens_per_second: Option<f64>,
+    /// Time since the request was sent
+    pub elapsed_ms: u64,
+}
+
+/// Running token estimate over streamed text. Each delta is counted once as it
+/// arrives, so long streams never rescan what was already seen.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokenEstimator {
+    chars: usize,
+}
+
+impl TokenEstimator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, text: &str) {
+        self.chars += text.chars().count();
+    }
+
+    /// Rough estimate of ~4 characters per token
+    pub fn tokens(&self) -> u32 {
+        self.chars.div_ceil(4) as u32
+    }
+}
+
+/// Collects [`StreamingMetrics`] as chunks arrive
+#[derive(Debug, Clone)]
+pub struct StreamingMetricsRecorder {
+    started: std::time::Instant,
+    first_chunk: Option<std::time::Instant>,
+    last_chunk: Option<std::time::Instant>,
+    total_gap_ms: u64,
+    gap_count: u64,
+    metrics: StreamingMetrics,
+    estimate: TokenEstimator,
+}
+
+impl StreamingMetricsRecorder {
+    pub fn start() -> Self {
+        Self {
+            started: std::time::Instant::now(),
+            first_chunk: None,
+            last_chunk: None,
+            total_gap_ms: 0,
+            gap_count: 0,
+            metrics: StreamingMetrics::default(),
+            estimate: TokenEstimator::new(),
+        }
+    }
+
+    /// Record a content chunk and return the metrics so far
+    pub fn record_chunk(&mut self, text: &str) -> StreamingMetrics {
+        let now = std::time::Instant::now();
+        if self.first_chunk.is_none() {
+            self.first_chunk = Some(now);
+            self.metrics.time_to_first_token_ms = Some(now.duration_since(self.started).as_millis() as u64);
+        }
+        if let Some(last) = self.last_chunk {
+            let gap = now.duration_since(last).as_millis() as u64;
+            self.total_gap_ms += gap;
+            self.gap_count += 1;
+            self.metrics.last_inter_chunk_latency_ms = Some(gap);
+            self.metrics.avg_inter_chunk_latency_ms = Some(self.total_gap_ms as f64 / self.gap_count as f64);
+            self.metrics.max_inter_chunk_latency_ms = Some(self.metrics.max_inter_chunk_latency_ms.unwrap_or(0).max(gap));
+        }
+        self.last_chunk = Some(now);
+        self.metrics.chunk_count += 1;
+        self.estimate.push(text);
+        // Estimated until the provider reports usage
+        self.metrics.tokens_so_far = self.metrics.tokens_so_far.max(self.estimate.tokens());
+        self.snapshot()
+    }
+
+    /// Replace the estimated token count with the provider-reported one
+    pub fn record_usage(&mut self, completion_tokens: u32) {
+        self.metrics.tokens_so_far = completion_tokens;
+    }
+
+    /// Current metrics with elapsed time and throughput refreshed
+    pub fn snapshot(&mut self) -> StreamingMetrics {
+        let now = std::time::Instant::now();
+        self.metrics.elapsed_ms = now.duration_since(self.started).as_millis() as u64;
+        if let Some(first) = self.first_chunk {
+            let seconds = now.duration_since(first).as_secs_f64();
+            if seconds > 0.0 {
+                self.metrics.tokens_per_second = Some(self.metrics.tokens_so_far as f64 / seconds);
+            }
+        }
+        self.metrics.clone()
+    }
+}
+
 /// Usage statistics for streaming responses
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StreamingUsage {
@@ -60,10 +312,178 @@ pub struct StreamingResponse {
     pub error: Option<String>,
     /// Timestamp when streaming completed
     pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// Time-to-first-token, chunk latency and token metrics for the stream
+    #[serde(default)]
+    pub metrics: StreamingMetrics,
     /// Additional metadata
     pub metadata: HashMap<String, serde_json::Value>,
 }
 
+/// Resilience settings for streaming calls
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamingOptions {
+    /// How many times a stream that fails with a transient error is retried
+    pub max_stream_retries: u32,
+    /// Delay before the first retry, doubled for every further attempt
+    pub retry_backoff_ms: u64,
+    /// Continue from the partial output instead of restarting the generation
+    pub resume_partial: bool,
+    /// Abort a stream that sends nothing for this long (None waits forever)
+    #[serde(default)]
+    pub idle_timeout_ms: Option<u64>,
+    /// After an idle timeout, retry the round as a non-streaming completion instead of failing
+    #[serde(default)]
+    pub fallback_to_completion: bool,
+    /// Merge token-level deltas into larger chunks before they reach the handler and stream
+    #[serde(default)]
+    pub coalescing: Option<ChunkCoalescing>,
+}
+
+/// When buffered text deltas are flushed as one chunk. Any configured condition triggers a flush.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChunkCoalescing {
+    /// Flush text that has been waiting this long
+    pub flush_interval_ms: Option<u64>,
+    /// Flush once this many characters are buffered
+    pub max_chars: Option<usize>,
+    /// Flush whenever the buffer ends at a sentence boundary
+    pub sentence_boundaries: bool,
+}
+
+impl ChunkCoalescing {
+    pub fn every_ms(flush_interval_ms: u64) -> Self {
+        Self { flush_interval_ms: Some(flush_interval_ms), ..Self::default() }
+    }
+
+    pub fn every_chars(max_chars: usize) -> Self {
+        Self { max_chars: Some(max_chars), ..Self::default() }
+    }
+
+    pub fn sentences() -> Self {
+        Self { sentence_boundaries: true, ..Self::default() }
+    }
+}
+
+/// Buffers text deltas according to a [`ChunkCoalescing`] policy
+#[derive(Debug, Clone)]
+pub struct ChunkCoalescer {
+    config: ChunkCoalescing,
+    buffer: String,
+    /// Characters in `buffer`, kept alongside it so size checks don't rescan the buffer
+    buffered_chars: usize,
+    first_buffered: Option<tokio::time::Instant>,
+}
+
+impl ChunkCoalescer {
+    pub fn new(config: ChunkCoalescing) -> Self {
+        Self {
+            config,
+            buffer: String::new(),
+            buffered_chars: 0,
+            first_buffered: None,
+        }
+    }
+
+    /// Buffer a delta; returns the text to emit if a flush condition is met
+    pub fn push(&mut self, text: &str) -> Option<String> {
+        self.buffer.push_str(text);
+        self.buffered_chars += text.chars().count();
+        if self.first_buffered.is_none() {
+            self.first_buffered = Some(tokio::time::Instant::now());
+        }
+
+        let config = &self.config;
+        let unconfigured = config.flush_interval_ms.is_none() && config.max_chars.is_none() && !config.sentence_boundaries;
+        let size_reached = config.max_chars.is_some_and(|max| self.buffered_chars >= max);
+        let interval_reached = self.deadline().is_some_and(|deadline| tokio::time::Instant::now() >= deadline);
+        let sentence_ended = config.sentence_boundaries && ends_sentence(&self.buffer);
+
+        if unconfigured || size_reached || interval_reached || sentence_ended {
+            self.take()
+        } else {
+            None
+        }
+    }
+
+    /// When buffered text is due by the interval condition
+    pub fn deadline(&self) -> Option<tokio::time::Instant> {
+        let interval = self.config.flush_interval_ms?;
+        self.first_buffered.map(|first| first + std::time::Duration::from_millis(interval))
+    }
+
+    /// Flush whatever is buffered
+    pub fn take(&mut self) -> Option<String> {
+        self.first_buffered = None;
+        self.buffered_chars = 0;
+        if self.buffer.is_empty() {
+            None
+        } else {
+            Some(std::mem::take(&mut self.buffer))
+        }
+    }
+}
+
+fn ends_sentence(text: &str) -> bool {
+    let trimmed = text.trim_end_matches([' ', '\t']);
+    text.ends_with('\n') || (trimmed.len() < text.len() && trimmed.ends_with(['.', '!', '?']))
+}
+
+impl Default for StreamingOptions {
+    fn default() -> Self {
+        Self {
+            max_stream_retries: 2,
+            retry_backoff_ms: 500,
+            resume_partial: true,
+            idle_timeout_ms: Some(120_000),
+            fallback_to_completion: false,
+            coalescing: None,
+        }
+    }
+}
+
+/// Whether a provider stream error is worth retrying (dropped connections, overload, gateway errors)
+pub fn is_transient_stream_error(error: &str) -> bool {
+    let error = error.to_lowercase();
+    [
+        "connection", "reset", "broken pipe", "timed out", "timeout", "unexpected eof",
+        "overloaded", "429", "500", "502", "503", "504", "529", "temporarily unavailable",
+    ]
+    .iter()
+    .any(|marker| error.contains(marker))
+}
+
+/// Drain a chunk stream and return the `AgentResponse` carried by its final chunk
+pub async fn collect_response<S>(chunks: S) -> Result<AgentResponse, String>
+where
+    S: futures::Stream<Item = Result<StreamingChunk, String>>,
+{
+    use futures::StreamExt;
+
+    let mut chunks = Box::pin(chunks);
+    while let Some(item) = chunks.next().await {
+        let chunk = item?;
+        if chunk.is_final {
+            return chunk.response
+                .map(|response| *response)
+                .ok_or_else(|| "Final chunk did not carry a response".to_string());
+        }
+    }
+    Err("Stream ended without a final chunk".to_string())
+}
+
+/// Progress of a streaming run before and between generations, so UIs can show
+/// activity during the gap before the first token
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "phase", rename_all = "snake_case")]
+pub enum StreamPhase {
+    /// Messages for the first request are assembled
+    PromptBuilt { message_count: usize, estimated_tokens: u32 },
+    /// A completion request went out to the provider (round 1 is the initial request)
+    RequestSent { round: u32 },
+    /// The model requested tools, which are about to run
+    ToolsExecuting { count: usize },
+}
+
 /// Callback function type for handling streaming chunks
 pub type StreamingCallback = Box<dyn Fn(StreamingChunk) + Send + Sync>;
 
@@ -102,6 +522,18 @@ pub trait StreamingHandler {
         let _ = (tool_name, call_id, result, execution_time_ms);
     }
     
+    /// Handle progress through the phases before and between generations
+    fn handle_progress(&self, phase: StreamPhase) {
+        // Default implementation - do nothing
+        let _ = phase;
+    }
+    
+    /// Handle an early warning that streamed structured output won't pass validation
+    fn handle_validation_warning(&self, message: String) {
+        // Default implementation - do nothing
+        let _ = message;
+    }
+    
     /// Handle the final streaming response
     fn handle_final(&self, response: StreamingResponse);
     
@@ -185,6 +617,7 @@ impl StreamingResponse {
             temperature,
             error: None,
             timestamp: chrono::Utc::now(),
+            metrics: StreamingMetrics::default(),
             metadata: HashMap::new(),
         }
     }
@@ -209,23 +642,59 @@ impl StreamingResponse {
             temperature,
             error: Some(error),
             timestamp: chrono::Utc::now(),
+            metrics: StreamingMetrics::default(),
             metadata: HashMap::new(),
         }
     }
 }
 
+impl StreamingResponse {
+    /// Attach the stream's timing metrics
+    pub fn with_metrics(mut self, metrics: StreamingMetrics) -> Self {
+        self.metrics = metrics;
+        self
+    }
+}
+
 impl StreamingChunk {
+    /// Empty chunk announcing a progress phase; the phase is in `metadata["progress"]`
+    pub fn progress(phase: &StreamPhase) -> Self {
+        let mut chunk = Self::new(String::new(), false, String::new());
+        chunk.metadata.insert("progress".to_string(), serde_json::json!(phase));
+        chunk
+    }
+
+    /// Whether this chunk only announces a progress phase
+    pub fn is_progress(&self) -> bool {
+        self.metadata.contains_key("progress")
+    }
+
+    /// Empty chunk telling consumers to discard the text shown so far because generation restarted
+    pub fn restart() -> Self {
+        let mut chunk = Self::new(String::new(), false, String::new());
+        chunk.metadata.insert("stream_restarted".to_string(), serde_json::json!(true));
+        chunk
+    }
+
+    /// Attach timing metrics to this chunk
+    pub fn with_metrics(mut self, metrics: StreamingMetrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
     /// Create a new streaming chunk
-    pub fn new(content: String, is_final: bool, accumulated_content: String) -> Self {
+    pub fn new(content: String, is_final: bool, accumulated_content: impl Into<AccumulatedText>) -> Self {
         Self {
             content,
             is_final,
-            accumulated_content,
+            accumulated_content: accumulated_content.into(),
             tool_calls: None,
             has_tool_calls: false,
             usage: None,
             finish_reason: None,
             timestamp: chrono::Utc::now(),
+            metrics: None,
+            response: None,
             metadata: HashMap::new(),
         }
     }
@@ -234,18 +703,20 @@ impl StreamingChunk {
     pub fn with_tool_calls(
         content: String,
         is_final: bool,
-        accumulated_content: String,
+        accumulated_content: impl Into<AccumulatedText>,
         tool_calls: Vec<crate::agent::agent::ToolCall>,
     ) -> Self {
         Self {
             content,
             is_final,
-            accumulated_content,
+            accumulated_content: accumulated_content.into(),
             tool_calls: Some(tool_calls.clone()),
             has_tool_calls: !tool_calls.is_empty(),
             usage: None,
             finish_reason: None,
             timestamp: chrono::Utc::now(),
+            metrics: None,
+            response: None,
             metadata: HashMap::new(),
         }
     }
@@ -253,19 +724,21 @@ impl StreamingChunk {
     /// Create a final chunk with usage statistics
     pub fn final_chunk(
         content: String,
-        accumulated_content: String,
+        accumulated_content: impl Into<AccumulatedText>,
         usage: Option<StreamingUsage>,
         finish_reason: Option<String>,
     ) -> Self {
         Self {
             content,
             is_final: true,
-            accumulated_content,
+            accumulated_content: accumulated_content.into(),
             tool_calls: None,
             has_tool_calls: false,
             usage,
             finish_reason,
             timestamp: chrono::Utc::now(),
+            metrics: None,
+            response: None,
             metadata: HashMap::new(),
         }
     }
@@ -0,0 +1,300 @@
+use crate::agent::agent::ToolCall;
+use crate::agent::streaming::{StreamingChunk, StreamingHandler, StreamingResponse};
+use std::io::{IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const CYAN: &str = "\x1b[36m";
+
+/// Options for [`TerminalRenderer`]
+#[derive(Debug, Clone)]
+pub struct TerminalRendererOptions {
+    /// Use ANSI colors (defaults to whether stdout is a terminal)
+    pub color: bool,
+    /// Animate a spinner while tools run
+    pub spinner: bool,
+    /// Print tool results below each tool call
+    pub show_tool_results: bool,
+    /// Tool results longer than this many lines are collapsed to a preview
+    pub max_tool_result_lines: usize,
+    /// Print token and timing statistics when the stream finishes
+    pub show_summary: bool,
+}
+
+impl Default for TerminalRendererOptions {
+    fn default() -> Self {
+        let is_terminal = std::io::stdout().is_terminal();
+        Self {
+            color: is_terminal,
+            spinner: is_terminal,
+            show_tool_results: true,
+            max_tool_result_lines: 5,
+            show_summary: true,
+        }
+    }
+}
+
+#[derive(Default)]
+struct RenderState {
+    /// Text of the current line not yet rendered
+    pending_line: String,
+    in_code_block: bool,
+}
+
+struct Spinner {
+    running: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+/// `StreamingHandler` that renders markdown to the terminal line by line: headings,
+/// lists, fenced code blocks and inline emphasis are styled, tools get a spinner while
+/// they run and long tool results are collapsed to a short preview.
+pub struct TerminalRenderer {
+    options: TerminalRendererOptions,
+    state: Mutex<RenderState>,
+    spinner: Mutex<Option<Spinner>>,
+}
+
+impl TerminalRenderer {
+    pub fn new() -> Self {
+        Self::with_options(TerminalRendererOptions::default())
+    }
+
+    pub fn with_options(options: TerminalRendererOptions) -> Self {
+        Self {
+            options,
+            state: Mutex::new(RenderState::default()),
+            spinner: Mutex::new(None),
+        }
+    }
+
+    fn style(&self, style: &str, text: &str) -> String {
+        if self.options.color {
+            format!("{}{}{}", style, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn print(&self, text: &str) {
+        let mut stdout = std::io::stdout().lock();
+        let _ = write!(stdout, "{}", text);
+        let _ = stdout.flush();
+    }
+
+    /// Render one complete markdown line
+    fn render_line(&self, line: &str, state: &mut RenderState) -> String {
+        let trimmed = line.trim_start();
+
+        if trimmed.starts_with("```") {
+            state.in_code_block = !state.in_code_block;
+            let label = trimmed.trim_start_matches('`').trim();
+            return if state.in_code_block && !label.is_empty() {
+                self.style(DIM, &format!("┌─ {}", label))
+            } else {
+                self.style(DIM, if state.in_code_block { "┌─" } else { "└─" })
+            };
+        }
+        if state.in_code_block {
+            return format!("{} {}", self.style(DIM, "│"), self.style(YELLOW, line));
+        }
+
+        if let Some(heading) = trimmed.strip_prefix('#') {
+            let text = heading.trim_start_matches('#').trim();
+            return self.style(&format!("{}{}", BOLD, CYAN), text);
+        }
+        if let Some(item) = trimmed.strip_prefix("- ").or_else(|| trimmed.strip_prefix("* ")) {
+            let indent = &line[..line.len() - trimmed.len()];
+            return format!("{}• {}", indent, self.render_inline(item));
+        }
+
+        self.render_inline(line)
+    }
+
+    /// Style `**bold**` and `` `code` `` spans
+    fn render_inline(&self, text: &str) -> String {
+        if !self.options.color {
+            return text.to_string();
+        }
+
+        let mut rendered = String::with_capacity(text.len());
+        let mut rest = text;
+        loop {
+            let bold = rest.find("**");
+            let code = rest.find('`');
+            let (start, marker, style) = match (bold, code) {
+                (Some(b), Some(c)) if c < b => (c, "`", YELLOW),
+                (Some(b), _) => (b, "**", BOLD),
+                (None, Some(c)) => (c, "`", YELLOW),
+                (None, None) => break,
+            };
+            let after = &rest[start + marker.len()..];
+            match after.find(marker) {
+                Some(end) => {
+                    rendered.push_str(&rest[..start]);
+                    rendered.push_str(&self.style(style, &after[..end]));
+                    rest = &after[end + marker.len()..];
+                }
+                None => break,
+            }
+        }
+        rendered.push_str(rest);
+        rendered
+    }
+
+    fn flush_pending(&self) {
+        let mut state = self.state.lock().unwrap();
+        if !state.pending_line.is_empty() {
+            let line = std::mem::take(&mut state.pending_line);
+            let rendered = self.render_line(&line, &mut state);
+            self.print(&format!("{}\n", rendered));
+        }
+    }
+
+    fn start_spinner(&self, label: String) {
+        self.stop_spinner();
+        if !self.options.spinner {
+            self.print(&format!("{}\n", self.style(DIM, &format!("⏳ {}", label))));
+            return;
+        }
+
+        let running = Arc::new(AtomicBool::new(true));
+        let flag = running.clone();
+        let color = self.options.color;
+        let thread = std::thread::spawn(move || {
+            const FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+            let mut frame = 0;
+            while flag.load(Ordering::Relaxed) {
+                let symbol = if color { format!("{}{}{}", CYAN, FRAMES[frame], RESET) } else { FRAMES[frame].to_string() };
+                let mut stdout = std::io::stdout().lock();
+                let _ = write!(stdout, "\r{} {}", symbol, label);
+                let _ = stdout.flush();
+                drop(stdout);
+                frame = (frame + 1) % FRAMES.len();
+                std::thread::sleep(Duration::from_millis(80));
+            }
+            // Clear the spinner line
+            let _ = write!(std::io::stdout(), "\r\x1b[2K");
+        });
+        *self.spinner.lock().unwrap() = Some(Spinner { running, thread });
+    }
+
+    fn stop_spinner(&self) {
+        if let Some(spinner) = self.spinner.lock().unwrap().take() {
+            spinner.running.store(false, Ordering::Relaxed);
+            let _ = spinner.thread.join();
+        }
+    }
+
+    /// Preview of a tool result, collapsed to the configured number of lines
+    fn collapse(&self, result: &str) -> String {
+        let lines: Vec<&str> = result.lines().collect();
+        let max = self.options.max_tool_result_lines.max(1);
+        let mut preview = lines.iter().take(max).map(|line| format!("   {}", line)).collect::<Vec<_>>().join("\n");
+        if lines.len() > max {
+            preview.push_str(&format!("\n   {}", self.style(DIM, &format!("… {} more lines", lines.len() - max))));
+        }
+        preview
+    }
+}
+
+impl Default for TerminalRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for TerminalRenderer {
+    fn drop(&mut self) {
+        self.stop_spinner();
+    }
+}
+
+impl StreamingHandler for TerminalRenderer {
+    fn handle_chunk(&self, chunk: StreamingChunk) {
+        if chunk.metadata.contains_key("stream_restarted") {
+            self.state.lock().unwrap().pending_line.clear();
+            self.print(&format!("\n{}\n", self.style(DIM, "↻ restarting response")));
+            return;
+        }
+
+        let mut state = self.state.lock().unwrap();
+        state.pending_line.push_str(&chunk.content);
+        let mut output = String::new();
+        while let Some(newline) = state.pending_line.find('\n') {
+            let line: String = state.pending_line.drain(..=newline).collect();
+            output.push_str(&self.render_line(line.trim_end_matches('\n'), &mut state));
+            output.push('\n');
+        }
+        drop(state);
+
+        if !output.is_empty() {
+            self.print(&output);
+        }
+        if chunk.is_final {
+            self.flush_pending();
+        }
+    }
+
+    fn handle_tool_call_start(&self, tool_name: String, _call_id: String) {
+        self.flush_pending();
+        self.print(&format!("{}\n", self.style(CYAN, &format!("🔧 {}", tool_name))));
+    }
+
+    fn handle_tool_call_ready(&self, tool_name: String, _call_id: String, complete_args: String) {
+        self.start_spinner(format!("Running {} {}", tool_name, self.style(DIM, &complete_args)));
+    }
+
+    fn handle_tool_call_executed(&self, tool_name: String, _call_id: String, result: String, execution_time_ms: u64) {
+        self.stop_spinner();
+        self.print(&format!(
+            "{}\n",
+            self.style(GREEN, &format!("✓ {} ({} ms)", tool_name, execution_time_ms))
+        ));
+        if self.options.show_tool_results && !result.is_empty() {
+            self.print(&format!("{}\n", self.collapse(&result)));
+        }
+    }
+
+    fn handle_tool_calls(&self, _tool_calls: Vec<ToolCall>) {
+        self.stop_spinner();
+    }
+
+    fn handle_validation_warning(&self, message: String) {
+        self.flush_pending();
+        self.print(&format!("{}\n", self.style(YELLOW, &format!("⚠ {}", message))));
+    }
+
+    fn handle_final(&self, response: StreamingResponse) {
+        self.stop_spinner();
+        self.flush_pending();
+        if self.options.show_summary && response.success {
+            self.print(&format!(
+                "{}\n",
+                self.style(
+                    DIM,
+                    &format!(
+                        "— {} tokens in {} ms{}",
+                        response.total_tokens,
+                        response.execution_time_ms,
+                        if response.tools_used.is_empty() { String::new() } else { format!(", tools: {}", response.tools_used.join(", ")) }
+                    )
+                )
+            ));
+        }
+    }
+
+    fn handle_error(&self, error: String) {
+        self.stop_spinner();
+        self.flush_pending();
+        self.print(&format!("{}\n", self.style(RED, &format!("✗ {}", error))));
+    }
+}
@@ -0,0 +1,173 @@
+use crate::agent::agent::{Agent, AgentResponse};
+use crate::agent::tool_schema::ToolSchemas;
+use crate::task::run_history::RunFilter;
+use merco_llmproxy::Tool;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+/// When a tool is worth removing from an agent's offer set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PruningThresholds {
+    /// Runs needed before anything is recommended
+    pub min_runs: usize,
+    /// Tools called in a smaller share of runs are recommended for removal
+    pub min_usage_rate: f64,
+    /// Tools whose calls succeed less often than this are recommended for removal
+    pub min_success_rate: f64,
+}
+
+impl Default for PruningThresholds {
+    fn default() -> Self {
+        Self {
+            min_runs: 20,
+            min_usage_rate: 0.02,
+            min_success_rate: 0.5,
+        }
+    }
+}
+
+/// Why a tool is recommended for removal
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum PruneReason {
+    NeverCalled,
+    RarelyCalled { usage_rate: f64 },
+    MostlyFails { success_rate: f64 },
+}
+
+/// How one tool was used across the analysed runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolUsageReport {
+    pub name: String,
+    /// Whether the agent offers the tool now; calls to other tools come from older
+    /// tool sets or made-up names
+    pub offered: bool,
+    pub calls: u64,
+    pub failures: u64,
+    /// Runs with at least one call
+    pub runs_used: usize,
+    /// Share of the analysed runs that called the tool
+    pub usage_rate: f64,
+    pub success_rate: Option<f64>,
+    pub average_latency_ms: Option<f64>,
+    pub p95_latency_ms: Option<u64>,
+    /// Prompt tokens the definition adds to every request
+    pub schema_tokens: u32,
+    pub recommendation: Option<PruneReason>,
+}
+
+/// Offered vs. called tools across an agent's runs, with tools worth removing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolAnalyticsReport {
+    pub runs: usize,
+    /// Offered tools first, least used first
+    pub tools: Vec<ToolUsageReport>,
+    /// Prompt tokens per request saved by removing every recommended tool
+    pub prunable_schema_tokens: u32,
+}
+
+impl ToolAnalyticsReport {
+    /// Analyse `responses` of an agent offering `offered`
+    pub fn new<'a>(
+        offered: &[Tool],
+        responses: impl IntoIterator<Item = &'a AgentResponse>,
+        thresholds: &PruningThresholds,
+    ) -> Self {
+        #[derive(Default)]
+        struct Usage {
+            calls: u64,
+            failures: u64,
+            runs_used: usize,
+            latencies: Vec<u64>,
+        }
+
+        let mut usage: BTreeMap<String, Usage> = offered.iter().map(|tool| (tool.name.clone(), Usage::default())).collect();
+        let mut runs = 0;
+        for response in responses {
+            runs += 1;
+            let mut called = HashSet::new();
+            for call in &response.tool_calls {
+                let entry = usage.entry(call.tool_name.clone()).or_default();
+                entry.calls += 1;
+                if call.error.is_some() {
+                    entry.failures += 1;
+                }
+                entry.latencies.push(call.execution_time_ms);
+                if called.insert(call.tool_name.as_str()) {
+                    entry.runs_used += 1;
+                }
+            }
+        }
+
+        let offered_names: HashSet<&str> = offered.iter().map(|tool| tool.name.as_str()).collect();
+        let mut tools: Vec<ToolUsageReport> = usage
+            .into_iter()
+            .map(|(name, mut usage)| {
+                let usage_rate = if runs == 0 { 0.0 } else { usage.runs_used as f64 / runs as f64 };
+                let success_rate = (usage.calls > 0).then(|| 1.0 - usage.failures as f64 / usage.calls as f64);
+                usage.latencies.sort_unstable();
+                let average_latency_ms = (!usage.latencies.is_empty())
+                    .then(|| usage.latencies.iter().sum::<u64>() as f64 / usage.latencies.len() as f64);
+                let p95_latency_ms = (!usage.latencies.is_empty())
+                    .then(|| usage.latencies[((usage.latencies.len() as f64 * 0.95).ceil() as usize).saturating_sub(1)]);
+                let is_offered = offered_names.contains(name.as_str());
+                let schema_tokens = offered
+                    .iter()
+                    .find(|tool| tool.name == name)
+                    .map(|tool| ToolSchemas::new(std::slice::from_ref(tool)).estimated_tokens())
+                    .unwrap_or(0);
+                let recommendation = if !is_offered || runs < thresholds.min_runs {
+                    None
+                } else if usage.calls == 0 {
+                    Some(PruneReason::NeverCalled)
+                } else if usage_rate < thresholds.min_usage_rate {
+                    Some(PruneReason::RarelyCalled { usage_rate })
+                } else {
+                    success_rate
+                        .filter(|rate| *rate < thresholds.min_success_rate)
+                        .map(|success_rate| PruneReason::MostlyFails { success_rate })
+                };
+                ToolUsageReport {
+                    name,
+                    offered: is_offered,
+                    calls: usage.calls,
+                    failures: usage.failures,
+                    runs_used: usage.runs_used,
+                    usage_rate,
+                    success_rate,
+                    average_latency_ms,
+                    p95_latency_ms,
+                    schema_tokens,
+                    recommendation,
+                }
+            })
+            .collect();
+        tools.sort_by(|a, b| b.offered.cmp(&a.offered).then(a.usage_rate.total_cmp(&b.usage_rate)));
+
+        let prunable_schema_tokens = tools
+            .iter()
+            .filter(|tool| tool.recommendation.is_some())
+            .map(|tool| tool.schema_tokens)
+            .sum();
+        Self { runs, tools, prunable_schema_tokens }
+    }
+
+    /// Tools recommended for removal, with the reason
+    pub fn recommendations(&self) -> impl Iterator<Item = (&str, &PruneReason)> {
+        self.tools
+            .iter()
+            .filter_map(|tool| tool.recommendation.as_ref().map(|reason| (tool.name.as_str(), reason)))
+    }
+}
+
+impl Agent {
+    /// Tool usage across the runs in the agent's run store (empty without one)
+    pub fn tool_analytics(&self, thresholds: &PruningThresholds) -> ToolAnalyticsReport {
+        let records = self
+            .run_store
+            .as_ref()
+            .map(|store| store.list(&RunFilter::new().with_agent_id(&self.id)))
+            .unwrap_or_default();
+        ToolAnalyticsReport::new(&self.tools, records.iter().map(|record| &record.response), thresholds)
+    }
+}
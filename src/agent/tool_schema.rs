@@ -0,0 +1,73 @@
+use merco_llmproxy::Tool;
+use serde_json::value::RawValue;
+use serde_json::{json, Value};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+use std::io;
+
+/// Tool definitions serialized once in the OpenAI `tools` format. Agents rebuild
+/// theirs when tools are added or removed instead of on every provider round.
+///
+/// Only providers of this crate send the cached JSON; `merco_llmproxy` providers
+/// take an owned tool list with each request and serialize it themselves.
+#[derive(Debug, Clone)]
+pub struct ToolSchemas {
+    /// Hash of the serialized definitions they were built from
+    fingerprint: u64,
+    json: Box<RawValue>,
+    estimated_tokens: u32,
+}
+
+impl ToolSchemas {
+    pub fn new(tools: &[Tool]) -> Self {
+        let schemas: Vec<Value> = tools.iter().map(|tool| json!({ "type": "function", "function": tool })).collect();
+        let json = serde_json::value::to_raw_value(&schemas).expect("tool schemas serialize to JSON");
+        // Same ~3.5 characters per token as prompt estimates
+        let estimated_tokens = if tools.is_empty() {
+            0
+        } else {
+            (json.get().len() as f64 / 3.5) as u32
+        };
+        Self {
+            fingerprint: fingerprint(tools),
+            json,
+            estimated_tokens,
+        }
+    }
+
+    /// The `tools` array of a chat completion request, already serialized
+    pub fn json(&self) -> &RawValue {
+        &self.json
+    }
+
+    /// Prompt tokens the definitions take up in every request
+    pub fn estimated_tokens(&self) -> u32 {
+        self.estimated_tokens
+    }
+
+    /// Whether these schemas were built from the same definitions, in order, so a
+    /// tool replaced under its old name with new parameters doesn't match
+    pub fn matches(&self, tools: &[Tool]) -> bool {
+        self.fingerprint == fingerprint(tools)
+    }
+}
+
+/// Hash of the tools' serialized definitions, written straight into the hasher
+fn fingerprint(tools: &[Tool]) -> u64 {
+    struct HashWriter(DefaultHasher);
+
+    impl io::Write for HashWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.write(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut writer = HashWriter(DefaultHasher::new());
+    serde_json::to_writer(&mut writer, tools).expect("tool schemas serialize to JSON");
+    writer.0.finish()
+}
@@ -0,0 +1,238 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// What was done
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    ToolExecution,
+    /// Recorded by memory integrations when they read or write an entry
+    MemoryAccess,
+    /// A request leaving the process, such as a provider call
+    OutboundRequest,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", content = "error", rename_all = "snake_case")]
+pub enum AuditOutcome {
+    Success,
+    Failure(String),
+}
+
+/// One entry of the audit trail. `hash` covers every other field including
+/// `prev_hash`, so editing or removing an entry breaks the chain after it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub sequence: u64,
+    pub timestamp: DateTime<Utc>,
+    /// Agent and, when known, the end user it acted for (`agent_id/user_id`)
+    pub actor: String,
+    pub task_id: Option<String>,
+    pub action: AuditAction,
+    /// Tool name, provider model or memory key
+    pub target: String,
+    /// SHA-256 of the parameters; the parameters themselves are not stored
+    pub params_hash: String,
+    pub outcome: AuditOutcome,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+impl AuditEntry {
+    fn compute_hash(&self) -> String {
+        let body = serde_json::json!({
+            "sequence": self.sequence,
+            "timestamp": self.timestamp,
+            "actor": self.actor,
+            "task_id": self.task_id,
+            "action": self.action,
+            "target": self.target,
+            "params_hash": self.params_hash,
+            "outcome": self.outcome,
+            "prev_hash": self.prev_hash,
+        });
+        sha256_hex(body.to_string().as_bytes())
+    }
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Hash the chain starts from
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Append-only storage for audit entries
+pub trait AuditSink: Send + Sync {
+    fn append(&self, entry: &AuditEntry) -> Result<(), String>;
+
+    /// Every stored entry in order, for verification
+    fn entries(&self) -> Result<Vec<AuditEntry>, String>;
+}
+
+/// Keeps the trail in memory, for tests and short-lived processes
+#[derive(Debug, Default)]
+pub struct MemoryAuditSink {
+    entries: Mutex<Vec<AuditEntry>>,
+}
+
+impl MemoryAuditSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl AuditSink for MemoryAuditSink {
+    fn append(&self, entry: &AuditEntry) -> Result<(), String> {
+        self.entries.lock().unwrap().push(entry.clone());
+        Ok(())
+    }
+
+    fn entries(&self) -> Result<Vec<AuditEntry>, String> {
+        Ok(self.entries.lock().unwrap().clone())
+    }
+}
+
+/// Appends one JSON entry per line to a file, opened in append mode for every write
+#[derive(Debug, Clone)]
+pub struct FileAuditSink {
+    path: PathBuf,
+}
+
+impl FileAuditSink {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl AuditSink for FileAuditSink {
+    fn append(&self, entry: &AuditEntry) -> Result<(), String> {
+        let line = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Failed to open audit log {}: {}", self.path.display(), e))?;
+        writeln!(file, "{}", line).map_err(|e| e.to_string())
+    }
+
+    fn entries(&self) -> Result<Vec<AuditEntry>, String> {
+        let file = match std::fs::File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.to_string()),
+        };
+        std::io::BufReader::new(file)
+            .lines()
+            .filter(|line| !matches!(line, Ok(l) if l.trim().is_empty()))
+            .map(|line| {
+                let line = line.map_err(|e| e.to_string())?;
+                serde_json::from_str(&line).map_err(|e| e.to_string())
+            })
+            .collect()
+    }
+}
+
+/// Hash-chained audit trail over a sink. Agents write to it when their
+/// `SecurityContext.audit_logging` is enabled.
+pub struct AuditLog {
+    sink: Arc<dyn AuditSink>,
+    /// Sequence and hash of the last entry
+    head: Mutex<(u64, String)>,
+}
+
+impl AuditLog {
+    /// Continues the chain of the entries already in `sink`
+    pub fn new(sink: Arc<dyn AuditSink>) -> Result<Self, String> {
+        let head = match sink.entries()?.last() {
+            Some(last) => (last.sequence + 1, last.hash.clone()),
+            None => (0, GENESIS_HASH.to_string()),
+        };
+        Ok(Self {
+            sink,
+            head: Mutex::new(head),
+        })
+    }
+
+    pub fn in_memory() -> Self {
+        Self {
+            sink: Arc::new(MemoryAuditSink::new()),
+            head: Mutex::new((0, GENESIS_HASH.to_string())),
+        }
+    }
+
+    pub fn to_file(path: impl AsRef<Path>) -> Result<Self, String> {
+        Self::new(Arc::new(FileAuditSink::new(path)))
+    }
+
+    pub fn record(
+        &self,
+        actor: &str,
+        task_id: Option<&str>,
+        action: AuditAction,
+        target: &str,
+        params: &str,
+        outcome: AuditOutcome,
+    ) -> Result<AuditEntry, String> {
+        // Held across the write so entries reach the sink in chain order
+        let mut head = self.head.lock().unwrap();
+        let mut entry = AuditEntry {
+            sequence: head.0,
+            timestamp: Utc::now(),
+            actor: actor.to_string(),
+            task_id: task_id.map(str::to_string),
+            action,
+            target: target.to_string(),
+            params_hash: sha256_hex(params.as_bytes()),
+            outcome,
+            prev_hash: head.1.clone(),
+            hash: String::new(),
+        };
+        entry.hash = entry.compute_hash();
+        self.sink.append(&entry)?;
+        *head = (entry.sequence + 1, entry.hash.clone());
+        Ok(entry)
+    }
+
+    pub fn entries(&self) -> Result<Vec<AuditEntry>, String> {
+        self.sink.entries()
+    }
+
+    /// Check the stored chain; the error names the first broken entry
+    pub fn verify(&self) -> Result<(), String> {
+        verify_chain(&self.sink.entries()?)
+    }
+}
+
+/// Check that every entry hashes correctly and links to the one before it
+pub fn verify_chain(entries: &[AuditEntry]) -> Result<(), String> {
+    let mut prev_hash = GENESIS_HASH.to_string();
+    let mut expected_sequence = entries.first().map(|e| e.sequence).unwrap_or(0);
+    for entry in entries {
+        if entry.sequence != expected_sequence {
+            return Err(format!("Audit entry {} is missing", expected_sequence));
+        }
+        // A trail may start after earlier entries were archived
+        if entry.sequence != entries[0].sequence && entry.prev_hash != prev_hash {
+            return Err(format!("Audit entry {} does not link to the previous entry", entry.sequence));
+        }
+        if entry.compute_hash() != entry.hash {
+            return Err(format!("Audit entry {} was modified", entry.sequence));
+        }
+        prev_hash = entry.hash.clone();
+        expected_sequence += 1;
+    }
+    Ok(())
+}
+
+impl std::fmt::Debug for AuditLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuditLog").field("next_sequence", &self.head.lock().unwrap().0).finish()
+    }
+}
@@ -0,0 +1,321 @@
+use crate::agent::agent::{Agent, AgentModelConfig};
+use crate::agent::provider::{LlmConfig, Provider};
+use crate::agent::role::{AgentRole, OutputFormat};
+use crate::agent::state::Permission;
+use crate::credentials::Credential;
+use crate::tools::registry::{local_tool, tool_definition};
+use anyhow::{anyhow, bail, Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+
+/// Environment variables starting with this (then `__`) override agent config files
+pub const AGENT_ENV_PREFIX: &str = "MERCO_AGENT";
+/// Environment variables starting with this (then `__`) override crew config files
+pub const CREW_ENV_PREFIX: &str = "MERCO_CREW";
+
+/// Model section of a config file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelConfigFile {
+    pub provider: Provider,
+    pub name: String,
+    #[serde(default = "default_temperature")]
+    pub temperature: f32,
+    #[serde(default = "default_max_tokens")]
+    pub max_tokens: u32,
+    /// A key, `env:NAME` or `file:PATH`
+    #[serde(default, skip_serializing)]
+    pub api_key: Option<Credential>,
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// For models the built-in table doesn't know
+    #[serde(default)]
+    pub context_window: Option<u32>,
+}
+
+impl ModelConfigFile {
+    pub fn to_model_config(&self) -> AgentModelConfig {
+        let mut llm_config = LlmConfig::new(self.provider.clone(), None);
+        llm_config.api_key = self.api_key.clone();
+        llm_config.base_url = self.base_url.clone();
+        let model = AgentModelConfig::new(llm_config, self.name.clone(), self.temperature, self.max_tokens);
+        match self.context_window {
+            Some(tokens) => model.with_context_window(tokens),
+            None => model,
+        }
+    }
+}
+
+/// Role section of a config file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoleConfigFile {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// What the agent works towards; listed in the role description and kept in
+    /// the role's `goals` metadata
+    #[serde(default)]
+    pub goals: Vec<String>,
+}
+
+impl RoleConfigFile {
+    pub fn to_role(&self) -> AgentRole {
+        if self.goals.is_empty() {
+            return AgentRole::new(self.name.clone(), self.description.clone());
+        }
+        let description = format!("{} Goals: {}", self.description, self.goals.join("; "));
+        AgentRole::new(self.name.clone(), description.trim_start().to_string())
+            .with_metadata("goals".to_string(), serde_json::json!(self.goals))
+    }
+}
+
+/// Limits section of a config file; unset values keep the agent's defaults
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LimitsConfigFile {
+    #[serde(default)]
+    pub max_concurrent_tasks: Option<usize>,
+    /// Timeout for each tool call
+    #[serde(default)]
+    pub max_response_time_ms: Option<u64>,
+    /// Hosts tools may contact (see `tools::check_url`); empty allows all
+    #[serde(default)]
+    pub allowed_domains: Option<Vec<String>>,
+    /// Permissions granted to the agent's tools
+    #[serde(default)]
+    pub permissions: Option<Vec<Permission>>,
+}
+
+/// An agent described in TOML, YAML or JSON:
+///
+/// ```toml
+/// name = "researcher"
+/// tools = ["read_file", "calculator"]
+/// output_format = "Markdown"
+///
+/// [model]
+/// provider = "OpenAI"
+/// name = "gpt-4o-mini"
+/// api_key = "env:OPENAI_API_KEY"
+///
+/// [limits]
+/// max_concurrent_tasks = 4
+/// allowed_domains = ["example.com"]
+/// ```
+///
+/// `MERCO_AGENT__MODEL__TEMPERATURE=0.2` and the like override file values
+/// (see `load`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentConfigFile {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub model: ModelConfigFile,
+    /// Defaults to a role named after the agent
+    #[serde(default)]
+    pub role: Option<RoleConfigFile>,
+    /// Names of registered local tools (`tools::register_tool`)
+    #[serde(default)]
+    pub tools: Vec<String>,
+    #[serde(default)]
+    pub limits: LimitsConfigFile,
+    #[serde(default = "default_output_format")]
+    pub output_format: OutputFormat,
+}
+
+impl AgentConfigFile {
+    /// Read a config file, applying `MERCO_AGENT__*` overrides
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Self::load_with_prefix(path, AGENT_ENV_PREFIX)
+    }
+
+    /// Read a config file, applying overrides from variables starting with `prefix`
+    pub fn load_with_prefix(path: impl AsRef<Path>, prefix: &str) -> Result<Self> {
+        load(path.as_ref(), prefix)
+    }
+
+    /// Build the agent. Fails when a listed tool isn't registered.
+    pub fn build(&self) -> Result<Agent> {
+        let mut builder = Agent::builder(&self.name, self.model.to_model_config())
+            .description(&self.description)
+            .output_format(self.output_format.clone());
+        if let Some(role) = &self.role {
+            builder = builder.role(role.to_role());
+        }
+        if let Some(max_concurrent_tasks) = self.limits.max_concurrent_tasks {
+            builder = builder.max_concurrent_tasks(max_concurrent_tasks);
+        }
+        for name in &self.tools {
+            let tool = local_tool(name).ok_or_else(|| anyhow!("Agent '{}' uses unknown tool '{}'", self.name, name))?;
+            builder = builder.tool(tool_definition(tool.as_ref()).map_err(|e| anyhow!(e))?);
+        }
+
+        let mut agent = builder.build();
+        let environment = &mut agent.context.environment;
+        if let Some(max_response_time_ms) = self.limits.max_response_time_ms {
+            environment.resource_limits.max_response_time_ms = max_response_time_ms;
+        }
+        if let Some(allowed_domains) = &self.limits.allowed_domains {
+            environment.network_context.allowed_domains = allowed_domains.clone();
+        }
+        if let Some(permissions) = &self.limits.permissions {
+            environment.security_context.permissions = permissions.clone();
+        }
+        Ok(agent)
+    }
+}
+
+/// Several agents in one file (`[[agents]]` in TOML, `agents:` in YAML).
+/// Overrides address agents by position: `MERCO_CREW__AGENTS__0__MODEL__NAME`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrewConfigFile {
+    #[serde(default)]
+    pub name: String,
+    pub agents: Vec<AgentConfigFile>,
+}
+
+impl CrewConfigFile {
+    /// Read a config file, applying `MERCO_CREW__*` overrides
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Self::load_with_prefix(path, CREW_ENV_PREFIX)
+    }
+
+    pub fn load_with_prefix(path: impl AsRef<Path>, prefix: &str) -> Result<Self> {
+        load(path.as_ref(), prefix)
+    }
+
+    /// Build every agent, in file order
+    pub fn build(&self) -> Result<Vec<Agent>> {
+        self.agents.iter().map(AgentConfigFile::build).collect()
+    }
+}
+
+fn default_temperature() -> f32 {
+    0.7
+}
+
+fn default_max_tokens() -> u32 {
+    1024
+}
+
+fn default_output_format() -> OutputFormat {
+    OutputFormat::Text
+}
+
+/// Parse `path` by extension (`.toml`, `.yaml`/`.yml`, `.json`), then apply overrides.
+/// TOML and YAML need the `config-files` feature.
+fn load<T: DeserializeOwned>(path: &Path, prefix: &str) -> Result<T> {
+    let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read config {}", path.display()))?;
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default().to_ascii_lowercase();
+    let value: Value = match extension.as_str() {
+        #[cfg(feature = "config-files")]
+        "toml" => toml::from_str(&text).with_context(|| format!("Invalid TOML in {}", path.display()))?,
+        #[cfg(feature = "config-files")]
+        "yaml" | "yml" => serde_yaml::from_str(&text).with_context(|| format!("Invalid YAML in {}", path.display()))?,
+        #[cfg(not(feature = "config-files"))]
+        "toml" | "yaml" | "yml" => bail!("Reading {} needs the `config-files` feature", path.display()),
+        "json" => serde_json::from_str(&text).with_context(|| format!("Invalid JSON in {}", path.display()))?,
+        _ => bail!("Unsupported config format for {} (expected .toml, .yaml or .json)", path.display()),
+    };
+    let overrides = env_overrides(prefix, std::env::vars())?;
+
+    // Overrides that parse as JSON are applied typed first. When that doesn't fit
+    // the config (`MODEL__NAME=4` for a string field), retry with the fewest of
+    // them taken as plain strings instead.
+    let typed: Vec<usize> = (0..overrides.len())
+        .filter(|&i| !parse_override(&overrides[i].raw).is_string())
+        .take(MAX_STRING_FALLBACKS)
+        .collect();
+    let mut attempts: Vec<u32> = (0..1u32 << typed.len()).collect();
+    attempts.sort_by_key(|mask| mask.count_ones());
+
+    let mut first_error = None;
+    for mask in attempts {
+        let mut candidate = value.clone();
+        for (i, env_override) in overrides.iter().enumerate() {
+            let as_string = typed.iter().position(|&t| t == i).is_some_and(|bit| mask & (1 << bit) != 0);
+            env_override.apply(&mut candidate, as_string)?;
+        }
+        match serde_json::from_value(candidate) {
+            Ok(config) => return Ok(config),
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    let error = first_error.expect("at least one attempt is made");
+    Err(error).with_context(|| format!("Invalid config in {}", path.display()))
+}
+
+/// Typed overrides tried as strings when the config doesn't deserialize; the rest stay typed
+const MAX_STRING_FALLBACKS: usize = 8;
+
+/// One `PREFIX__A__B=value` variable
+struct EnvOverride {
+    key: String,
+    path: Vec<String>,
+    raw: String,
+}
+
+impl EnvOverride {
+    fn apply(&self, value: &mut Value, as_string: bool) -> Result<()> {
+        let new_value = if as_string { Value::String(self.raw.clone()) } else { parse_override(&self.raw) };
+        set_path(value, &self.path, new_value).with_context(|| format!("Cannot apply config override {}", self.key))
+    }
+}
+
+/// The variables starting with `prefix`, sorted so that one overriding a parent of
+/// another applies deterministically
+fn env_overrides(prefix: &str, vars: impl IntoIterator<Item = (String, String)>) -> Result<Vec<EnvOverride>> {
+    let prefix = format!("{}__", prefix);
+    let mut vars: Vec<(String, String)> = vars.into_iter().filter(|(key, _)| key.starts_with(&prefix)).collect();
+    vars.sort();
+    vars.into_iter()
+        .map(|(key, raw)| {
+            let path: Vec<String> = key[prefix.len()..].split("__").map(str::to_ascii_lowercase).collect();
+            if path.iter().any(String::is_empty) {
+                bail!("Malformed config override {}", key);
+            }
+            Ok(EnvOverride { key, path, raw })
+        })
+        .collect()
+}
+
+/// JSON when the value parses as such (numbers, booleans, lists), otherwise a string
+fn parse_override(raw: &str) -> Value {
+    serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()))
+}
+
+/// Apply `PREFIX__A__B=value` variables to `value.a.b`. Segments are lowercased and
+/// numeric ones index arrays; values are parsed as JSON when possible (numbers,
+/// booleans, lists) and taken as strings otherwise. `load` additionally falls back
+/// to strings for values the config's field types don't accept.
+pub fn apply_env_overrides(
+    value: &mut Value,
+    prefix: &str,
+    vars: impl IntoIterator<Item = (String, String)>,
+) -> Result<()> {
+    for env_override in env_overrides(prefix, vars)? {
+        env_override.apply(value, false)?;
+    }
+    Ok(())
+}
+
+fn set_path(value: &mut Value, path: &[String], new_value: Value) -> Result<()> {
+    let Some((segment, rest)) = path.split_first() else {
+        *value = new_value;
+        return Ok(());
+    };
+    if value.is_null() {
+        *value = Value::Object(Default::default());
+    }
+    let child = match value {
+        Value::Object(map) => map.entry(segment.clone()).or_insert(Value::Null),
+        Value::Array(items) => {
+            let index: usize = segment.parse().map_err(|_| anyhow!("'{}' is not an array index", segment))?;
+            items.get_mut(index).ok_or_else(|| anyhow!("index {} is out of range", index))?
+        }
+        _ => bail!("'{}' is not a table", segment),
+    };
+    set_path(child, rest, new_value)
+}
@@ -0,0 +1,196 @@
+use crate::cost::tracker::{CostFilter, CostTracker};
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+/// Who a budget applies to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BudgetScope {
+    /// Every run recorded in the tracker
+    Deployment,
+    Agent(String),
+    User(String),
+}
+
+/// Window the spend is summed over
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BudgetPeriod {
+    Total,
+    /// Calendar day in UTC
+    Daily,
+    /// Calendar month in UTC
+    Monthly,
+}
+
+impl BudgetPeriod {
+    /// Start of the period containing `now`
+    fn start(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            BudgetPeriod::Total => None,
+            BudgetPeriod::Daily => Utc.with_ymd_and_hms(now.year(), now.month(), now.day(), 0, 0, 0).single(),
+            BudgetPeriod::Monthly => Utc.with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0).single(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Budget {
+    pub scope: BudgetScope,
+    pub limit_usd: f64,
+    pub period: BudgetPeriod,
+    /// Fraction of the limit at which the alert fires once per period
+    pub alert_threshold: f64,
+    /// Refuse provider requests that would exceed the limit
+    pub hard_cap: bool,
+}
+
+impl Budget {
+    /// Alert at 80%, refuse calls at 100%
+    pub fn new(scope: BudgetScope, limit_usd: f64, period: BudgetPeriod) -> Self {
+        Self {
+            scope,
+            limit_usd,
+            period,
+            alert_threshold: 0.8,
+            hard_cap: true,
+        }
+    }
+
+    pub fn with_alert_threshold(mut self, fraction: f64) -> Self {
+        self.alert_threshold = fraction.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Only alert, never refuse calls
+    pub fn soft(mut self) -> Self {
+        self.hard_cap = false;
+        self
+    }
+
+    fn applies_to(&self, agent_id: &str, user_id: Option<&str>) -> bool {
+        match &self.scope {
+            BudgetScope::Deployment => true,
+            BudgetScope::Agent(id) => id == agent_id,
+            BudgetScope::User(id) => user_id == Some(id.as_str()),
+        }
+    }
+
+    fn filter(&self, now: DateTime<Utc>) -> CostFilter {
+        let mut filter = match &self.scope {
+            BudgetScope::Deployment => CostFilter::new(),
+            BudgetScope::Agent(id) => CostFilter::new().agent(id),
+            BudgetScope::User(id) => CostFilter::new().user(id),
+        };
+        if let Some(start) = self.period.start(now) {
+            filter = filter.since(start);
+        }
+        filter
+    }
+}
+
+/// A budget crossing its alert threshold
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetAlert {
+    pub budget: Budget,
+    pub spent_usd: f64,
+    pub fraction_used: f64,
+}
+
+pub type BudgetAlertCallback = Arc<dyn Fn(&BudgetAlert) + Send + Sync>;
+
+/// Whether an error message is a hard-cap refusal (`AgentError::BudgetExceeded`),
+/// which retrying can't fix
+pub(crate) fn is_budget_refusal(message: &str) -> bool {
+    message.starts_with("Budget exceeded:")
+}
+
+struct BudgetEntry {
+    budget: Budget,
+    /// Start of the period the alert last fired in, so it fires once per period
+    alerted_for: Option<Option<DateTime<Utc>>>,
+}
+
+/// Soft alerts and hard spending caps over the costs in a `CostTracker`,
+/// checked by agents before every provider request
+pub struct BudgetManager {
+    tracker: Arc<CostTracker>,
+    budgets: Mutex<Vec<BudgetEntry>>,
+    on_alert: Option<BudgetAlertCallback>,
+}
+
+impl BudgetManager {
+    pub fn new(tracker: Arc<CostTracker>) -> Self {
+        Self {
+            tracker,
+            budgets: Mutex::new(Vec::new()),
+            on_alert: None,
+        }
+    }
+
+    pub fn with_budget(self, budget: Budget) -> Self {
+        self.add_budget(budget);
+        self
+    }
+
+    pub fn add_budget(&self, budget: Budget) {
+        self.budgets.lock().unwrap().push(BudgetEntry { budget, alerted_for: None });
+    }
+
+    pub fn on_alert<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&BudgetAlert) + Send + Sync + 'static,
+    {
+        self.on_alert = Some(Arc::new(callback));
+        self
+    }
+
+    pub fn tracker(&self) -> Arc<CostTracker> {
+        self.tracker.clone()
+    }
+
+    /// Check a request expected to cost `estimated_usd`. Fires alerts for budgets past
+    /// their threshold (returned so the caller can publish them) and refuses the
+    /// request when it would take a hard-capped budget over its limit.
+    pub fn check(&self, agent_id: &str, user_id: Option<&str>, estimated_usd: f64) -> Result<Vec<BudgetAlert>, String> {
+        let now = Utc::now();
+        let mut alerts = Vec::new();
+        let mut budgets = self.budgets.lock().unwrap();
+        for entry in budgets.iter_mut().filter(|e| e.budget.applies_to(agent_id, user_id)) {
+            let budget = &entry.budget;
+            let spent = self.tracker.summary(&budget.filter(now)).cost_usd;
+
+            if budget.hard_cap && spent + estimated_usd > budget.limit_usd {
+                return Err(format!(
+                    "{:?} has spent ${:.4} of its ${:.2} {:?} budget",
+                    budget.scope, spent, budget.limit_usd, budget.period
+                ));
+            }
+
+            let period = budget.period.start(now);
+            let fraction_used = if budget.limit_usd > 0.0 { spent / budget.limit_usd } else { 1.0 };
+            if fraction_used >= budget.alert_threshold && entry.alerted_for != Some(period) {
+                entry.alerted_for = Some(period);
+                alerts.push(BudgetAlert {
+                    budget: budget.clone(),
+                    spent_usd: spent,
+                    fraction_used,
+                });
+            }
+        }
+        drop(budgets);
+
+        if let Some(callback) = &self.on_alert {
+            alerts.iter().for_each(|alert| callback(alert));
+        }
+        Ok(alerts)
+    }
+}
+
+impl std::fmt::Debug for BudgetManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BudgetManager")
+            .field("budgets", &self.budgets.lock().unwrap().iter().map(|e| &e.budget).collect::<Vec<_>>())
+            .field("on_alert", &self.on_alert.is_some())
+            .finish()
+    }
+}
@@ -0,0 +1,7 @@
+pub mod budget;
+pub mod pricing;
+pub mod tracker;
+
+pub use budget::{Budget, BudgetAlert, BudgetManager, BudgetPeriod, BudgetScope};
+pub use pricing::{ModelPricing, PricingTable};
+pub use tracker::{CostFilter, CostRecord, CostSummary, CostTracker};
@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Price of a model in USD per million tokens
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPricing {
+    pub input_per_million: f64,
+    pub output_per_million: f64,
+}
+
+impl ModelPricing {
+    pub fn new(input_per_million: f64, output_per_million: f64) -> Self {
+        Self {
+            input_per_million,
+            output_per_million,
+        }
+    }
+
+    pub fn cost(&self, input_tokens: u32, output_tokens: u32) -> f64 {
+        (input_tokens as f64 * self.input_per_million + output_tokens as f64 * self.output_per_million) / 1_000_000.0
+    }
+}
+
+/// List prices at the time of writing; providers change them, so override with `set`
+const DEFAULT_PRICES: &[(&str, f64, f64)] = &[
+    ("gpt-4o-mini", 0.15, 0.60),
+    ("gpt-4o", 2.50, 10.00),
+    ("gpt-4.1-nano", 0.10, 0.40),
+    ("gpt-4.1-mini", 0.40, 1.60),
+    ("gpt-4.1", 2.00, 8.00),
+    ("gpt-4-turbo", 10.00, 30.00),
+    ("gpt-3.5-turbo", 0.50, 1.50),
+    ("o3-mini", 1.10, 4.40),
+    ("o1-mini", 1.10, 4.40),
+    ("o1", 15.00, 60.00),
+    ("claude-3-5-haiku", 0.80, 4.00),
+    ("claude-3-5-sonnet", 3.00, 15.00),
+    ("claude-3-7-sonnet", 3.00, 15.00),
+    ("claude-3-haiku", 0.25, 1.25),
+    ("claude-3-opus", 15.00, 75.00),
+    ("gemini-1.5-flash", 0.075, 0.30),
+    ("gemini-1.5-pro", 1.25, 5.00),
+    ("gemini-2.0-flash", 0.10, 0.40),
+];
+
+/// Per-model prices. Models are matched exactly first, then by the longest known
+/// prefix, so dated variants such as `gpt-4o-2024-08-06` use their family's price.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PricingTable {
+    prices: HashMap<String, ModelPricing>,
+    /// Used for models without a price; None means they cost nothing (e.g. local models)
+    pub fallback: Option<ModelPricing>,
+}
+
+impl PricingTable {
+    /// No prices at all
+    pub fn empty() -> Self {
+        Self {
+            prices: HashMap::new(),
+            fallback: None,
+        }
+    }
+
+    pub fn set(&mut self, model: &str, pricing: ModelPricing) {
+        self.prices.insert(model.to_lowercase(), pricing);
+    }
+
+    pub fn with_price(mut self, model: &str, pricing: ModelPricing) -> Self {
+        self.set(model, pricing);
+        self
+    }
+
+    pub fn with_fallback(mut self, pricing: ModelPricing) -> Self {
+        self.fallback = Some(pricing);
+        self
+    }
+
+    pub fn get(&self, model: &str) -> Option<ModelPricing> {
+        let model = model.to_lowercase();
+        // Provider-qualified names such as "openai/gpt-4o"
+        let model = model.rsplit('/').next().unwrap_or(&model);
+        if let Some(pricing) = self.prices.get(model) {
+            return Some(*pricing);
+        }
+        self.prices
+            .iter()
+            .filter(|(known, _)| model.starts_with(known.as_str()))
+            .max_by_key(|(known, _)| known.len())
+            .map(|(_, pricing)| *pricing)
+            .or(self.fallback)
+    }
+
+    pub fn cost(&self, model: &str, input_tokens: u32, output_tokens: u32) -> f64 {
+        self.get(model).map(|p| p.cost(input_tokens, output_tokens)).unwrap_or(0.0)
+    }
+}
+
+impl Default for PricingTable {
+    fn default() -> Self {
+        let mut table = Self::empty();
+        for (model, input, output) in DEFAULT_PRICES {
+            table.set(model, ModelPricing::new(*input, *output));
+        }
+        table
+    }
+}
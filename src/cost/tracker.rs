@@ -0,0 +1,237 @@
+use crate::agent::agent::AgentResponse;
+use crate::cost::pricing::{ModelPricing, PricingTable};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Mutex, RwLock};
+
+/// Cost of one agent run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostRecord {
+    pub timestamp: DateTime<Utc>,
+    pub agent_id: String,
+    pub user_id: Option<String>,
+    pub task_id: Option<String>,
+    pub model: String,
+    pub tags: Vec<String>,
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    pub cost_usd: f64,
+}
+
+/// Selects records; unset fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct CostFilter {
+    pub agent_id: Option<String>,
+    pub user_id: Option<String>,
+    pub tag: Option<String>,
+    pub model: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl CostFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn agent(mut self, agent_id: &str) -> Self {
+        self.agent_id = Some(agent_id.to_string());
+        self
+    }
+
+    pub fn user(mut self, user_id: &str) -> Self {
+        self.user_id = Some(user_id.to_string());
+        self
+    }
+
+    pub fn tag(mut self, tag: &str) -> Self {
+        self.tag = Some(tag.to_string());
+        self
+    }
+
+    pub fn model(mut self, model: &str) -> Self {
+        self.model = Some(model.to_string());
+        self
+    }
+
+    /// Records in `[since, until)`
+    pub fn window(mut self, since: DateTime<Utc>, until: DateTime<Utc>) -> Self {
+        self.since = Some(since);
+        self.until = Some(until);
+        self
+    }
+
+    pub fn since(mut self, since: DateTime<Utc>) -> Self {
+        self.since = Some(since);
+        self
+    }
+
+    pub fn matches(&self, record: &CostRecord) -> bool {
+        self.agent_id.as_ref().is_none_or(|id| &record.agent_id == id)
+            && self.user_id.as_ref().is_none_or(|id| record.user_id.as_ref() == Some(id))
+            && self.tag.as_ref().is_none_or(|tag| record.tags.contains(tag))
+            && self.model.as_ref().is_none_or(|model| &record.model == model)
+            && self.since.is_none_or(|since| record.timestamp >= since)
+            && self.until.is_none_or(|until| record.timestamp < until)
+    }
+}
+
+/// Totals over a set of records
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CostSummary {
+    pub runs: usize,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+}
+
+impl CostSummary {
+    fn add(&mut self, record: &CostRecord) {
+        self.runs += 1;
+        self.input_tokens += record.input_tokens as u64;
+        self.output_tokens += record.output_tokens as u64;
+        self.cost_usd += record.cost_usd;
+    }
+}
+
+/// Prices every run from its token counts and aggregates the costs for reporting.
+/// Attach to agents with `Agent::with_cost_tracker`; share one tracker across agents
+/// to report on a whole deployment.
+#[derive(Debug, Default)]
+pub struct CostTracker {
+    pricing: RwLock<PricingTable>,
+    records: Mutex<Vec<CostRecord>>,
+}
+
+impl CostTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_pricing(pricing: PricingTable) -> Self {
+        Self {
+            pricing: RwLock::new(pricing),
+            records: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Override or add the price of a model
+    pub fn set_pricing(&self, model: &str, pricing: ModelPricing) {
+        self.pricing.write().unwrap().set(model, pricing);
+    }
+
+    pub fn pricing(&self) -> PricingTable {
+        self.pricing.read().unwrap().clone()
+    }
+
+    /// Cost of a call before it is made, e.g. to check a budget
+    pub fn estimate(&self, model: &str, input_tokens: u32, output_tokens: u32) -> f64 {
+        self.pricing.read().unwrap().cost(model, input_tokens, output_tokens)
+    }
+
+    /// Price and store the run behind `response`
+    pub fn record_response(&self, agent_id: &str, response: &AgentResponse) -> CostRecord {
+        let record = CostRecord {
+            timestamp: response.timestamp,
+            agent_id: agent_id.to_string(),
+            user_id: response.user_id.clone(),
+            task_id: response.task_id.clone(),
+            model: response.model_used.clone(),
+            tags: response.tags.clone(),
+            input_tokens: response.input_tokens,
+            output_tokens: response.output_tokens,
+            cost_usd: self.estimate(&response.model_used, response.input_tokens, response.output_tokens),
+        };
+        self.record(record.clone());
+        record
+    }
+
+    pub fn record(&self, record: CostRecord) {
+        self.records.lock().unwrap().push(record);
+    }
+
+    pub fn records(&self, filter: &CostFilter) -> Vec<CostRecord> {
+        self.records.lock().unwrap().iter().filter(|r| filter.matches(r)).cloned().collect()
+    }
+
+    pub fn summary(&self, filter: &CostFilter) -> CostSummary {
+        let mut summary = CostSummary::default();
+        for record in self.records.lock().unwrap().iter().filter(|r| filter.matches(r)) {
+            summary.add(record);
+        }
+        summary
+    }
+
+    fn group_by(&self, filter: &CostFilter, keys: impl Fn(&CostRecord) -> Vec<String>) -> BTreeMap<String, CostSummary> {
+        let mut groups: BTreeMap<String, CostSummary> = BTreeMap::new();
+        for record in self.records.lock().unwrap().iter().filter(|r| filter.matches(r)) {
+            for key in keys(record) {
+                groups.entry(key).or_default().add(record);
+            }
+        }
+        groups
+    }
+
+    pub fn by_agent(&self, filter: &CostFilter) -> BTreeMap<String, CostSummary> {
+        self.group_by(filter, |r| vec![r.agent_id.clone()])
+    }
+
+    /// Runs without a user are grouped under an empty key
+    pub fn by_user(&self, filter: &CostFilter) -> BTreeMap<String, CostSummary> {
+        self.group_by(filter, |r| vec![r.user_id.clone().unwrap_or_default()])
+    }
+
+    /// A run with several tags counts towards each of them
+    pub fn by_tag(&self, filter: &CostFilter) -> BTreeMap<String, CostSummary> {
+        self.group_by(filter, |r| r.tags.clone())
+    }
+
+    pub fn by_model(&self, filter: &CostFilter) -> BTreeMap<String, CostSummary> {
+        self.group_by(filter, |r| vec![r.model.clone()])
+    }
+
+    /// Totals per UTC day (`YYYY-MM-DD`)
+    pub fn by_day(&self, filter: &CostFilter) -> BTreeMap<String, CostSummary> {
+        self.group_by(filter, |r| vec![r.timestamp.format("%Y-%m-%d").to_string()])
+    }
+
+    pub fn export_json(&self, filter: &CostFilter) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&self.records(filter))
+    }
+
+    pub fn export_csv(&self, filter: &CostFilter) -> String {
+        let mut lines = vec!["timestamp,agent_id,user_id,task_id,model,tags,input_tokens,output_tokens,cost_usd".to_string()];
+        for record in self.records(filter) {
+            let fields = [
+                record.timestamp.to_rfc3339(),
+                record.agent_id,
+                record.user_id.unwrap_or_default(),
+                record.task_id.unwrap_or_default(),
+                record.model,
+                record.tags.join(";"),
+                record.input_tokens.to_string(),
+                record.output_tokens.to_string(),
+                format!("{:.6}", record.cost_usd),
+            ];
+            lines.push(fields.iter().map(|f| csv_field(f)).collect::<Vec<_>>().join(","));
+        }
+        lines.join("\n")
+    }
+
+    /// Drop records older than `before`, returning how many were removed
+    pub fn prune(&self, before: DateTime<Utc>) -> usize {
+        let mut records = self.records.lock().unwrap();
+        let count = records.len();
+        records.retain(|r| r.timestamp >= before);
+        count - records.len()
+    }
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
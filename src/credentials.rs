@@ -0,0 +1,237 @@
+use serde::{Deserialize, Deserializer};
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::SystemTime;
+
+/// A source of a secret such as an API key. Sources are read on every use, so a
+/// key rotated at the source is picked up without restarting the agent.
+pub trait CredentialProvider: Send + Sync {
+    /// Describes the source for error messages, never the secret itself
+    fn describe(&self) -> String;
+
+    /// The current value of the secret
+    fn fetch(&self) -> Result<String, String>;
+}
+
+/// A key held in memory; replace it with `set`
+pub struct StaticCredential {
+    key: RwLock<String>,
+}
+
+impl StaticCredential {
+    pub fn new(key: impl Into<String>) -> Self {
+        Self { key: RwLock::new(key.into()) }
+    }
+
+    pub fn set(&self, key: impl Into<String>) {
+        *self.key.write().unwrap() = key.into();
+    }
+}
+
+impl CredentialProvider for StaticCredential {
+    fn describe(&self) -> String {
+        "static key".to_string()
+    }
+
+    fn fetch(&self) -> Result<String, String> {
+        Ok(self.key.read().unwrap().clone())
+    }
+}
+
+/// A key read from an environment variable
+pub struct EnvCredential {
+    var: String,
+}
+
+impl EnvCredential {
+    pub fn new(var: &str) -> Self {
+        Self { var: var.to_string() }
+    }
+}
+
+impl CredentialProvider for EnvCredential {
+    fn describe(&self) -> String {
+        format!("environment variable {}", self.var)
+    }
+
+    fn fetch(&self) -> Result<String, String> {
+        std::env::var(&self.var).map_err(|e| format!("Failed to read {}: {}", self.describe(), e))
+    }
+}
+
+/// A key read from a file, e.g. a mounted Kubernetes or Docker secret. The file
+/// is re-read only when its modification time changes.
+pub struct FileCredential {
+    path: PathBuf,
+    cached: Mutex<Option<(SystemTime, String)>>,
+}
+
+impl FileCredential {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into(), cached: Mutex::new(None) }
+    }
+}
+
+impl CredentialProvider for FileCredential {
+    fn describe(&self) -> String {
+        format!("file {}", self.path.display())
+    }
+
+    fn fetch(&self) -> Result<String, String> {
+        let modified = std::fs::metadata(&self.path)
+            .and_then(|metadata| metadata.modified())
+            .map_err(|e| format!("Failed to read {}: {}", self.describe(), e))?;
+        let mut cached = self.cached.lock().unwrap();
+        if let Some((at, key)) = cached.as_ref() {
+            if *at == modified {
+                return Ok(key.clone());
+            }
+        }
+        let key = std::fs::read_to_string(&self.path)
+            .map_err(|e| format!("Failed to read {}: {}", self.describe(), e))?
+            .trim()
+            .to_string();
+        *cached = Some((modified, key.clone()));
+        Ok(key)
+    }
+}
+
+/// A key stored in the OS keyring (macOS Keychain, Windows Credential Manager,
+/// Secret Service on Linux)
+#[cfg(feature = "keyring")]
+pub struct KeyringCredential {
+    service: String,
+    user: String,
+}
+
+#[cfg(feature = "keyring")]
+impl KeyringCredential {
+    pub fn new(service: &str, user: &str) -> Self {
+        Self { service: service.to_string(), user: user.to_string() }
+    }
+}
+
+#[cfg(feature = "keyring")]
+impl CredentialProvider for KeyringCredential {
+    fn describe(&self) -> String {
+        format!("keyring entry {}/{}", self.service, self.user)
+    }
+
+    fn fetch(&self) -> Result<String, String> {
+        keyring::Entry::new(&self.service, &self.user)
+            .and_then(|entry| entry.get_password())
+            .map_err(|e| format!("Failed to read {}: {}", self.describe(), e))
+    }
+}
+
+type RotationCallback = dyn Fn(&str) + Send + Sync;
+
+/// A handle on a secret, shared by the clients that use it. Each use resolves
+/// the current value from the provider; when it differs from the last one the
+/// rotation callbacks run with the source's description.
+#[derive(Clone)]
+pub struct Credential {
+    provider: Arc<dyn CredentialProvider>,
+    last: Arc<Mutex<Option<String>>>,
+    on_rotate: Arc<RwLock<Vec<Arc<RotationCallback>>>>,
+}
+
+impl Credential {
+    pub fn new(provider: Arc<dyn CredentialProvider>) -> Self {
+        Self {
+            provider,
+            last: Arc::new(Mutex::new(None)),
+            on_rotate: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+
+    /// A fixed key held in memory
+    pub fn fixed(key: impl Into<String>) -> Self {
+        Self::new(Arc::new(StaticCredential::new(key)))
+    }
+
+    pub fn from_env(var: &str) -> Self {
+        Self::new(Arc::new(EnvCredential::new(var)))
+    }
+
+    pub fn from_file(path: impl Into<PathBuf>) -> Self {
+        Self::new(Arc::new(FileCredential::new(path)))
+    }
+
+    #[cfg(feature = "keyring")]
+    pub fn from_keyring(service: &str, user: &str) -> Self {
+        Self::new(Arc::new(KeyringCredential::new(service, user)))
+    }
+
+    /// Run `callback` whenever the resolved key changes
+    pub fn on_rotate<F>(&self, callback: F)
+    where
+        F: Fn(&str) + Send + Sync + 'static,
+    {
+        self.on_rotate.write().unwrap().push(Arc::new(callback));
+    }
+
+    /// The current key
+    pub fn resolve(&self) -> Result<String, String> {
+        let key = self.provider.fetch()?;
+        let rotated = {
+            let mut last = self.last.lock().unwrap();
+            let rotated = last.as_ref().is_some_and(|previous| *previous != key);
+            if last.as_ref() != Some(&key) {
+                *last = Some(key.clone());
+            }
+            rotated
+        };
+        if rotated {
+            let source = self.provider.describe();
+            for callback in self.on_rotate.read().unwrap().iter() {
+                callback(&source);
+            }
+        }
+        Ok(key)
+    }
+
+    /// The current key, logging and returning None if the source can't be read
+    pub fn resolve_or_log(&self) -> Option<String> {
+        match self.resolve() {
+            Ok(key) => Some(key),
+            Err(e) => {
+                eprintln!("{}", e);
+                None
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Credential {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credential").field("source", &self.provider.describe()).finish()
+    }
+}
+
+impl From<String> for Credential {
+    fn from(key: String) -> Self {
+        Self::fixed(key)
+    }
+}
+
+impl From<&str> for Credential {
+    fn from(key: &str) -> Self {
+        Self::fixed(key)
+    }
+}
+
+/// Keys in config files are plain strings, or `env:NAME` / `file:PATH` to read them from there
+impl<'de> Deserialize<'de> for Credential {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Ok(if let Some(var) = value.strip_prefix("env:") {
+            Self::from_env(var)
+        } else if let Some(path) = value.strip_prefix("file:") {
+            Self::from_file(path)
+        } else {
+            Self::fixed(value)
+        })
+    }
+}
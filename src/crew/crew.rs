@@ -0,0 +1,110 @@
+use crate::agent::agent::{Agent, AgentResponse};
+use crate::task::task::Task;
+use crate::telemetry::traced;
+use serde::{Deserialize, Serialize};
+
+/// An agent and the task it runs in a crew
+pub struct CrewStep {
+    pub agent: Agent,
+    pub task: Task,
+}
+
+/// Agents that work on one job together. `kickoff_sequential` runs the steps in
+/// order, handing each agent's output to the next one.
+pub struct Crew {
+    pub name: String,
+    pub steps: Vec<CrewStep>,
+}
+
+impl Crew {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            steps: Vec::new(),
+        }
+    }
+
+    pub fn with_step(mut self, agent: Agent, task: Task) -> Self {
+        self.steps.push(CrewStep { agent, task });
+        self
+    }
+
+    pub fn agents(&self) -> impl Iterator<Item = &Agent> {
+        self.steps.iter().map(|step| &step.agent)
+    }
+
+    /// Run every step in order. Each step's task is prefixed with the output of the
+    /// step before it; the run stops at the first step that fails.
+    pub async fn kickoff_sequential(&mut self) -> CrewResult {
+        let mut result = CrewResult {
+            crew: self.name.clone(),
+            success: false,
+            steps: Vec::new(),
+            failed_step: None,
+            final_output: None,
+            execution_time_ms: 0,
+            total_tokens: 0,
+        };
+
+        let mut previous: Option<(String, String)> = None;
+        for index in 0..self.steps.len() {
+            let step = &mut self.steps[index];
+            let task = chain_task(&step.task, previous.as_ref());
+            let response = traced!(step.agent.call(task), "merco.crew.step", crew = %self.name, step = index).await;
+
+            result.execution_time_ms += response.execution_time_ms;
+            result.total_tokens += response.total_tokens;
+            let success = response.success;
+            previous = Some((step.agent.name.clone(), response.content.clone()));
+            result.steps.push(CrewStepResult {
+                agent_id: step.agent.id.clone(),
+                agent_name: step.agent.name.clone(),
+                response,
+            });
+
+            if !success {
+                result.failed_step = Some(index);
+                return result;
+            }
+        }
+
+        result.success = true;
+        result.final_output = result.steps.last().map(|step| step.response.content.clone());
+        result
+    }
+}
+
+/// The task sent for a step, prefixed with the previous agent's output
+fn chain_task(task: &Task, previous: Option<&(String, String)>) -> Task {
+    let mut task = task.with_new_id();
+    if let Some((agent_name, output)) = previous {
+        task.description = format!(
+            "Result from the previous step ({}):\n{}\n\nCurrent step: {}",
+            agent_name, output, task.description
+        );
+    }
+    task
+}
+
+/// What one agent produced in a crew run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrewStepResult {
+    pub agent_id: String,
+    pub agent_name: String,
+    pub response: AgentResponse,
+}
+
+/// Outcome of a crew run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrewResult {
+    pub crew: String,
+    pub success: bool,
+    /// Results of the steps that ran, in order; steps after a failure don't run
+    pub steps: Vec<CrewStepResult>,
+    /// Index of the step that failed, if any
+    pub failed_step: Option<usize>,
+    /// Output of the last step when all steps completed
+    pub final_output: Option<String>,
+    pub execution_time_ms: u64,
+    pub total_tokens: u32,
+}
@@ -0,0 +1,3 @@
+pub mod crew;
+
+pub use crew::{Crew, CrewResult, CrewStep, CrewStepResult};
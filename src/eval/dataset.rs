@@ -0,0 +1,127 @@
+use crate::task::task::Task;
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+
+/// How an output is checked
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "metric", rename_all = "snake_case")]
+pub enum Criterion {
+    /// Output equals `expected` after trimming
+    ExactMatch {
+        expected: String,
+        #[serde(default)]
+        ignore_case: bool,
+    },
+    Contains { text: String },
+    /// The JSON in the output has `expected` at the dotted `path` (e.g. `items.0.price`)
+    JsonField { path: String, expected: Value },
+    /// Similarity to `expected` of at least `threshold`, using the runner's scorer
+    Similarity { expected: String, threshold: f64 },
+}
+
+impl Criterion {
+    /// Short label used in reports
+    pub fn label(&self) -> String {
+        match self {
+            Criterion::ExactMatch { .. } => "exact_match".to_string(),
+            Criterion::Contains { text } => format!("contains:{}", text),
+            Criterion::JsonField { path, .. } => format!("json_field:{}", path),
+            Criterion::Similarity { .. } => "similarity".to_string(),
+        }
+    }
+}
+
+/// One input task and what its output must satisfy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalCase {
+    pub id: String,
+    pub input: String,
+    pub criteria: Vec<Criterion>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl EvalCase {
+    pub fn new(id: &str, input: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            input: input.to_string(),
+            criteria: Vec::new(),
+            tags: Vec::new(),
+        }
+    }
+
+    pub fn expect(mut self, criterion: Criterion) -> Self {
+        self.criteria.push(criterion);
+        self
+    }
+
+    pub fn expect_exact(self, expected: &str) -> Self {
+        self.expect(Criterion::ExactMatch { expected: expected.to_string(), ignore_case: false })
+    }
+
+    pub fn expect_contains(self, text: &str) -> Self {
+        self.expect(Criterion::Contains { text: text.to_string() })
+    }
+
+    pub fn expect_json_field(self, path: &str, expected: Value) -> Self {
+        self.expect(Criterion::JsonField { path: path.to_string(), expected })
+    }
+
+    pub fn expect_similar(self, expected: &str, threshold: f64) -> Self {
+        self.expect(Criterion::Similarity { expected: expected.to_string(), threshold })
+    }
+
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        self.tags.push(tag.to_string());
+        self
+    }
+
+    pub(crate) fn to_task(&self) -> Task {
+        let mut task = Task::new(self.input.clone(), None);
+        task.tags = self.tags.clone();
+        task
+    }
+}
+
+/// Named list of evaluation cases
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EvalDataset {
+    pub name: String,
+    pub cases: Vec<EvalCase>,
+}
+
+impl EvalDataset {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            cases: Vec::new(),
+        }
+    }
+
+    pub fn with_case(mut self, case: EvalCase) -> Self {
+        self.cases.push(case);
+        self
+    }
+
+    /// Load a dataset from JSON (`{"name": .., "cases": [..]}`) or from JSON Lines
+    /// with one case per line, named after the file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        if path.extension().is_some_and(|ext| ext == "jsonl") {
+            let cases = text
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .enumerate()
+                .map(|(i, line)| serde_json::from_str(line).map_err(|e| anyhow!("Invalid case on line {}: {}", i + 1, e)))
+                .collect::<Result<Vec<EvalCase>>>()?;
+            let name = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+            Ok(Self { name, cases })
+        } else {
+            Ok(serde_json::from_str(&text)?)
+        }
+    }
+}
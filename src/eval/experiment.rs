@@ -0,0 +1,166 @@
+use crate::agent::agent::{Agent, AgentResponse};
+use crate::stable_hash::fnv1a;
+use crate::cost::PricingTable;
+use crate::task::task::Task;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// An agent configuration taking part in an experiment
+#[derive(Clone)]
+pub struct Variant {
+    pub name: String,
+    /// Relative share of traffic, e.g. 90 and 10 for a 90/10 split
+    pub weight: u32,
+    pub agent: Agent,
+}
+
+/// Comparative metrics of one variant
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VariantStats {
+    pub runs: usize,
+    pub successes: usize,
+    pub cost_usd: f64,
+    pub total_latency_ms: u64,
+    pub total_tokens: u64,
+    pub evaluated: usize,
+    pub total_eval_score: f64,
+}
+
+impl VariantStats {
+    pub fn success_rate(&self) -> f64 {
+        ratio(self.successes as f64, self.runs)
+    }
+
+    pub fn average_cost_usd(&self) -> f64 {
+        ratio(self.cost_usd, self.runs)
+    }
+
+    pub fn average_latency_ms(&self) -> f64 {
+        ratio(self.total_latency_ms as f64, self.runs)
+    }
+
+    /// Mean evaluation score over runs whose task requested evaluation
+    pub fn average_eval_score(&self) -> Option<f64> {
+        (self.evaluated > 0).then(|| self.total_eval_score / self.evaluated as f64)
+    }
+}
+
+fn ratio(total: f64, count: usize) -> f64 {
+    if count == 0 { 0.0 } else { total / count as f64 }
+}
+
+/// Routes tasks between variant agent configurations by weight, tags each
+/// response with its variant and aggregates per-variant metrics.
+///
+/// Assignment is deterministic: the same user (or, without one, the same task id)
+/// always gets the same variant of an experiment.
+pub struct Experiment {
+    name: String,
+    variants: Vec<Variant>,
+    pricing: PricingTable,
+    stats: Mutex<BTreeMap<String, VariantStats>>,
+}
+
+impl Experiment {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            variants: Vec::new(),
+            pricing: PricingTable::default(),
+            stats: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn with_variant(mut self, name: &str, weight: u32, agent: Agent) -> Self {
+        self.variants.push(Variant {
+            name: name.to_string(),
+            weight,
+            agent,
+        });
+        self
+    }
+
+    /// Prices used for the cost comparison
+    pub fn with_pricing(mut self, pricing: PricingTable) -> Self {
+        self.pricing = pricing;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Variant `task` is routed to, None when no variant has weight
+    pub fn assign(&self, task: &Task) -> Option<&Variant> {
+        let total: u64 = self.variants.iter().map(|v| v.weight as u64).sum();
+        if total == 0 {
+            return None;
+        }
+        let key = task.user_id.as_deref().unwrap_or(&task.id);
+        let mut bucket = fnv1a(format!("{}:{}", self.name, key).as_bytes()) % total;
+        self.variants.iter().find(|variant| {
+            if bucket < variant.weight as u64 {
+                true
+            } else {
+                bucket -= variant.weight as u64;
+                false
+            }
+        })
+    }
+
+    /// Run `task` on its variant. The response is tagged `variant:<name>` and carries
+    /// the experiment and variant in its metadata.
+    pub async fn call(&self, mut task: Task) -> Result<AgentResponse, String> {
+        let variant = self
+            .assign(&task)
+            .ok_or_else(|| format!("Experiment '{}' has no variants with weight", self.name))?;
+        let tag = format!("variant:{}", variant.name);
+        task.tags.push(tag);
+
+        let mut agent = variant.agent.clone();
+        let mut response = agent.call(task).await;
+        response.metadata.insert("experiment".to_string(), serde_json::Value::String(self.name.clone()));
+        response.metadata.insert("experiment_variant".to_string(), serde_json::Value::String(variant.name.clone()));
+        self.record(&variant.name, &response);
+        Ok(response)
+    }
+
+    fn record(&self, variant: &str, response: &AgentResponse) {
+        let mut stats = self.stats.lock().unwrap();
+        let entry = stats.entry(variant.to_string()).or_default();
+        entry.runs += 1;
+        if response.success {
+            entry.successes += 1;
+        }
+        entry.cost_usd += self.pricing.cost(&response.model_used, response.input_tokens, response.output_tokens);
+        entry.total_latency_ms += response.execution_time_ms;
+        entry.total_tokens += response.total_tokens as u64;
+        if let Some(evaluation) = &response.evaluation {
+            entry.evaluated += 1;
+            entry.total_eval_score += evaluation.score;
+        }
+    }
+
+    /// Metrics per variant name
+    pub fn stats(&self) -> BTreeMap<String, VariantStats> {
+        self.stats.lock().unwrap().clone()
+    }
+
+    /// Variant with the best success rate, ties broken by lower average cost
+    pub fn leader(&self) -> Option<String> {
+        self.stats()
+            .into_iter()
+            .filter(|(_, stats)| stats.runs > 0)
+            .max_by(|(_, a), (_, b)| {
+                a.success_rate()
+                    .total_cmp(&b.success_rate())
+                    .then(b.average_cost_usd().total_cmp(&a.average_cost_usd()))
+            })
+            .map(|(name, _)| name)
+    }
+
+    pub fn reset(&self) {
+        self.stats.lock().unwrap().clear();
+    }
+}
@@ -0,0 +1,7 @@
+pub mod dataset;
+pub mod experiment;
+pub mod runner;
+
+pub use dataset::{Criterion, EvalCase, EvalDataset};
+pub use experiment::{Experiment, Variant, VariantStats};
+pub use runner::{CaseResult, CriterionResult, EvalReport, EvalRunner, VariantReport};
@@ -0,0 +1,241 @@
+use crate::agent::agent::{Agent, AgentResponse};
+use crate::agent::output_handler::extract_json;
+use crate::agent::scoring::{LexicalScorer, OutputScorer};
+use crate::eval::dataset::{Criterion, EvalDataset};
+use crate::task::inputs::lookup;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Outcome of one criterion on one output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CriterionResult {
+    pub criterion: String,
+    /// In [0, 1]; binary metrics score 0 or 1
+    pub score: f64,
+    pub passed: bool,
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaseResult {
+    pub case_id: String,
+    /// Every criterion passed and the run succeeded
+    pub passed: bool,
+    pub output: String,
+    pub error: Option<String>,
+    pub criteria: Vec<CriterionResult>,
+    pub execution_time_ms: u64,
+    pub total_tokens: u32,
+}
+
+/// Results of one agent configuration on the dataset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VariantReport {
+    pub variant: String,
+    pub cases: Vec<CaseResult>,
+}
+
+impl VariantReport {
+    pub fn passed(&self) -> usize {
+        self.cases.iter().filter(|c| c.passed).count()
+    }
+
+    pub fn pass_rate(&self) -> f64 {
+        if self.cases.is_empty() {
+            0.0
+        } else {
+            self.passed() as f64 / self.cases.len() as f64
+        }
+    }
+
+    pub fn average_latency_ms(&self) -> f64 {
+        if self.cases.is_empty() {
+            0.0
+        } else {
+            self.cases.iter().map(|c| c.execution_time_ms as f64).sum::<f64>() / self.cases.len() as f64
+        }
+    }
+
+    pub fn total_tokens(&self) -> u64 {
+        self.cases.iter().map(|c| c.total_tokens as u64).sum()
+    }
+
+    pub fn case(&self, case_id: &str) -> Option<&CaseResult> {
+        self.cases.iter().find(|c| c.case_id == case_id)
+    }
+
+    /// Cases that pass in `baseline` but fail here
+    pub fn regressions_from(&self, baseline: &VariantReport) -> Vec<String> {
+        self.cases
+            .iter()
+            .filter(|case| !case.passed && baseline.case(&case.case_id).is_some_and(|b| b.passed))
+            .map(|case| case.case_id.clone())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalReport {
+    pub dataset: String,
+    pub started_at: DateTime<Utc>,
+    pub variants: Vec<VariantReport>,
+}
+
+impl EvalReport {
+    pub fn variant(&self, name: &str) -> Option<&VariantReport> {
+        self.variants.iter().find(|v| v.variant == name)
+    }
+
+    /// Cases `candidate` fails that `baseline` passes, both variants of this report
+    pub fn regressions(&self, baseline: &str, candidate: &str) -> Vec<String> {
+        match (self.variant(baseline), self.variant(candidate)) {
+            (Some(baseline), Some(candidate)) => candidate.regressions_from(baseline),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Per variant, the cases that regressed since `previous` (e.g. the last saved run)
+    pub fn regressions_since(&self, previous: &EvalReport) -> Vec<(String, Vec<String>)> {
+        self.variants
+            .iter()
+            .filter_map(|variant| {
+                let regressed = variant.regressions_from(previous.variant(&variant.variant)?);
+                (!regressed.is_empty()).then(|| (variant.variant.clone(), regressed))
+            })
+            .collect()
+    }
+
+    /// One line per variant with pass rate, latency and tokens
+    pub fn summary(&self) -> String {
+        self.variants
+            .iter()
+            .map(|v| {
+                format!(
+                    "{}: {}/{} passed ({:.1}%), avg {:.0}ms, {} tokens",
+                    v.variant,
+                    v.passed(),
+                    v.cases.len(),
+                    v.pass_rate() * 100.0,
+                    v.average_latency_ms(),
+                    v.total_tokens()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+}
+
+/// Runs a dataset against one or more agent configurations
+pub struct EvalRunner {
+    dataset: EvalDataset,
+    variants: Vec<(String, Agent)>,
+    scorer: Arc<dyn OutputScorer>,
+}
+
+impl EvalRunner {
+    pub fn new(dataset: EvalDataset) -> Self {
+        Self {
+            dataset,
+            variants: Vec::new(),
+            scorer: Arc::new(LexicalScorer),
+        }
+    }
+
+    pub fn with_variant(mut self, name: &str, agent: Agent) -> Self {
+        self.variants.push((name.to_string(), agent));
+        self
+    }
+
+    /// Scorer for similarity criteria (lexical by default)
+    pub fn with_scorer(mut self, scorer: Arc<dyn OutputScorer>) -> Self {
+        self.scorer = scorer;
+        self
+    }
+
+    /// Run every case against every variant, one at a time
+    pub async fn run(&mut self) -> EvalReport {
+        let started_at = Utc::now();
+        let mut variants = Vec::new();
+        for (name, agent) in &mut self.variants {
+            let mut cases = Vec::new();
+            for case in &self.dataset.cases {
+                let response = agent.call(case.to_task()).await;
+                let mut criteria = Vec::new();
+                for criterion in &case.criteria {
+                    criteria.push(score_criterion(self.scorer.as_ref(), criterion, &response).await);
+                }
+                cases.push(CaseResult {
+                    case_id: case.id.clone(),
+                    passed: response.success && criteria.iter().all(|c| c.passed),
+                    output: response.content.clone(),
+                    error: response.error.clone(),
+                    criteria,
+                    execution_time_ms: response.execution_time_ms,
+                    total_tokens: response.total_tokens,
+                });
+            }
+            variants.push(VariantReport {
+                variant: name.clone(),
+                cases,
+            });
+        }
+        EvalReport {
+            dataset: self.dataset.name.clone(),
+            started_at,
+            variants,
+        }
+    }
+}
+
+fn binary(criterion: &Criterion, passed: bool, detail: Option<String>) -> CriterionResult {
+    CriterionResult {
+        criterion: criterion.label(),
+        score: if passed { 1.0 } else { 0.0 },
+        passed,
+        detail,
+    }
+}
+
+/// Score one criterion against a response
+pub async fn score_criterion(scorer: &dyn OutputScorer, criterion: &Criterion, response: &AgentResponse) -> CriterionResult {
+    let output = response.content.trim();
+    match criterion {
+        Criterion::ExactMatch { expected, ignore_case } => {
+            let passed = if *ignore_case {
+                output.to_lowercase() == expected.trim().to_lowercase()
+            } else {
+                output == expected.trim()
+            };
+            binary(criterion, passed, None)
+        }
+        Criterion::Contains { text } => binary(criterion, output.contains(text.as_str()), None),
+        Criterion::JsonField { path, expected } => {
+            let value = extract_json(output).and_then(|json| serde_json::from_str::<serde_json::Value>(json).ok());
+            match value.as_ref().and_then(|value| lookup(value, path)) {
+                Some(actual) => binary(criterion, actual == expected, (actual != expected).then(|| format!("was {}", actual))),
+                None => binary(criterion, false, Some("field not found".to_string())),
+            }
+        }
+        Criterion::Similarity { expected, threshold } => match scorer.score(output, expected).await {
+            Ok(score) => CriterionResult {
+                criterion: criterion.label(),
+                score,
+                passed: score >= *threshold,
+                detail: Some(format!("{} similarity", scorer.name())),
+            },
+            Err(e) => binary(criterion, false, Some(e)),
+        },
+    }
+}
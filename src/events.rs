@@ -0,0 +1,157 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// What happened
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EventKind {
+    TaskStarted {
+        description: String,
+        streaming: bool,
+    },
+    LlmRequest {
+        model: String,
+        round: usize,
+        message_count: usize,
+    },
+    ToolExecuted {
+        tool: String,
+        execution_time_ms: u64,
+        success: bool,
+    },
+    ValidationFailed {
+        attempt: usize,
+        report: crate::task::validation_report::ValidationReport,
+    },
+    /// Published by memory integrations when they persist an entry
+    MemoryStored {
+        key: String,
+        kind: String,
+    },
+    /// A budget passed its alert threshold, published once per budget period
+    BudgetAlert {
+        scope: crate::cost::BudgetScope,
+        spent_usd: f64,
+        limit_usd: f64,
+        fraction_used: f64,
+    },
+    /// Periodic liveness signal from `Agent::start_heartbeat`
+    Heartbeat {
+        uptime_seconds: u64,
+        idle_seconds: u64,
+        active_tasks: usize,
+    },
+    /// A task has been processing longer than the heartbeat's stall threshold
+    TaskStalled {
+        running_ms: u64,
+    },
+    TaskCompleted {
+        success: bool,
+        cancelled: bool,
+        execution_time_ms: u64,
+        total_tokens: u32,
+        error: Option<String>,
+    },
+}
+
+/// A lifecycle event with the agent and task it belongs to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentEvent {
+    pub timestamp: DateTime<Utc>,
+    pub agent_id: String,
+    pub task_id: Option<String>,
+    pub trace_id: Option<String>,
+    #[serde(flatten)]
+    pub kind: EventKind,
+}
+
+impl AgentEvent {
+    pub fn new(agent_id: &str, task: Option<&crate::task::task::Task>, kind: EventKind) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            agent_id: agent_id.to_string(),
+            task_id: task.map(|t| t.id.clone()),
+            trace_id: task.and_then(|t| t.trace_id.clone()),
+            kind,
+        }
+    }
+}
+
+pub type EventSubscriber = Arc<dyn Fn(&AgentEvent) + Send + Sync>;
+
+/// Handle returned by `subscribe`, used to unsubscribe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u64);
+
+/// Delivers lifecycle events to every registered subscriber, synchronously and in
+/// registration order. Agents publish to the global bus unless given their own.
+#[derive(Default)]
+pub struct EventBus {
+    subscribers: RwLock<Vec<(SubscriptionId, EventSubscriber)>>,
+    next_id: AtomicU64,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bus shared by every agent that wasn't given its own
+    pub fn global() -> Arc<EventBus> {
+        static GLOBAL: OnceLock<Arc<EventBus>> = OnceLock::new();
+        GLOBAL.get_or_init(|| Arc::new(EventBus::new())).clone()
+    }
+
+    pub fn subscribe<F>(&self, subscriber: F) -> SubscriptionId
+    where
+        F: Fn(&AgentEvent) + Send + Sync + 'static,
+    {
+        let id = SubscriptionId(self.next_id.fetch_add(1, Ordering::Relaxed));
+        self.subscribers.write().unwrap().push((id, Arc::new(subscriber)));
+        id
+    }
+
+    /// Receive events on a channel, for async consumers such as UI updates or persistence
+    pub fn subscribe_channel(&self) -> (SubscriptionId, tokio::sync::mpsc::UnboundedReceiver<AgentEvent>) {
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        let id = self.subscribe(move |event| {
+            let _ = sender.send(event.clone());
+        });
+        (id, receiver)
+    }
+
+    pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        let mut subscribers = self.subscribers.write().unwrap();
+        let before = subscribers.len();
+        subscribers.retain(|(subscription, _)| *subscription != id);
+        subscribers.len() != before
+    }
+
+    pub fn has_subscribers(&self) -> bool {
+        !self.subscribers.read().unwrap().is_empty()
+    }
+
+    pub fn publish(&self, event: AgentEvent) {
+        // Snapshot so subscribers may (un)subscribe without deadlocking
+        let subscribers: Vec<EventSubscriber> = self
+            .subscribers
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(_, subscriber)| subscriber.clone())
+            .collect();
+        for subscriber in subscribers {
+            subscriber(&event);
+        }
+    }
+}
+
+impl std::fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventBus")
+            .field("subscribers", &self.subscribers.read().unwrap().len())
+            .finish()
+    }
+}
@@ -1,10 +1,32 @@
+mod telemetry;
+mod stable_hash;
+
 pub mod agent;
 pub mod task;
 pub mod crew;
+pub mod scheduler;
+pub mod mcp;
+pub mod a2a;
+pub mod events;
+#[cfg(feature = "audit")]
+pub mod audit;
+pub mod redaction;
+pub mod injection;
+pub mod credentials;
+pub mod config;
+#[cfg(feature = "audit")]
+pub mod provenance;
+pub mod tenant;
+pub mod cost;
+pub mod eval;
+pub mod tools;
+#[cfg(feature = "server")]
+pub mod server;
 
 // Re-export main types for easier access
 pub use agent::Agent;
 pub use agent::AgentModelConfig;
+pub use agent::AgentBuilder;
 pub use agent::AgentResponse;
 pub use agent::TaskResult;
 pub use agent::AgentError;
@@ -18,3 +40,9 @@ pub use agent::StreamingHandler;
 pub use agent::StreamingChunk;
 pub use agent::StreamingResponse;
 pub use task::task::Task;
+pub use task::task::TaskPriority;
+pub use crew::{Crew, CrewResult};
+pub use task::cancellation::{CancellationToken, TaskHandle};
+pub use events::{AgentEvent, EventBus, EventKind};
+pub use tenant::TenantId;
+pub use config::{AgentConfigFile, CrewConfigFile};
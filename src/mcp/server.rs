@@ -0,0 +1,206 @@
+use crate::agent::agent::Agent;
+use crate::task::task::Task;
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+
+/// MCP protocol revision implemented by the server
+pub const MCP_PROTOCOL_VERSION: &str = "2024-11-05";
+
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Exposes agents to MCP hosts (IDEs, desktop clients) over JSON-RPC on stdio.
+///
+/// Every agent is listed both as a tool, which runs a task on the agent and
+/// returns its output, and as a prompt, which returns the agent's instructions
+/// for the given input so the host's own model can follow them.
+pub struct McpServer {
+    name: String,
+    version: String,
+    agents: BTreeMap<String, Arc<Agent>>,
+}
+
+impl McpServer {
+    pub fn new(name: &str, version: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            version: version.to_string(),
+            agents: BTreeMap::new(),
+        }
+    }
+
+    /// Expose an agent under `tool_name` (letters, digits, `_` and `-`)
+    pub fn add_agent(mut self, tool_name: &str, agent: Agent) -> Self {
+        self.agents.insert(tool_name.to_string(), Arc::new(agent));
+        self
+    }
+
+    /// Serve on the process's stdin/stdout until stdin closes
+    pub async fn serve_stdio(&self) -> Result<()> {
+        self.serve(BufReader::new(tokio::io::stdin()), tokio::io::stdout()).await
+    }
+
+    /// Serve newline-delimited JSON-RPC messages from `reader`, writing replies to `writer`
+    pub async fn serve<R, W>(&self, reader: R, mut writer: W) -> Result<()>
+    where
+        R: AsyncBufRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        let mut lines = reader.lines();
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(reply) = self.handle_message(&line).await {
+                writer.write_all(reply.to_string().as_bytes()).await?;
+                writer.write_all(b"\n").await?;
+                writer.flush().await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Handle one JSON-RPC message; notifications get no reply
+    pub async fn handle_message(&self, message: &str) -> Option<Value> {
+        let request: Value = match serde_json::from_str(message) {
+            Ok(request) => request,
+            Err(e) => return Some(error_reply(Value::Null, PARSE_ERROR, &format!("Parse error: {}", e))),
+        };
+        let id = request.get("id").cloned();
+        let method = match request.get("method").and_then(|m| m.as_str()) {
+            Some(method) => method,
+            None => return Some(error_reply(id.unwrap_or(Value::Null), INVALID_REQUEST, "Missing method")),
+        };
+        // Notifications (no id) such as `notifications/initialized` need no answer
+        let id = id?;
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+        let result = match method {
+            "initialize" => Ok(self.initialize()),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(self.list_tools()),
+            "tools/call" => self.call_tool(&params).await,
+            "prompts/list" => Ok(self.list_prompts()),
+            "prompts/get" => self.get_prompt(&params),
+            _ => Err((METHOD_NOT_FOUND, format!("Method not found: {}", method))),
+        };
+
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => error_reply(id, code, &message),
+        })
+    }
+
+    fn initialize(&self) -> Value {
+        json!({
+            "protocolVersion": MCP_PROTOCOL_VERSION,
+            "capabilities": {
+                "tools": { "listChanged": false },
+                "prompts": { "listChanged": false },
+            },
+            "serverInfo": { "name": self.name, "version": self.version },
+        })
+    }
+
+    fn list_tools(&self) -> Value {
+        let tools: Vec<Value> = self
+            .agents
+            .iter()
+            .map(|(name, agent)| {
+                json!({
+                    "name": name,
+                    "description": format!("{}: {}", agent.name, agent.description),
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "task": { "type": "string", "description": "What the agent should do" },
+                            "expected_output": { "type": "string", "description": "Optional description of the expected result" },
+                        },
+                        "required": ["task"],
+                    },
+                })
+            })
+            .collect();
+        json!({ "tools": tools })
+    }
+
+    async fn call_tool(&self, params: &Value) -> std::result::Result<Value, (i64, String)> {
+        let (agent, arguments) = self.resolve(params, "arguments")?;
+        let description = arguments
+            .get("task")
+            .and_then(|t| t.as_str())
+            .ok_or_else(|| (INVALID_PARAMS, "Missing string argument 'task'".to_string()))?;
+        let expected_output = arguments.get("expected_output").and_then(|e| e.as_str()).map(str::to_string);
+
+        let response = agent.execute_task(Task::new(description.to_string(), expected_output)).await;
+        // Agent failures are tool results, not protocol errors, so the host's model can see them
+        let text = if response.success {
+            response.content
+        } else {
+            response.error.unwrap_or_else(|| "Agent failed".to_string())
+        };
+        Ok(json!({
+            "content": [{ "type": "text", "text": text }],
+            "isError": !response.success,
+        }))
+    }
+
+    fn list_prompts(&self) -> Value {
+        let prompts: Vec<Value> = self
+            .agents
+            .iter()
+            .map(|(name, agent)| {
+                json!({
+                    "name": name,
+                    "description": format!("Instructions of agent {}", agent.name),
+                    "arguments": [
+                        { "name": "task", "description": "What the agent should do", "required": true },
+                    ],
+                })
+            })
+            .collect();
+        json!({ "prompts": prompts })
+    }
+
+    fn get_prompt(&self, params: &Value) -> std::result::Result<Value, (i64, String)> {
+        let (agent, arguments) = self.resolve(params, "arguments")?;
+        let description = arguments
+            .get("task")
+            .and_then(|t| t.as_str())
+            .ok_or_else(|| (INVALID_PARAMS, "Missing argument 'task'".to_string()))?;
+
+        // MCP prompts have no system role, so the instructions lead the user message
+        let task = Task::new(description.to_string(), None);
+        let text = format!("{}\n\n{}", agent.build_system_prompt(), agent.build_task_prompt(&task));
+        Ok(json!({
+            "description": agent.description,
+            "messages": [{ "role": "user", "content": { "type": "text", "text": text } }],
+        }))
+    }
+
+    fn resolve<'a>(&self, params: &'a Value, arguments_key: &str) -> std::result::Result<(&Arc<Agent>, &'a Value), (i64, String)> {
+        let name = params
+            .get("name")
+            .and_then(|n| n.as_str())
+            .ok_or_else(|| (INVALID_PARAMS, "Missing 'name'".to_string()))?;
+        let agent = self
+            .agents
+            .get(name)
+            .ok_or_else(|| (INVALID_PARAMS, format!("Unknown agent: {}", name)))?;
+        Ok((agent, params.get(arguments_key).unwrap_or(&Value::Null)))
+    }
+}
+
+fn error_reply(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
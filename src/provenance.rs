@@ -0,0 +1,166 @@
+use crate::audit::sha256_hex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::OnceLock;
+
+/// Where and when a tool result was produced, recorded on its `ToolCall`.
+///
+/// `content_hash` covers the tool name, parameters and result as recorded (after
+/// redaction). With a signer configured, `signature` covers the hash together
+/// with the other fields, so a consumer holding the key can check that a fact
+/// came from this tool execution and was not edited afterwards.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolProvenance {
+    /// Version the tool reports, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_version: Option<String>,
+    /// Host the tool ran on
+    pub host: String,
+    /// When the call completed
+    pub completed_at: chrono::DateTime<chrono::Utc>,
+    /// SHA-256 of the tool name, parameters and result
+    pub content_hash: String,
+    /// Hex signature over the fields above
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// Identifies the key that produced `signature`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<String>,
+}
+
+impl ToolProvenance {
+    pub fn new(tool_name: &str, parameters: &str, result: &str, tool_version: Option<String>) -> Self {
+        Self {
+            tool_version,
+            host: host_name().to_string(),
+            completed_at: chrono::Utc::now(),
+            content_hash: content_hash(tool_name, parameters, result),
+            signature: None,
+            key_id: None,
+        }
+    }
+
+    pub fn sign(&mut self, signer: &dyn ProvenanceSigner) {
+        self.signature = Some(signer.sign(&self.signed_payload()));
+        self.key_id = Some(signer.key_id().to_string());
+    }
+
+    /// Check the hash against the recorded call and, given a signer, the signature
+    pub fn verify(
+        &self,
+        tool_name: &str,
+        parameters: &str,
+        result: &str,
+        signer: Option<&dyn ProvenanceSigner>,
+    ) -> Result<(), String> {
+        if content_hash(tool_name, parameters, result) != self.content_hash {
+            return Err(format!("Content of the '{}' call does not match its provenance hash", tool_name));
+        }
+        let Some(signer) = signer else {
+            return Ok(());
+        };
+        let signature = self
+            .signature
+            .as_deref()
+            .ok_or_else(|| format!("Provenance of the '{}' call is not signed", tool_name))?;
+        if self.key_id.as_deref() != Some(signer.key_id()) {
+            return Err(format!(
+                "Provenance of the '{}' call was signed with key {:?}, not '{}'",
+                tool_name,
+                self.key_id,
+                signer.key_id()
+            ));
+        }
+        if !signer.verify(&self.signed_payload(), signature) {
+            return Err(format!("Provenance signature of the '{}' call is invalid", tool_name));
+        }
+        Ok(())
+    }
+
+    fn signed_payload(&self) -> Vec<u8> {
+        serde_json::json!({
+            "tool_version": self.tool_version,
+            "host": self.host,
+            "completed_at": self.completed_at,
+            "content_hash": self.content_hash,
+        })
+        .to_string()
+        .into_bytes()
+    }
+}
+
+/// Signs provenance records; implement it to sign with a KMS or asymmetric key
+pub trait ProvenanceSigner: Send + Sync {
+    /// Recorded next to each signature so consumers can pick the right key
+    fn key_id(&self) -> &str;
+
+    /// Hex signature of `payload`
+    fn sign(&self, payload: &[u8]) -> String;
+
+    fn verify(&self, payload: &[u8], signature: &str) -> bool {
+        constant_time_eq(self.sign(payload).as_bytes(), signature.as_bytes())
+    }
+}
+
+/// HMAC-SHA256 with a shared secret
+pub struct HmacSigner {
+    key_id: String,
+    key: Vec<u8>,
+}
+
+impl HmacSigner {
+    pub fn new(key_id: &str, key: impl Into<Vec<u8>>) -> Self {
+        Self { key_id: key_id.to_string(), key: key.into() }
+    }
+}
+
+impl ProvenanceSigner for HmacSigner {
+    fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    fn sign(&self, payload: &[u8]) -> String {
+        hmac_sha256(&self.key, payload).iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+fn content_hash(tool_name: &str, parameters: &str, result: &str) -> String {
+    let content = serde_json::json!({ "tool": tool_name, "parameters": parameters, "result": result });
+    sha256_hex(content.to_string().as_bytes())
+}
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    const BLOCK_SIZE: usize = 64;
+    let mut block = [0u8; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner = Sha256::new();
+    inner.update(block.map(|b| b ^ 0x36));
+    inner.update(message);
+    let mut outer = Sha256::new();
+    outer.update(block.map(|b| b ^ 0x5c));
+    outer.update(inner.finalize());
+    outer.finalize().into()
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Name of this machine, looked up once
+fn host_name() -> &'static str {
+    static HOST: OnceLock<String> = OnceLock::new();
+    HOST.get_or_init(|| {
+        std::env::var("HOSTNAME")
+            .ok()
+            .or_else(|| std::env::var("COMPUTERNAME").ok())
+            .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| "unknown".to_string())
+    })
+}
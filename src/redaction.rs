@@ -0,0 +1,187 @@
+use regex::{Captures, Regex};
+use std::borrow::Cow;
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// What a redacted secret is replaced with
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Well-known credential formats. A named `secret` group limits the redaction to
+/// that part of the match, so `api_key=...` keeps its key name.
+const BUILTIN_PATTERNS: &[&str] = &[
+    // OpenAI, Anthropic and similar `sk-` keys
+    r"\bsk-[A-Za-z0-9_-]{20,}",
+    // AWS access key ids
+    r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b",
+    // GitHub tokens
+    r"\bgh[pousr]_[A-Za-z0-9]{36,}",
+    // Slack tokens
+    r"\bxox[abprs]-[A-Za-z0-9-]{10,}",
+    // Hugging Face tokens
+    r"\bhf_[A-Za-z0-9]{30,}",
+    // Google API keys
+    r"\bAIza[0-9A-Za-z_-]{35}",
+    // JSON web tokens
+    r"\beyJ[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+",
+    // Authorization headers
+    r"(?i)\bbearer\s+(?P<secret>[A-Za-z0-9._~+/-]{16,}=*)",
+    // key=value and "key": "value" assignments of secret-looking names
+    r#"(?i)\b(?:api[_-]?key|secret|token|password|passwd|access[_-]?key)\b["']?\s*[:=]\s*["']?(?P<secret>[^\s"',;}]{8,})"#,
+];
+
+/// Candidates for the entropy check: long runs of key-like characters
+const ENTROPY_CANDIDATE: &str = r"[A-Za-z0-9+/_=-]{24,}";
+
+/// Bits per character above which a candidate counts as random. Hex digests stay
+/// below it (at most 4 bits), random base64/alphanumeric keys land above.
+const DEFAULT_ENTROPY_THRESHOLD: f64 = 4.2;
+
+/// Scrubs API keys and tokens from text before it is logged or persisted: known
+/// credential formats, user-supplied patterns and, optionally, high-entropy strings.
+///
+/// Agents apply their redactor to persisted run records and recorded tool calls,
+/// and to the tool errors they log. `Redactor::global()` is used unless an agent
+/// is given its own with `Agent::with_redactor`.
+#[derive(Debug)]
+pub struct Redactor {
+    patterns: RwLock<Vec<Regex>>,
+    entropy_candidate: Regex,
+    /// None disables the entropy heuristic
+    entropy_threshold: Option<f64>,
+}
+
+impl Redactor {
+    /// Built-in patterns plus the entropy heuristic
+    pub fn new() -> Self {
+        Self {
+            patterns: RwLock::new(
+                BUILTIN_PATTERNS
+                    .iter()
+                    .map(|pattern| Regex::new(pattern).expect("built-in redaction pattern"))
+                    .collect(),
+            ),
+            entropy_candidate: Regex::new(ENTROPY_CANDIDATE).expect("entropy candidate pattern"),
+            entropy_threshold: Some(DEFAULT_ENTROPY_THRESHOLD),
+        }
+    }
+
+    /// Redactor shared by every agent that wasn't given its own
+    pub fn global() -> Arc<Redactor> {
+        static GLOBAL: OnceLock<Arc<Redactor>> = OnceLock::new();
+        GLOBAL.get_or_init(|| Arc::new(Redactor::new())).clone()
+    }
+
+    /// Also redact matches of `pattern`; a `(?P<secret>...)` group limits it to that part
+    pub fn with_pattern(self, pattern: &str) -> Result<Self, String> {
+        self.add_pattern(pattern)?;
+        Ok(self)
+    }
+
+    /// Add a pattern to a redactor already in use, e.g. the global one
+    pub fn add_pattern(&self, pattern: &str) -> Result<(), String> {
+        let regex = Regex::new(pattern).map_err(|e| format!("Invalid redaction pattern '{}': {}", pattern, e))?;
+        self.patterns.write().unwrap().push(regex);
+        Ok(())
+    }
+
+    pub fn with_entropy_threshold(mut self, bits_per_char: f64) -> Self {
+        self.entropy_threshold = Some(bits_per_char);
+        self
+    }
+
+    /// Only redact pattern matches
+    pub fn without_entropy_check(mut self) -> Self {
+        self.entropy_threshold = None;
+        self
+    }
+
+    /// `text` with every secret replaced by `[REDACTED]`; borrowed when nothing matched
+    pub fn redact<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut redacted = Cow::Borrowed(text);
+        for pattern in self.patterns.read().unwrap().iter() {
+            let replaced = match pattern.replace_all(&redacted, replace_secret) {
+                Cow::Owned(replaced) => replaced,
+                Cow::Borrowed(_) => continue,
+            };
+            redacted = Cow::Owned(replaced);
+        }
+        if let Some(threshold) = self.entropy_threshold {
+            let replaced = self.entropy_candidate.replace_all(&redacted, |caps: &Captures| {
+                let candidate = &caps[0];
+                if candidate != REDACTED && looks_random(candidate, threshold) {
+                    REDACTED.to_string()
+                } else {
+                    candidate.to_string()
+                }
+            });
+            if replaced != redacted {
+                redacted = Cow::Owned(replaced.into_owned());
+            }
+        }
+        redacted
+    }
+
+    /// Redact in place
+    pub fn redact_string(&self, text: &mut String) {
+        let redacted = match self.redact(text) {
+            Cow::Owned(redacted) => redacted,
+            Cow::Borrowed(_) => return,
+        };
+        *text = redacted;
+    }
+
+    /// Redact every string in a JSON value in place; object keys are kept
+    pub fn redact_value(&self, value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::String(text) => self.redact_string(text),
+            serde_json::Value::Array(items) => items.iter_mut().for_each(|item| self.redact_value(item)),
+            serde_json::Value::Object(object) => object.values_mut().for_each(|item| self.redact_value(item)),
+            _ => {}
+        }
+    }
+}
+
+impl Default for Redactor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Redact `text` with the global redactor, for log lines
+pub fn redact(text: &str) -> Cow<'_, str> {
+    Redactor::global().redact(text)
+}
+
+fn replace_secret(caps: &Captures) -> String {
+    let whole = caps.get(0).expect("match");
+    match caps.name("secret") {
+        Some(secret) => {
+            let start = secret.start() - whole.start();
+            let end = secret.end() - whole.start();
+            format!("{}{}{}", &whole.as_str()[..start], REDACTED, &whole.as_str()[end..])
+        }
+        None => REDACTED.to_string(),
+    }
+}
+
+/// Mixed letters and digits with a Shannon entropy above `threshold` bits per character
+fn looks_random(candidate: &str, threshold: f64) -> bool {
+    let has_letter = candidate.chars().any(|c| c.is_ascii_alphabetic());
+    let has_digit = candidate.chars().any(|c| c.is_ascii_digit());
+    has_letter && has_digit && shannon_entropy(candidate) >= threshold
+}
+
+fn shannon_entropy(text: &str) -> f64 {
+    let mut counts = [0usize; 256];
+    for byte in text.bytes() {
+        counts[byte as usize] += 1;
+    }
+    let len = text.len() as f64;
+    counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
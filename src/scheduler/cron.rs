@@ -0,0 +1,196 @@
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Datelike, Duration, TimeZone, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Standard five-field cron expression: minute hour day-of-month month day-of-week.
+/// Supports `*`, numbers, ranges (`1-5`), steps (`*/15`, `1-30/5`) and lists (`1,15,30`).
+/// Day-of-week uses 0-6 with Sunday as 0 (7 is also accepted as Sunday). Times are UTC.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CronExpression {
+    source: String,
+    minutes: BTreeSet<u32>,
+    hours: BTreeSet<u32>,
+    days_of_month: BTreeSet<u32>,
+    months: BTreeSet<u32>,
+    days_of_week: BTreeSet<u32>,
+    day_of_month_restricted: bool,
+    day_of_week_restricted: bool,
+}
+
+impl CronExpression {
+    pub fn parse(expression: &str) -> Result<Self> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(anyhow!(
+                "Cron expression must have 5 fields (minute hour day month weekday), got {}",
+                fields.len()
+            ));
+        }
+
+        let mut days_of_week = parse_field(fields[4], 0, 7, "day-of-week")?;
+        // 7 is an alias for Sunday
+        if days_of_week.remove(&7) {
+            days_of_week.insert(0);
+        }
+
+        Ok(Self {
+            source: expression.to_string(),
+            minutes: parse_field(fields[0], 0, 59, "minute")?,
+            hours: parse_field(fields[1], 0, 23, "hour")?,
+            days_of_month: parse_field(fields[2], 1, 31, "day-of-month")?,
+            months: parse_field(fields[3], 1, 12, "month")?,
+            days_of_week,
+            day_of_month_restricted: fields[2] != "*",
+            day_of_week_restricted: fields[4] != "*",
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    fn matches_day(&self, time: &DateTime<Utc>) -> bool {
+        let dom = self.days_of_month.contains(&time.day());
+        let dow = self.days_of_week.contains(&time.weekday().num_days_from_sunday());
+        // Classic cron semantics: when both day fields are restricted, either may match
+        match (self.day_of_month_restricted, self.day_of_week_restricted) {
+            (true, true) => dom || dow,
+            (true, false) => dom,
+            (false, true) => dow,
+            (false, false) => true,
+        }
+    }
+
+    /// Next firing time strictly after `after`, or None if none exists within ~4 years
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let start = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let mut time = start;
+        let limit = start + Duration::days(366 * 4);
+
+        while time < limit {
+            if !self.months.contains(&time.month()) {
+                // Jump to the first minute of the next month
+                let (year, month) = if time.month() == 12 { (time.year() + 1, 1) } else { (time.year(), time.month() + 1) };
+                time = Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).single()?;
+                continue;
+            }
+            if !self.matches_day(&time) {
+                time = (time + Duration::days(1)).with_hour(0)?.with_minute(0)?;
+                continue;
+            }
+            if !self.hours.contains(&time.hour()) {
+                time = (time + Duration::hours(1)).with_minute(0)?;
+                continue;
+            }
+            if !self.minutes.contains(&time.minute()) {
+                time += Duration::minutes(1);
+                continue;
+            }
+            return Some(time);
+        }
+
+        None
+    }
+}
+
+impl std::fmt::Display for CronExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+fn parse_field(field: &str, min: u32, max: u32, name: &str) -> Result<BTreeSet<u32>> {
+    let mut values = BTreeSet::new();
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step.parse()
+                    .map_err(|_| anyhow!("Invalid step '{}' in {} field", step, name))?;
+                if step == 0 {
+                    return Err(anyhow!("Step cannot be zero in {} field", name));
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (parse_value(start, min, max, name)?, parse_value(end, min, max, name)?)
+        } else {
+            let value = parse_value(range, min, max, name)?;
+            // "5/10" means starting at 5, every 10
+            if step > 1 { (value, max) } else { (value, value) }
+        };
+
+        if start > end {
+            return Err(anyhow!("Invalid range {}-{} in {} field", start, end, name));
+        }
+
+        values.extend((start..=end).step_by(step as usize));
+    }
+
+    Ok(values)
+}
+
+fn parse_value(value: &str, min: u32, max: u32, name: &str) -> Result<u32> {
+    let parsed: u32 = value.parse()
+        .map_err(|_| anyhow!("Invalid value '{}' in {} field", value, name))?;
+    if parsed < min || parsed > max {
+        return Err(anyhow!("Value {} out of range {}-{} in {} field", parsed, min, max, name));
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, hour, minute, 0).unwrap()
+    }
+
+    fn next(expression: &str, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        CronExpression::parse(expression).unwrap().next_after(after)
+    }
+
+    #[test]
+    fn steps_fire_strictly_after() {
+        assert_eq!(next("*/15 * * * *", at(2026, 10, 16, 10, 7)), Some(at(2026, 10, 16, 10, 15)));
+        assert_eq!(next("*/15 * * * *", at(2026, 10, 16, 10, 15) + Duration::seconds(30)), Some(at(2026, 10, 16, 10, 30)));
+        assert_eq!(next("5/20 * * * *", at(2026, 10, 16, 10, 30)), Some(at(2026, 10, 16, 10, 45)));
+    }
+
+    #[test]
+    fn weekday_ranges_skip_to_next_week() {
+        // 2026-10-16 is a Friday
+        assert_eq!(next("0 9 * * 1-5", at(2026, 10, 16, 10, 0)), Some(at(2026, 10, 19, 9, 0)));
+        // 7 is Sunday
+        assert_eq!(next("0 12 * * 7", at(2026, 10, 16, 10, 0)), Some(at(2026, 10, 18, 12, 0)));
+    }
+
+    #[test]
+    fn restricted_day_fields_match_either() {
+        assert_eq!(next("0 0 1 * 5", at(2026, 10, 16, 0, 0)), Some(at(2026, 10, 23, 0, 0)));
+        // Sunday 2026-11-01 matches the day of month
+        assert_eq!(next("0 0 1 * 5", at(2026, 10, 30, 0, 0)), Some(at(2026, 11, 1, 0, 0)));
+    }
+
+    #[test]
+    fn months_roll_over_the_year() {
+        assert_eq!(next("0 0 1 1 *", at(2026, 6, 1, 0, 0)), Some(at(2027, 1, 1, 0, 0)));
+        assert_eq!(next("30 8 29 2 *", at(2026, 3, 1, 0, 0)), Some(at(2028, 2, 29, 8, 30)));
+        assert_eq!(next("0 0 30 2 *", at(2026, 1, 1, 0, 0)), None);
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        for expression in ["* * * *", "60 * * * *", "* 24 * * *", "*/0 * * * *", "5-1 * * * *", "a * * * *", "* * 0 * *", "* * * 13 *", "* * * * 8"] {
+            assert!(CronExpression::parse(expression).is_err(), "{} should be rejected", expression);
+        }
+        assert_eq!(CronExpression::parse("0 9 * * 1-5").unwrap().to_string(), "0 9 * * 1-5");
+    }
+}
@@ -0,0 +1,5 @@
+pub mod cron;
+pub mod scheduler;
+
+pub use cron::CronExpression;
+pub use scheduler::{JobStatus, OverlapPolicy, Schedule, ScheduledJob, Scheduler};
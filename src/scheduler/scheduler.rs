@@ -0,0 +1,327 @@
+use crate::agent::agent::{Agent, AgentResponse};
+use crate::scheduler::cron::CronExpression;
+use crate::task::cancellation::CancellationToken;
+use crate::task::run_history::{RunRecord, RunStore};
+use crate::task::task::Task;
+use crate::telemetry::traced;
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// When a job fires
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Schedule {
+    /// Fire repeatedly with a fixed gap, starting one interval after registration
+    Interval(Duration),
+    /// Fire on a cron expression (UTC)
+    Cron(CronExpression),
+}
+
+impl Schedule {
+    /// Fails for a zero interval, which would fire continuously
+    pub fn every(interval: Duration) -> Result<Self> {
+        if interval.is_zero() {
+            bail!("Schedule interval must be greater than zero");
+        }
+        Ok(Schedule::Interval(interval))
+    }
+
+    pub fn cron(expression: &str) -> Result<Self> {
+        Ok(Schedule::Cron(CronExpression::parse(expression)?))
+    }
+
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            // A zero interval (only constructible directly) never fires
+            Schedule::Interval(interval) if interval.is_zero() => None,
+            Schedule::Interval(interval) => chrono::Duration::from_std(*interval).ok().map(|d| after + d),
+            Schedule::Cron(expression) => expression.next_after(after),
+        }
+    }
+}
+
+/// What to do when a job fires while its previous run is still in progress
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum OverlapPolicy {
+    /// Drop the new firing
+    Skip,
+    /// Run the new firing once the previous one has finished
+    Queue,
+    /// Run concurrently on a copy of the agent
+    Allow,
+}
+
+/// A recurring task bound to an agent
+#[derive(Debug, Clone)]
+pub struct ScheduledJob {
+    pub id: String,
+    pub name: String,
+    pub schedule: Schedule,
+    /// Template cloned (with a fresh id) for every run
+    pub task: Task,
+    pub overlap: OverlapPolicy,
+}
+
+impl ScheduledJob {
+    pub fn new(name: &str, schedule: Schedule, task: Task) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            schedule,
+            task,
+            overlap: OverlapPolicy::Skip,
+        }
+    }
+
+    pub fn with_overlap(mut self, overlap: OverlapPolicy) -> Self {
+        self.overlap = overlap;
+        self
+    }
+}
+
+/// Runtime statistics for a job
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JobStatus {
+    pub id: String,
+    pub name: String,
+    pub next_run: Option<DateTime<Utc>>,
+    pub last_run: Option<DateTime<Utc>>,
+    pub last_success: Option<bool>,
+    pub run_count: u64,
+    pub skipped_count: u64,
+    pub running: bool,
+}
+
+struct JobEntry {
+    job: ScheduledJob,
+    agent: Arc<tokio::sync::Mutex<Agent>>,
+    status: Arc<Mutex<JobStatus>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+/// Runs recurring tasks on agents and records every result in a run history store
+pub struct Scheduler {
+    jobs: Mutex<HashMap<String, JobEntry>>,
+    run_store: Option<Arc<dyn RunStore>>,
+    shutdown: CancellationToken,
+    started: AtomicBool,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self {
+            jobs: Mutex::new(HashMap::new()),
+            run_store: None,
+            shutdown: CancellationToken::new(),
+            started: AtomicBool::new(false),
+        }
+    }
+
+    /// Record every scheduled run in this store
+    pub fn with_run_store(mut self, store: Arc<dyn RunStore>) -> Self {
+        self.run_store = Some(store);
+        self
+    }
+
+    /// Register a job. If the scheduler is already running the job starts immediately.
+    pub fn add_job(&self, job: ScheduledJob, agent: Arc<tokio::sync::Mutex<Agent>>) -> String {
+        let job_id = job.id.clone();
+        let status = Arc::new(Mutex::new(JobStatus {
+            id: job.id.clone(),
+            name: job.name.clone(),
+            ..JobStatus::default()
+        }));
+
+        let mut entry = JobEntry {
+            job,
+            agent,
+            status,
+            handle: None,
+        };
+        if self.started.load(Ordering::SeqCst) {
+            entry.handle = Some(self.spawn_job(&entry));
+        }
+
+        self.jobs.lock().unwrap().insert(job_id.clone(), entry);
+        job_id
+    }
+
+    /// Stop and remove a job. Returns false if the id is unknown.
+    pub fn remove_job(&self, job_id: &str) -> bool {
+        match self.jobs.lock().unwrap().remove(job_id) {
+            Some(entry) => {
+                if let Some(handle) = entry.handle {
+                    handle.abort();
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Start firing all registered jobs
+    pub fn start(&self) {
+        if self.started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let mut jobs = self.jobs.lock().unwrap();
+        for entry in jobs.values_mut() {
+            if entry.handle.is_none() {
+                entry.handle = Some(self.spawn_job(entry));
+            }
+        }
+    }
+
+    /// Stop all job loops. Runs already in progress are left to finish.
+    pub fn stop(&self) {
+        self.shutdown.cancel();
+        let mut jobs = self.jobs.lock().unwrap();
+        for entry in jobs.values_mut() {
+            if let Some(handle) = entry.handle.take() {
+                handle.abort();
+            }
+        }
+    }
+
+    /// Stop all job loops, wait up to `timeout` for runs in progress to finish and
+    /// flush the run store. Returns false if runs were still going at the timeout.
+    pub async fn shutdown(&self, timeout: Duration) -> bool {
+        self.stop();
+        let deadline = tokio::time::Instant::now() + timeout;
+        let drained = loop {
+            if !self.list_jobs().iter().any(|job| job.running) {
+                break true;
+            }
+            if tokio::time::Instant::now() >= deadline {
+                break false;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        };
+        if let Some(store) = &self.run_store {
+            if let Err(e) = store.flush() {
+                eprintln!("Failed to flush scheduler run history: {}", e);
+            }
+        }
+        drained
+    }
+
+    pub fn job_status(&self, job_id: &str) -> Option<JobStatus> {
+        self.jobs.lock().unwrap()
+            .get(job_id)
+            .map(|entry| entry.status.lock().unwrap().clone())
+    }
+
+    pub fn list_jobs(&self) -> Vec<JobStatus> {
+        self.jobs.lock().unwrap()
+            .values()
+            .map(|entry| entry.status.lock().unwrap().clone())
+            .collect()
+    }
+
+    fn spawn_job(&self, entry: &JobEntry) -> JoinHandle<()> {
+        let job = entry.job.clone();
+        let agent = entry.agent.clone();
+        let status = entry.status.clone();
+        let run_store = self.run_store.clone();
+        let shutdown = self.shutdown.clone();
+
+        tokio::spawn(async move {
+            let running = Arc::new(AtomicBool::new(false));
+            let run_lock = Arc::new(tokio::sync::Mutex::new(()));
+
+            loop {
+                let now = Utc::now();
+                let next = match job.schedule.next_after(now) {
+                    Some(next) => next,
+                    None => break,
+                };
+                status.lock().unwrap().next_run = Some(next);
+
+                let delay = (next - now).to_std().unwrap_or(Duration::ZERO);
+                tokio::select! {
+                    _ = tokio::time::sleep(delay) => {}
+                    _ = shutdown.cancelled() => break,
+                }
+
+                // Claimed before spawning, so firings during a run are skipped rather than queued
+                if job.overlap == OverlapPolicy::Skip
+                    && running.compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst).is_err()
+                {
+                    status.lock().unwrap().skipped_count += 1;
+                    continue;
+                }
+
+                let job = job.clone();
+                let agent = agent.clone();
+                let status = status.clone();
+                let run_store = run_store.clone();
+                let running = running.clone();
+                let run_lock = run_lock.clone();
+
+                tokio::spawn(async move {
+                    // Queue and Skip serialize runs of the same job; Allow runs freely
+                    let _guard = match job.overlap {
+                        OverlapPolicy::Allow => None,
+                        OverlapPolicy::Skip | OverlapPolicy::Queue => Some(run_lock.lock().await),
+                    };
+                    status.lock().unwrap().running = true;
+
+                    let task = job.task.with_new_id();
+                    let (agent_id, agent_name, response) = traced!(
+                        run_once(&agent, task.clone(), job.overlap),
+                        "merco.scheduler.run",
+                        job = %job.name,
+                        task_id = %task.id
+                    ).await;
+
+                    if let Some(store) = &run_store {
+                        let mut record = RunRecord::new(&agent_id, &agent_name, &task, &response);
+                        record.redact(&crate::redaction::Redactor::global());
+                        if let Err(e) = store.save(record) {
+                            eprintln!("Failed to record scheduled run of '{}': {}", job.name, e);
+                        }
+                    }
+
+                    let mut status = status.lock().unwrap();
+                    status.running = false;
+                    status.last_run = Some(Utc::now());
+                    status.last_success = Some(response.success);
+                    status.run_count += 1;
+                    running.store(false, Ordering::SeqCst);
+                });
+            }
+        })
+    }
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Execute one firing of a job. Concurrent runs use a snapshot of the agent so
+/// they don't hold the agent lock for the whole call.
+async fn run_once(
+    agent: &Arc<tokio::sync::Mutex<Agent>>,
+    task: Task,
+    overlap: OverlapPolicy,
+) -> (String, String, AgentResponse) {
+    match overlap {
+        OverlapPolicy::Allow => {
+            let mut snapshot = agent.lock().await.clone();
+            let response = snapshot.call(task).await;
+            (snapshot.id.clone(), snapshot.name.clone(), response)
+        }
+        OverlapPolicy::Skip | OverlapPolicy::Queue => {
+            let mut agent = agent.lock().await;
+            let response = agent.call(task).await;
+            (agent.id.clone(), agent.name.clone(), response)
+        }
+    }
+}
@@ -0,0 +1,314 @@
+use crate::agent::agent::{Agent, AgentResponse};
+use crate::agent::sse::{sse_stream, SseOptions};
+use crate::agent::streaming::{StreamingChunk, StreamingHandler};
+use crate::task::cancellation::CancellationToken;
+use crate::task::task::Task;
+use axum::body::Body;
+use axum::extract::{Path, Request, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Decides whether a request may proceed, e.g. by checking a bearer token.
+/// `Err` carries the message returned with `401 Unauthorized`.
+pub type AuthHook = Arc<dyn Fn(&HeaderMap) -> Result<(), String> + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// A task submitted over HTTP
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskRecord {
+    pub task_id: String,
+    pub agent_id: String,
+    pub status: TaskStatus,
+    pub submitted_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub response: Option<AgentResponse>,
+    #[serde(skip)]
+    cancel_token: CancellationToken,
+}
+
+/// Body of `POST /agents/{id}/tasks` and `POST /agents/{id}/stream`
+#[derive(Debug, Clone, Deserialize)]
+pub struct TaskRequest {
+    pub description: String,
+    pub expected_output: Option<String>,
+    pub inputs: Option<Value>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl TaskRequest {
+    fn into_task(self) -> Task {
+        let mut task = Task::new(self.description, self.expected_output).with_tags(self.tags);
+        if let Some(inputs) = self.inputs {
+            task = task.with_inputs(inputs);
+        }
+        task
+    }
+}
+
+/// How long finished tasks stay retrievable, and how many are kept at most
+#[derive(Debug, Clone, Copy)]
+struct Retention {
+    ttl: Duration,
+    max_finished: usize,
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Self {
+            ttl: Duration::from_secs(3600),
+            max_finished: 1000,
+        }
+    }
+}
+
+struct ServerState {
+    agents: HashMap<String, Arc<Agent>>,
+    tasks: Mutex<HashMap<String, TaskRecord>>,
+    retention: Retention,
+    auth: Option<AuthHook>,
+}
+
+impl ServerState {
+    /// Drop finished tasks past their retention, then the oldest beyond the cap.
+    /// Running tasks are always kept.
+    fn prune(&self, tasks: &mut HashMap<String, TaskRecord>) {
+        if let Ok(ttl) = chrono::Duration::from_std(self.retention.ttl) {
+            let cutoff = Utc::now() - ttl;
+            tasks.retain(|_, record| !record.finished_at.is_some_and(|finished| finished <= cutoff));
+        }
+        let mut finished: Vec<(DateTime<Utc>, String)> = tasks
+            .values()
+            .filter_map(|record| record.finished_at.map(|at| (at, record.task_id.clone())))
+            .collect();
+        if finished.len() > self.retention.max_finished {
+            finished.sort();
+            for (_, task_id) in &finished[..finished.len() - self.retention.max_finished] {
+                tasks.remove(task_id);
+            }
+        }
+    }
+}
+
+/// HTTP service exposing agents:
+///
+/// - `GET /agents` lists the agents
+/// - `POST /agents/{id}/tasks` starts a task in the background and returns its id
+/// - `GET /tasks/{task_id}` returns the task's status and, once done, its response
+/// - `POST /tasks/{task_id}/cancel` cancels a running task
+/// - `POST /agents/{id}/stream` runs a task and streams it as server-sent events
+///
+/// Requests must pass the `with_auth` hook; serving without one has to be asked
+/// for with `allow_unauthenticated`.
+pub struct AgentServer {
+    agents: HashMap<String, Arc<Agent>>,
+    auth: Option<AuthHook>,
+    allow_unauthenticated: bool,
+    retention: Retention,
+}
+
+impl AgentServer {
+    pub fn new() -> Self {
+        Self {
+            agents: HashMap::new(),
+            auth: None,
+            allow_unauthenticated: false,
+            retention: Retention::default(),
+        }
+    }
+
+    /// Serve `agent` under `/agents/{id}`
+    pub fn add_agent(mut self, id: &str, agent: Agent) -> Self {
+        self.agents.insert(id.to_string(), Arc::new(agent));
+        self
+    }
+
+    pub fn with_auth<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&HeaderMap) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.auth = Some(Arc::new(hook));
+        self
+    }
+
+    /// Serve without an auth hook, e.g. behind a proxy that authenticates
+    pub fn allow_unauthenticated(mut self) -> Self {
+        self.allow_unauthenticated = true;
+        self
+    }
+
+    /// Keep finished tasks for `ttl` and at most `max_finished` of them (1 hour and
+    /// 1000 by default); older ones answer `404`
+    pub fn with_task_retention(mut self, ttl: Duration, max_finished: usize) -> Self {
+        self.retention = Retention { ttl, max_finished };
+        self
+    }
+
+    /// The routes, for mounting into an existing axum application
+    pub fn router(self) -> Router {
+        if self.auth.is_none() && !self.allow_unauthenticated {
+            eprintln!("AgentServer: no auth hook set; every request will be accepted");
+        }
+        let state = Arc::new(ServerState {
+            agents: self.agents,
+            tasks: Mutex::new(HashMap::new()),
+            retention: self.retention,
+            auth: self.auth,
+        });
+        Router::new()
+            .route("/agents", get(list_agents))
+            .route("/agents/:id/tasks", post(submit_task))
+            .route("/agents/:id/stream", post(stream_task))
+            .route("/tasks/:task_id", get(get_task))
+            .route("/tasks/:task_id/cancel", post(cancel_task))
+            .layer(middleware::from_fn_with_state(state.clone(), authorize))
+            .with_state(state)
+    }
+
+    /// Listen on `addr` until the process stops
+    pub async fn serve(self, addr: SocketAddr) -> anyhow::Result<()> {
+        if self.auth.is_none() && !self.allow_unauthenticated {
+            anyhow::bail!("Refusing to serve without auth; set with_auth or allow_unauthenticated");
+        }
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        axum::serve(listener, self.router()).await?;
+        Ok(())
+    }
+}
+
+impl Default for AgentServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn error(status: StatusCode, message: &str) -> Response {
+    (status, Json(json!({ "error": message }))).into_response()
+}
+
+async fn authorize(State(state): State<Arc<ServerState>>, request: Request, next: Next) -> Response {
+    if let Some(auth) = &state.auth {
+        if let Err(message) = auth(request.headers()) {
+            return error(StatusCode::UNAUTHORIZED, &message);
+        }
+    }
+    next.run(request).await
+}
+
+async fn list_agents(State(state): State<Arc<ServerState>>) -> Json<Value> {
+    let agents: Vec<Value> = state
+        .agents
+        .iter()
+        .map(|(id, agent)| json!({ "id": id, "name": agent.name, "description": agent.description }))
+        .collect();
+    Json(json!({ "agents": agents }))
+}
+
+async fn submit_task(
+    State(state): State<Arc<ServerState>>,
+    Path(agent_id): Path<String>,
+    Json(request): Json<TaskRequest>,
+) -> Response {
+    let agent = match state.agents.get(&agent_id) {
+        Some(agent) => Arc::clone(agent),
+        None => return error(StatusCode::NOT_FOUND, &format!("Unknown agent: {}", agent_id)),
+    };
+    let task = request.into_task();
+    let task_id = task.id.clone();
+    let record = TaskRecord {
+        task_id: task_id.clone(),
+        agent_id,
+        status: TaskStatus::Running,
+        submitted_at: Utc::now(),
+        finished_at: None,
+        response: None,
+        cancel_token: task.cancel_handle(),
+    };
+    {
+        let mut tasks = state.tasks.lock().unwrap();
+        state.prune(&mut tasks);
+        tasks.insert(task_id.clone(), record.clone());
+    }
+
+    let background = state.clone();
+    let id = task_id.clone();
+    tokio::spawn(async move {
+        let response = agent.call_concurrent(task).await;
+        if let Some(record) = background.tasks.lock().unwrap().get_mut(&id) {
+            record.status = if response.cancelled {
+                TaskStatus::Cancelled
+            } else if response.success {
+                TaskStatus::Completed
+            } else {
+                TaskStatus::Failed
+            };
+            record.finished_at = Some(Utc::now());
+            record.response = Some(response);
+        }
+    });
+
+    (StatusCode::ACCEPTED, Json(record)).into_response()
+}
+
+async fn get_task(State(state): State<Arc<ServerState>>, Path(task_id): Path<String>) -> Response {
+    let mut tasks = state.tasks.lock().unwrap();
+    state.prune(&mut tasks);
+    match tasks.get(&task_id) {
+        Some(record) => Json(record.clone()).into_response(),
+        None => error(StatusCode::NOT_FOUND, &format!("Unknown task: {}", task_id)),
+    }
+}
+
+async fn cancel_task(State(state): State<Arc<ServerState>>, Path(task_id): Path<String>) -> Response {
+    match state.tasks.lock().unwrap().get(&task_id) {
+        Some(record) => {
+            record.cancel_token.cancel();
+            (StatusCode::ACCEPTED, Json(record.clone())).into_response()
+        }
+        None => error(StatusCode::NOT_FOUND, &format!("Unknown task: {}", task_id)),
+    }
+}
+
+/// Consumers read the SSE stream; nothing needs handling on the server side
+struct SilentHandler;
+
+impl StreamingHandler for SilentHandler {
+    fn handle_chunk(&self, _chunk: StreamingChunk) {}
+}
+
+async fn stream_task(
+    State(state): State<Arc<ServerState>>,
+    Path(agent_id): Path<String>,
+    Json(request): Json<TaskRequest>,
+) -> Response {
+    let Some(agent) = state.agents.get(&agent_id) else {
+        return error(StatusCode::NOT_FOUND, &format!("Unknown agent: {}", agent_id));
+    };
+    let chunks = agent.call_stream_with_handler(request.into_task(), SilentHandler).await;
+    let frames = sse_stream(chunks, SseOptions::default()).map(Ok::<_, std::convert::Infallible>);
+
+    Response::builder()
+        .header(header::CONTENT_TYPE, "text/event-stream")
+        .header(header::CACHE_CONTROL, "no-cache")
+        .body(Body::from_stream(frames))
+        .unwrap_or_else(|_| error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to build response"))
+}
@@ -0,0 +1,15 @@
+//! Hashes that stay the same across processes and releases, for keys that are
+//! persisted or must route consistently. Not for security; the `audit` feature's
+//! SHA-256 covers tamper evidence.
+
+/// 64-bit FNV-1a hash of `data`
+pub(crate) fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// `fnv1a` as 16 hex digits
+pub(crate) fn fnv1a_hex(data: &[u8]) -> String {
+    format!("{:016x}", fnv1a(data))
+}
@@ -0,0 +1,59 @@
+use crate::agent::agent::{AgentResponse, ToolCall};
+use crate::task::task::Task;
+use std::sync::Arc;
+
+pub type OnStartCallback = Arc<dyn Fn(&Task) + Send + Sync>;
+pub type OnToolCallCallback = Arc<dyn Fn(&ToolCall) + Send + Sync>;
+pub type OnRetryCallback = Arc<dyn Fn(usize, &str) + Send + Sync>;
+pub type OnCompleteCallback = Arc<dyn Fn(&AgentResponse) + Send + Sync>;
+
+/// Optional lifecycle hooks attached to a task.
+/// A lighter-weight alternative to implementing a full `StreamingHandler`.
+#[derive(Clone, Default)]
+pub struct TaskCallbacks {
+    /// Called once before the agent starts working on the task
+    pub on_start: Option<OnStartCallback>,
+    /// Called after every tool execution with its result
+    pub on_tool_call: Option<OnToolCallCallback>,
+    /// Called before a retry with the failed attempt number and the reason
+    pub on_retry: Option<OnRetryCallback>,
+    /// Called with the final response
+    pub on_complete: Option<OnCompleteCallback>,
+}
+
+impl TaskCallbacks {
+    pub fn notify_start(&self, task: &Task) {
+        if let Some(callback) = &self.on_start {
+            callback(task);
+        }
+    }
+
+    pub fn notify_tool_call(&self, tool_call: &ToolCall) {
+        if let Some(callback) = &self.on_tool_call {
+            callback(tool_call);
+        }
+    }
+
+    pub fn notify_retry(&self, attempt: usize, reason: &str) {
+        if let Some(callback) = &self.on_retry {
+            callback(attempt, reason);
+        }
+    }
+
+    pub fn notify_complete(&self, response: &AgentResponse) {
+        if let Some(callback) = &self.on_complete {
+            callback(response);
+        }
+    }
+}
+
+impl std::fmt::Debug for TaskCallbacks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TaskCallbacks")
+            .field("on_start", &self.on_start.is_some())
+            .field("on_tool_call", &self.on_tool_call.is_some())
+            .field("on_retry", &self.on_retry.is_some())
+            .field("on_complete", &self.on_complete.is_some())
+            .finish()
+    }
+}
@@ -0,0 +1,107 @@
+use crate::agent::agent::AgentResponse;
+use crate::task::queue::TaskQueue;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+/// Cooperative cancellation signal shared between a task and its submitter
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<CancellationState>,
+}
+
+#[derive(Debug, Default)]
+struct CancellationState {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation; wakes everything waiting on `cancelled()`
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        self.inner.notify.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Resolves once cancellation has been requested
+    pub async fn cancelled(&self) {
+        loop {
+            // Register before checking the flag so a cancel in between isn't missed
+            let notified = self.inner.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// Handle to a task submitted to an agent's queue
+#[derive(Clone)]
+pub struct TaskHandle {
+    task_id: String,
+    token: CancellationToken,
+    queue: Arc<TaskQueue>,
+    model_used: String,
+    temperature: f32,
+    output_format: String,
+}
+
+impl TaskHandle {
+    pub(crate) fn new(
+        task_id: String,
+        token: CancellationToken,
+        queue: Arc<TaskQueue>,
+        model_used: String,
+        temperature: f32,
+        output_format: String,
+    ) -> Self {
+        Self {
+            task_id,
+            token,
+            queue,
+            model_used,
+            temperature,
+            output_format,
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.task_id
+    }
+
+    /// Cancel the task. A task that hasn't started is dropped from the queue
+    /// and completed with a cancelled response; an in-flight task is aborted
+    /// at its next await point or before its next tool execution.
+    pub fn cancel(&self) {
+        self.token.cancel();
+
+        if let Some(task) = self.queue.remove_pending(&self.task_id) {
+            let mut response = AgentResponse::cancelled(
+                0,
+                self.model_used.clone(),
+                self.temperature,
+                self.output_format.clone(),
+            );
+            response.apply_task_context(&task);
+            self.queue.complete(&self.task_id, response);
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Wait for the task to finish and return its result
+    pub async fn result(&self) -> Option<AgentResponse> {
+        self.queue.wait_for_result(&self.task_id).await
+    }
+}
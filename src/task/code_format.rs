@@ -0,0 +1,114 @@
+use anyhow::{Result, anyhow};
+
+/// A fenced block found in a response
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeBlock {
+    /// Language tag after the opening fence, lowercased; empty when missing
+    pub language: String,
+    pub code: String,
+}
+
+/// All fenced code blocks in `output`, in order
+pub fn find_code_blocks(output: &str) -> Vec<CodeBlock> {
+    let mut blocks = Vec::new();
+    let mut lines = output.lines();
+    while let Some(line) = lines.next() {
+        let Some(tag) = line.trim_start().strip_prefix("```") else {
+            continue;
+        };
+        let mut code = Vec::new();
+        let mut closed = false;
+        for line in lines.by_ref() {
+            if line.trim() == "```" {
+                closed = true;
+                break;
+            }
+            code.push(line);
+        }
+        if closed {
+            blocks.push(CodeBlock {
+                language: tag.trim().to_lowercase(),
+                code: code.join("\n"),
+            });
+        }
+    }
+    blocks
+}
+
+/// Canonical name for common language tags and their aliases
+pub fn normalize_language(language: &str) -> String {
+    let language = language.trim().to_lowercase();
+    match language.as_str() {
+        "rs" => "rust",
+        "py" | "python3" => "python",
+        "js" | "node" => "javascript",
+        "ts" => "typescript",
+        "yml" => "yaml",
+        "sh" | "shell" | "zsh" => "bash",
+        "c++" | "cc" | "hpp" => "cpp",
+        "golang" => "go",
+        other => other,
+    }
+    .to_string()
+}
+
+/// Extract the code block for `language` from a response. Blocks tagged with another
+/// language are rejected; an untagged block, or a response without fences, is
+/// taken as-is.
+pub fn extract_code(output: &str, language: &str) -> Result<String> {
+    let expected = normalize_language(language);
+    let blocks = find_code_blocks(output);
+    if blocks.is_empty() {
+        if output.trim().is_empty() {
+            return Err(anyhow!("Output is empty"));
+        }
+        return Ok(output.trim().to_string());
+    }
+
+    if let Some(block) = blocks.iter().find(|b| normalize_language(&b.language) == expected) {
+        return Ok(block.code.clone());
+    }
+    if let Some(block) = blocks.iter().find(|b| b.language.is_empty()) {
+        return Ok(block.code.clone());
+    }
+    let found: Vec<&str> = blocks.iter().map(|b| b.language.as_str()).collect();
+    Err(anyhow!(
+        "Expected a ```{} code block, found blocks tagged: {}",
+        expected,
+        found.join(", ")
+    ))
+}
+
+/// Parse the code when a checker exists for its language; others pass unchecked.
+/// Rust and YAML are only checked with the `code-format` feature.
+pub fn check_syntax(code: &str, language: &str) -> Result<()> {
+    match normalize_language(language).as_str() {
+        #[cfg(feature = "code-format")]
+        "rust" => syn::parse_file(code)
+            .map(|_| ())
+            .map_err(|e| {
+                let start = e.span().start();
+                anyhow!("Rust syntax error at line {}, column {}: {}", start.line, start.column, e)
+            }),
+        "json" => serde_json::from_str::<serde_json::Value>(code)
+            .map(|_| ())
+            .map_err(|e| anyhow!("JSON syntax error: {}", e)),
+        #[cfg(feature = "code-format")]
+        "yaml" => serde_yaml::from_str::<serde_yaml::Value>(code)
+            .map(|_| ())
+            .map_err(|e| anyhow!("YAML syntax error: {}", e)),
+        _ => Ok(()),
+    }
+}
+
+/// Extract the code and, when requested, check its syntax
+pub fn validate_code(output: &str, language: &str, syntax_check: bool) -> Result<String> {
+    let code = extract_code(output, language)?;
+    if code.trim().is_empty() {
+        return Err(anyhow!("Code block is empty"));
+    }
+    if syntax_check {
+        check_syntax(&code, language)?;
+    }
+    Ok(code)
+}
@@ -0,0 +1,178 @@
+use anyhow::{Result, anyhow};
+use crate::task::task::strip_code_fence;
+
+// Column definition for CSV output
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CsvColumn {
+    pub name: String,
+    pub column_type: CsvColumnType,
+    pub description: Option<String>,
+}
+
+impl CsvColumn {
+    pub fn new(name: &str, column_type: CsvColumnType) -> Self {
+        Self {
+            name: name.to_string(),
+            column_type,
+            description: None,
+        }
+    }
+
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum CsvColumnType {
+    String,
+    Number,
+    Boolean,
+}
+
+/// Parse CSV text into rows of fields, honouring double-quoted fields
+pub fn parse_csv(input: &str, delimiter: char) -> Result<Vec<Vec<String>>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = strip_code_fence(input).chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            if c == '"' {
+                if chars.peek() == Some(&'"') {
+                    field.push('"');
+                    chars.next();
+                } else {
+                    in_quotes = false;
+                }
+            } else {
+                field.push(c);
+            }
+        } else if c == '"' && field.trim_start_matches(' ').is_empty() {
+            // Spaces after the delimiter don't stop a field from being quoted
+            field.clear();
+            in_quotes = true;
+        } else if c == delimiter {
+            row.push(std::mem::take(&mut field));
+        } else if c == '\n' || c == '\r' {
+            if c == '\r' && chars.peek() == Some(&'\n') {
+                chars.next();
+            }
+            row.push(std::mem::take(&mut field));
+            // Skip blank lines
+            if !(row.len() == 1 && row[0].trim().is_empty()) {
+                rows.push(std::mem::take(&mut row));
+            } else {
+                row.clear();
+            }
+        } else {
+            field.push(c);
+        }
+    }
+
+    if in_quotes {
+        return Err(anyhow!("Unterminated quoted field in CSV output"));
+    }
+
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    Ok(rows)
+}
+
+/// Validate CSV output against the column schema and return the data rows (header excluded)
+pub fn validate_csv(output: &str, columns: &[CsvColumn], delimiter: char) -> Result<Vec<Vec<String>>> {
+    let mut rows = parse_csv(output, delimiter)?;
+    if rows.is_empty() {
+        return Err(anyhow!("CSV output is empty"));
+    }
+
+    let header = rows.remove(0);
+    if header.len() != columns.len() {
+        return Err(anyhow!(
+            "CSV header has {} columns, expected {}",
+            header.len(),
+            columns.len()
+        ));
+    }
+
+    for (i, (actual, expected)) in header.iter().zip(columns.iter()).enumerate() {
+        if actual.trim() != expected.name {
+            return Err(anyhow!(
+                "CSV header column {} is '{}', expected '{}'",
+                i + 1,
+                actual.trim(),
+                expected.name
+            ));
+        }
+    }
+
+    for (row_index, row) in rows.iter().enumerate() {
+        // Row numbers are 1-based and account for the header line
+        let line = row_index + 2;
+        if row.len() != columns.len() {
+            return Err(anyhow!(
+                "CSV row {} has {} columns, expected {}",
+                line,
+                row.len(),
+                columns.len()
+            ));
+        }
+
+        for (value, column) in row.iter().zip(columns.iter()) {
+            validate_cell(value.trim(), column, line)?;
+        }
+    }
+
+    Ok(rows)
+}
+
+fn validate_cell(value: &str, column: &CsvColumn, line: usize) -> Result<()> {
+    match column.column_type {
+        CsvColumnType::String => Ok(()),
+        CsvColumnType::Number => {
+            if value.parse::<f64>().is_err() {
+                return Err(anyhow!(
+                    "CSV row {} column '{}' must be a number, got: '{}'",
+                    line,
+                    column.name,
+                    value
+                ));
+            }
+            Ok(())
+        }
+        CsvColumnType::Boolean => {
+            match value.to_lowercase().as_str() {
+                "true" | "false" => Ok(()),
+                _ => Err(anyhow!(
+                    "CSV row {} column '{}' must be a boolean, got: '{}'",
+                    line,
+                    column.name,
+                    value
+                )),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quoted_field_after_a_space() {
+        let rows = parse_csv("a, \"b, c\"\n", ',').unwrap();
+        assert_eq!(rows, vec![vec!["a".to_string(), "b, c".to_string()]]);
+    }
+
+    #[test]
+    fn quote_inside_an_unquoted_field_is_literal() {
+        let rows = parse_csv("a,b \"c\"", ',').unwrap();
+        assert_eq!(rows, vec![vec!["a".to_string(), "b \"c\"".to_string()]]);
+    }
+}
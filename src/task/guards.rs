@@ -0,0 +1,47 @@
+use futures::future::BoxFuture;
+use std::future::Future;
+use std::sync::Arc;
+
+pub type SyncGuardFn = Arc<dyn Fn(&str) -> Result<(), String> + Send + Sync>;
+pub type AsyncGuardFn = Arc<dyn Fn(String) -> BoxFuture<'static, Result<(), String>> + Send + Sync>;
+
+/// Business-rule check run on a task's output after format validation.
+/// A failing guard feeds its message into the correction-retry loop.
+#[derive(Clone)]
+pub enum TaskGuard {
+    Sync(SyncGuardFn),
+    Async(AsyncGuardFn),
+}
+
+impl TaskGuard {
+    pub fn from_fn<F>(guard: F) -> Self
+    where
+        F: Fn(&str) -> Result<(), String> + Send + Sync + 'static,
+    {
+        TaskGuard::Sync(Arc::new(guard))
+    }
+
+    pub fn from_async_fn<F, Fut>(guard: F) -> Self
+    where
+        F: Fn(String) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        TaskGuard::Async(Arc::new(move |output| Box::pin(guard(output))))
+    }
+
+    pub async fn check(&self, output: &str) -> Result<(), String> {
+        match self {
+            TaskGuard::Sync(guard) => guard(output),
+            TaskGuard::Async(guard) => guard(output.to_string()).await,
+        }
+    }
+}
+
+impl std::fmt::Debug for TaskGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaskGuard::Sync(_) => write!(f, "TaskGuard::Sync"),
+            TaskGuard::Async(_) => write!(f, "TaskGuard::Async"),
+        }
+    }
+}
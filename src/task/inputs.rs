@@ -0,0 +1,60 @@
+use serde_json::Value;
+
+tokio::task_local! {
+    static TASK_INPUTS: Option<Value>;
+}
+
+/// Structured inputs of the task whose tool call is currently executing.
+/// Tools can read this instead of having the model echo large payloads back as arguments.
+pub fn current_task_inputs() -> Option<Value> {
+    TASK_INPUTS.try_with(|inputs| inputs.clone()).ok().flatten()
+}
+
+/// Run a (synchronous) tool execution with the task's inputs available as ambient context
+pub fn with_task_inputs<F, R>(inputs: Option<Value>, f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    TASK_INPUTS.sync_scope(inputs, f)
+}
+
+/// Render `{{key}}` / `{{nested.key}}` placeholders from the inputs.
+/// Strings are inserted verbatim, other values as compact JSON; unknown keys are left untouched.
+pub fn render_template(template: &str, inputs: &Value) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        rendered.push_str(&rest[..start]);
+        let after_open = &rest[start + 2..];
+
+        match after_open.find("}}") {
+            Some(end) => {
+                let key = after_open[..end].trim();
+                match lookup(inputs, key) {
+                    Some(Value::String(s)) => rendered.push_str(s),
+                    Some(value) => rendered.push_str(&value.to_string()),
+                    None => rendered.push_str(&rest[start..start + 2 + end + 2]),
+                }
+                rest = &after_open[end + 2..];
+            }
+            None => {
+                rendered.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+
+    rendered.push_str(rest);
+    rendered
+}
+
+pub(crate) fn lookup<'a>(inputs: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(inputs, |value, segment| {
+        match value {
+            Value::Object(map) => map.get(segment),
+            Value::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => None,
+        }
+    })
+}
@@ -0,0 +1,186 @@
+use crate::agent::output_handler::OutputValidator;
+use crate::agent::role::OutputFormat;
+use serde_json::Value;
+#[cfg(feature = "language")]
+use whatlang::Lang;
+
+/// Responses shorter than this are not checked; detection is unreliable on a few words
+const MIN_DETECTION_CHARS: usize = 40;
+
+/// ISO 639-1 codes and their English names
+const LANGUAGES: &[(&str, &str)] = &[
+    ("en", "English"),
+    ("de", "German"),
+    ("fr", "French"),
+    ("es", "Spanish"),
+    ("it", "Italian"),
+    ("pt", "Portuguese"),
+    ("nl", "Dutch"),
+    ("tr", "Turkish"),
+    ("ru", "Russian"),
+    ("uk", "Ukrainian"),
+    ("pl", "Polish"),
+    ("cs", "Czech"),
+    ("sv", "Swedish"),
+    ("da", "Danish"),
+    ("fi", "Finnish"),
+    ("el", "Greek"),
+    ("hu", "Hungarian"),
+    ("ro", "Romanian"),
+    ("ar", "Arabic"),
+    ("he", "Hebrew"),
+    ("fa", "Persian"),
+    ("hi", "Hindi"),
+    ("zh", "Chinese"),
+    ("ja", "Japanese"),
+    ("ko", "Korean"),
+    ("vi", "Vietnamese"),
+    ("th", "Thai"),
+    ("id", "Indonesian"),
+];
+
+/// whatlang languages by ISO 639-1 code
+#[cfg(feature = "language")]
+const WHATLANG_CODES: &[(&str, Lang)] = &[
+    ("en", Lang::Eng),
+    ("de", Lang::Deu),
+    ("fr", Lang::Fra),
+    ("es", Lang::Spa),
+    ("it", Lang::Ita),
+    ("pt", Lang::Por),
+    ("nl", Lang::Nld),
+    ("tr", Lang::Tur),
+    ("ru", Lang::Rus),
+    ("uk", Lang::Ukr),
+    ("pl", Lang::Pol),
+    ("cs", Lang::Ces),
+    ("sv", Lang::Swe),
+    ("da", Lang::Dan),
+    ("fi", Lang::Fin),
+    ("el", Lang::Ell),
+    ("hu", Lang::Hun),
+    ("ro", Lang::Ron),
+    ("ar", Lang::Ara),
+    ("he", Lang::Heb),
+    ("fa", Lang::Pes),
+    ("hi", Lang::Hin),
+    ("zh", Lang::Cmn),
+    ("ja", Lang::Jpn),
+    ("ko", Lang::Kor),
+    ("vi", Lang::Vie),
+    ("th", Lang::Tha),
+    ("id", Lang::Ind),
+];
+
+/// Base language of a locale tag: "de-AT" and "de_AT" become "de"
+pub fn base_language(locale: &str) -> String {
+    locale
+        .split(['-', '_'])
+        .next()
+        .unwrap_or(locale)
+        .trim()
+        .to_lowercase()
+}
+
+/// English name of a language code, for prompts and errors
+pub fn language_name(code: &str) -> String {
+    let code = base_language(code);
+    LANGUAGES
+        .iter()
+        .find(|(iso, _)| *iso == code)
+        .map(|(_, name)| name.to_string())
+        .unwrap_or(code)
+}
+
+/// ISO 639-1 code of the language `text` is written in, when detection is reliable
+#[cfg(feature = "language")]
+pub fn detect_language(text: &str) -> Option<String> {
+    let info = whatlang::detect(text)?;
+    if !info.is_reliable() {
+        return None;
+    }
+    Some(
+        WHATLANG_CODES
+            .iter()
+            .find(|(_, lang)| *lang == info.lang())
+            .map(|(iso, _)| iso.to_string())
+            .unwrap_or_else(|| info.lang().code().to_string()),
+    )
+}
+
+/// Detection needs the `language` feature; without it nothing is detected and
+/// every output passes the language check
+#[cfg(not(feature = "language"))]
+pub fn detect_language(_text: &str) -> Option<String> {
+    None
+}
+
+/// Natural-language text of an output: string values of JSON, prose without code blocks
+fn prose(output: &str) -> String {
+    if let Ok(value) = serde_json::from_str::<Value>(crate::task::task::strip_code_fence(output)) {
+        let mut strings = Vec::new();
+        collect_strings(&value, &mut strings);
+        return strings.join(" ");
+    }
+    let mut text = Vec::new();
+    let mut in_code = false;
+    for line in output.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+        } else if !in_code {
+            text.push(line);
+        }
+    }
+    text.join("\n")
+}
+
+fn collect_strings(value: &Value, strings: &mut Vec<String>) {
+    match value {
+        Value::String(text) => strings.push(text.clone()),
+        Value::Array(items) => items.iter().for_each(|item| collect_strings(item, strings)),
+        Value::Object(object) => object.values().for_each(|item| collect_strings(item, strings)),
+        _ => {}
+    }
+}
+
+/// Check that `output` is written in `expected` (an ISO 639-1 code or locale such as "de-AT").
+/// Returns the detected language on mismatch; short or ambiguous text passes.
+pub fn check_language(output: &str, expected: &str) -> Result<(), String> {
+    let text = prose(output);
+    if text.trim().chars().count() < MIN_DETECTION_CHARS {
+        return Ok(());
+    }
+    match detect_language(&text) {
+        Some(detected) if detected != base_language(expected) => Err(detected),
+        _ => Ok(()),
+    }
+}
+
+/// Agent-wide language constraint, for use with `OutputHandler::with_validator`
+pub struct LanguageValidator {
+    language: String,
+}
+
+impl LanguageValidator {
+    pub fn new(language: &str) -> Self {
+        Self {
+            language: language.to_string(),
+        }
+    }
+}
+
+impl OutputValidator for LanguageValidator {
+    fn name(&self) -> &str {
+        "language"
+    }
+
+    fn validate(&self, output: &str, _format: &OutputFormat) -> Result<(), String> {
+        check_language(output, &self.language).map_err(|detected| {
+            format!(
+                "Response must be written in {}, but it is in {}",
+                language_name(&self.language),
+                language_name(&detected)
+            )
+        })
+    }
+}
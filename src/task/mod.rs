@@ -1 +1,15 @@
 pub mod task;
+pub mod csv_format;
+pub mod xml_format;
+pub mod code_format;
+pub mod partial_json;
+pub mod queue;
+pub mod run_history;
+pub mod cancellation;
+pub mod callbacks;
+pub mod guards;
+pub mod multi_step;
+pub mod inputs;
+pub mod schema_registry;
+pub mod validation_report;
+pub mod language;
@@ -0,0 +1,178 @@
+use crate::agent::agent::AgentResponse;
+use crate::task::task::Task;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// A task defined as an ordered list of sub-steps. Each step is validated
+/// on its own and checkpointed, so a failed run can resume at the failed step.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultiStepTask {
+    pub id: String,
+    pub name: String,
+    pub steps: Vec<Task>,
+    /// Whether earlier step outputs are injected into later step prompts
+    pub carry_context: bool,
+}
+
+impl MultiStepTask {
+    pub fn new(name: &str) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            steps: Vec::new(),
+            carry_context: true,
+        }
+    }
+
+    /// Use a caller-chosen id so checkpoints can be found again after a restart
+    pub fn with_id(mut self, id: &str) -> Self {
+        self.id = id.to_string();
+        self
+    }
+
+    pub fn with_step(mut self, step: Task) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    pub fn with_context_carry(mut self, enabled: bool) -> Self {
+        self.carry_context = enabled;
+        self
+    }
+
+    /// Build the task actually sent for a step, prefixed with earlier outputs
+    pub fn prepare_step(&self, index: usize, previous: &[StepCheckpoint]) -> Task {
+        let mut step = self.steps[index].clone();
+        if self.carry_context && !previous.is_empty() {
+            let mut context = String::from("Results from previous steps:\n");
+            for checkpoint in previous {
+                context.push_str(&format!("\n--- Step {} ---\n{}\n", checkpoint.step_index + 1, checkpoint.output));
+            }
+            step.description = format!("{}\n\nCurrent step: {}", context, step.description);
+        }
+        step
+    }
+}
+
+/// Persisted result of a completed step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepCheckpoint {
+    pub step_index: usize,
+    pub task_id: String,
+    pub output: String,
+    pub response: AgentResponse,
+    pub completed_at: DateTime<Utc>,
+}
+
+/// Outcome of running (or resuming) a multi-step task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultiStepResult {
+    pub task_id: String,
+    pub success: bool,
+    /// Responses for the steps executed in this run (checkpointed steps are not re-run)
+    pub step_responses: Vec<AgentResponse>,
+    /// Index of the first step executed in this run
+    pub resumed_from: usize,
+    /// Index of the step that failed, if any
+    pub failed_step: Option<usize>,
+    /// Output of the last step when all steps completed
+    pub final_output: Option<String>,
+}
+
+/// Storage for step checkpoints, keyed by multi-step task id
+pub trait CheckpointStore: Send + Sync {
+    fn load(&self, task_id: &str) -> Result<Vec<StepCheckpoint>>;
+    fn save(&self, task_id: &str, checkpoint: StepCheckpoint) -> Result<()>;
+    fn clear(&self, task_id: &str) -> Result<()>;
+}
+
+/// Checkpoints kept in process memory
+#[derive(Default)]
+pub struct InMemoryCheckpointStore {
+    checkpoints: Mutex<HashMap<String, Vec<StepCheckpoint>>>,
+}
+
+impl InMemoryCheckpointStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl CheckpointStore for InMemoryCheckpointStore {
+    fn load(&self, task_id: &str) -> Result<Vec<StepCheckpoint>> {
+        Ok(self.checkpoints.lock().unwrap().get(task_id).cloned().unwrap_or_default())
+    }
+
+    fn save(&self, task_id: &str, checkpoint: StepCheckpoint) -> Result<()> {
+        let mut checkpoints = self.checkpoints.lock().unwrap();
+        let entries = checkpoints.entry(task_id.to_string()).or_default();
+        entries.retain(|c| c.step_index != checkpoint.step_index);
+        entries.push(checkpoint);
+        entries.sort_by_key(|c| c.step_index);
+        Ok(())
+    }
+
+    fn clear(&self, task_id: &str) -> Result<()> {
+        self.checkpoints.lock().unwrap().remove(task_id);
+        Ok(())
+    }
+}
+
+/// Checkpoints persisted as one JSON file per multi-step task in a directory
+pub struct FileCheckpointStore {
+    directory: PathBuf,
+}
+
+impl FileCheckpointStore {
+    pub fn new<P: AsRef<Path>>(directory: P) -> Result<Self> {
+        std::fs::create_dir_all(directory.as_ref())?;
+        Ok(Self {
+            directory: directory.as_ref().to_path_buf(),
+        })
+    }
+
+    fn path_for(&self, task_id: &str) -> PathBuf {
+        let safe_id: String = task_id
+            .chars()
+            .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        self.directory.join(format!("{}.json", safe_id))
+    }
+}
+
+impl CheckpointStore for FileCheckpointStore {
+    fn load(&self, task_id: &str) -> Result<Vec<StepCheckpoint>> {
+        let path = self.path_for(task_id);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    fn save(&self, task_id: &str, checkpoint: StepCheckpoint) -> Result<()> {
+        let mut checkpoints = self.load(task_id)?;
+        checkpoints.retain(|c| c.step_index != checkpoint.step_index);
+        checkpoints.push(checkpoint);
+        checkpoints.sort_by_key(|c| c.step_index);
+
+        // Write to a temporary file first so a crash can't leave a truncated checkpoint
+        let path = self.path_for(task_id);
+        let tmp_path = path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, serde_json::to_string_pretty(&checkpoints)?)?;
+        std::fs::rename(tmp_path, path)?;
+        Ok(())
+    }
+
+    fn clear(&self, task_id: &str) -> Result<()> {
+        let path = self.path_for(task_id);
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
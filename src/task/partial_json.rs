@@ -0,0 +1,485 @@
+use crate::task::task::{JsonFieldType, JsonSchema, OutputFormat};
+use serde_json::{json, Map, Value};
+
+/// Result of checking streamed output against a JSON format so far
+#[derive(Debug, Clone, PartialEq)]
+pub enum PartialJsonStatus {
+    /// Nothing decisive yet (only whitespace or an opening code fence)
+    Pending,
+    /// A JSON object is being streamed and nothing contradicts the schema so far;
+    /// the fields completed so far are in `IncrementalJsonValidator::fields`
+    Partial,
+    /// A complete JSON object that satisfies the schema
+    Complete(Value),
+    /// The output can no longer satisfy the expected format
+    Violated(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Phase {
+    /// Before the JSON body: whitespace and an optional opening code fence
+    Prefix,
+    /// Inside the JSON body, before or within the top-level object
+    Body,
+    /// After the top-level object closed, at this byte offset
+    Closed(usize),
+}
+
+/// Validates JSON output while it streams in. Text is scanned once: each top-level
+/// field is parsed and checked against the schema as soon as its value ends, so type
+/// errors, unexpected fields in strict mode and leading prose are reported before the
+/// stream ends without re-parsing what came before.
+#[derive(Debug, Clone)]
+pub struct IncrementalJsonValidator {
+    schema: JsonSchema,
+    strict: bool,
+    buffer: String,
+    /// Bytes of `buffer` already scanned
+    scanned: usize,
+    phase: Phase,
+    fenced: bool,
+    opened: bool,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    /// Start of the top-level field being streamed
+    member_start: usize,
+    members: usize,
+    fields: Map<String, Value>,
+    violation: Option<String>,
+}
+
+impl IncrementalJsonValidator {
+    pub fn new(schema: JsonSchema, strict: bool) -> Self {
+        Self {
+            schema,
+            strict,
+            buffer: String::new(),
+            scanned: 0,
+            phase: Phase::Prefix,
+            fenced: false,
+            opened: false,
+            depth: 0,
+            in_string: false,
+            escaped: false,
+            member_start: 0,
+            members: 0,
+            fields: Map::new(),
+            violation: None,
+        }
+    }
+
+    /// Validator for a task's output format, if it expects JSON
+    pub fn for_format(format: &OutputFormat) -> Option<Self> {
+        match format {
+            OutputFormat::Json { schema, strict } => Some(Self::new(schema.clone(), *strict)),
+            _ => None,
+        }
+    }
+
+    /// Append streamed text and check what it completes. Once violated, the validator
+    /// stays violated.
+    pub fn feed(&mut self, delta: &str) -> PartialJsonStatus {
+        if let Some(reason) = &self.violation {
+            return PartialJsonStatus::Violated(reason.clone());
+        }
+        self.buffer.push_str(delta);
+        let status = self.advance();
+        if let PartialJsonStatus::Violated(reason) = &status {
+            self.violation = Some(reason.clone());
+        }
+        status
+    }
+
+    pub fn violation(&self) -> Option<&str> {
+        self.violation.as_deref()
+    }
+
+    /// Top-level fields whose values have been streamed completely
+    pub fn fields(&self) -> &Map<String, Value> {
+        &self.fields
+    }
+
+    /// Summary suitable for chunk metadata
+    pub fn report(&self) -> Value {
+        json!({
+            "valid": self.violation.is_none(),
+            "violation": self.violation,
+        })
+    }
+
+    fn advance(&mut self) -> PartialJsonStatus {
+        if self.phase == Phase::Prefix {
+            let trimmed = self.buffer.trim_start();
+            let offset = self.buffer.len() - trimmed.len();
+            if trimmed.is_empty() || (trimmed.len() < 3 && "```".starts_with(trimmed)) {
+                return PartialJsonStatus::Pending;
+            }
+            match trimmed.strip_prefix("```") {
+                Some(rest) => {
+                    // Wait for the language tag line to finish
+                    let Some(newline) = rest.find('\n') else {
+                        return PartialJsonStatus::Pending;
+                    };
+                    self.fenced = true;
+                    self.scanned = offset + 3 + newline + 1;
+                }
+                None => self.scanned = offset,
+            }
+            self.phase = Phase::Body;
+        }
+
+        if self.phase == Phase::Body {
+            if let Some(reason) = self.scan() {
+                return PartialJsonStatus::Violated(reason);
+            }
+        }
+
+        match self.phase {
+            Phase::Closed(end) => match self.check_trailing(end) {
+                Some(reason) => PartialJsonStatus::Violated(reason),
+                None => PartialJsonStatus::Complete(Value::Object(self.fields.clone())),
+            },
+            _ if self.opened => PartialJsonStatus::Partial,
+            _ => PartialJsonStatus::Pending,
+        }
+    }
+
+    /// Scan the new text of the body, checking each top-level field it completes
+    fn scan(&mut self) -> Option<String> {
+        let start = self.scanned;
+        self.scanned = self.buffer.len();
+        let mut completed = Vec::new();
+        for (i, c) in self.buffer[start..].char_indices() {
+            let i = start + i;
+            if !self.opened {
+                if c.is_whitespace() {
+                    continue;
+                }
+                if c != '{' {
+                    return Some(format!("Output should start with a JSON object, got: {}", preview(self.buffer[i..].trim())));
+                }
+                self.opened = true;
+                self.depth = 1;
+                self.member_start = i + 1;
+                continue;
+            }
+            if self.in_string {
+                match c {
+                    _ if self.escaped => self.escaped = false,
+                    '\\' => self.escaped = true,
+                    '"' => self.in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+            match c {
+                '"' => self.in_string = true,
+                '{' | '[' => self.depth += 1,
+                ',' if self.depth == 1 => {
+                    completed.push((self.member_start, i, false));
+                    self.member_start = i + 1;
+                }
+                '}' | ']' => {
+                    self.depth -= 1;
+                    if self.depth == 0 {
+                        if c != '}' {
+                            return Some("Output is not valid JSON: the object is closed with ']'".to_string());
+                        }
+                        completed.push((self.member_start, i, true));
+                        self.phase = Phase::Closed(i + 1);
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for (from, to, last) in completed {
+            if let Some(reason) = self.check_member(from, to, last) {
+                return Some(reason);
+            }
+        }
+        if matches!(self.phase, Phase::Closed(_)) {
+            if let Some(missing) = self.schema.required_fields.iter().find(|f| !self.fields.contains_key(&f.name)) {
+                return Some(format!("Missing required field: '{}'", missing.name));
+            }
+        }
+        None
+    }
+
+    /// Parse and check the top-level field in `buffer[from..to]`
+    fn check_member(&mut self, from: usize, to: usize, last: bool) -> Option<String> {
+        let member = self.buffer[from..to].trim();
+        self.members += 1;
+        if member.is_empty() {
+            // Only `{}` has an empty member; otherwise it's a stray or trailing comma
+            return (!last || self.members > 1).then(|| format!("Output is not valid JSON: empty member in {}", preview(&self.buffer[from..])));
+        }
+        let parsed = match serde_json::from_str::<Map<String, Value>>(&format!("{{{}}}", member)) {
+            Ok(parsed) => parsed,
+            Err(e) => return Some(format!("Output is not valid JSON: {}", e)),
+        };
+        for (key, value) in parsed {
+            if let Some(reason) = check_field(&key, &value, &self.schema, self.strict) {
+                return Some(reason);
+            }
+            self.fields.insert(key, value);
+        }
+        None
+    }
+
+    /// Only whitespace and, for fenced output, the closing fence may follow the object
+    fn check_trailing(&self, end: usize) -> Option<String> {
+        let trailing = self.buffer[end..].trim();
+        // A closing fence may still be arriving
+        if trailing.is_empty() || (self.fenced && "```".starts_with(trailing)) {
+            None
+        } else {
+            Some(format!("Unexpected text after JSON: {}", preview(trailing)))
+        }
+    }
+}
+
+/// Parse the longest prefix of an incomplete JSON document that can be closed off.
+/// Values still being written are dropped rather than guessed at, so a key whose
+/// value hasn't started is left out instead of becoming `null`.
+pub fn complete_partial_json(text: &str) -> Option<Value> {
+    let mut candidate = text;
+    loop {
+        if let Some(Ok(value)) = close_open(candidate).map(|closed| serde_json::from_str::<Value>(&closed)) {
+            return Some(value);
+        }
+        candidate = &candidate[..last_boundary(candidate)?];
+    }
+}
+
+/// Close an unterminated string and any open arrays/objects; None if the text ends
+/// with a key awaiting its value
+fn close_open(text: &str) -> Option<String> {
+    let mut stack = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for c in text.chars() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' => stack.push('}'),
+            '[' => stack.push(']'),
+            '}' | ']' => {
+                stack.pop();
+            }
+            _ => {}
+        }
+    }
+
+    let mut closed = text.to_string();
+    if in_string {
+        if escaped {
+            closed.pop();
+        }
+        closed.push('"');
+    }
+
+    let trimmed_len = closed.trim_end().len();
+    closed.truncate(trimmed_len);
+    if closed.ends_with(',') {
+        closed.pop();
+    } else if closed.ends_with(':') {
+        return None;
+    }
+
+    closed.extend(stack.into_iter().rev());
+    Some(closed)
+}
+
+/// Position to cut back to: just before the last comma or just after the last
+/// opening bracket (outside strings), whichever comes later, if it shortens the text
+fn last_boundary(text: &str) -> Option<usize> {
+    let mut boundary = None;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (i, c) in text.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            ',' => boundary = Some(i),
+            '{' | '[' if i + 1 < text.len() => boundary = Some(i + 1),
+            _ => {}
+        }
+    }
+
+    boundary.filter(|&cut| cut < text.len())
+}
+
+fn check_field(key: &str, value: &Value, schema: &JsonSchema, strict: bool) -> Option<String> {
+    let field = schema
+        .required_fields
+        .iter()
+        .chain(schema.optional_fields.iter())
+        .find(|f| f.name == key);
+    match field {
+        Some(field) if !matches_type(value, &field.field_type) => {
+            Some(format!("Field '{}' must be {:?}, got: {}", key, field.field_type, value))
+        }
+        None if strict => Some(format!("Unexpected field in strict mode: '{}'", key)),
+        _ => None,
+    }
+}
+
+fn matches_type(value: &Value, field_type: &JsonFieldType) -> bool {
+    match field_type {
+        JsonFieldType::String => value.is_string(),
+        JsonFieldType::Number => value.is_number(),
+        JsonFieldType::Boolean => value.is_boolean(),
+        JsonFieldType::Array(element_type) => value
+            .as_array()
+            .is_some_and(|items| items.iter().all(|item| matches_type(item, element_type))),
+        JsonFieldType::Object => value.is_object(),
+    }
+}
+
+fn preview(text: &str) -> String {
+    let preview: String = text.chars().take(40).collect();
+    if preview.len() < text.len() {
+        format!("{}...", preview)
+    } else {
+        preview
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::task::JsonField;
+
+    fn schema() -> JsonSchema {
+        JsonSchema {
+            required_fields: vec![JsonField {
+                name: "name".to_string(),
+                field_type: JsonFieldType::String,
+                description: None,
+            }],
+            optional_fields: vec![JsonField {
+                name: "age".to_string(),
+                field_type: JsonFieldType::Number,
+                description: None,
+            }],
+        }
+    }
+
+    fn feed_all(validator: &mut IncrementalJsonValidator, deltas: &[&str]) -> Vec<PartialJsonStatus> {
+        deltas.iter().map(|delta| validator.feed(delta)).collect()
+    }
+
+    #[test]
+    fn key_awaiting_value_is_not_a_violation() {
+        let mut validator = IncrementalJsonValidator::new(schema(), true);
+        let statuses = feed_all(&mut validator, &["{\"name\":", " \"Ada\"", ", \"age\":", " 36}"]);
+        assert_eq!(statuses[0], PartialJsonStatus::Partial);
+        assert_eq!(statuses[2], PartialJsonStatus::Partial);
+        assert_eq!(statuses[3], PartialJsonStatus::Complete(json!({"name": "Ada", "age": 36})));
+        assert_eq!(validator.violation(), None);
+    }
+
+    #[test]
+    fn split_inside_strings_and_escapes() {
+        let mut validator = IncrementalJsonValidator::new(schema(), true);
+        let statuses = feed_all(&mut validator, &["{\"na", "me\": \"a, \\\"b", "\\\" }\"}"]);
+        assert_eq!(statuses[2], PartialJsonStatus::Complete(json!({"name": "a, \"b\" }"})));
+    }
+
+    #[test]
+    fn wrong_type_is_reported_when_the_value_ends() {
+        let mut validator = IncrementalJsonValidator::new(schema(), false);
+        assert_eq!(validator.feed("{\"age\": \"thirty"), PartialJsonStatus::Partial);
+        assert!(matches!(validator.feed("\", "), PartialJsonStatus::Violated(reason) if reason.contains("'age'")));
+    }
+
+    #[test]
+    fn strict_mode_rejects_unknown_fields() {
+        let mut validator = IncrementalJsonValidator::new(schema(), true);
+        assert!(matches!(validator.feed("{\"extra\": 1,"), PartialJsonStatus::Violated(_)));
+        let mut lenient = IncrementalJsonValidator::new(schema(), false);
+        assert_eq!(lenient.feed("{\"extra\": 1,"), PartialJsonStatus::Partial);
+    }
+
+    #[test]
+    fn missing_required_field_on_close() {
+        let mut validator = IncrementalJsonValidator::new(schema(), false);
+        assert!(matches!(validator.feed("{\"age\": 3}"), PartialJsonStatus::Violated(reason) if reason.contains("'name'")));
+    }
+
+    #[test]
+    fn nested_values_are_checked_as_a_whole() {
+        let mut validator = IncrementalJsonValidator::new(JsonSchema { required_fields: vec![], optional_fields: vec![] }, false);
+        let statuses = feed_all(&mut validator, &["{\"a\": {\"b\": [1, ", "2]}, \"c\": [", "{}]}"]);
+        assert_eq!(statuses[2], PartialJsonStatus::Complete(json!({"a": {"b": [1, 2]}, "c": [{}]})));
+    }
+
+    #[test]
+    fn leading_prose_is_rejected() {
+        let mut validator = IncrementalJsonValidator::new(schema(), false);
+        assert!(matches!(validator.feed("Sure! {"), PartialJsonStatus::Violated(_)));
+    }
+
+    #[test]
+    fn code_fences_are_allowed_around_the_object() {
+        let mut validator = IncrementalJsonValidator::new(schema(), false);
+        let statuses = feed_all(&mut validator, &["``", "`json", "\n{\"name\": \"x\"}\n`", "``\n"]);
+        assert_eq!(statuses[0], PartialJsonStatus::Pending);
+        assert_eq!(statuses[1], PartialJsonStatus::Pending);
+        assert!(matches!(statuses[2], PartialJsonStatus::Complete(_)));
+        assert!(matches!(statuses[3], PartialJsonStatus::Complete(_)));
+    }
+
+    #[test]
+    fn trailing_text_and_malformed_objects_are_rejected() {
+        let mut trailing = IncrementalJsonValidator::new(schema(), false);
+        assert!(matches!(trailing.feed("{\"name\": \"x\"} done"), PartialJsonStatus::Violated(_)));
+        let mut trailing_comma = IncrementalJsonValidator::new(schema(), false);
+        assert!(matches!(trailing_comma.feed("{\"name\": \"x\",}"), PartialJsonStatus::Violated(_)));
+        let mut bracket = IncrementalJsonValidator::new(schema(), false);
+        assert!(matches!(bracket.feed("{\"name\": \"x\"]"), PartialJsonStatus::Violated(_)));
+    }
+
+    #[test]
+    fn empty_object_without_required_fields() {
+        let mut validator = IncrementalJsonValidator::new(JsonSchema { required_fields: vec![], optional_fields: vec![] }, true);
+        assert_eq!(validator.feed("{ }"), PartialJsonStatus::Complete(json!({})));
+    }
+
+    #[test]
+    fn violation_is_sticky() {
+        let mut validator = IncrementalJsonValidator::new(schema(), false);
+        assert!(matches!(validator.feed("nope"), PartialJsonStatus::Violated(_)));
+        assert!(matches!(validator.feed("{\"name\": \"x\"}"), PartialJsonStatus::Violated(_)));
+    }
+
+    #[test]
+    fn complete_partial_json_drops_values_still_being_written() {
+        assert_eq!(complete_partial_json("{\"a\": 1, \"b\":"), Some(json!({"a": 1})));
+        assert_eq!(complete_partial_json("{\"a\": 1, \"b\": tr"), Some(json!({"a": 1})));
+        assert_eq!(complete_partial_json("{\"a\": [1, 2"), Some(json!({"a": [1, 2]})));
+        assert_eq!(complete_partial_json("{\"a\": \"hel"), Some(json!({"a": "hel"})));
+        assert_eq!(complete_partial_json("{\"a\""), Some(json!({})));
+    }
+}
@@ -0,0 +1,194 @@
+use crate::agent::agent::AgentResponse;
+use crate::task::task::{Task, TaskPriority};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::sync::Mutex;
+use tokio::sync::Notify;
+
+/// Finished results kept for retrieval; beyond this the oldest unread ones are dropped
+const MAX_UNREAD_RESULTS: usize = 1000;
+
+/// Lifecycle status of a task submitted to an agent's queue
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum QueuedTaskStatus {
+    Queued,
+    Running,
+    Completed,
+    Unknown,
+}
+
+/// Entry in the priority heap; ties on priority are broken by submission order
+struct QueuedTask {
+    priority: TaskPriority,
+    sequence: u64,
+    task: Task,
+}
+
+impl PartialEq for QueuedTask {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority && self.sequence == other.sequence
+    }
+}
+
+impl Eq for QueuedTask {}
+
+impl PartialOrd for QueuedTask {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for QueuedTask {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Higher priority first, then earlier submissions first
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.sequence.cmp(&self.sequence))
+    }
+}
+
+#[derive(Default)]
+struct QueueState {
+    pending: BinaryHeap<QueuedTask>,
+    running: HashSet<String>,
+    /// Finished results with their completion order, removed once read
+    results: HashMap<String, (u64, AgentResponse)>,
+    next_completion: u64,
+    worker_running: bool,
+    next_sequence: u64,
+}
+
+/// Priority queue of tasks waiting to be executed by an agent's worker loop
+#[derive(Default)]
+pub struct TaskQueue {
+    state: Mutex<QueueState>,
+    completed: Notify,
+}
+
+impl TaskQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a task to the queue. Returns true if a worker must be started to drain it.
+    pub fn push(&self, task: Task) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let sequence = state.next_sequence;
+        state.next_sequence += 1;
+        state.pending.push(QueuedTask {
+            priority: task.priority,
+            sequence,
+            task,
+        });
+
+        if state.worker_running {
+            false
+        } else {
+            state.worker_running = true;
+            true
+        }
+    }
+
+    /// Take the highest-priority task and mark it running. When the queue is
+    /// empty the worker is marked stopped so the next push starts a new one.
+    pub fn next_task(&self) -> Option<Task> {
+        let mut state = self.state.lock().unwrap();
+        match state.pending.pop() {
+            Some(queued) => {
+                state.running.insert(queued.task.id.clone());
+                Some(queued.task)
+            }
+            None => {
+                state.worker_running = false;
+                None
+            }
+        }
+    }
+
+    /// Remove a task that hasn't started yet, returning it if it was pending
+    pub fn remove_pending(&self, task_id: &str) -> Option<Task> {
+        let mut state = self.state.lock().unwrap();
+        let mut removed = None;
+        let remaining: Vec<QueuedTask> = state.pending
+            .drain()
+            .filter_map(|queued| {
+                if removed.is_none() && queued.task.id == task_id {
+                    removed = Some(queued.task);
+                    None
+                } else {
+                    Some(queued)
+                }
+            })
+            .collect();
+        state.pending = remaining.into_iter().collect();
+        removed
+    }
+
+    /// Store the result of a finished task and wake anyone awaiting it. Once more
+    /// than `MAX_UNREAD_RESULTS` are waiting to be read, the oldest is dropped.
+    pub fn complete(&self, task_id: &str, response: AgentResponse) {
+        {
+            let mut state = self.state.lock().unwrap();
+            state.running.remove(task_id);
+            let completion = state.next_completion;
+            state.next_completion += 1;
+            state.results.insert(task_id.to_string(), (completion, response));
+            if state.results.len() > MAX_UNREAD_RESULTS {
+                let oldest = state.results
+                    .iter()
+                    .min_by_key(|(_, (completion, _))| *completion)
+                    .map(|(id, _)| id.clone());
+                if let Some(oldest) = oldest {
+                    state.results.remove(&oldest);
+                }
+            }
+        }
+        self.completed.notify_waiters();
+    }
+
+    /// Current status of a task by id
+    pub fn status(&self, task_id: &str) -> QueuedTaskStatus {
+        let state = self.state.lock().unwrap();
+        if state.results.contains_key(task_id) {
+            QueuedTaskStatus::Completed
+        } else if state.running.contains(task_id) {
+            QueuedTaskStatus::Running
+        } else if state.pending.iter().any(|q| q.task.id == task_id) {
+            QueuedTaskStatus::Queued
+        } else {
+            QueuedTaskStatus::Unknown
+        }
+    }
+
+    /// Remove and return a finished result, if available
+    pub fn take_result(&self, task_id: &str) -> Option<AgentResponse> {
+        self.state.lock().unwrap().results.remove(task_id).map(|(_, response)| response)
+    }
+
+    /// Wait until the task finishes and return its result.
+    /// Returns None if the task id is not known to this queue.
+    pub async fn wait_for_result(&self, task_id: &str) -> Option<AgentResponse> {
+        loop {
+            // Register for wakeups before checking so a completion in between isn't missed
+            let notified = self.completed.notified();
+
+            match self.status(task_id) {
+                QueuedTaskStatus::Completed => return self.take_result(task_id),
+                QueuedTaskStatus::Unknown => return None,
+                QueuedTaskStatus::Queued | QueuedTaskStatus::Running => {}
+            }
+
+            notified.await;
+        }
+    }
+
+    /// Number of tasks waiting to be started
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
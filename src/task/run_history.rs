@@ -0,0 +1,319 @@
+use crate::agent::agent::{AgentResponse, TaskResult};
+use crate::task::task::Task;
+use crate::tenant::TenantId;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// A single persisted task execution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub task_id: String,
+    pub agent_id: String,
+    pub agent_name: String,
+    pub task: Task,
+    pub response: AgentResponse,
+    pub result: TaskResult,
+    pub recorded_at: DateTime<Utc>,
+    /// Tenant of the task; records without one predate tenants or ran on shared agents
+    #[serde(default)]
+    pub tenant_id: Option<TenantId>,
+    /// Provider responses of the run, present when the agent records run traces
+    #[serde(default)]
+    pub trace: Option<crate::agent::replay::RunTrace>,
+}
+
+impl RunRecord {
+    pub fn new(agent_id: &str, agent_name: &str, task: &Task, response: &AgentResponse) -> Self {
+        Self {
+            task_id: task.id.clone(),
+            agent_id: agent_id.to_string(),
+            agent_name: agent_name.to_string(),
+            task: task.clone(),
+            response: response.clone(),
+            result: TaskResult::from(response),
+            recorded_at: Utc::now(),
+            tenant_id: task.tenant_id.clone(),
+            trace: None,
+        }
+    }
+
+    /// Scrub secrets from the task, its inputs and metadata, the response and the
+    /// provider trace before the record is stored
+    pub fn redact(&mut self, redactor: &crate::redaction::Redactor) {
+        redactor.redact_string(&mut self.task.description);
+        if let Some(inputs) = &mut self.task.inputs {
+            redactor.redact_value(inputs);
+        }
+        self.task.metadata.values_mut().for_each(|value| redactor.redact_value(value));
+        self.response.metadata.values_mut().for_each(|value| redactor.redact_value(value));
+        redactor.redact_string(&mut self.response.content);
+        if let Some(raw) = &mut self.response.raw_content {
+            redactor.redact_string(raw);
+        }
+        if let Some(error) = &mut self.response.error {
+            redactor.redact_string(error);
+        }
+        self.response.tool_calls.iter_mut().for_each(|call| call.redact(redactor));
+        redactor.redact_string(&mut self.result.output);
+        if let Some(trace) = &mut self.trace {
+            trace.redact(redactor);
+        }
+    }
+}
+
+/// Filter for querying run history; unset fields match everything
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunFilter {
+    pub agent_id: Option<String>,
+    pub tenant_id: Option<TenantId>,
+    pub success: Option<bool>,
+    pub tag: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub limit: Option<usize>,
+}
+
+impl RunFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_agent_id(mut self, agent_id: &str) -> Self {
+        self.agent_id = Some(agent_id.to_string());
+        self
+    }
+
+    pub fn with_tenant(mut self, tenant_id: TenantId) -> Self {
+        self.tenant_id = Some(tenant_id);
+        self
+    }
+
+    pub fn with_success(mut self, success: bool) -> Self {
+        self.success = Some(success);
+        self
+    }
+
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        self.tag = Some(tag.to_string());
+        self
+    }
+
+    pub fn since(mut self, since: DateTime<Utc>) -> Self {
+        self.since = Some(since);
+        self
+    }
+
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn matches(&self, record: &RunRecord) -> bool {
+        if let Some(agent_id) = &self.agent_id {
+            if &record.agent_id != agent_id {
+                return false;
+            }
+        }
+        if let Some(tenant_id) = &self.tenant_id {
+            if record.tenant_id.as_ref() != Some(tenant_id) {
+                return false;
+            }
+        }
+        if let Some(success) = self.success {
+            if record.response.success != success {
+                return false;
+            }
+        }
+        if let Some(tag) = &self.tag {
+            if !record.task.tags.contains(tag) {
+                return false;
+            }
+        }
+        if let Some(since) = self.since {
+            if record.recorded_at < since {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Apply the filter to records ordered oldest first, returning newest first
+    fn apply<'a>(&self, records: impl DoubleEndedIterator<Item = &'a RunRecord>) -> Vec<RunRecord> {
+        records
+            .rev()
+            .filter(|r| self.matches(r))
+            .take(self.limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Storage for completed task runs
+pub trait RunStore: Send + Sync {
+    /// Persist a run record
+    fn save(&self, record: RunRecord) -> Result<()>;
+
+    /// Fetch the most recent record for a task id
+    fn get(&self, task_id: &str) -> Option<RunRecord>;
+
+    /// List records matching the filter, newest first
+    fn list(&self, filter: &RunFilter) -> Vec<RunRecord>;
+
+    /// Make every saved record durable, e.g. before shutdown
+    fn flush(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Run history kept in process memory
+#[derive(Default)]
+pub struct InMemoryRunStore {
+    records: Mutex<Vec<RunRecord>>,
+}
+
+impl InMemoryRunStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl RunStore for InMemoryRunStore {
+    fn save(&self, record: RunRecord) -> Result<()> {
+        self.records.lock().unwrap().push(record);
+        Ok(())
+    }
+
+    fn get(&self, task_id: &str) -> Option<RunRecord> {
+        self.records.lock().unwrap()
+            .iter()
+            .rev()
+            .find(|r| r.task_id == task_id)
+            .cloned()
+    }
+
+    fn list(&self, filter: &RunFilter) -> Vec<RunRecord> {
+        filter.apply(self.records.lock().unwrap().iter())
+    }
+}
+
+/// Run history persisted as an append-only JSON Lines file, so results
+/// survive process restarts. Records are read from the file when queried
+/// rather than held in memory; lines that don't parse, such as one torn by
+/// a crash mid-write, are skipped.
+pub struct JsonlRunStore {
+    path: PathBuf,
+    /// Serializes appends and flushes
+    writer: Mutex<()>,
+}
+
+impl JsonlRunStore {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let store = Self {
+            path,
+            writer: Mutex::new(()),
+        };
+
+        if store.path.exists() {
+            for (line_number, line) in BufReader::new(File::open(&store.path)?).split(b'\n').enumerate() {
+                if let Err(e) = parse_line(&line?) {
+                    eprintln!("Skipping corrupt run record on line {} of {}: {}", line_number + 1, store.path.display(), e);
+                }
+            }
+            // A torn last line has no newline; end it so the next record starts on its own line
+            let mut file = File::open(&store.path)?;
+            if file.seek(SeekFrom::End(0))? > 0 {
+                file.seek(SeekFrom::End(-1))?;
+                let mut last = [0u8];
+                file.read_exact(&mut last)?;
+                if last[0] != b'\n' {
+                    writeln!(OpenOptions::new().append(true).open(&store.path)?)?;
+                }
+            }
+        }
+
+        Ok(store)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Visit the records of the file oldest first, reading only lines `wanted` accepts
+    fn scan(&self, wanted: impl Fn(&str) -> bool, mut visit: impl FnMut(RunRecord)) {
+        let Ok(file) = File::open(&self.path) else {
+            return;
+        };
+        for line in BufReader::new(file).split(b'\n') {
+            let Ok(line) = line else {
+                return;
+            };
+            if !std::str::from_utf8(&line).is_ok_and(&wanted) {
+                continue;
+            }
+            if let Ok(Some(record)) = parse_line(&line) {
+                visit(record);
+            }
+        }
+    }
+}
+
+/// The record on a line of the file; None for blank lines
+fn parse_line(line: &[u8]) -> serde_json::Result<Option<RunRecord>> {
+    if line.trim_ascii().is_empty() {
+        return Ok(None);
+    }
+    serde_json::from_slice(line).map(Some)
+}
+
+impl RunStore for JsonlRunStore {
+    fn save(&self, record: RunRecord) -> Result<()> {
+        let _writer = self.writer.lock().unwrap();
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(&record)?)?;
+        Ok(())
+    }
+
+    fn flush(&self) -> Result<()> {
+        let _writer = self.writer.lock().unwrap();
+        if self.path.exists() {
+            OpenOptions::new().append(true).open(&self.path)?.sync_all()?;
+        }
+        Ok(())
+    }
+
+    fn get(&self, task_id: &str) -> Option<RunRecord> {
+        let mut latest = None;
+        // Only lines mentioning the id can hold its record, so the rest aren't parsed
+        self.scan(|line| line.contains(task_id), |record| {
+            if record.task_id == task_id {
+                latest = Some(record);
+            }
+        });
+        latest
+    }
+
+    fn list(&self, filter: &RunFilter) -> Vec<RunRecord> {
+        // Keep only the newest `limit` matches while reading oldest first
+        let limit = filter.limit.unwrap_or(usize::MAX);
+        let mut matches = VecDeque::new();
+        self.scan(|_| true, |record| {
+            if filter.matches(&record) {
+                matches.push_back(record);
+                if matches.len() > limit {
+                    matches.pop_front();
+                }
+            }
+        });
+        matches.into_iter().rev().collect()
+    }
+}
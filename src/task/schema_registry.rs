@@ -0,0 +1,123 @@
+use crate::task::task::{JsonField, JsonSchema, OutputFormat};
+use anyhow::{anyhow, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{OnceLock, RwLock};
+
+/// One registered version of a named JSON output schema
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct RegisteredSchema {
+    pub name: String,
+    pub version: u32,
+    pub schema: JsonSchema,
+    pub strict: bool,
+}
+
+impl RegisteredSchema {
+    pub fn output_format(&self) -> OutputFormat {
+        OutputFormat::Json {
+            schema: self.schema.clone(),
+            strict: self.strict,
+        }
+    }
+
+    /// `name@version`, as recorded in task metadata
+    pub fn reference(&self) -> String {
+        format!("{}@{}", self.name, self.version)
+    }
+}
+
+/// JSON output schemas registered once by name and referenced from tasks.
+/// References are `name` for the latest version or `name@version` for a fixed one.
+#[derive(Debug, Default)]
+pub struct SchemaRegistry {
+    schemas: RwLock<HashMap<String, BTreeMap<u32, RegisteredSchema>>>,
+}
+
+impl SchemaRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry used by `Task::with_schema`
+    pub fn global() -> &'static SchemaRegistry {
+        static GLOBAL: OnceLock<SchemaRegistry> = OnceLock::new();
+        GLOBAL.get_or_init(SchemaRegistry::new)
+    }
+
+    /// Register a schema version. Published versions are immutable: registering an
+    /// existing version with different fields is an error, re-registering it unchanged is not.
+    pub fn register(
+        &self,
+        name: &str,
+        version: u32,
+        required_fields: Vec<JsonField>,
+        optional_fields: Vec<JsonField>,
+        strict: bool,
+    ) -> Result<RegisteredSchema> {
+        if name.is_empty() || name.contains('@') {
+            return Err(anyhow!("Invalid schema name: '{}'", name));
+        }
+        let entry = RegisteredSchema {
+            name: name.to_string(),
+            version,
+            schema: JsonSchema { required_fields, optional_fields },
+            strict,
+        };
+
+        let mut schemas = self.schemas.write().unwrap();
+        let versions = schemas.entry(name.to_string()).or_default();
+        if let Some(existing) = versions.get(&version) {
+            if existing != &entry {
+                return Err(anyhow!("Schema {} is already registered with different fields", entry.reference()));
+            }
+        }
+        versions.insert(version, entry.clone());
+        Ok(entry)
+    }
+
+    /// Resolve `name` (latest version) or `name@version`
+    pub fn resolve(&self, reference: &str) -> Result<RegisteredSchema> {
+        let (name, version) = match reference.split_once('@') {
+            Some((name, version)) => {
+                let version = version
+                    .parse::<u32>()
+                    .map_err(|_| anyhow!("Invalid schema version in '{}'", reference))?;
+                (name, Some(version))
+            }
+            None => (reference, None),
+        };
+
+        let schemas = self.schemas.read().unwrap();
+        let versions = schemas.get(name).ok_or_else(|| anyhow!("Unknown schema: '{}'", name))?;
+        let entry = match version {
+            Some(version) => versions.get(&version),
+            None => versions.values().next_back(),
+        };
+        entry.cloned().ok_or_else(|| anyhow!("Unknown schema version: '{}'", reference))
+    }
+
+    /// Registered versions of `name`, oldest first
+    pub fn versions(&self, name: &str) -> Vec<u32> {
+        self.schemas
+            .read()
+            .unwrap()
+            .get(name)
+            .map(|versions| versions.keys().copied().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.schemas.read().unwrap().keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    pub fn unregister(&self, name: &str, version: u32) -> bool {
+        let mut schemas = self.schemas.write().unwrap();
+        let removed = schemas.get_mut(name).is_some_and(|versions| versions.remove(&version).is_some());
+        if schemas.get(name).is_some_and(|versions| versions.is_empty()) {
+            schemas.remove(name);
+        }
+        removed
+    }
+}
@@ -1,5 +1,15 @@
 use serde_json::Value;
 use anyhow::{Result, anyhow};
+use std::collections::HashMap;
+use crate::task::cancellation::CancellationToken;
+use crate::task::callbacks::TaskCallbacks;
+use crate::task::guards::TaskGuard;
+use crate::agent::agent::{AgentResponse, ToolCall};
+use crate::task::csv_format::{self, CsvColumn, CsvColumnType};
+use crate::task::xml_format::{self, XmlElementSchema, XmlSchema};
+use crate::task::code_format;
+use crate::task::language;
+use crate::task::validation_report::{ValidationIssueKind, ValidationReport};
 
 // Enum to define different output format types
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -9,6 +19,17 @@ pub enum OutputFormat {
         schema: JsonSchema,
         strict: bool, // Whether to enforce strict validation (all fields required)
     },
+    Csv {
+        columns: Vec<CsvColumn>,
+        delimiter: char,
+    },
+    Xml {
+        schema: XmlSchema,
+    },
+    Code {
+        language: String,
+        syntax_check: bool, // Parse the code when a checker exists for the language
+    },
 }
 
 // JSON Schema definition for validation
@@ -34,20 +55,243 @@ pub enum JsonFieldType {
     Object, // Nested object (simplified for now)
 }
 
+// Scheduling priority of a task; higher priorities are dequeued first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, serde::Serialize, serde::Deserialize)]
+pub enum TaskPriority {
+    Low,
+    #[default]
+    Normal,
+    High,
+    Critical,
+}
+
 #[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
 pub struct Task {
+    #[serde(default = "new_task_id")]
+    pub id: String, // Stable identifier used to look up queued results
     pub description: String,
     pub expected_output: Option<String>,
     pub output_format: OutputFormat, // New field for typed output
+    #[serde(default)]
+    pub priority: TaskPriority,
+    #[serde(default)]
+    pub metadata: HashMap<String, Value>, // Arbitrary caller data, copied onto the response
+    #[serde(default)]
+    pub tags: Vec<String>, // Labels for grouping runs in analytics
+    #[serde(default)]
+    pub trace_id: Option<String>, // Upstream request/trace id for log correlation
+    #[serde(default)]
+    pub user_id: Option<String>, // End user the task runs on behalf of, for attribution
+    #[serde(default)]
+    pub tenant_id: Option<crate::tenant::TenantId>, // Customer the task belongs to; set from the agent when absent
+    #[serde(default)]
+    pub inputs: Option<Value>, // Structured inputs rendered into the prompt and exposed to tools
+    #[serde(default)]
+    pub input_template: Option<String>, // Optional {{key}} template for rendering inputs
+    #[serde(default)]
+    pub evaluation_threshold: Option<f64>, // Score output against expected_output when set
+    #[serde(default)]
+    pub language: Option<String>, // Required response language, e.g. "tr" or "de-AT"
+    #[serde(skip)]
+    pub cancel_token: CancellationToken, // Shared with clones of this task
+    #[serde(skip)]
+    pub callbacks: TaskCallbacks, // Lifecycle hooks, not persisted
+    #[serde(skip)]
+    pub guards: Vec<TaskGuard>, // Business-rule checks run after format validation
+}
+
+fn new_task_id() -> String {
+    uuid::Uuid::new_v4().to_string()
 }
 
 impl Task {
     pub fn new(description: String, expected_output: Option<String>) -> Self {
         Self {
+            id: new_task_id(),
             description,
             expected_output,
             output_format: OutputFormat::Text, // Default to text
+            priority: TaskPriority::Normal,
+            metadata: HashMap::new(),
+            tags: Vec::new(),
+            trace_id: None,
+            user_id: None,
+            tenant_id: None,
+            inputs: None,
+            input_template: None,
+            evaluation_threshold: None,
+            language: None,
+            cancel_token: CancellationToken::new(),
+            callbacks: TaskCallbacks::default(),
+            guards: Vec::new(),
+        }
+    }
+
+    // Add a business-rule check; a failure triggers the correction-retry loop
+    pub fn with_guard<F>(mut self, guard: F) -> Self
+    where
+        F: Fn(&str) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.guards.push(TaskGuard::from_fn(guard));
+        self
+    }
+
+    // Add an async business-rule check (e.g. one that queries a database)
+    pub fn with_async_guard<F, Fut>(mut self, guard: F) -> Self
+    where
+        F: Fn(String) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = std::result::Result<(), String>> + Send + 'static,
+    {
+        self.guards.push(TaskGuard::from_async_fn(guard));
+        self
+    }
+
+    // Run all guards in order, stopping at the first failure
+    pub async fn run_guards(&self, output: &str) -> std::result::Result<(), String> {
+        for guard in &self.guards {
+            guard.check(output).await?;
+        }
+        Ok(())
+    }
+
+    // Called once before the agent starts working on the task
+    pub fn on_start<F: Fn(&Task) + Send + Sync + 'static>(mut self, callback: F) -> Self {
+        self.callbacks.on_start = Some(std::sync::Arc::new(callback));
+        self
+    }
+
+    // Called after every tool execution
+    pub fn on_tool_call<F: Fn(&ToolCall) + Send + Sync + 'static>(mut self, callback: F) -> Self {
+        self.callbacks.on_tool_call = Some(std::sync::Arc::new(callback));
+        self
+    }
+
+    // Called before each retry with the failed attempt number and reason
+    pub fn on_retry<F: Fn(usize, &str) + Send + Sync + 'static>(mut self, callback: F) -> Self {
+        self.callbacks.on_retry = Some(std::sync::Arc::new(callback));
+        self
+    }
+
+    // Called with the final response
+    pub fn on_complete<F: Fn(&AgentResponse) + Send + Sync + 'static>(mut self, callback: F) -> Self {
+        self.callbacks.on_complete = Some(std::sync::Arc::new(callback));
+        self
+    }
+
+    // Attach structured inputs instead of concatenating them into the description
+    pub fn with_inputs(mut self, inputs: Value) -> Self {
+        self.inputs = Some(inputs);
+        self
+    }
+
+    // Template used to render inputs into the prompt, e.g. "Customer: {{customer.name}}"
+    pub fn with_input_template(mut self, template: &str) -> Self {
+        self.input_template = Some(template.to_string());
+        self
+    }
+
+    // Prompt section for the structured inputs, if any
+    pub fn render_inputs(&self) -> Option<String> {
+        let inputs = self.inputs.as_ref()?;
+        Some(match &self.input_template {
+            Some(template) => crate::task::inputs::render_template(template, inputs),
+            None => format!(
+                "Inputs:\n```json\n{}\n```",
+                serde_json::to_string_pretty(inputs).unwrap_or_else(|_| inputs.to_string())
+            ),
+        })
+    }
+
+    // Score the output against expected_output; the response records pass/fail at this threshold
+    pub fn with_evaluation(mut self, threshold: f64) -> Self {
+        self.evaluation_threshold = Some(threshold.clamp(0.0, 1.0));
+        self
+    }
+
+    // Require the response in a language (ISO 639-1 code or locale); mismatches are corrected
+    pub fn with_language(mut self, language: &str) -> Self {
+        self.language = Some(language.to_string());
+        self
+    }
+
+    // Copy of this task with a new id and its own cancellation token, for re-running a template
+    pub fn with_new_id(&self) -> Self {
+        let mut task = self.clone();
+        task.id = new_task_id();
+        task.cancel_token = CancellationToken::new();
+        task
+    }
+
+    // Token that cancels this task when triggered, whether it runs via call() or a queue
+    pub fn cancel_handle(&self) -> CancellationToken {
+        self.cancel_token.clone()
+    }
+
+    // Attach a metadata entry that flows into the AgentResponse
+    pub fn with_metadata(mut self, key: &str, value: Value) -> Self {
+        self.metadata.insert(key.to_string(), value);
+        self
+    }
+
+    // Add a tag (duplicates are ignored)
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        if !self.tags.iter().any(|t| t == tag) {
+            self.tags.push(tag.to_string());
         }
+        self
+    }
+
+    // Add several tags at once
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        for tag in tags {
+            self = self.with_tag(&tag);
+        }
+        self
+    }
+
+    // Correlate this task with an upstream request or trace id
+    pub fn with_trace_id(mut self, trace_id: &str) -> Self {
+        self.trace_id = Some(trace_id.to_string());
+        self
+    }
+
+    // Attribute this task (cost, limits, audit) to an end user
+    pub fn with_user(mut self, user_id: &str) -> Self {
+        self.user_id = Some(user_id.to_string());
+        self
+    }
+
+    // Run the task for a tenant; agents of other tenants refuse it
+    pub fn with_tenant(mut self, tenant_id: crate::tenant::TenantId) -> Self {
+        self.tenant_id = Some(tenant_id);
+        self
+    }
+
+    // Short identifier used as a prefix in log lines
+    pub fn log_context(&self) -> String {
+        match &self.trace_id {
+            Some(trace_id) => format!("task={} trace={}", self.id, trace_id),
+            None => format!("task={}", self.id),
+        }
+    }
+
+    // Set the scheduling priority used by the agent task queue
+    pub fn with_priority(mut self, priority: TaskPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    // Use a JSON schema from the global SchemaRegistry: "name" (latest) or "name@version"
+    pub fn with_schema(self, reference: &str) -> Result<Self> {
+        self.with_schema_from(crate::task::schema_registry::SchemaRegistry::global(), reference)
+    }
+
+    // Use a JSON schema from a specific registry; the resolved version is recorded in metadata
+    pub fn with_schema_from(mut self, registry: &crate::task::schema_registry::SchemaRegistry, reference: &str) -> Result<Self> {
+        let schema = registry.resolve(reference)?;
+        self.output_format = schema.output_format();
+        self.metadata.insert("schema".to_string(), Value::String(schema.reference()));
+        Ok(self)
     }
 
     // Constructor for JSON output format
@@ -59,8 +303,6 @@ impl Task {
         strict: bool,
     ) -> Self {
         Self {
-            description,
-            expected_output,
             output_format: OutputFormat::Json {
                 schema: JsonSchema {
                     required_fields,
@@ -68,6 +310,7 @@ impl Task {
                 },
                 strict,
             },
+            ..Self::new(description, expected_output)
         }
     }
 
@@ -90,6 +333,49 @@ impl Task {
         Self::new_with_json_output(description, expected_output, fields, vec![], strict)
     }
 
+    // Constructor for CSV output format
+    pub fn new_with_csv_output(
+        description: String,
+        expected_output: Option<String>,
+        columns: Vec<CsvColumn>,
+        delimiter: char,
+    ) -> Self {
+        Self {
+            output_format: OutputFormat::Csv { columns, delimiter },
+            ..Self::new(description, expected_output)
+        }
+    }
+
+    // Constructor for code output: the response is reduced to the code block in `language`
+    pub fn new_with_code_output(
+        description: String,
+        expected_output: Option<String>,
+        language: &str,
+        syntax_check: bool,
+    ) -> Self {
+        Self {
+            output_format: OutputFormat::Code {
+                language: language.to_string(),
+                syntax_check,
+            },
+            ..Self::new(description, expected_output)
+        }
+    }
+
+    // Constructor for XML output format
+    pub fn new_with_xml_output(
+        description: String,
+        expected_output: Option<String>,
+        root: XmlElementSchema,
+    ) -> Self {
+        Self {
+            output_format: OutputFormat::Xml {
+                schema: XmlSchema { root },
+            },
+            ..Self::new(description, expected_output)
+        }
+    }
+
     // Validate agent output against the expected format
     pub fn validate_output(&self, output: &str) -> Result<()> {
         match &self.output_format {
@@ -103,90 +389,74 @@ impl Task {
             OutputFormat::Json { schema, strict } => {
                 self.validate_json_output(output, schema, *strict)
             }
+            OutputFormat::Csv { columns, delimiter } => {
+                csv_format::validate_csv(output, columns, *delimiter).map(|_| ())
+            }
+            OutputFormat::Xml { schema } => {
+                xml_format::validate_xml(output, schema).map(|_| ())
+            }
+            OutputFormat::Code { language, syntax_check } => {
+                code_format::validate_code(output, language, *syntax_check).map(|_| ())
+            }
         }
     }
 
-    // JSON-specific validation
-    fn validate_json_output(&self, output: &str, schema: &JsonSchema, strict: bool) -> Result<()> {
-        // Parse the output as JSON
-        let parsed: Value = serde_json::from_str(output.trim())
-            .map_err(|e| anyhow!("Output is not valid JSON: {}", e))?;
-
-        // Ensure it's a JSON object
-        let obj = parsed.as_object()
-            .ok_or_else(|| anyhow!("JSON output must be an object, got: {}", parsed))?;
-
-        // Validate required fields
-        for field in &schema.required_fields {
-            if !obj.contains_key(&field.name) {
-                return Err(anyhow!("Missing required field: '{}'", field.name));
-            }
-
-            let value = &obj[&field.name];
-            self.validate_field_type(value, &field.field_type, &field.name)?;
+    // Just the code from a response, None for non-code tasks
+    pub fn extract_code(&self, output: &str) -> Option<String> {
+        match &self.output_format {
+            OutputFormat::Code { language, .. } => code_format::extract_code(output, language).ok(),
+            _ => None,
         }
+    }
 
-        // Validate optional fields (if present)
-        for field in &schema.optional_fields {
-            if let Some(value) = obj.get(&field.name) {
-                self.validate_field_type(value, &field.field_type, &field.name)?;
+    // Parse CSV output into data rows (header excluded), None for non-CSV tasks
+    pub fn parse_csv_rows(&self, output: &str) -> Option<Vec<Vec<String>>> {
+        match &self.output_format {
+            OutputFormat::Csv { columns, delimiter } => {
+                csv_format::validate_csv(output, columns, *delimiter).ok()
             }
+            _ => None,
         }
+    }
 
-        // In strict mode, ensure no extra fields are present
-        if strict {
-            let expected_fields: std::collections::HashSet<&String> = schema
-                .required_fields
-                .iter()
-                .chain(schema.optional_fields.iter())
-                .map(|f| &f.name)
-                .collect();
-
-            for key in obj.keys() {
-                if !expected_fields.contains(key) {
-                    return Err(anyhow!("Unexpected field in strict mode: '{}'", key));
-                }
-            }
+    // JSON-specific validation
+    fn validate_json_output(&self, output: &str, schema: &JsonSchema, strict: bool) -> Result<()> {
+        let report = json_report(output, schema, strict);
+        if report.is_valid() {
+            Ok(())
+        } else {
+            Err(anyhow!(report.summary()))
         }
-
-        Ok(())
     }
 
-    // Validate individual field types
-    fn validate_field_type(&self, value: &Value, expected_type: &JsonFieldType, field_name: &str) -> Result<()> {
-        match expected_type {
-            JsonFieldType::String => {
-                if !value.is_string() {
-                    return Err(anyhow!("Field '{}' must be a string, got: {}", field_name, value));
-                }
-            }
-            JsonFieldType::Number => {
-                if !value.is_number() {
-                    return Err(anyhow!("Field '{}' must be a number, got: {}", field_name, value));
-                }
-            }
-            JsonFieldType::Boolean => {
-                if !value.is_boolean() {
-                    return Err(anyhow!("Field '{}' must be a boolean, got: {}", field_name, value));
-                }
-            }
-            JsonFieldType::Array(element_type) => {
-                let arr = value.as_array()
-                    .ok_or_else(|| anyhow!("Field '{}' must be an array, got: {}", field_name, value))?;
-                
-                // Validate each element in the array
-                for (i, element) in arr.iter().enumerate() {
-                    self.validate_field_type(element, element_type, &format!("{}[{}]", field_name, i))?;
-                }
-            }
-            JsonFieldType::Object => {
-                if !value.is_object() {
-                    return Err(anyhow!("Field '{}' must be an object, got: {}", field_name, value));
-                }
-                // For now, we just check it's an object. Could extend to nested schema validation.
+    // All problems with the output, rather than just the first; JSON schemas are
+    // checked field by field, other formats report their first error
+    pub fn validation_report(&self, output: &str) -> ValidationReport {
+        let mut report = match &self.output_format {
+            OutputFormat::Json { schema, strict } => json_report(output, schema, *strict),
+            _ => match self.validate_output(output) {
+                Ok(()) => ValidationReport::new(),
+                Err(e) => ValidationReport::single(ValidationIssueKind::Syntax, e.to_string()),
+            },
+        };
+
+        if let Some(expected) = &self.language {
+            if let Err(detected) = language::check_language(output, expected) {
+                report.push(
+                    None,
+                    ValidationIssueKind::Language {
+                        expected: language::base_language(expected),
+                        detected: detected.clone(),
+                    },
+                    format!(
+                        "Response must be written in {}, but it is in {}",
+                        language::language_name(expected),
+                        language::language_name(&detected)
+                    ),
+                );
             }
         }
-        Ok(())
+        report
     }
 
     // Generate a prompt section describing the expected output format
@@ -231,6 +501,42 @@ impl Task {
                 prompt.push_str("Ensure your response is valid JSON and follows this exact structure.");
                 prompt
             }
+            OutputFormat::Csv { columns, delimiter } => {
+                let mut prompt = format!(
+                    "You must respond with CSV data using '{}' as the delimiter.\n\n",
+                    delimiter
+                );
+
+                let header: Vec<&str> = columns.iter().map(|c| c.name.as_str()).collect();
+                prompt.push_str(&format!("The first line must be exactly this header:\n{}\n\n", header.join(&delimiter.to_string())));
+
+                prompt.push_str("Columns:\n");
+                for column in columns {
+                    prompt.push_str(&format!(
+                        "- {}: {}{}\n",
+                        column.name,
+                        self.csv_type_to_string(&column.column_type),
+                        column.description.as_ref().map(|d| format!(" - {}", d)).unwrap_or_default()
+                    ));
+                }
+
+                prompt.push_str("\nQuote fields that contain the delimiter, quotes, or line breaks. ");
+                prompt.push_str("Do not add explanations or code fences - output only the CSV rows.");
+                prompt
+            }
+            OutputFormat::Xml { schema } => {
+                let mut prompt = "You must respond with a well-formed XML document with the following structure:\n\n".to_string();
+                self.describe_xml_element(&schema.root, 0, &mut prompt);
+                prompt.push_str("\nEscape special characters (&, <, >) in text and attribute values. ");
+                prompt.push_str("Do not add explanations or code fences - output only the XML document.");
+                prompt
+            }
+            OutputFormat::Code { language, .. } => {
+                format!(
+                    "Respond with a single fenced code block tagged `{}` containing the complete code:\n\n```{}\n<code>\n```\n\nDo not include other code blocks.",
+                    language, language
+                )
+            }
         }
     }
 
@@ -244,4 +550,148 @@ impl Task {
             JsonFieldType::Object => "object".to_string(),
         }
     }
+
+    // Helper to describe an XML element schema as an indented outline
+    fn describe_xml_element(&self, element: &XmlElementSchema, depth: usize, prompt: &mut String) {
+        let attributes: Vec<String> = element.attributes
+            .iter()
+            .map(|a| format!("{}=\"...\"{}", a.name, if a.required { "" } else { " (optional)" }))
+            .collect();
+
+        prompt.push_str(&format!(
+            "{}<{}{}{}>  // {}{}{}\n",
+            "  ".repeat(depth),
+            element.name,
+            if attributes.is_empty() { "" } else { " " },
+            attributes.join(" "),
+            if element.required { "REQUIRED" } else { "OPTIONAL" },
+            if element.multiple { ", may repeat" } else { "" },
+            element.description.as_ref().map(|d| format!(" - {}", d)).unwrap_or_default()
+        ));
+
+        for child in &element.children {
+            self.describe_xml_element(child, depth + 1, prompt);
+        }
+    }
+
+    // Helper to convert CsvColumnType to string representation
+    fn csv_type_to_string(&self, column_type: &CsvColumnType) -> String {
+        match column_type {
+            CsvColumnType::String => "string".to_string(),
+            CsvColumnType::Number => "number".to_string(),
+            CsvColumnType::Boolean => "boolean (true/false)".to_string(),
+        }
+    }
+}
+
+/// Check the output against a JSON schema, collecting every problem found
+fn json_report(output: &str, schema: &JsonSchema, strict: bool) -> ValidationReport {
+    let mut report = ValidationReport::new();
+
+    // Parse the output as JSON
+    let parsed: Value = match serde_json::from_str(strip_code_fence(output)) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            report.push(None, ValidationIssueKind::Syntax, format!("Output is not valid JSON: {}", e));
+            return report;
+        }
+    };
+
+    // Ensure it's a JSON object
+    let Some(obj) = parsed.as_object() else {
+        report.push(None, ValidationIssueKind::Syntax, format!("JSON output must be an object, got: {}", parsed));
+        return report;
+    };
+
+    for field in &schema.required_fields {
+        match obj.get(&field.name) {
+            Some(value) => check_field_type(value, &field.field_type, &field.name, &mut report),
+            None => report.push(
+                Some(&field.name),
+                ValidationIssueKind::MissingField,
+                format!("Missing required field: '{}'", field.name),
+            ),
+        }
+    }
+
+    for field in &schema.optional_fields {
+        if let Some(value) = obj.get(&field.name) {
+            check_field_type(value, &field.field_type, &field.name, &mut report);
+        }
+    }
+
+    // In strict mode, no extra fields are allowed
+    if strict {
+        let expected_fields: std::collections::HashSet<&String> = schema
+            .required_fields
+            .iter()
+            .chain(schema.optional_fields.iter())
+            .map(|f| &f.name)
+            .collect();
+
+        for key in obj.keys() {
+            if !expected_fields.contains(key) {
+                report.push(
+                    Some(key),
+                    ValidationIssueKind::UnexpectedField,
+                    format!("Unexpected field in strict mode: '{}'", key),
+                );
+            }
+        }
+    }
+
+    report
+}
+
+fn json_type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+fn check_field_type(value: &Value, expected_type: &JsonFieldType, path: &str, report: &mut ValidationReport) {
+    let (matches, expected) = match expected_type {
+        JsonFieldType::String => (value.is_string(), "a string"),
+        JsonFieldType::Number => (value.is_number(), "a number"),
+        JsonFieldType::Boolean => (value.is_boolean(), "a boolean"),
+        JsonFieldType::Object => (value.is_object(), "an object"),
+        JsonFieldType::Array(element_type) => {
+            if let Some(items) = value.as_array() {
+                for (i, element) in items.iter().enumerate() {
+                    check_field_type(element, element_type, &format!("{}[{}]", path, i), report);
+                }
+            }
+            (value.is_array(), "an array")
+        }
+    };
+    if !matches {
+        report.push(
+            Some(path),
+            ValidationIssueKind::TypeMismatch {
+                expected: expected.to_string(),
+                found: json_type_name(value).to_string(),
+            },
+            format!("Field '{}' must be {}, got: {}", path, expected, value),
+        );
+    }
+}
+
+/// Strip an optional markdown code fence (e.g. ```json or ```csv) around the output
+pub(crate) fn strip_code_fence(output: &str) -> &str {
+    let trimmed = output.trim();
+    if trimmed.starts_with("```") && trimmed.ends_with("```") && trimmed.len() > 6 {
+        let inner = &trimmed[3..trimmed.len() - 3];
+        // Drop the language tag line
+        match inner.find('\n') {
+            Some(pos) => inner[pos + 1..].trim(),
+            None => inner.trim(),
+        }
+    } else {
+        trimmed
+    }
 }
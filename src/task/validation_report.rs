@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+
+/// What kind of problem a validation issue describes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ValidationIssueKind {
+    /// The output could not be parsed in the required format at all
+    Syntax,
+    MissingField,
+    TypeMismatch { expected: String, found: String },
+    UnexpectedField,
+    /// Failed a format-level check of the output handler or a registered validator
+    Format,
+    /// Failed one of the task's business-rule guards
+    Guard,
+    /// Written in another language than the task requires (ISO 639-1 codes)
+    Language { expected: String, detected: String },
+    /// Broke a rule of the agent's content policy
+    Policy { rule: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidationIssue {
+    /// Field path such as `items[2].price`; None for whole-output issues
+    pub path: Option<String>,
+    #[serde(flatten)]
+    pub kind: ValidationIssueKind,
+    pub message: String,
+}
+
+/// Every problem found in one output, for callers and for the correction prompt
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ValidationReport {
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Report with a single whole-output issue
+    pub fn single(kind: ValidationIssueKind, message: impl Into<String>) -> Self {
+        let mut report = Self::new();
+        report.push(None, kind, message);
+        report
+    }
+
+    pub fn push(&mut self, path: Option<&str>, kind: ValidationIssueKind, message: impl Into<String>) {
+        self.issues.push(ValidationIssue {
+            path: path.map(str::to_string),
+            kind,
+            message: message.into(),
+        });
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    pub fn missing_fields(&self) -> Vec<&str> {
+        self.paths_of(|kind| matches!(kind, ValidationIssueKind::MissingField))
+    }
+
+    pub fn unexpected_fields(&self) -> Vec<&str> {
+        self.paths_of(|kind| matches!(kind, ValidationIssueKind::UnexpectedField))
+    }
+
+    pub fn type_mismatches(&self) -> Vec<&ValidationIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| matches!(issue.kind, ValidationIssueKind::TypeMismatch { .. }))
+            .collect()
+    }
+
+    fn paths_of(&self, filter: impl Fn(&ValidationIssueKind) -> bool) -> Vec<&str> {
+        self.issues
+            .iter()
+            .filter(|issue| filter(&issue.kind))
+            .filter_map(|issue| issue.path.as_deref())
+            .collect()
+    }
+
+    /// One-line description, used as the error message
+    pub fn summary(&self) -> String {
+        self.issues.iter().map(|issue| issue.message.as_str()).collect::<Vec<_>>().join("; ")
+    }
+
+    /// Correction message sent back to the model, listing each problem precisely
+    pub fn correction_prompt(&self) -> String {
+        format!(
+            "Your previous response was invalid:\n{}Please provide a corrected response in the required format, fixing every item above.",
+            self.correction_items()
+        )
+    }
+
+    /// The problems of `correction_prompt`, one `- ` line each
+    pub fn correction_items(&self) -> String {
+        let mut prompt = String::new();
+        let missing = self.missing_fields();
+        if !missing.is_empty() {
+            prompt.push_str(&format!("- Missing required fields: {}\n", missing.join(", ")));
+        }
+        for issue in self.type_mismatches() {
+            if let ValidationIssueKind::TypeMismatch { expected, found } = &issue.kind {
+                prompt.push_str(&format!(
+                    "- Field '{}' must be {}, but was {}\n",
+                    issue.path.as_deref().unwrap_or_default(),
+                    expected,
+                    found
+                ));
+            }
+        }
+        let unexpected = self.unexpected_fields();
+        if !unexpected.is_empty() {
+            prompt.push_str(&format!("- Remove fields that are not allowed: {}\n", unexpected.join(", ")));
+        }
+        for issue in &self.issues {
+            if matches!(
+                issue.kind,
+                ValidationIssueKind::Syntax
+                    | ValidationIssueKind::Format
+                    | ValidationIssueKind::Guard
+                    | ValidationIssueKind::Language { .. }
+                    | ValidationIssueKind::Policy { .. }
+            ) {
+                prompt.push_str(&format!("- {}\n", issue.message));
+            }
+        }
+        prompt
+    }
+}
+
+impl std::fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.summary())
+    }
+}
@@ -0,0 +1,434 @@
+use anyhow::{Result, anyhow};
+use crate::task::task::strip_code_fence;
+
+/// Deepest element nesting accepted, so hostile output can't exhaust the stack
+const MAX_DEPTH: usize = 256;
+
+// Minimal element schema for XML output
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct XmlSchema {
+    pub root: XmlElementSchema,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct XmlElementSchema {
+    pub name: String,
+    pub attributes: Vec<XmlAttributeSchema>,
+    pub children: Vec<XmlElementSchema>,
+    pub required: bool,   // Whether at least one occurrence must be present
+    pub multiple: bool,   // Whether the element may repeat
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct XmlAttributeSchema {
+    pub name: String,
+    pub required: bool,
+}
+
+impl XmlElementSchema {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            attributes: Vec::new(),
+            children: Vec::new(),
+            required: true,
+            multiple: false,
+            description: None,
+        }
+    }
+
+    pub fn with_attribute(mut self, name: &str, required: bool) -> Self {
+        self.attributes.push(XmlAttributeSchema {
+            name: name.to_string(),
+            required,
+        });
+        self
+    }
+
+    pub fn with_child(mut self, child: XmlElementSchema) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    pub fn optional(mut self) -> Self {
+        self.required = false;
+        self
+    }
+
+    pub fn repeated(mut self) -> Self {
+        self.multiple = true;
+        self
+    }
+
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+}
+
+/// Parsed XML element
+#[derive(Debug, Clone, PartialEq)]
+pub struct XmlNode {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<XmlNode>,
+    pub text: String,
+}
+
+impl XmlNode {
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Parse a well-formed XML document into its root element
+pub fn parse_xml(input: &str) -> Result<XmlNode> {
+    let mut parser = XmlParser {
+        input: strip_code_fence(input).as_bytes(),
+        pos: 0,
+    };
+
+    parser.skip_prolog()?;
+    let root = parser.parse_element(1)?;
+    parser.skip_misc()?;
+
+    if parser.pos < parser.input.len() {
+        return Err(anyhow!("Unexpected content after the root element at byte {}", parser.pos));
+    }
+
+    Ok(root)
+}
+
+/// Validate XML output for well-formedness and against the element schema
+pub fn validate_xml(output: &str, schema: &XmlSchema) -> Result<XmlNode> {
+    let root = parse_xml(output)?;
+
+    if root.name != schema.root.name {
+        return Err(anyhow!(
+            "Root element is <{}>, expected <{}>",
+            root.name,
+            schema.root.name
+        ));
+    }
+
+    validate_element(&root, &schema.root, &root.name)?;
+    Ok(root)
+}
+
+fn validate_element(node: &XmlNode, schema: &XmlElementSchema, path: &str) -> Result<()> {
+    for attribute in &schema.attributes {
+        if attribute.required && node.attribute(&attribute.name).is_none() {
+            return Err(anyhow!(
+                "Element '{}' is missing required attribute '{}'",
+                path,
+                attribute.name
+            ));
+        }
+    }
+
+    for child_schema in &schema.children {
+        let matches: Vec<&XmlNode> = node
+            .children
+            .iter()
+            .filter(|child| child.name == child_schema.name)
+            .collect();
+
+        if matches.is_empty() && child_schema.required {
+            return Err(anyhow!(
+                "Element '{}' is missing required child <{}>",
+                path,
+                child_schema.name
+            ));
+        }
+
+        if matches.len() > 1 && !child_schema.multiple {
+            return Err(anyhow!(
+                "Element '{}' must contain at most one <{}>, found {}",
+                path,
+                child_schema.name,
+                matches.len()
+            ));
+        }
+
+        for child in matches {
+            validate_element(child, child_schema, &format!("{}/{}", path, child.name))?;
+        }
+    }
+
+    Ok(())
+}
+
+struct XmlParser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> XmlParser<'a> {
+    fn starts_with(&self, token: &str) -> bool {
+        self.input[self.pos..].starts_with(token.as_bytes())
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.pos < self.input.len() && self.input[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    fn skip_until(&mut self, terminator: &str) -> Result<()> {
+        while self.pos < self.input.len() {
+            if self.starts_with(terminator) {
+                self.pos += terminator.len();
+                return Ok(());
+            }
+            self.pos += 1;
+        }
+        Err(anyhow!("Unterminated construct, expected '{}'", terminator))
+    }
+
+    // Skip the XML declaration, doctype, comments, and processing instructions
+    fn skip_prolog(&mut self) -> Result<()> {
+        loop {
+            self.skip_whitespace();
+            if self.starts_with("<?") {
+                self.skip_until("?>")?;
+            } else if self.starts_with("<!--") {
+                self.skip_until("-->")?;
+            } else if self.starts_with("<!DOCTYPE") {
+                self.skip_until(">")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn skip_misc(&mut self) -> Result<()> {
+        loop {
+            self.skip_whitespace();
+            if self.starts_with("<!--") {
+                self.skip_until("-->")?;
+            } else if self.starts_with("<?") {
+                self.skip_until("?>")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn parse_name(&mut self) -> Result<String> {
+        let start = self.pos;
+        while self.pos < self.input.len() {
+            let c = self.input[self.pos];
+            if c.is_ascii_alphanumeric() || matches!(c, b'_' | b'-' | b'.' | b':') || c >= 0x80 {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        if start == self.pos {
+            return Err(anyhow!("Expected a name at byte {}", start));
+        }
+        Ok(String::from_utf8_lossy(&self.input[start..self.pos]).to_string())
+    }
+
+    fn parse_element(&mut self, depth: usize) -> Result<XmlNode> {
+        if !self.starts_with("<") {
+            return Err(anyhow!("Expected '<' at byte {}", self.pos));
+        }
+        if depth > MAX_DEPTH {
+            return Err(anyhow!("Elements are nested deeper than {} levels at byte {}", MAX_DEPTH, self.pos));
+        }
+        self.pos += 1;
+
+        let name = self.parse_name()?;
+        let mut attributes = Vec::new();
+
+        loop {
+            self.skip_whitespace();
+            if self.starts_with("/>") {
+                self.pos += 2;
+                return Ok(XmlNode {
+                    name,
+                    attributes,
+                    children: Vec::new(),
+                    text: String::new(),
+                });
+            }
+            if self.starts_with(">") {
+                self.pos += 1;
+                break;
+            }
+
+            let attribute_name = self.parse_name()?;
+            self.skip_whitespace();
+            if !self.starts_with("=") {
+                return Err(anyhow!("Attribute '{}' on <{}> has no value", attribute_name, name));
+            }
+            self.pos += 1;
+            self.skip_whitespace();
+
+            let quote = *self.input.get(self.pos)
+                .ok_or_else(|| anyhow!("Unexpected end of input in <{}>", name))?;
+            if quote != b'"' && quote != b'\'' {
+                return Err(anyhow!("Attribute '{}' on <{}> must be quoted", attribute_name, name));
+            }
+            self.pos += 1;
+            let start = self.pos;
+            while self.pos < self.input.len() && self.input[self.pos] != quote {
+                self.pos += 1;
+            }
+            if self.pos >= self.input.len() {
+                return Err(anyhow!("Unterminated attribute '{}' on <{}>", attribute_name, name));
+            }
+            let raw = &self.input[start..self.pos];
+            if raw.contains(&b'<') {
+                return Err(anyhow!("Attribute '{}' on <{}> contains an unescaped '<'", attribute_name, name));
+            }
+            let value = decode_entities(raw, start)?;
+            self.pos += 1;
+
+            if attributes.iter().any(|(key, _): &(String, String)| key == &attribute_name) {
+                return Err(anyhow!("Duplicate attribute '{}' on <{}>", attribute_name, name));
+            }
+            attributes.push((attribute_name, value));
+        }
+
+        let mut children = Vec::new();
+        let mut text = String::new();
+
+        loop {
+            if self.pos >= self.input.len() {
+                return Err(anyhow!("Element <{}> is not closed", name));
+            }
+
+            if self.starts_with("</") {
+                self.pos += 2;
+                let closing = self.parse_name()?;
+                if closing != name {
+                    return Err(anyhow!("Mismatched closing tag </{}>, expected </{}>", closing, name));
+                }
+                self.skip_whitespace();
+                if !self.starts_with(">") {
+                    return Err(anyhow!("Malformed closing tag </{}>", closing));
+                }
+                self.pos += 1;
+                return Ok(XmlNode {
+                    name,
+                    attributes,
+                    children,
+                    text: text.trim().to_string(),
+                });
+            } else if self.starts_with("<!--") {
+                self.skip_until("-->")?;
+            } else if self.starts_with("<![CDATA[") {
+                self.pos += 9;
+                let start = self.pos;
+                self.skip_until("]]>")?;
+                text.push_str(&String::from_utf8_lossy(&self.input[start..self.pos - 3]));
+            } else if self.starts_with("<?") {
+                self.skip_until("?>")?;
+            } else if self.starts_with("<") {
+                children.push(self.parse_element(depth + 1)?);
+            } else {
+                let start = self.pos;
+                while self.pos < self.input.len() && self.input[self.pos] != b'<' {
+                    self.pos += 1;
+                }
+                text.push_str(&decode_entities(&self.input[start..self.pos], start)?);
+            }
+        }
+    }
+}
+
+/// Replace the predefined and numeric character references in text or an
+/// attribute value. A bare '&' is an error; `offset` locates `raw` in the input.
+fn decode_entities(raw: &[u8], offset: usize) -> Result<String> {
+    let raw = String::from_utf8_lossy(raw);
+    let mut decoded = String::with_capacity(raw.len());
+    let mut rest = raw.as_ref();
+    while let Some(amp) = rest.find('&') {
+        decoded.push_str(&rest[..amp]);
+        let at = offset + (raw.len() - rest.len()) + amp;
+        let reference = &rest[amp + 1..];
+        let end = reference
+            .find(';')
+            .filter(|&end| end > 0 && end <= 10)
+            .ok_or_else(|| anyhow!("Unescaped '&' at byte {}", at))?;
+        let entity = &reference[..end];
+        let character = match entity {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => match entity.strip_prefix('#') {
+                Some(hex) if hex.starts_with(['x', 'X']) => u32::from_str_radix(&hex[1..], 16).ok().and_then(char::from_u32),
+                Some(decimal) => decimal.parse().ok().and_then(char::from_u32),
+                None => return Err(anyhow!("Unknown entity '&{};' at byte {}", entity, at)),
+            },
+        };
+        decoded.push(character.ok_or_else(|| anyhow!("Invalid character reference '&{};' at byte {}", entity, at))?);
+        rest = &reference[end + 1..];
+    }
+    decoded.push_str(rest);
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_entities_in_text_and_attributes() {
+        let root = parse_xml(r#"<note title="Tom &amp; Jerry">1 &lt; 2 &#38; 3 &#x3E; 2</note>"#).unwrap();
+        assert_eq!(root.attribute("title"), Some("Tom & Jerry"));
+        assert_eq!(root.text, "1 < 2 & 3 > 2");
+    }
+
+    #[test]
+    fn rejects_unescaped_ampersand_and_lt() {
+        assert!(parse_xml("<note>Tom & Jerry</note>").is_err());
+        assert!(parse_xml("<note>&bogus;</note>").is_err());
+        assert!(parse_xml(r#"<note title="a < b"/>"#).is_err());
+        assert!(parse_xml("<note>1 < 2</note>").is_err());
+    }
+
+    #[test]
+    fn cdata_is_kept_verbatim() {
+        let root = parse_xml("<code><![CDATA[a && b < c]]></code>").unwrap();
+        assert_eq!(root.text, "a && b < c");
+    }
+
+    #[test]
+    fn limits_nesting_depth() {
+        let nested = |depth: usize| format!("{}{}", "<a>".repeat(depth), "</a>".repeat(depth));
+        assert!(parse_xml(&nested(MAX_DEPTH)).is_ok());
+        assert!(parse_xml(&nested(MAX_DEPTH + 1)).is_err());
+    }
+
+    #[test]
+    fn validates_against_schema() {
+        let schema = XmlSchema {
+            root: XmlElementSchema::new("order")
+                .with_attribute("id", true)
+                .with_child(XmlElementSchema::new("item").repeated())
+                .with_child(XmlElementSchema::new("note").optional()),
+        };
+        assert!(validate_xml(r#"<order id="1"><item/><item/></order>"#, &schema).is_ok());
+        assert!(validate_xml("<order><item/></order>", &schema).is_err());
+        assert!(validate_xml(r#"<order id="1"></order>"#, &schema).is_err());
+        assert!(validate_xml(r#"<order id="1"><item/><note/><note/></order>"#, &schema).is_err());
+        assert!(validate_xml(r#"<invoice id="1"><item/></invoice>"#, &schema).is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_documents() {
+        assert!(parse_xml("<a><b></a></b>").is_err());
+        assert!(parse_xml("<a>").is_err());
+        assert!(parse_xml("<a/><b/>").is_err());
+        assert!(parse_xml(r#"<a x="1" x="2"/>"#).is_err());
+    }
+}
@@ -0,0 +1,18 @@
+//! Span helpers for the `tracing` feature. Without the feature they compile to the
+//! bare expression, so instrumented code carries no cost or dependency.
+//!
+//! Span names follow `merco.<area>.<operation>`; fields use `agent_id`, `task_id`,
+//! `trace_id`, `model`, `tool` and `round` consistently.
+
+/// Run a future inside a span
+macro_rules! traced {
+    ($future:expr, $name:literal $(, $($fields:tt)*)?) => {{
+        #[cfg(feature = "tracing")]
+        let future = tracing::Instrument::instrument($future, tracing::info_span!($name $(, $($fields)*)?));
+        #[cfg(not(feature = "tracing"))]
+        let future = $future;
+        future
+    }};
+}
+
+pub(crate) use traced;
@@ -0,0 +1,100 @@
+use crate::task::run_history::{RunFilter, RunRecord, RunStore};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+
+/// Customer a piece of work belongs to. An agent built for one tenant
+/// (`Agent::with_tenant`) refuses tasks of any other, tasks without a tenant
+/// take the agent's, and run records carry the task's tenant.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TenantId(String);
+
+impl TenantId {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self(id.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for TenantId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for TenantId {
+    fn from(id: &str) -> Self {
+        Self::new(id)
+    }
+}
+
+impl From<String> for TenantId {
+    fn from(id: String) -> Self {
+        Self(id)
+    }
+}
+
+/// Tenant the work runs under: the task's, else the owner's. Fails when both
+/// are set and differ. Owners without a tenant (shared agents) serve any tenant.
+pub fn resolve_tenant(owner: Option<&TenantId>, requested: Option<&TenantId>) -> Result<Option<TenantId>, String> {
+    match (owner, requested) {
+        (Some(owner), Some(requested)) if owner != requested => Err(format!(
+            "tenant '{}' cannot run work of tenant '{}'",
+            owner, requested
+        )),
+        (owner, requested) => Ok(requested.or(owner).cloned()),
+    }
+}
+
+/// A run store seen through one tenant: lookups only return that tenant's
+/// records and saving another tenant's record fails. Hand this, not the shared
+/// store, to code serving a single customer.
+pub struct TenantRunStore {
+    inner: Arc<dyn RunStore>,
+    tenant: TenantId,
+}
+
+impl TenantRunStore {
+    pub fn new(inner: Arc<dyn RunStore>, tenant: TenantId) -> Self {
+        Self { inner, tenant }
+    }
+
+    pub fn tenant(&self) -> &TenantId {
+        &self.tenant
+    }
+}
+
+impl RunStore for TenantRunStore {
+    fn save(&self, record: RunRecord) -> Result<()> {
+        if record.tenant_id.as_ref() != Some(&self.tenant) {
+            return Err(anyhow!(
+                "Run {} of tenant {:?} cannot be saved to the store of tenant '{}'",
+                record.task_id,
+                record.tenant_id.as_ref().map(TenantId::as_str),
+                self.tenant
+            ));
+        }
+        self.inner.save(record)
+    }
+
+    fn get(&self, task_id: &str) -> Option<RunRecord> {
+        self.inner
+            .get(task_id)
+            .filter(|record| record.tenant_id.as_ref() == Some(&self.tenant))
+    }
+
+    fn list(&self, filter: &RunFilter) -> Vec<RunRecord> {
+        let mut filter = filter.clone();
+        filter.tenant_id = Some(self.tenant.clone());
+        self.inner.list(&filter)
+    }
+
+    fn flush(&self) -> Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,23 @@
+pub mod registry;
+pub mod process;
+pub mod sandbox;
+pub mod permissions;
+pub mod network;
+#[cfg(feature = "wasm-tools")]
+pub mod wasm;
+#[cfg(feature = "tools-std")]
+pub mod std_tools;
+#[cfg(feature = "python")]
+pub mod python;
+
+pub use registry::{LocalTool, register_tool, unregister_tool, run_tool, tool_definition};
+pub use process::ProcessTool;
+pub use sandbox::{LimitViolation, ToolFailure, run_tool_with_limits, call_cancelled, abandoned_calls};
+pub use permissions::{set_tool_permissions, required_permissions, check_tool_permissions};
+pub use network::{check_url, domain_allowed};
+#[cfg(feature = "wasm-tools")]
+pub use wasm::{WasmTool, WasmCapabilities, WasmLimits};
+#[cfg(feature = "tools-std")]
+pub use std_tools::{std_tools, register_std_tools, ConfirmCommand, FetchUrlTool};
+#[cfg(feature = "python")]
+pub use python::{PythonTool, register_python_tools};
@@ -0,0 +1,116 @@
+use std::sync::Arc;
+
+tokio::task_local! {
+    static ALLOWED_DOMAINS: Arc<Vec<String>>;
+}
+
+/// Run a (synchronous) tool execution with the agent's `NetworkContext.allowed_domains`
+/// in effect for `check_url`
+pub fn with_allowed_domains<F, R>(allowed_domains: Arc<Vec<String>>, f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    ALLOWED_DOMAINS.sync_scope(allowed_domains, f)
+}
+
+/// The calling agent's `allowed_domains` during a tool call, if it restricts any
+pub(crate) fn restricted_domains() -> Option<Arc<Vec<String>>> {
+    ALLOWED_DOMAINS.try_with(Arc::clone).ok().filter(|allowed| !allowed.is_empty())
+}
+
+/// Whether `host` is covered by `allowed`. Entries match the domain and its
+/// subdomains (`example.com` covers `api.example.com`); `*.example.com` covers only
+/// subdomains and `*` everything. An empty list allows every host.
+pub fn domain_allowed(host: &str, allowed: &[String]) -> bool {
+    if allowed.is_empty() {
+        return true;
+    }
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    allowed.iter().any(|entry| {
+        let entry = entry.trim().trim_end_matches('.').to_ascii_lowercase();
+        if entry == "*" {
+            return true;
+        }
+        match entry.strip_prefix("*.") {
+            Some(parent) => host.ends_with(&format!(".{}", parent)),
+            None => host == entry || host.ends_with(&format!(".{}", entry)),
+        }
+    })
+}
+
+/// Gatekeeper for outbound requests made by tools: fails for URLs whose host the
+/// calling agent's `allowed_domains` doesn't cover, logging the denied attempt.
+/// Built-in network tools call it before every request; custom tools should too.
+/// Outside an agent's tool call nothing is restricted.
+///
+/// Traffic that doesn't pass through here can't be checked per host. While an
+/// allowlist is in effect, `ProcessTool` (and the shell tool built on it) runs
+/// without network access, `PythonTool` refuses calls unless declared offline,
+/// and WASM tools have no network imports to begin with.
+pub fn check_url(url: &str) -> Result<(), String> {
+    let Ok(allowed) = ALLOWED_DOMAINS.try_with(Arc::clone) else {
+        return Ok(());
+    };
+    let host = reqwest::Url::parse(url)
+        .map_err(|e| format!("Invalid URL '{}': {}", url, e))?
+        .host_str()
+        .map(str::to_string)
+        .ok_or_else(|| format!("URL '{}' has no host", url))?;
+    if domain_allowed(&host, &allowed) {
+        return Ok(());
+    }
+    eprintln!("Blocked outbound request to {} (host '{}' is not in allowed_domains)", crate::redaction::redact(url), host);
+    Err(format!("Requests to '{}' are not allowed; allowed domains: {}", host, allowed.join(", ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allowed(entries: &[&str]) -> Vec<String> {
+        entries.iter().map(|entry| entry.to_string()).collect()
+    }
+
+    #[test]
+    fn domain_covers_itself_and_subdomains() {
+        let list = allowed(&["example.com"]);
+        assert!(domain_allowed("example.com", &list));
+        assert!(domain_allowed("api.example.com", &list));
+        assert!(domain_allowed("API.Example.COM.", &list));
+    }
+
+    #[test]
+    fn lookalike_hosts_are_rejected() {
+        let list = allowed(&["example.com"]);
+        assert!(!domain_allowed("evil-example.com", &list));
+        assert!(!domain_allowed("notexample.com", &list));
+        assert!(!domain_allowed("example.com.evil.net", &list));
+        assert!(!domain_allowed("example.co", &list));
+    }
+
+    #[test]
+    fn wildcard_entries() {
+        let subdomains = allowed(&["*.example.com"]);
+        assert!(domain_allowed("a.b.example.com", &subdomains));
+        assert!(!domain_allowed("example.com", &subdomains));
+        assert!(!domain_allowed("evil-example.com", &subdomains));
+        assert!(domain_allowed("anything.net", &allowed(&["*"])));
+        assert!(domain_allowed("anything.net", &[]));
+    }
+
+    #[test]
+    fn check_url_applies_only_inside_a_tool_call() {
+        assert!(check_url("https://evil.net/").is_ok());
+        let list = Arc::new(allowed(&["example.com"]));
+        with_allowed_domains(list, || {
+            assert!(check_url("https://api.example.com/v1").is_ok());
+            assert!(check_url("https://evil.net/").is_err());
+            // Credentials before the host don't change where the request goes
+            assert!(check_url("https://example.com@evil.net/").is_err());
+            assert!(check_url("https://evil.net/?next=https://example.com").is_err());
+            assert!(check_url("not a url").is_err());
+            assert!(restricted_domains().is_some());
+        });
+        assert!(restricted_domains().is_none());
+    }
+}
@@ -0,0 +1,43 @@
+use crate::agent::state::Permission;
+use crate::tools::registry::local_tool;
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+fn overrides() -> &'static RwLock<HashMap<String, Vec<Permission>>> {
+    static OVERRIDES: OnceLock<RwLock<HashMap<String, Vec<Permission>>>> = OnceLock::new();
+    OVERRIDES.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// Set the permissions a tool needs, e.g. for `#[merco_tool]` functions or to
+/// override what a local tool declares. An empty list lets every agent call it.
+pub fn set_tool_permissions(name: &str, permissions: Vec<Permission>) {
+    overrides().write().unwrap().insert(name.to_string(), permissions);
+}
+
+/// Permissions an agent's `SecurityContext` must grant to call the tool:
+/// the override if set, else what the local tool declares, else none
+pub fn required_permissions(name: &str) -> Vec<Permission> {
+    if let Some(permissions) = overrides().read().unwrap().get(name) {
+        return permissions.clone();
+    }
+    local_tool(name).map(|tool| tool.required_permissions()).unwrap_or_default()
+}
+
+/// Refuse a tool call the granted permissions don't cover; `Admin` covers all
+pub fn check_tool_permissions(name: &str, granted: &[Permission]) -> Result<(), String> {
+    if granted.contains(&Permission::Admin) {
+        return Ok(());
+    }
+    let missing: Vec<Permission> = required_permissions(name)
+        .into_iter()
+        .filter(|permission| !granted.contains(permission))
+        .collect();
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Permission denied: tool '{}' requires {:?} but the agent is only granted {:?}",
+            name, missing, granted
+        ))
+    }
+}
@@ -0,0 +1,366 @@
+use crate::agent::state::{Permission, ResourceLimits};
+use crate::task::inputs::{lookup, render_template};
+use crate::tools::network::restricted_domains;
+use crate::tools::registry::LocalTool;
+use serde_json::Value;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// A tool backed by an external executable.
+///
+/// Arguments are rendered from an argv template whose `{{key}}` placeholders are
+/// filled from the model's JSON arguments; the full JSON arguments are also
+/// written to the process's stdin. Whatever the process prints to stdout is the
+/// tool result, and a non-zero exit status is a tool error carrying stderr.
+/// When the calling agent restricts `allowed_domains`, the process runs in its own
+/// network namespace without network access (Linux only; elsewhere it is refused).
+#[derive(Debug, Clone)]
+pub struct ProcessTool {
+    name: String,
+    description: String,
+    parameters: Value,
+    program: PathBuf,
+    argv: Vec<String>,
+    /// Values filled into the argument template must stay inside this directory
+    root: Option<PathBuf>,
+    working_dir: Option<PathBuf>,
+    /// Environment variables passed through; everything else is cleared
+    env_allowlist: Vec<String>,
+    timeout: Duration,
+    max_memory_bytes: Option<u64>,
+    max_cpu_seconds: Option<u64>,
+    max_output_bytes: usize,
+    permissions: Vec<Permission>,
+}
+
+impl ProcessTool {
+    pub fn new(name: &str, description: &str, parameters: Value, program: impl Into<PathBuf>) -> Self {
+        Self {
+            name: name.to_string(),
+            description: description.to_string(),
+            parameters,
+            program: program.into(),
+            argv: Vec::new(),
+            root: None,
+            working_dir: None,
+            env_allowlist: vec!["PATH".to_string()],
+            timeout: Duration::from_secs(30),
+            max_memory_bytes: None,
+            max_cpu_seconds: None,
+            max_output_bytes: 1024 * 1024,
+            permissions: Vec::new(),
+        }
+    }
+
+    /// Argument template, e.g. `["--city", "{{city}}"]`
+    pub fn with_args(mut self, argv: Vec<&str>) -> Self {
+        self.argv = argv.into_iter().map(str::to_string).collect();
+        self
+    }
+
+    /// Run inside `root` and reject argument values that, as paths, escape it
+    pub fn confined_to(mut self, root: impl Into<PathBuf>) -> Self {
+        let root = root.into();
+        self.working_dir = Some(root.clone());
+        self.root = Some(root);
+        self
+    }
+
+    /// Run inside `dir` without restricting arguments
+    pub fn with_working_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.working_dir = Some(dir.into());
+        self
+    }
+
+    pub fn with_env_allowlist(mut self, names: Vec<&str>) -> Self {
+        self.env_allowlist = names.into_iter().map(str::to_string).collect();
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Address space limit of the process (Unix only)
+    pub fn with_max_memory_bytes(mut self, bytes: u64) -> Self {
+        self.max_memory_bytes = Some(bytes);
+        self
+    }
+
+    pub fn with_max_output_bytes(mut self, bytes: usize) -> Self {
+        self.max_output_bytes = bytes;
+        self
+    }
+
+    /// Permissions an agent must be granted to call this tool, e.g. `Write` for one that changes files
+    pub fn with_permissions(mut self, permissions: Vec<Permission>) -> Self {
+        self.permissions = permissions;
+        self
+    }
+
+    /// CPU time the process may use before the kernel stops it
+    pub fn with_max_cpu_seconds(mut self, seconds: u64) -> Self {
+        self.max_cpu_seconds = Some(seconds);
+        self
+    }
+
+    /// Apply the memory, CPU and response time limits of an agent's environment.
+    /// The CPU budget is `max_cpu_percent` of the response time; a response time of
+    /// 0 (no limit) keeps the tool's own timeout.
+    pub fn with_resource_limits(mut self, limits: &ResourceLimits) -> Self {
+        self.max_memory_bytes = Some(limits.max_memory_mb * 1024 * 1024);
+        if let Some(cpu) = cpu_seconds(limits) {
+            self.max_cpu_seconds = Some(cpu);
+        }
+        if limits.max_response_time_ms > 0 {
+            self.timeout = Duration::from_millis(limits.max_response_time_ms);
+        }
+        self
+    }
+
+    /// This tool with each limit lowered to the agent's where that is stricter
+    fn within(&self, limits: &ResourceLimits) -> Self {
+        let mut tool = self.clone();
+        let memory = limits.max_memory_mb * 1024 * 1024;
+        tool.max_memory_bytes = Some(self.max_memory_bytes.map_or(memory, |own| own.min(memory)));
+        if let Some(cpu) = cpu_seconds(limits) {
+            tool.max_cpu_seconds = Some(self.max_cpu_seconds.map_or(cpu, |own| own.min(cpu)));
+        }
+        if limits.max_response_time_ms > 0 {
+            tool.timeout = self.timeout.min(Duration::from_millis(limits.max_response_time_ms));
+        }
+        tool
+    }
+
+    fn render_args(&self, arguments: &Value) -> Result<Vec<String>, String> {
+        if let Some(root) = &self.root {
+            // Check the model's values rather than whole arguments, so a value
+            // embedded in a flag (`--file={{path}}`) is still confined
+            let root = root.canonicalize().map_err(|e| format!("Tool root {} unavailable: {}", root.display(), e))?;
+            for template in &self.argv {
+                for value in placeholder_values(template, arguments) {
+                    confine(&root, &value)?;
+                }
+            }
+        }
+        Ok(self.argv.iter().map(|arg| render_template(arg, arguments)).collect())
+    }
+
+    fn run(&self, arguments: &str) -> Result<String, String> {
+        let parsed: Value = serde_json::from_str(arguments).map_err(|e| format!("Invalid tool arguments: {}", e))?;
+        let args = self.render_args(&parsed)?;
+
+        let mut command = Command::new(&self.program);
+        command
+            .args(&args)
+            .env_clear()
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        for name in &self.env_allowlist {
+            if let Ok(value) = std::env::var(name) {
+                command.env(name, value);
+            }
+        }
+        if let Some(dir) = &self.working_dir {
+            command.current_dir(dir);
+        }
+        // Its traffic can't be checked against the agent's allowed_domains, so it gets none
+        let isolate_network = restricted_domains().is_some();
+        #[cfg(not(target_os = "linux"))]
+        if isolate_network {
+            return Err(format!(
+                "Tool '{}' can't run while allowed_domains is set: network isolation needs Linux",
+                self.name
+            ));
+        }
+        #[cfg(unix)]
+        if self.max_memory_bytes.is_some() || self.max_cpu_seconds.is_some() || isolate_network {
+            use std::os::unix::process::CommandExt;
+            let (memory, cpu) = (self.max_memory_bytes, self.max_cpu_seconds);
+            // SAFETY: unshare and setrlimit are async-signal-safe and only affect the child
+            unsafe {
+                command.pre_exec(move || {
+                    // A fresh network namespace has only a loopback device, which is down
+                    #[cfg(target_os = "linux")]
+                    if isolate_network && libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNET) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                    let limits = [(libc::RLIMIT_AS, memory), (libc::RLIMIT_CPU, cpu)];
+                    for (resource, value) in limits {
+                        let Some(value) = value else { continue };
+                        let limit = libc::rlimit { rlim_cur: value as libc::rlim_t, rlim_max: value as libc::rlim_t };
+                        if libc::setrlimit(resource, &limit) != 0 {
+                            return Err(std::io::Error::last_os_error());
+                        }
+                    }
+                    Ok(())
+                });
+            }
+        }
+
+        let mut child = command
+            .spawn()
+            .map_err(|e| format!("Failed to start {}: {}", self.program.display(), e))?;
+
+        // Read both pipes on their own threads so a chatty process can't block on a full pipe
+        let stdout = child.stdout.take().map(|pipe| read_capped(pipe, self.max_output_bytes));
+        let stderr = child.stderr.take().map(|pipe| read_capped(pipe, 64 * 1024));
+        // Written from its own thread too: a process that never reads its input would
+        // otherwise block the write, and with it the timeout, once the pipe is full
+        if let Some(mut stdin) = child.stdin.take() {
+            let input = arguments.to_string();
+            std::thread::spawn(move || {
+                // A process that ignores its input may exit before reading it
+                let _ = stdin.write_all(input.as_bytes());
+            });
+        }
+
+        let started = Instant::now();
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break status,
+                Ok(None) if started.elapsed() >= self.timeout => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(format!("Tool '{}' timed out after {} ms", self.name, self.timeout.as_millis()));
+                }
+                Ok(None) => std::thread::sleep(Duration::from_millis(10)),
+                Err(e) => return Err(format!("Failed to wait for tool '{}': {}", self.name, e)),
+            }
+        };
+
+        let stdout = stdout.map(|reader| reader.join().unwrap_or_default()).unwrap_or_default();
+        let stderr = stderr.map(|reader| reader.join().unwrap_or_default()).unwrap_or_default();
+        if status.success() {
+            Ok(stdout)
+        } else {
+            Err(format!("Tool '{}' exited with {}: {}", self.name, status, stderr.trim()))
+        }
+    }
+}
+
+/// CPU seconds allowed by `max_cpu_percent` of the response time, at least one;
+/// None without a response time limit
+fn cpu_seconds(limits: &ResourceLimits) -> Option<u64> {
+    (limits.max_response_time_ms > 0)
+        .then(|| (limits.max_response_time_ms * limits.max_cpu_percent as u64).div_ceil(100_000).max(1))
+}
+
+/// The values `template`'s placeholders are filled with, as `render_template` inserts them
+fn placeholder_values(template: &str, arguments: &Value) -> Vec<String> {
+    let mut values = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let after_open = &rest[start + 2..];
+        let Some(end) = after_open.find("}}") else {
+            break;
+        };
+        match lookup(arguments, after_open[..end].trim()) {
+            Some(Value::String(s)) => values.push(s.clone()),
+            Some(value) => values.push(value.to_string()),
+            None => {}
+        }
+        rest = &after_open[end + 2..];
+    }
+    values
+}
+
+/// Reject a value that, taken as a path relative to the (canonical) `root`, leaves
+/// it: absolute paths, `..` components and symlinks pointing elsewhere. A value
+/// that is itself a flag (`--file=/etc/passwd`) is checked after its `=`.
+fn confine(root: &Path, value: &str) -> Result<(), String> {
+    let candidates = std::iter::once(value).chain(value.split_once('=').map(|(_, path)| path));
+    for candidate in candidates {
+        let path = Path::new(candidate);
+        let escapes = path.is_absolute()
+            || path.components().any(|c| matches!(c, Component::ParentDir | Component::Prefix(_)))
+            || {
+                let joined = root.join(path);
+                let existing = joined.ancestors().find(|p| p.exists()).unwrap_or(root);
+                !existing.canonicalize().is_ok_and(|canonical| canonical.starts_with(root))
+            };
+        if escapes {
+            return Err(format!("Argument '{}' points outside {}", value, root.display()));
+        }
+    }
+    Ok(())
+}
+
+/// Keep the first `max_bytes` of a pipe and discard the rest, reading until the
+/// process closes it so the process never blocks on a full pipe
+fn read_capped<R: Read + Send + 'static>(mut pipe: R, max_bytes: usize) -> std::thread::JoinHandle<String> {
+    std::thread::spawn(move || {
+        let mut buffer = Vec::new();
+        let _ = (&mut pipe).take(max_bytes as u64).read_to_end(&mut buffer);
+        let _ = std::io::copy(&mut pipe, &mut std::io::sink());
+        String::from_utf8_lossy(&buffer).into_owned()
+    })
+}
+
+impl LocalTool for ProcessTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters(&self) -> Value {
+        self.parameters.clone()
+    }
+
+    fn call(&self, arguments: &str) -> Result<String, String> {
+        self.run(arguments)
+    }
+
+    fn call_with_limits(&self, arguments: &str, limits: &ResourceLimits) -> Result<String, String> {
+        self.within(limits).run(arguments)
+    }
+
+    fn required_permissions(&self) -> Vec<Permission> {
+        self.permissions.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn confined(root: &Path) -> ProcessTool {
+        ProcessTool::new("cat", "Print a file", json!({}), "cat")
+            .confined_to(root)
+            .with_args(vec!["--file={{path}}", "{{mode}}"])
+    }
+
+    #[test]
+    fn renders_values_inside_the_root() {
+        let root = tempfile::tempdir().unwrap();
+        let args = confined(root.path()).render_args(&json!({ "path": "notes/today.md", "mode": "plain" })).unwrap();
+        assert_eq!(args, vec!["--file=notes/today.md", "plain"]);
+    }
+
+    #[test]
+    fn rejects_values_embedded_in_flags() {
+        let root = tempfile::tempdir().unwrap();
+        let tool = confined(root.path());
+        assert!(tool.render_args(&json!({ "path": "/etc/passwd", "mode": "plain" })).is_err());
+        assert!(tool.render_args(&json!({ "path": "../secret", "mode": "plain" })).is_err());
+        assert!(tool.render_args(&json!({ "path": "notes", "mode": "--out=/etc/passwd" })).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_symlinks_leaving_the_root() {
+        let root = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        std::os::unix::fs::symlink(outside.path(), root.path().join("escape")).unwrap();
+        let tool = confined(root.path());
+        assert!(tool.render_args(&json!({ "path": "escape/passwd", "mode": "plain" })).is_err());
+    }
+}
@@ -0,0 +1,168 @@
+use crate::tools::network::restricted_domains;
+use crate::tools::registry::{register_tool, LocalTool};
+use merco_llmproxy::Tool;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde_json::{json, Map, Value};
+use std::sync::Arc;
+
+/// A Python function exposed as a tool. The argument schema is derived from the
+/// function's signature and type hints, the description from its docstring.
+/// Results that aren't strings are returned as JSON via `json.dumps`.
+///
+/// Python code's network traffic bypasses `network::check_url`, so calls are
+/// refused while the calling agent restricts `allowed_domains`, unless the tool is
+/// declared `offline`.
+pub struct PythonTool {
+    name: String,
+    description: String,
+    parameters: Value,
+    function: Py<PyAny>,
+    offline: bool,
+}
+
+impl PythonTool {
+    pub fn from_function(function: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let py = function.py();
+        let inspect = py.import_bound("inspect")?;
+        let typing = py.import_bound("typing")?;
+
+        let name: String = function.getattr("__name__")?.extract()?;
+        let description = inspect
+            .call_method1("getdoc", (function,))?
+            .extract::<Option<String>>()?
+            .unwrap_or_default();
+
+        let hints = typing.call_method1("get_type_hints", (function,))?;
+        let empty = inspect.getattr("Parameter")?.getattr("empty")?;
+        let signature = inspect.call_method1("signature", (function,))?;
+
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+        for parameter in signature.getattr("parameters")?.call_method0("values")?.iter()? {
+            let parameter = parameter?;
+            let parameter_name: String = parameter.getattr("name")?.extract()?;
+            let schema = match hints.get_item(&parameter_name) {
+                Ok(hint) => type_schema(&hint)?,
+                Err(_) => json!({}),
+            };
+            if parameter.getattr("default")?.is(&empty) {
+                required.push(parameter_name.clone());
+            }
+            properties.insert(parameter_name, schema);
+        }
+
+        Ok(Self {
+            name,
+            description,
+            parameters: json!({ "type": "object", "properties": properties, "required": required }),
+            function: function.clone().unbind(),
+            offline: false,
+        })
+    }
+
+    /// Declare that the function makes no network requests, so agents with
+    /// `allowed_domains` may call it
+    pub fn offline(mut self) -> Self {
+        self.offline = true;
+        self
+    }
+
+    /// Load `function` from an importable Python module
+    pub fn from_module(module: &str, function: &str) -> PyResult<Self> {
+        Python::with_gil(|py| {
+            let function = py.import_bound(module)?.getattr(function)?;
+            Self::from_function(&function)
+        })
+    }
+
+    fn invoke(&self, arguments: &str) -> PyResult<String> {
+        Python::with_gil(|py| {
+            let json = py.import_bound("json")?;
+            let kwargs = json.call_method1("loads", (arguments,))?.downcast_into::<PyDict>()?;
+            let result = self.function.bind(py).call((), Some(&kwargs))?;
+            match result.extract::<String>() {
+                Ok(text) => Ok(text),
+                Err(_) => json.call_method1("dumps", (result,))?.extract(),
+            }
+        })
+    }
+}
+
+/// JSON schema for a type hint; unknown types accept anything
+fn type_schema(hint: &Bound<'_, PyAny>) -> PyResult<Value> {
+    let py = hint.py();
+    let builtins = py.import_bound("builtins")?;
+    let typing = py.import_bound("typing")?;
+
+    let origin = typing.call_method1("get_origin", (hint,))?;
+    let args: Vec<Bound<'_, PyAny>> = typing.call_method1("get_args", (hint,))?.extract()?;
+
+    // Optional[X] / X | None
+    if origin.is(&typing.getattr("Union")?) || origin.is(&py.import_bound("types")?.getattr("UnionType")?) {
+        let none_type = py.None().into_bound(py).get_type();
+        let variants: Vec<_> = args.iter().filter(|arg| !arg.is(&none_type)).collect();
+        return match variants.as_slice() {
+            [single] => type_schema(single),
+            _ => Ok(json!({})),
+        };
+    }
+
+    let target = if origin.is_none() { hint.clone() } else { origin };
+    let is = |name: &str| -> PyResult<bool> { Ok(target.is(&builtins.getattr(name)?)) };
+    // bool is a subclass of int, so it is checked first
+    Ok(if is("bool")? {
+        json!({ "type": "boolean" })
+    } else if is("int")? {
+        json!({ "type": "integer" })
+    } else if is("float")? {
+        json!({ "type": "number" })
+    } else if is("str")? {
+        json!({ "type": "string" })
+    } else if is("list")? || is("tuple")? || is("set")? {
+        match args.first() {
+            Some(item) => json!({ "type": "array", "items": type_schema(item)? }),
+            None => json!({ "type": "array" }),
+        }
+    } else if is("dict")? {
+        json!({ "type": "object" })
+    } else {
+        json!({})
+    })
+}
+
+impl LocalTool for PythonTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters(&self) -> Value {
+        self.parameters.clone()
+    }
+
+    fn call(&self, arguments: &str) -> Result<String, String> {
+        if !self.offline && restricted_domains().is_some() {
+            return Err(format!(
+                "Python tool '{}' can't run while allowed_domains is set unless it is declared offline",
+                self.name
+            ));
+        }
+        self.invoke(arguments).map_err(|e| format!("Python tool '{}' raised: {}", self.name, e))
+    }
+}
+
+/// Register functions of a Python module as tools and return their definitions
+pub fn register_python_tools(module: &str, functions: &[&str]) -> Result<Vec<Tool>, String> {
+    functions
+        .iter()
+        .map(|function| {
+            let tool = PythonTool::from_module(module, function)
+                .map_err(|e| format!("Failed to load {}.{}: {}", module, function, e))?;
+            register_tool(Arc::new(tool))
+        })
+        .collect()
+}
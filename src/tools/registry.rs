@@ -0,0 +1,74 @@
+use crate::agent::state::{Permission, ResourceLimits};
+use merco_llmproxy::{execute_tool, Tool};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// A tool implemented in this crate's runtimes rather than with `#[merco_tool]`
+pub trait LocalTool: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn description(&self) -> &str;
+
+    /// JSON schema of the arguments object
+    fn parameters(&self) -> Value;
+
+    /// Run the tool with the model's JSON arguments
+    fn call(&self, arguments: &str) -> Result<String, String>;
+
+    /// Run under an agent's resource limits. Tools that can cap their own memory
+    /// or CPU (subprocesses, WASM) override this; the wall-clock limit is applied
+    /// to every tool by the agent's supervisor.
+    fn call_with_limits(&self, arguments: &str, _limits: &ResourceLimits) -> Result<String, String> {
+        self.call(arguments)
+    }
+
+    /// Permissions an agent needs to call the tool; none by default
+    fn required_permissions(&self) -> Vec<Permission> {
+        Vec::new()
+    }
+
+    /// Version recorded in the provenance of the tool's results
+    fn version(&self) -> Option<&str> {
+        None
+    }
+}
+
+fn registry() -> &'static RwLock<HashMap<String, Arc<dyn LocalTool>>> {
+    static REGISTRY: OnceLock<RwLock<HashMap<String, Arc<dyn LocalTool>>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// Definition the model sees for a local tool
+pub fn tool_definition(tool: &dyn LocalTool) -> Result<Tool, String> {
+    serde_json::from_value(json!({
+        "name": tool.name(),
+        "description": tool.description(),
+        "parameters": tool.parameters(),
+    }))
+    .map_err(|e| format!("Invalid definition for tool '{}': {}", tool.name(), e))
+}
+
+/// Make a local tool callable by agents. Returns its definition, to be added to
+/// an agent with `add_tool`. A tool registered under an existing name replaces it.
+pub fn register_tool(tool: Arc<dyn LocalTool>) -> Result<Tool, String> {
+    let definition = tool_definition(tool.as_ref())?;
+    registry().write().unwrap().insert(tool.name().to_string(), tool);
+    Ok(definition)
+}
+
+pub fn unregister_tool(name: &str) -> bool {
+    registry().write().unwrap().remove(name).is_some()
+}
+
+pub fn local_tool(name: &str) -> Option<Arc<dyn LocalTool>> {
+    registry().read().unwrap().get(name).cloned()
+}
+
+/// Execute a tool call: local tools first, then tools registered with merco-llmproxy
+pub fn run_tool(name: &str, arguments: &str) -> Result<String, String> {
+    match local_tool(name) {
+        Some(tool) => tool.call(arguments),
+        None => execute_tool(name, arguments),
+    }
+}
@@ -0,0 +1,156 @@
+use crate::agent::state::{EnvironmentContext, ResourceLimits};
+use crate::task::inputs::with_task_inputs;
+use crate::tools::network::with_allowed_domains;
+use crate::tools::registry::{local_tool, run_tool};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+tokio::task_local! {
+    static CALL_STATE: Arc<AtomicU8>;
+}
+
+const RUNNING: u8 = 0;
+const FINISHED: u8 = 1;
+const ABANDONED: u8 = 2;
+
+/// Calls abandoned after their time limit whose workers are still running
+static ABANDONED_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+/// Whether the supervisor has given up on the current tool call. Long-running
+/// `LocalTool`s should poll this and return early, since their result is
+/// discarded and their worker keeps a blocking thread busy until they do.
+pub fn call_cancelled() -> bool {
+    CALL_STATE.try_with(|state| state.load(Ordering::Acquire) == ABANDONED).unwrap_or(false)
+}
+
+/// Tool calls abandoned after their time limit that haven't returned yet
+pub fn abandoned_calls() -> usize {
+    ABANDONED_CALLS.load(Ordering::Acquire)
+}
+
+/// A resource limit that stopped a tool call, recorded on its `ToolCall`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "limit", rename_all = "snake_case")]
+pub enum LimitViolation {
+    /// The call ran past `max_response_time_ms`; its result is discarded.
+    /// `abandoned` counts the calls, this one included, still occupying a
+    /// blocking thread when it was given up on.
+    ResponseTime {
+        limit_ms: u64,
+        #[serde(default)]
+        abandoned: usize,
+    },
+    /// The tool panicked or its worker was lost
+    Aborted { message: String },
+}
+
+impl fmt::Display for LimitViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitViolation::ResponseTime { limit_ms, abandoned } => write!(
+                f,
+                "exceeded the response time limit of {} ms ({} abandoned call(s) still running)",
+                limit_ms, abandoned
+            ),
+            LimitViolation::Aborted { message } => write!(f, "aborted: {}", message),
+        }
+    }
+}
+
+/// A failed tool call. `violation` is set when the supervisor stopped it.
+#[derive(Debug, Clone)]
+pub struct ToolFailure {
+    pub message: String,
+    pub violation: Option<LimitViolation>,
+}
+
+impl ToolFailure {
+    fn violation(tool: &str, violation: LimitViolation) -> Self {
+        Self {
+            message: format!("Tool '{}' {}", tool, violation),
+            violation: Some(violation),
+        }
+    }
+}
+
+impl From<String> for ToolFailure {
+    fn from(message: String) -> Self {
+        Self { message, violation: None }
+    }
+}
+
+impl fmt::Display for ToolFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Execute a tool call with `limits` passed down to tools that can enforce them
+/// (subprocess and WASM tools cap memory and CPU themselves)
+pub fn run_tool_with_limits(name: &str, arguments: &str, limits: &ResourceLimits) -> Result<String, String> {
+    match local_tool(name) {
+        Some(tool) => tool.call_with_limits(arguments, limits),
+        None => run_tool(name, arguments),
+    }
+}
+
+/// Run a tool call on the blocking pool under an agent's `ResourceLimits`, so a
+/// slow tool can't stall the executor. A call still running after
+/// `max_response_time_ms` (0 means no limit) is abandoned and reported as a
+/// violation, and `call_cancelled` turns true for the tool to notice; subprocess
+/// and WASM tools are killed by their own limits, which are tightened to the
+/// agent's. The agent's `allowed_domains` apply to
+/// `network::check_url` during the call.
+pub(crate) async fn supervise(
+    name: &str,
+    arguments: &str,
+    inputs: Option<Value>,
+    environment: &EnvironmentContext,
+) -> Result<String, ToolFailure> {
+    let limits = &environment.resource_limits;
+    let state = Arc::new(AtomicU8::new(RUNNING));
+    let call = {
+        let (name, arguments, limits) = (name.to_string(), arguments.to_string(), limits.clone());
+        let allowed_domains = Arc::new(environment.network_context.allowed_domains.clone());
+        let state = state.clone();
+        tokio::task::spawn_blocking(move || {
+            let result = CALL_STATE.sync_scope(state.clone(), || {
+                with_task_inputs(inputs, || {
+                    with_allowed_domains(allowed_domains, || run_tool_with_limits(&name, &arguments, &limits))
+                })
+            });
+            if state.swap(FINISHED, Ordering::AcqRel) == ABANDONED {
+                ABANDONED_CALLS.fetch_sub(1, Ordering::AcqRel);
+            }
+            result
+        })
+    };
+
+    let joined = if limits.max_response_time_ms == 0 {
+        call.await
+    } else {
+        match tokio::time::timeout(Duration::from_millis(limits.max_response_time_ms), call).await {
+            Ok(joined) => joined,
+            Err(_) => {
+                // Count the call before marking it, so a worker finishing in between
+                // never decrements below zero
+                let mut abandoned = ABANDONED_CALLS.fetch_add(1, Ordering::AcqRel) + 1;
+                if state.compare_exchange(RUNNING, ABANDONED, Ordering::AcqRel, Ordering::Acquire).is_err() {
+                    abandoned = ABANDONED_CALLS.fetch_sub(1, Ordering::AcqRel) - 1;
+                }
+                return Err(ToolFailure::violation(
+                    name,
+                    LimitViolation::ResponseTime { limit_ms: limits.max_response_time_ms, abandoned },
+                ));
+            }
+        }
+    };
+    match joined {
+        Ok(result) => result.map_err(ToolFailure::from),
+        Err(e) => Err(ToolFailure::violation(name, LimitViolation::Aborted { message: e.to_string() })),
+    }
+}
@@ -0,0 +1,617 @@
+use crate::agent::http::HttpClientConfig;
+use crate::agent::state::{Permission, ResourceLimits};
+use crate::tools::network::check_url;
+use crate::tools::process::ProcessTool;
+use crate::tools::registry::{register_tool, LocalTool};
+use merco_llmproxy::Tool;
+use serde_json::{json, Value};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+/// Decides whether a shell command proposed by the model may run
+pub type ConfirmCommand = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
+fn parse_arguments(arguments: &str) -> Result<Value, String> {
+    serde_json::from_str(arguments).map_err(|e| format!("Invalid tool arguments: {}", e))
+}
+
+fn string_argument<'a>(arguments: &'a Value, key: &str) -> Result<&'a str, String> {
+    arguments
+        .get(key)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| format!("Missing string argument '{}'", key))
+}
+
+/// Resolve a model-supplied relative path inside `root`, rejecting anything that could leave it
+fn resolve_in_root(root: &Path, relative: &str) -> Result<PathBuf, String> {
+    let path = Path::new(relative);
+    if path.is_absolute() || path.components().any(|c| matches!(c, Component::ParentDir | Component::Prefix(_))) {
+        return Err(format!("Path '{}' must be relative to the workspace and may not contain '..'", relative));
+    }
+    let resolved = root.join(path);
+
+    // Symlinks inside the root could still point elsewhere
+    let root = root.canonicalize().map_err(|e| format!("Workspace root unavailable: {}", e))?;
+    let existing = resolved.ancestors().find(|p| p.exists()).unwrap_or(&root);
+    let canonical = existing.canonicalize().map_err(|e| e.to_string())?;
+    if !canonical.starts_with(&root) {
+        return Err(format!("Path '{}' resolves outside the workspace", relative));
+    }
+    Ok(resolved)
+}
+
+/// Reads a UTF-8 file inside the workspace root
+pub struct ReadFileTool {
+    root: PathBuf,
+    max_bytes: u64,
+}
+
+impl ReadFileTool {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into(), max_bytes: 256 * 1024 }
+    }
+}
+
+impl LocalTool for ReadFileTool {
+    fn name(&self) -> &str {
+        "read_file"
+    }
+
+    fn version(&self) -> Option<&str> {
+        Some(env!("CARGO_PKG_VERSION"))
+    }
+
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::Read]
+    }
+
+    fn description(&self) -> &str {
+        "Read a text file from the workspace. Paths are relative to the workspace root."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": { "path": { "type": "string", "description": "Relative file path" } },
+            "required": ["path"],
+        })
+    }
+
+    fn call(&self, arguments: &str) -> Result<String, String> {
+        let arguments = parse_arguments(arguments)?;
+        let path = resolve_in_root(&self.root, string_argument(&arguments, "path")?)?;
+        let size = std::fs::metadata(&path).map_err(|e| e.to_string())?.len();
+        if size > self.max_bytes {
+            return Err(format!("File is {} bytes, the limit is {}", size, self.max_bytes));
+        }
+        std::fs::read_to_string(&path).map_err(|e| e.to_string())
+    }
+}
+
+/// Writes (creates or replaces) a UTF-8 file inside the workspace root
+pub struct WriteFileTool {
+    root: PathBuf,
+}
+
+impl WriteFileTool {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl LocalTool for WriteFileTool {
+    fn name(&self) -> &str {
+        "write_file"
+    }
+
+    fn version(&self) -> Option<&str> {
+        Some(env!("CARGO_PKG_VERSION"))
+    }
+
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::Write]
+    }
+
+    fn description(&self) -> &str {
+        "Write a text file in the workspace, replacing it if it exists. Paths are relative to the workspace root."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string", "description": "Relative file path" },
+                "content": { "type": "string", "description": "Full file content" },
+            },
+            "required": ["path", "content"],
+        })
+    }
+
+    fn call(&self, arguments: &str) -> Result<String, String> {
+        let arguments = parse_arguments(arguments)?;
+        let path = resolve_in_root(&self.root, string_argument(&arguments, "path")?)?;
+        let content = string_argument(&arguments, "content")?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        std::fs::write(&path, content).map_err(|e| e.to_string())?;
+        Ok(format!("Wrote {} bytes", content.len()))
+    }
+}
+
+/// Lists a directory inside the workspace root
+pub struct ListDirectoryTool {
+    root: PathBuf,
+}
+
+impl ListDirectoryTool {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl LocalTool for ListDirectoryTool {
+    fn name(&self) -> &str {
+        "list_directory"
+    }
+
+    fn version(&self) -> Option<&str> {
+        Some(env!("CARGO_PKG_VERSION"))
+    }
+
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::Read]
+    }
+
+    fn description(&self) -> &str {
+        "List the entries of a workspace directory. Directories are marked with a trailing '/'."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": { "path": { "type": "string", "description": "Relative directory path, '.' for the root" } },
+        })
+    }
+
+    fn call(&self, arguments: &str) -> Result<String, String> {
+        let arguments = parse_arguments(arguments)?;
+        let relative = arguments.get("path").and_then(|v| v.as_str()).unwrap_or(".");
+        let path = resolve_in_root(&self.root, relative)?;
+
+        let mut entries: Vec<String> = std::fs::read_dir(&path)
+            .map_err(|e| e.to_string())?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                if entry.file_type().is_ok_and(|t| t.is_dir()) {
+                    format!("{}/", name)
+                } else {
+                    name
+                }
+            })
+            .collect();
+        entries.sort();
+        Ok(entries.join("\n"))
+    }
+}
+
+/// Runs a shell command in the workspace after the confirmation hook approves it
+pub struct ShellCommandTool {
+    confirm: ConfirmCommand,
+    process: ProcessTool,
+}
+
+impl ShellCommandTool {
+    pub fn new(root: impl Into<PathBuf>, confirm: ConfirmCommand) -> Self {
+        let process = ProcessTool::new("run_shell_command", "", Value::Null, "sh")
+            .with_args(vec!["-c", "{{command}}"])
+            .with_working_dir(root)
+            .with_timeout(Duration::from_secs(60));
+        Self { confirm, process }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.process = self.process.with_timeout(timeout);
+        self
+    }
+
+    fn approve(&self, arguments: &str) -> Result<(), String> {
+        let parsed = parse_arguments(arguments)?;
+        let command = string_argument(&parsed, "command")?;
+        if !(self.confirm)(command) {
+            return Err(format!("Command was not approved: {}", command));
+        }
+        Ok(())
+    }
+}
+
+impl LocalTool for ShellCommandTool {
+    fn name(&self) -> &str {
+        "run_shell_command"
+    }
+
+    fn version(&self) -> Option<&str> {
+        Some(env!("CARGO_PKG_VERSION"))
+    }
+
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::Execute]
+    }
+
+    fn description(&self) -> &str {
+        "Run a shell command in the workspace directory and return its output. Commands may be refused."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": { "command": { "type": "string", "description": "Command line passed to sh -c" } },
+            "required": ["command"],
+        })
+    }
+
+    fn call(&self, arguments: &str) -> Result<String, String> {
+        self.approve(arguments)?;
+        self.process.call(arguments)
+    }
+
+    fn call_with_limits(&self, arguments: &str, limits: &ResourceLimits) -> Result<String, String> {
+        self.approve(arguments)?;
+        self.process.call_with_limits(arguments, limits)
+    }
+}
+
+/// Current date and time
+pub struct DateTimeTool;
+
+impl LocalTool for DateTimeTool {
+    fn name(&self) -> &str {
+        "current_datetime"
+    }
+
+    fn description(&self) -> &str {
+        "Get the current date and time in UTC and in the local time zone of the host."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({ "type": "object", "properties": {} })
+    }
+
+    fn call(&self, _arguments: &str) -> Result<String, String> {
+        let utc = chrono::Utc::now();
+        let local = chrono::Local::now();
+        Ok(json!({
+            "utc": utc.to_rfc3339(),
+            "local": local.to_rfc3339(),
+            "weekday": local.format("%A").to_string(),
+            "unix_timestamp": utc.timestamp(),
+        })
+        .to_string())
+    }
+}
+
+/// Fetches a web page or API response over HTTP(S). Every request, including each
+/// redirect hop, goes through `network::check_url`, so only the calling agent's
+/// `allowed_domains` are reachable. Not part of `std_tools`; register it where web access is wanted.
+pub struct FetchUrlTool {
+    max_bytes: usize,
+    timeout: Duration,
+}
+
+impl FetchUrlTool {
+    pub fn new() -> Self {
+        Self { max_bytes: 256 * 1024, timeout: Duration::from_secs(20) }
+    }
+
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Follows redirects itself so every hop is checked before it is requested
+    async fn fetch(&self, url: &str) -> Result<String, String> {
+        let mut url = reqwest::Url::parse(url).map_err(|e| format!("Invalid URL '{}': {}", url, e))?;
+        let mut redirects = 0;
+        let response = loop {
+            let response = fetch_client()
+                .get(url.clone())
+                .timeout(self.timeout)
+                .send()
+                .await
+                .map_err(|e| format!("Request to {} failed: {}", url, e))?;
+            if !response.status().is_redirection() {
+                break response;
+            }
+            let Some(location) = response.headers().get(reqwest::header::LOCATION) else {
+                break response;
+            };
+            redirects += 1;
+            if redirects > MAX_FETCH_REDIRECTS {
+                return Err(format!("Too many redirects fetching {}", url));
+            }
+            let location = location.to_str().map_err(|_| format!("Invalid redirect from {}", url))?;
+            url = url.join(location).map_err(|e| format!("Invalid redirect from {} to '{}': {}", url, location, e))?;
+            if !matches!(url.scheme(), "http" | "https") {
+                return Err(format!("Refusing to follow a redirect to '{}'", url));
+            }
+            check_url(url.as_str())?;
+        };
+        let status = response.status();
+        let body = response.text().await.map_err(|e| format!("Failed to read {}: {}", url, e))?;
+        let mut end = body.len().min(self.max_bytes);
+        while !body.is_char_boundary(end) {
+            end -= 1;
+        }
+        Ok(json!({
+            "status": status.as_u16(),
+            "body": &body[..end],
+            "truncated": end < body.len(),
+        })
+        .to_string())
+    }
+}
+
+const MAX_FETCH_REDIRECTS: usize = 10;
+
+/// Like the shared client, but returns redirects to `FetchUrlTool` instead of following them
+fn fetch_client() -> reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT
+        .get_or_init(|| HttpClientConfig { max_redirects: 0, ..HttpClientConfig::default() }.build().unwrap_or_default())
+        .clone()
+}
+
+impl Default for FetchUrlTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LocalTool for FetchUrlTool {
+    fn name(&self) -> &str {
+        "fetch_url"
+    }
+
+    fn version(&self) -> Option<&str> {
+        Some(env!("CARGO_PKG_VERSION"))
+    }
+
+    fn description(&self) -> &str {
+        "Fetch the content of an http(s) URL. Only allowlisted domains can be reached."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": { "url": { "type": "string", "description": "Absolute http or https URL" } },
+            "required": ["url"],
+        })
+    }
+
+    fn call(&self, arguments: &str) -> Result<String, String> {
+        let arguments = parse_arguments(arguments)?;
+        let url = string_argument(&arguments, "url")?;
+        if !(url.starts_with("http://") || url.starts_with("https://")) {
+            return Err(format!("Only http and https URLs can be fetched, not '{}'", url));
+        }
+        check_url(url)?;
+        // Tools run on the blocking pool, so waiting on the runtime here is fine
+        tokio::runtime::Handle::current().block_on(self.fetch(url))
+    }
+
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::Read]
+    }
+}
+
+/// Evaluates arithmetic expressions exactly as written, so the model doesn't have to
+pub struct CalculatorTool;
+
+impl LocalTool for CalculatorTool {
+    fn name(&self) -> &str {
+        "calculate"
+    }
+
+    fn description(&self) -> &str {
+        "Evaluate an arithmetic expression with + - * / % ^ and parentheses."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": { "expression": { "type": "string", "description": "For example (2 + 3) * 4.5" } },
+            "required": ["expression"],
+        })
+    }
+
+    fn call(&self, arguments: &str) -> Result<String, String> {
+        let arguments = parse_arguments(arguments)?;
+        let value = evaluate(string_argument(&arguments, "expression")?)?;
+        Ok(value.to_string())
+    }
+}
+
+/// Evaluate an arithmetic expression by recursive descent
+pub fn evaluate(expression: &str) -> Result<f64, String> {
+    let tokens: Vec<char> = expression.chars().filter(|c| !c.is_whitespace()).collect();
+    let mut parser = ExpressionParser { tokens, position: 0 };
+    let value = parser.sum()?;
+    if parser.position < parser.tokens.len() {
+        return Err(format!("Unexpected '{}' at position {}", parser.tokens[parser.position], parser.position));
+    }
+    if !value.is_finite() {
+        return Err("Result is not a finite number".to_string());
+    }
+    Ok(value)
+}
+
+struct ExpressionParser {
+    tokens: Vec<char>,
+    position: usize,
+}
+
+impl ExpressionParser {
+    fn peek(&self) -> Option<char> {
+        self.tokens.get(self.position).copied()
+    }
+
+    fn sum(&mut self) -> Result<f64, String> {
+        let mut value = self.product()?;
+        while let Some(op @ ('+' | '-')) = self.peek() {
+            self.position += 1;
+            let rhs = self.product()?;
+            value = if op == '+' { value + rhs } else { value - rhs };
+        }
+        Ok(value)
+    }
+
+    fn product(&mut self) -> Result<f64, String> {
+        let mut value = self.unary()?;
+        while let Some(op @ ('*' | '/' | '%')) = self.peek() {
+            self.position += 1;
+            let rhs = self.unary()?;
+            value = match op {
+                '*' => value * rhs,
+                _ if rhs == 0.0 => return Err("Division by zero".to_string()),
+                '/' => value / rhs,
+                _ => value % rhs,
+            };
+        }
+        Ok(value)
+    }
+
+    fn unary(&mut self) -> Result<f64, String> {
+        match self.peek() {
+            Some('-') => {
+                self.position += 1;
+                Ok(-self.unary()?)
+            }
+            Some('+') => {
+                self.position += 1;
+                self.unary()
+            }
+            _ => self.power(),
+        }
+    }
+
+    /// `^` is right-associative and binds tighter than unary minus on its left
+    /// operand (`-2^2` is -4); the exponent may carry its own sign (`2^-1`)
+    fn power(&mut self) -> Result<f64, String> {
+        let base = self.atom()?;
+        if self.peek() == Some('^') {
+            self.position += 1;
+            let exponent = self.unary()?;
+            return Ok(base.powf(exponent));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<f64, String> {
+        match self.peek() {
+            Some('(') => {
+                self.position += 1;
+                let value = self.sum()?;
+                if self.peek() != Some(')') {
+                    return Err("Missing closing parenthesis".to_string());
+                }
+                self.position += 1;
+                Ok(value)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                let start = self.position;
+                while self.peek().is_some_and(|c| c.is_ascii_digit() || c == '.') {
+                    self.position += 1;
+                }
+                let number: String = self.tokens[start..self.position].iter().collect();
+                number.parse().map_err(|_| format!("Invalid number '{}'", number))
+            }
+            Some(c) => Err(format!("Unexpected '{}' at position {}", c, self.position)),
+            None => Err("Unexpected end of expression".to_string()),
+        }
+    }
+}
+
+/// The curated standard tools, with file and shell access limited to `root`
+pub fn std_tools(root: impl Into<PathBuf>, confirm: ConfirmCommand) -> Vec<Arc<dyn LocalTool>> {
+    let root = root.into();
+    vec![
+        Arc::new(ReadFileTool::new(root.clone())),
+        Arc::new(WriteFileTool::new(root.clone())),
+        Arc::new(ListDirectoryTool::new(root.clone())),
+        Arc::new(ShellCommandTool::new(root, confirm)),
+        Arc::new(DateTimeTool),
+        Arc::new(CalculatorTool),
+    ]
+}
+
+/// Register the standard tools and return their definitions for `Agent::add_tool`
+pub fn register_std_tools(root: impl Into<PathBuf>, confirm: ConfirmCommand) -> Result<Vec<Tool>, String> {
+    std_tools(root, confirm).into_iter().map(register_tool).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evaluates_with_precedence() {
+        assert_eq!(evaluate("1 + 2 * 3"), Ok(7.0));
+        assert_eq!(evaluate("(1 + 2) * 3"), Ok(9.0));
+        assert_eq!(evaluate("10 - 4 - 3"), Ok(3.0));
+        assert_eq!(evaluate("2 ^ 3 ^ 2"), Ok(512.0));
+        assert_eq!(evaluate("-2 ^ 2"), Ok(-4.0));
+        assert_eq!(evaluate("(-2) ^ 2"), Ok(4.0));
+        assert_eq!(evaluate("2 ^ -1"), Ok(0.5));
+        assert_eq!(evaluate("7 % 4 + .5"), Ok(3.5));
+    }
+
+    #[test]
+    fn rejects_malformed_expressions() {
+        assert_eq!(evaluate("1 / 0"), Err("Division by zero".to_string()));
+        assert!(evaluate("5 % 0").is_err());
+        assert!(evaluate("(1 + 2").is_err());
+        assert!(evaluate("1 +").is_err());
+        assert!(evaluate("1.2.3").is_err());
+        assert!(evaluate("2 x 3").is_err());
+        assert!(evaluate("").is_err());
+        assert!(evaluate("10 ^ 400").is_err());
+    }
+
+    #[test]
+    fn resolves_paths_inside_the_root() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir(root.path().join("notes")).unwrap();
+        assert_eq!(resolve_in_root(root.path(), "notes/today.md"), Ok(root.path().join("notes/today.md")));
+        assert_eq!(resolve_in_root(root.path(), "new/dir/file.txt"), Ok(root.path().join("new/dir/file.txt")));
+        assert!(resolve_in_root(root.path(), ".").is_ok());
+    }
+
+    #[test]
+    fn rejects_parent_and_absolute_paths() {
+        let root = tempfile::tempdir().unwrap();
+        assert!(resolve_in_root(root.path(), "../secret").is_err());
+        assert!(resolve_in_root(root.path(), "notes/../../secret").is_err());
+        assert!(resolve_in_root(root.path(), "/etc/passwd").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_symlinks_leaving_the_root() {
+        let root = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        std::os::unix::fs::symlink(outside.path(), root.path().join("escape")).unwrap();
+        std::os::unix::fs::symlink(root.path().join("notes"), root.path().join("inside")).unwrap();
+        std::fs::create_dir(root.path().join("notes")).unwrap();
+
+        assert!(resolve_in_root(root.path(), "escape").is_err());
+        assert!(resolve_in_root(root.path(), "escape/new.txt").is_err());
+        assert!(resolve_in_root(root.path(), "inside/new.txt").is_ok());
+    }
+}
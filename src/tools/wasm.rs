@@ -0,0 +1,217 @@
+use crate::agent::state::ResourceLimits;
+use crate::tools::registry::LocalTool;
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+use std::path::Path;
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::time::Duration;
+use wasmtime::{Caller, Config, Engine, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+/// Host functions a WASM tool may import from the `env` module. Modules that
+/// import anything not granted here fail to load, so a tool can never reach
+/// the filesystem, network or environment.
+#[derive(Debug, Clone, Default)]
+pub struct WasmCapabilities {
+    /// `env.log(ptr: i32, len: i32)`: write a UTF-8 message to stderr
+    pub log: bool,
+    /// `env.now_ms() -> i64`: wall clock time in milliseconds since the Unix epoch
+    pub clock: bool,
+}
+
+/// Resource limits of a single WASM tool call
+#[derive(Debug, Clone)]
+pub struct WasmLimits {
+    /// Instructions budget (wasmtime fuel) per call
+    pub fuel: u64,
+    /// Wall clock limit per call
+    pub timeout: Duration,
+    /// Upper bound for the module's linear memory
+    pub max_memory_bytes: usize,
+    /// Upper bound for the returned string
+    pub max_output_bytes: usize,
+}
+
+impl Default for WasmLimits {
+    fn default() -> Self {
+        Self {
+            fuel: 1_000_000_000,
+            timeout: Duration::from_secs(5),
+            max_memory_bytes: 64 * 1024 * 1024,
+            max_output_bytes: 1024 * 1024,
+        }
+    }
+}
+
+struct HostState {
+    limits: StoreLimits,
+}
+
+/// A tool backed by a WASM module.
+///
+/// The module exports `memory`, `alloc(len: i32) -> i32` and
+/// `run(ptr: i32, len: i32) -> i64`. `run` receives the JSON arguments as UTF-8
+/// at `ptr` and returns the result's location packed as `(ptr << 32) | len`.
+/// Every call gets a fresh instance, so no state survives between calls.
+pub struct WasmTool {
+    name: String,
+    description: String,
+    parameters: Value,
+    engine: Engine,
+    module: Module,
+    capabilities: WasmCapabilities,
+    limits: WasmLimits,
+    // Epoch interruption is engine-wide, so calls on one tool run one at a time
+    call_lock: Mutex<()>,
+}
+
+impl WasmTool {
+    pub fn from_file<P: AsRef<Path>>(name: &str, description: &str, parameters: Value, path: P) -> Result<Self> {
+        let bytes = std::fs::read(path)?;
+        Self::from_bytes(name, description, parameters, &bytes)
+    }
+
+    /// Load a module from `.wasm` or `.wat` bytes
+    pub fn from_bytes(name: &str, description: &str, parameters: Value, bytes: &[u8]) -> Result<Self> {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        config.epoch_interruption(true);
+        let engine = Engine::new(&config)?;
+        let module = Module::new(&engine, bytes)?;
+
+        Ok(Self {
+            name: name.to_string(),
+            description: description.to_string(),
+            parameters,
+            engine,
+            module,
+            capabilities: WasmCapabilities::default(),
+            limits: WasmLimits::default(),
+            call_lock: Mutex::new(()),
+        })
+    }
+
+    pub fn with_capabilities(mut self, capabilities: WasmCapabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    pub fn with_limits(mut self, limits: WasmLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    fn linker(&self) -> Result<Linker<HostState>> {
+        let mut linker = Linker::new(&self.engine);
+        if self.capabilities.log {
+            let name = self.name.clone();
+            linker.func_wrap("env", "log", move |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| {
+                if let Some(memory) = caller.get_export("memory").and_then(|e| e.into_memory()) {
+                    if let Ok(message) = read_string(&memory, &caller, ptr, len, 64 * 1024) {
+                        eprintln!("[wasm tool {}] {}", name, crate::redaction::redact(&message));
+                    }
+                }
+            })?;
+        }
+        if self.capabilities.clock {
+            linker.func_wrap("env", "now_ms", || chrono::Utc::now().timestamp_millis())?;
+        }
+        Ok(linker)
+    }
+
+    fn run(&self, arguments: &str, limits: &WasmLimits) -> Result<String> {
+        let _guard = self.call_lock.lock().unwrap();
+
+        let mut store = Store::new(
+            &self.engine,
+            HostState {
+                limits: StoreLimitsBuilder::new().memory_size(limits.max_memory_bytes).build(),
+            },
+        );
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(limits.fuel)?;
+        store.set_epoch_deadline(1);
+
+        // Interrupt the call once the timeout elapses, unless it finishes first
+        let (done, finished) = mpsc::channel::<()>();
+        let engine = self.engine.clone();
+        let timeout = limits.timeout;
+        let watchdog = std::thread::spawn(move || {
+            if finished.recv_timeout(timeout).is_err() {
+                engine.increment_epoch();
+            }
+        });
+
+        let result = self.invoke(&mut store, arguments, limits.max_output_bytes);
+        let _ = done.send(());
+        let _ = watchdog.join();
+
+        result.map_err(|e| {
+            if store.get_fuel().is_ok_and(|fuel| fuel == 0) {
+                anyhow!("WASM tool '{}' ran out of fuel", self.name)
+            } else {
+                e
+            }
+        })
+    }
+
+    fn invoke(&self, store: &mut Store<HostState>, arguments: &str, max_output_bytes: usize) -> Result<String> {
+        let instance = self
+            .linker()?
+            .instantiate(&mut *store, &self.module)
+            .map_err(|e| anyhow!("WASM tool '{}' failed to load (missing capability?): {}", self.name, e))?;
+        let memory = instance
+            .get_memory(&mut *store, "memory")
+            .ok_or_else(|| anyhow!("WASM tool '{}' does not export memory", self.name))?;
+        let alloc = instance.get_typed_func::<i32, i32>(&mut *store, "alloc")?;
+        let run = instance.get_typed_func::<(i32, i32), i64>(&mut *store, "run")?;
+
+        let input = arguments.as_bytes();
+        let input_len = i32::try_from(input.len()).map_err(|_| anyhow!("Arguments too large"))?;
+        let input_ptr = alloc.call(&mut *store, input_len)?;
+        memory.write(&mut *store, input_ptr as u32 as usize, input)?;
+
+        let packed = run.call(&mut *store, (input_ptr, input_len))? as u64;
+        let (ptr, len) = ((packed >> 32) as i32, (packed & 0xffff_ffff) as i32);
+        read_string(&memory, &*store, ptr, len, max_output_bytes)
+    }
+}
+
+fn read_string<T>(memory: &Memory, store: impl wasmtime::AsContext<Data = T>, ptr: i32, len: i32, max_len: usize) -> Result<String> {
+    let len = len as u32 as usize;
+    if len > max_len {
+        return Err(anyhow!("WASM output of {} bytes exceeds the {} byte limit", len, max_len));
+    }
+    let mut buffer = vec![0u8; len];
+    memory.read(&store, ptr as u32 as usize, &mut buffer)?;
+    Ok(String::from_utf8(buffer)?)
+}
+
+impl LocalTool for WasmTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters(&self) -> Value {
+        self.parameters.clone()
+    }
+
+    fn call(&self, arguments: &str) -> std::result::Result<String, String> {
+        self.run(arguments, &self.limits).map_err(|e| e.to_string())
+    }
+
+    /// Memory and wall-clock limits are lowered to the agent's where stricter;
+    /// fuel remains the tool's CPU budget
+    fn call_with_limits(&self, arguments: &str, limits: &ResourceLimits) -> std::result::Result<String, String> {
+        let mut own = self.limits.clone();
+        own.max_memory_bytes = own.max_memory_bytes.min((limits.max_memory_mb * 1024 * 1024) as usize);
+        if limits.max_response_time_ms > 0 {
+            own.timeout = own.timeout.min(Duration::from_millis(limits.max_response_time_ms));
+        }
+        self.run(arguments, &own).map_err(|e| e.to_string())
+    }
+}